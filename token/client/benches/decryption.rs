@@ -0,0 +1,39 @@
+//! Benchmark of repeated confidential balance decryptions with and without the decryption cache
+//!
+//! Run with `cargo bench -p spl-token-client --bench decryption`.
+
+use {
+    spl_token_2022::solana_zk_token_sdk::{
+        encryption::elgamal::ElGamalKeypair, zk_token_elgamal::pod::ElGamalCiphertext,
+    },
+    spl_token_client::decryption::ConfidentialBalanceDecryptor,
+    std::time::{Duration, Instant},
+};
+
+const READS: u32 = 10;
+
+/// A large amount makes the discrete log expensive
+const AMOUNT: u64 = 3_000_000;
+
+fn bench(decryptor: &ConfidentialBalanceDecryptor) -> Duration {
+    let elgamal_keypair = ElGamalKeypair::new_rand();
+    let ciphertext: ElGamalCiphertext = elgamal_keypair.pubkey().encrypt(AMOUNT).into();
+
+    let start = Instant::now();
+    for _ in 0..READS {
+        assert_eq!(
+            decryptor.decrypt(&elgamal_keypair, &ciphertext),
+            Some(AMOUNT)
+        );
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let uncached = bench(&ConfidentialBalanceDecryptor::new());
+    let cached = bench(&ConfidentialBalanceDecryptor::with_cache(16));
+    println!(
+        "{} decryptions of {}: {:?} without cache, {:?} with cache",
+        READS, AMOUNT, uncached, cached
+    );
+}
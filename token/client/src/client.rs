@@ -179,6 +179,21 @@ where
 
     async fn get_account(&self, address: Pubkey) -> ProgramClientResult<Option<Account>>;
 
+    /// Fetch several accounts at once, in the order of `addresses`
+    ///
+    /// The default implementation fetches the accounts one by one with `get_account`, so that
+    /// existing implementations of this trait keep compiling.
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ProgramClientResult<Vec<Option<Account>>> {
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            accounts.push(self.get_account(*address).await?);
+        }
+        Ok(accounts)
+    }
+
     async fn simulate_transaction(
         &self,
         transaction: &Transaction,
@@ -282,6 +297,23 @@ where
         })
         .await
     }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ProgramClientResult<Vec<Option<Account>>> {
+        self.run_in_lock(|client| {
+            let addresses = addresses.to_vec();
+            Box::pin(async move {
+                let mut accounts = Vec::with_capacity(addresses.len());
+                for address in addresses {
+                    accounts.push(client.get_account(address).await?);
+                }
+                Ok(accounts)
+            })
+        })
+        .await
+    }
 }
 
 /// Program client for `RpcClient` from crate `solana-client`.
@@ -339,6 +371,17 @@ where
             .await?
             .value)
     }
+
+    async fn get_multiple_accounts(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ProgramClientResult<Vec<Option<Account>>> {
+        Ok(self
+            .client
+            .get_multiple_accounts_with_commitment(addresses, self.client.commitment())
+            .await?
+            .value)
+    }
 }
 
 /// Program client for offline signing.
@@ -395,4 +438,11 @@ where
     async fn get_account(&self, _address: Pubkey) -> ProgramClientResult<Option<Account>> {
         Err("Unable to fetch account in offline mode".into())
     }
+
+    async fn get_multiple_accounts(
+        &self,
+        _addresses: &[Pubkey],
+    ) -> ProgramClientResult<Vec<Option<Account>>> {
+        Err("Unable to fetch accounts in offline mode".into())
+    }
 }
@@ -0,0 +1,168 @@
+//! Decryption of confidential balances with an optional cache
+//!
+//! Decrypting an ElGamal ciphertext requires solving a discrete log, which dominates the cost of
+//! reading confidential balances. Every change to a balance re-randomizes its ciphertext, so a
+//! ciphertext that was already decrypted under a given key can be answered from a cache without
+//! any invalidation.
+
+use {
+    spl_token_2022::solana_zk_token_sdk::{
+        encryption::elgamal::ElGamalKeypair,
+        zk_token_elgamal::pod::{ElGamalCiphertext, ElGamalPubkey},
+    },
+    std::{collections::HashMap, sync::Mutex},
+};
+
+/// Cache key of a ciphertext decrypted under the secret key of an ElGamal public key
+///
+/// The public key stands in for the secret key, so that no secret material is retained.
+type CacheKey = ([u8; 32], [u8; 64]);
+
+/// Least recently used cache of decrypted ciphertexts
+#[derive(Debug)]
+struct DecryptionCache {
+    capacity: usize,
+    tick: u64,
+    entries: HashMap<CacheKey, (u64, u64)>,
+}
+
+impl DecryptionCache {
+    fn get(&mut self, key: &CacheKey) -> Option<u64> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(amount, last_used)| {
+            *last_used = tick;
+            *amount
+        })
+    }
+
+    fn insert(&mut self, key: CacheKey, amount: u64) {
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            let least_recently_used = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| *key);
+            if let Some(least_recently_used) = least_recently_used {
+                self.entries.remove(&least_recently_used);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, (amount, self.tick));
+    }
+}
+
+/// Decrypts confidential balance ciphertexts, optionally memoizing the results
+#[derive(Debug, Default)]
+pub struct ConfidentialBalanceDecryptor {
+    cache: Option<Mutex<DecryptionCache>>,
+}
+
+impl ConfidentialBalanceDecryptor {
+    /// Create a decryptor that does not cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a decryptor that caches up to `capacity` decrypted ciphertexts, evicting the least
+    /// recently used one when full
+    ///
+    /// A `capacity` of zero disables the cache.
+    pub fn with_cache(capacity: usize) -> Self {
+        let cache = (capacity > 0).then(|| {
+            Mutex::new(DecryptionCache {
+                capacity,
+                tick: 0,
+                entries: HashMap::with_capacity(capacity),
+            })
+        });
+        Self { cache }
+    }
+
+    /// Decrypt a ciphertext with the secret key of `elgamal_keypair`
+    ///
+    /// Returns `None` if the ciphertext is malformed or does not decrypt to a 32-bit value, in
+    /// which case nothing is cached.
+    pub fn decrypt(
+        &self,
+        elgamal_keypair: &ElGamalKeypair,
+        ciphertext: &ElGamalCiphertext,
+    ) -> Option<u64> {
+        let cache = match &self.cache {
+            Some(cache) => cache,
+            None => return ciphertext.decrypt(elgamal_keypair.secret()),
+        };
+
+        let key = (
+            ElGamalPubkey::from(*elgamal_keypair.pubkey()).0,
+            ciphertext.0,
+        );
+        if let Some(amount) = cache.lock().unwrap().get(&key) {
+            return Some(amount);
+        }
+
+        // decrypt without holding the lock, so other threads are not blocked on the discrete log
+        let amount = ciphertext.decrypt(elgamal_keypair.secret())?;
+        cache.lock().unwrap().insert(key, amount);
+        Some(amount)
+    }
+
+    /// Number of decrypted ciphertexts currently in the cache
+    pub fn cached_len(&self) -> usize {
+        self.cache
+            .as_ref()
+            .map(|cache| cache.lock().unwrap().entries.len())
+            .unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decrypt_with_cache() {
+        let elgamal_keypair = ElGamalKeypair::new_rand();
+        let other_elgamal_keypair = ElGamalKeypair::new_rand();
+        let decryptor = ConfidentialBalanceDecryptor::with_cache(2);
+
+        let ciphertext_a: ElGamalCiphertext = elgamal_keypair.pubkey().encrypt(1_u64).into();
+        let ciphertext_b: ElGamalCiphertext = elgamal_keypair.pubkey().encrypt(2_u64).into();
+        let ciphertext_c: ElGamalCiphertext = elgamal_keypair.pubkey().encrypt(3_u64).into();
+
+        assert_eq!(decryptor.decrypt(&elgamal_keypair, &ciphertext_a), Some(1));
+        assert_eq!(decryptor.decrypt(&elgamal_keypair, &ciphertext_a), Some(1));
+        assert_eq!(decryptor.cached_len(), 1);
+
+        // entries are kept per key
+        assert_ne!(
+            decryptor.decrypt(&other_elgamal_keypair, &ciphertext_a),
+            Some(1)
+        );
+
+        // the least recently used entry is evicted
+        assert_eq!(decryptor.decrypt(&elgamal_keypair, &ciphertext_b), Some(2));
+        assert_eq!(decryptor.decrypt(&elgamal_keypair, &ciphertext_b), Some(2));
+        assert_eq!(decryptor.decrypt(&elgamal_keypair, &ciphertext_c), Some(3));
+        assert_eq!(decryptor.cached_len(), 2);
+        let key = |ciphertext: &ElGamalCiphertext| {
+            (
+                ElGamalPubkey::from(*elgamal_keypair.pubkey()).0,
+                ciphertext.0,
+            )
+        };
+        let mut cache = decryptor.cache.as_ref().unwrap().lock().unwrap();
+        assert!(cache.get(&key(&ciphertext_b)).is_some());
+        assert!(cache.get(&key(&ciphertext_c)).is_some());
+    }
+
+    #[test]
+    fn test_decrypt_without_cache() {
+        let elgamal_keypair = ElGamalKeypair::new_rand();
+        let decryptor = ConfidentialBalanceDecryptor::with_cache(0);
+
+        let ciphertext: ElGamalCiphertext = elgamal_keypair.pubkey().encrypt(42_u64).into();
+        assert_eq!(decryptor.decrypt(&elgamal_keypair, &ciphertext), Some(42));
+        assert_eq!(decryptor.cached_len(), 0);
+    }
+}
@@ -0,0 +1,154 @@
+//! Deterministic derivation of confidential transfer keys from a BIP-39 seed phrase
+//!
+//! `ElGamalKeypair::new_from_signer` and `AeKey::new_from_signer` derive keys from a signature
+//! of a signer, which ties the keys to a specific signing key. The helpers in this module derive
+//! the same kinds of keys from a seed phrase instead, so that they can be recovered by any wallet
+//! that knows the seed phrase.
+//!
+//! The derivation is as follows:
+//!   1. The 64-byte BIP-39 seed is computed from the seed phrase and passphrase as in
+//!      `generate_seed_from_seed_phrase_and_passphrase`, i.e. with PBKDF2-HMAC-SHA512 over 2048
+//!      iterations and the salt `"mnemonic" || passphrase`.
+//!   2. An ed25519 keypair is derived from the seed and the derivation path with SLIP-0010, as in
+//!      `keypair_from_seed_and_derivation_path`.
+//!   3. The ElGamal keypair and the authenticated encryption key are derived from the ed25519
+//!      keypair with `ElGamalKeypair::new_from_signer` and `AeKey::new_from_signer` respectively,
+//!      using the empty public seed.
+//!
+//! The seed phrase is not checked against the BIP-39 word list.
+
+use {
+    solana_sdk::{
+        derivation_path::DerivationPath,
+        signer::keypair::{
+            generate_seed_from_seed_phrase_and_passphrase, keypair_from_seed_and_derivation_path,
+            Keypair,
+        },
+    },
+    spl_token_2022::solana_zk_token_sdk::encryption::{
+        auth_encryption::AeKey, elgamal::ElGamalKeypair,
+    },
+    std::error,
+};
+
+/// Derive the ed25519 keypair that confidential transfer keys are derived from
+fn keypair_from_seed_phrase_and_derivation_path(
+    seed_phrase: &str,
+    passphrase: &str,
+    derivation_path: &DerivationPath,
+) -> Result<Keypair, Box<dyn error::Error>> {
+    let seed = generate_seed_from_seed_phrase_and_passphrase(seed_phrase, passphrase);
+    keypair_from_seed_and_derivation_path(&seed, Some(derivation_path.clone()))
+}
+
+/// Derivation of an ElGamal keypair from a seed phrase
+pub trait ElGamalKeypairFromSeedPhrase: Sized {
+    /// Derive an ElGamal keypair from a seed phrase, passphrase, and derivation path
+    fn new_from_seed_phrase_and_derivation_path(
+        seed_phrase: &str,
+        passphrase: &str,
+        derivation_path: &DerivationPath,
+    ) -> Result<Self, Box<dyn error::Error>>;
+}
+
+impl ElGamalKeypairFromSeedPhrase for ElGamalKeypair {
+    fn new_from_seed_phrase_and_derivation_path(
+        seed_phrase: &str,
+        passphrase: &str,
+        derivation_path: &DerivationPath,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        let keypair =
+            keypair_from_seed_phrase_and_derivation_path(seed_phrase, passphrase, derivation_path)?;
+        ElGamalKeypair::new_from_signer(&keypair, &[])
+    }
+}
+
+/// Derivation of an authenticated encryption key from a seed phrase
+pub trait AeKeyFromSeedPhrase: Sized {
+    /// Derive an authenticated encryption key from a seed phrase, passphrase, and derivation path
+    fn new_from_seed_phrase_and_derivation_path(
+        seed_phrase: &str,
+        passphrase: &str,
+        derivation_path: &DerivationPath,
+    ) -> Result<Self, Box<dyn error::Error>>;
+}
+
+impl AeKeyFromSeedPhrase for AeKey {
+    fn new_from_seed_phrase_and_derivation_path(
+        seed_phrase: &str,
+        passphrase: &str,
+        derivation_path: &DerivationPath,
+    ) -> Result<Self, Box<dyn error::Error>> {
+        let keypair =
+            keypair_from_seed_phrase_and_derivation_path(seed_phrase, passphrase, derivation_path)?;
+        AeKey::new_from_signer(&keypair, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED_PHRASE: &str = "abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon \
+        abandon abandon abandon abandon art";
+
+    #[test]
+    fn elgamal_keypair_from_seed_phrase_is_deterministic() {
+        let derivation_path = DerivationPath::new_bip44(Some(0), Some(0));
+        let keypair = ElGamalKeypair::new_from_seed_phrase_and_derivation_path(
+            SEED_PHRASE,
+            "",
+            &derivation_path,
+        )
+        .unwrap();
+        let same_keypair = ElGamalKeypair::new_from_seed_phrase_and_derivation_path(
+            SEED_PHRASE,
+            "",
+            &derivation_path,
+        )
+        .unwrap();
+        assert_eq!(keypair.pubkey(), same_keypair.pubkey());
+
+        let ciphertext = keypair.pubkey().encrypt(42_u64);
+        assert_eq!(same_keypair.secret().decrypt_u32(&ciphertext), Some(42));
+
+        let other_account_keypair = ElGamalKeypair::new_from_seed_phrase_and_derivation_path(
+            SEED_PHRASE,
+            "",
+            &DerivationPath::new_bip44(Some(1), Some(0)),
+        )
+        .unwrap();
+        assert_ne!(keypair.pubkey(), other_account_keypair.pubkey());
+
+        let other_passphrase_keypair = ElGamalKeypair::new_from_seed_phrase_and_derivation_path(
+            SEED_PHRASE,
+            "passphrase",
+            &derivation_path,
+        )
+        .unwrap();
+        assert_ne!(keypair.pubkey(), other_passphrase_keypair.pubkey());
+    }
+
+    #[test]
+    fn ae_key_from_seed_phrase_is_deterministic() {
+        let derivation_path = DerivationPath::new_bip44(Some(0), Some(0));
+        let key =
+            AeKey::new_from_seed_phrase_and_derivation_path(SEED_PHRASE, "", &derivation_path)
+                .unwrap();
+        let same_key =
+            AeKey::new_from_seed_phrase_and_derivation_path(SEED_PHRASE, "", &derivation_path)
+                .unwrap();
+
+        let ciphertext = key.encrypt(42);
+        assert_eq!(same_key.decrypt(&ciphertext), Some(42));
+
+        let other_account_key = AeKey::new_from_seed_phrase_and_derivation_path(
+            SEED_PHRASE,
+            "",
+            &DerivationPath::new_bip44(Some(1), Some(0)),
+        )
+        .unwrap();
+        assert_eq!(other_account_key.decrypt(&ciphertext), None);
+    }
+}
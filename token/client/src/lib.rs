@@ -1,5 +1,8 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod client;
+pub mod decryption;
+pub mod key_derivation;
+pub mod metrics;
 pub mod output;
 pub mod token;
 
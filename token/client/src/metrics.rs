@@ -0,0 +1,77 @@
+//! Hooks for instrumenting confidential transfer operations of the token client
+//!
+//! Proof generation, transaction submission, and balance decryption dominate the latency of the
+//! confidential transfer helpers. A `ConfidentialMetricsSink` installed with
+//! `Token::with_metrics_sink` is invoked before and after each of these phases, so that services
+//! can export the timings to their own metrics or tracing backend.
+
+use std::{
+    future::Future,
+    time::{Duration, Instant},
+};
+
+/// A phase of a confidential transfer operation that is reported to a metrics sink
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConfidentialPhase {
+    /// Generation of the zero-knowledge proof data for an instruction
+    ProofGeneration,
+    /// Submission of the transaction and, depending on the client, waiting for its confirmation
+    Submission,
+    /// Decryption of the balances of a confidential token account
+    Decryption,
+}
+
+/// Receives the timings of confidential transfer operations from the token client
+///
+/// `operation` names the client helper that is being measured, e.g. `"transfer"`, so that a phase
+/// can be attributed to it. Both methods default to doing nothing, so that a sink only needs to
+/// implement the events it is interested in. Sinks are invoked synchronously and should not
+/// block.
+pub trait ConfidentialMetricsSink: Send + Sync {
+    /// Called before a phase of an operation starts
+    fn before(&self, _operation: &'static str, _phase: ConfidentialPhase) {}
+
+    /// Called after a phase of an operation finished, with its duration and whether it succeeded
+    fn after(
+        &self,
+        _operation: &'static str,
+        _phase: ConfidentialPhase,
+        _elapsed: Duration,
+        _success: bool,
+    ) {
+    }
+}
+
+/// A metrics sink that ignores all events
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopConfidentialMetricsSink;
+
+impl ConfidentialMetricsSink for NoopConfidentialMetricsSink {}
+
+/// Report a synchronous phase of an operation to `sink`
+pub(crate) fn measure<R, E>(
+    sink: &dyn ConfidentialMetricsSink,
+    operation: &'static str,
+    phase: ConfidentialPhase,
+    f: impl FnOnce() -> Result<R, E>,
+) -> Result<R, E> {
+    sink.before(operation, phase);
+    let start = Instant::now();
+    let result = f();
+    sink.after(operation, phase, start.elapsed(), result.is_ok());
+    result
+}
+
+/// Report an asynchronous phase of an operation to `sink`
+pub(crate) async fn measure_async<R, E>(
+    sink: &dyn ConfidentialMetricsSink,
+    operation: &'static str,
+    phase: ConfidentialPhase,
+    future: impl Future<Output = Result<R, E>>,
+) -> Result<R, E> {
+    sink.before(operation, phase);
+    let start = Instant::now();
+    let result = future.await;
+    sink.after(operation, phase, start.elapsed(), result.is_ok());
+    result
+}
@@ -1,18 +1,32 @@
 use {
-    crate::client::{ProgramClient, ProgramClientError, SendTransaction, SimulateTransaction},
+    crate::{
+        client::{ProgramClient, ProgramClientError, SendTransaction, SimulateTransaction},
+        decryption::ConfidentialBalanceDecryptor,
+        metrics::{
+            measure, measure_async, ConfidentialMetricsSink, ConfidentialPhase,
+            NoopConfidentialMetricsSink,
+        },
+    },
     futures_util::TryFutureExt,
-    solana_program_test::tokio::time,
+    solana_program_test::{tokio::time, BanksClientError},
+    solana_rpc_client_api::{
+        client_error::Error as RpcClientError, request::MAX_MULTIPLE_ACCOUNTS,
+    },
     solana_sdk::{
-        account::Account as BaseAccount,
+        account::{from_account, Account as BaseAccount},
+        clock::Clock,
+        compute_budget::{self, ComputeBudgetInstruction},
         hash::Hash,
-        instruction::{AccountMeta, Instruction},
+        instruction::{AccountMeta, Instruction, InstructionError},
         message::Message,
+        packet::PACKET_DATA_SIZE,
         program_error::ProgramError,
         program_pack::Pack,
-        pubkey::Pubkey,
+        pubkey::{Pubkey, MAX_SEED_LEN},
         signer::{signers::Signers, Signer, SignerError},
-        system_instruction,
-        transaction::Transaction,
+        system_instruction, sysvar,
+        transaction::{Transaction, TransactionError},
+        transport::TransportError,
     },
     spl_associated_token_account::{
         get_associated_token_address_with_program_id, instruction::create_associated_token_account,
@@ -23,10 +37,32 @@ use {
             confidential_transfer::{
                 self,
                 account_info::{
-                    ApplyPendingBalanceAccountInfo, EmptyAccountAccountInfo, TransferAccountInfo,
-                    WithdrawAccountInfo,
+                    generate_auditor_proof_data, generate_delegate_allowance_proof_data,
+                    generate_maximum_transfer_amount_proof_data,
+                    generate_mint_confidential_proof_data, ApplyPendingBalanceAccountInfo,
+                    AuditorProofData, DelegateAllowanceProofData, EmptyAccountAccountInfo,
+                    EmptyAccountFullAccountInfo, ForwardPendingBalanceAccountInfo,
+                    ForwardPendingBalanceProofData, MaximumTransferAmountProofData,
+                    MintConfidentialProofData, RotateElGamalKeyAccountInfo, TransferAccountInfo,
+                    TransferSplitProofData, TransferSplitValidityProofData, WithdrawAccountInfo,
+                },
+                instruction::{
+                    close_context_state, BatchedGroupedCiphertext2HandlesValidityProofContext,
+                    BatchedRangeProofContext, CiphertextCiphertextEqualityProofContext,
+                    CiphertextCommitmentEqualityProofContext, ConfidentialTransferInstruction,
+                    ContextStateInfo, ProofContextState, ProofInstruction,
+                    PubkeyValidityProofContext, TransferData, TransferProofContext, WithdrawData,
+                    WithdrawProofContext, ZeroBalanceProofContext,
                 },
-                ConfidentialTransferAccount,
+                reserves::ReservesAttestation,
+                CiphertextBytesEq, ConfidentialTransferAccount, ConfidentialTransferAuditors,
+                ConfidentialTransferCreditsUntilSlot, ConfidentialTransferDelegateAllowance,
+                ConfidentialTransferMaximumTransferAmount, ConfidentialTransferPullAuthorization,
+                DecryptableBalance as PodDecryptableBalance, EncryptedBalance,
+                EncryptedTransferReference,
+            },
+            confidential_transfer_fee::{
+                self, ConfidentialTransferFeeAmount, ConfidentialTransferFeeConfig,
             },
             cpi_guard, default_account_state, interest_bearing_mint, memo_transfer,
             metadata_pointer, transfer_fee, transfer_hook, BaseStateWithExtensions, ExtensionType,
@@ -36,16 +72,21 @@ use {
         proof::ProofLocation,
         solana_zk_token_sdk::{
             encryption::{
-                auth_encryption::AeKey,
+                auth_encryption::{AeCiphertext, AeKey},
                 elgamal::{ElGamalKeypair, ElGamalPubkey, ElGamalSecretKey},
             },
-            zk_token_elgamal::pod::ElGamalPubkey as PodElGamalPubkey,
+            instruction::ZkProofData,
+            zk_token_elgamal::pod::{
+                ElGamalPubkey as PodElGamalPubkey, Pod, TransferAmountCiphertext, Zeroable,
+            },
+            zk_token_proof_program,
         },
         state::{Account, AccountState, Mint, Multisig},
     },
     spl_token_metadata_interface::state::{Field, TokenMetadata},
     std::{
         fmt, io,
+        mem::size_of,
         sync::{Arc, RwLock},
         time::{Duration, Instant},
     },
@@ -85,14 +126,28 @@ pub enum TokenError {
     Key(SignerError),
     #[error("account decryption failed")]
     AccountDecryption,
+    #[error("ElGamal balance decryption failed")]
+    ElGamalBalanceDecryption,
+    #[error("authenticated balance decryption failed")]
+    AeBalanceDecryption,
+    #[error("available balance may exceed the maximum available balance")]
+    MaximumAvailableBalanceExceeded,
     #[error("not enough funds in account")]
     NotEnoughFunds,
+    #[error("insufficient confidential funds: requested {requested}, available {available}")]
+    InsufficientConfidentialFunds { requested: u64, available: u64 },
     #[error("missing memo signer")]
     MissingMemoSigner,
+    #[error("missing memo before a transfer into {0}, which requires memos")]
+    MissingMemo(Pubkey),
     #[error("decimals required, but missing")]
     MissingDecimals,
     #[error("decimals specified, but incorrect")]
     InvalidDecimals,
+    #[error("ElGamal public key cannot be proven valid")]
+    InvalidElGamalPubkey,
+    #[error("destination ElGamal public key changed since it was fetched")]
+    DestinationElGamalPubkeyChanged,
 }
 impl PartialEq for TokenError {
     fn eq(&self, other: &Self) -> bool {
@@ -112,10 +167,26 @@ impl PartialEq for TokenError {
                 Self::MaximumDepositTransferAmountExceeded,
             ) => true,
             (Self::AccountDecryption, Self::AccountDecryption) => true,
+            (Self::ElGamalBalanceDecryption, Self::ElGamalBalanceDecryption) => true,
+            (Self::AeBalanceDecryption, Self::AeBalanceDecryption) => true,
+            (Self::MaximumAvailableBalanceExceeded, Self::MaximumAvailableBalanceExceeded) => true,
             (Self::NotEnoughFunds, Self::NotEnoughFunds) => true,
+            (
+                Self::InsufficientConfidentialFunds {
+                    requested: requested_a,
+                    available: available_a,
+                },
+                Self::InsufficientConfidentialFunds {
+                    requested: requested_b,
+                    available: available_b,
+                },
+            ) => requested_a == requested_b && available_a == available_b,
             (Self::MissingMemoSigner, Self::MissingMemoSigner) => true,
+            (Self::MissingMemo(ref a), Self::MissingMemo(ref b)) => a == b,
             (Self::MissingDecimals, Self::MissingDecimals) => true,
             (Self::InvalidDecimals, Self::InvalidDecimals) => true,
+            (Self::InvalidElGamalPubkey, Self::InvalidElGamalPubkey) => true,
+            (Self::DestinationElGamalPubkeyChanged, Self::DestinationElGamalPubkeyChanged) => true,
             _ => false,
         }
     }
@@ -157,6 +228,19 @@ pub enum ExtensionInitializationParams {
         authority: Option<Pubkey>,
         metadata_address: Option<Pubkey>,
     },
+    ConfidentialMintSupply {
+        supply_elgamal_pubkey: PodElGamalPubkey,
+    },
+    ConfidentialTransferAuditors {
+        auditor_elgamal_pubkeys: Vec<PodElGamalPubkey>,
+    },
+    ConfidentialTransferMaximumTransferAmount {
+        maximum_transfer_amount: u64,
+    },
+    ConfidentialTransferFeeConfig {
+        authority: Option<Pubkey>,
+        withdraw_withheld_authority_elgamal_pubkey: PodElGamalPubkey,
+    },
 }
 impl ExtensionInitializationParams {
     /// Get the extension type associated with the init params
@@ -171,6 +255,16 @@ impl ExtensionInitializationParams {
             Self::PermanentDelegate { .. } => ExtensionType::PermanentDelegate,
             Self::TransferHook { .. } => ExtensionType::TransferHook,
             Self::MetadataPointer { .. } => ExtensionType::MetadataPointer,
+            Self::ConfidentialMintSupply { .. } => ExtensionType::ConfidentialMintSupply,
+            Self::ConfidentialTransferAuditors { .. } => {
+                ExtensionType::ConfidentialTransferAuditors
+            }
+            Self::ConfidentialTransferMaximumTransferAmount { .. } => {
+                ExtensionType::ConfidentialTransferMaximumTransferAmount
+            }
+            Self::ConfidentialTransferFeeConfig { .. } => {
+                ExtensionType::ConfidentialTransferFeeConfig
+            }
         }
     }
     /// Generate an appropriate initialization instruction for the given mint
@@ -251,13 +345,265 @@ impl ExtensionInitializationParams {
                 authority,
                 metadata_address,
             ),
+            Self::ConfidentialMintSupply {
+                supply_elgamal_pubkey,
+            } => confidential_transfer::instruction::initialize_confidential_mint_supply(
+                token_program_id,
+                mint,
+                supply_elgamal_pubkey,
+            ),
+            Self::ConfidentialTransferAuditors {
+                auditor_elgamal_pubkeys,
+            } => confidential_transfer::instruction::initialize_auditors(
+                token_program_id,
+                mint,
+                &auditor_elgamal_pubkeys,
+            ),
+            Self::ConfidentialTransferMaximumTransferAmount {
+                maximum_transfer_amount,
+            } => confidential_transfer::instruction::initialize_maximum_transfer_amount(
+                token_program_id,
+                mint,
+                maximum_transfer_amount,
+            ),
+            Self::ConfidentialTransferFeeConfig {
+                authority,
+                withdraw_withheld_authority_elgamal_pubkey,
+            } => {
+                confidential_transfer_fee::instruction::initialize_confidential_transfer_fee_config(
+                    token_program_id,
+                    mint,
+                    authority,
+                    withdraw_withheld_authority_elgamal_pubkey,
+                )
+            }
         }
     }
 }
 
 pub type TokenResult<T> = Result<T, TokenError>;
 
-#[derive(Debug)]
+const DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER: u64 = 65536;
+
+/// A balance encrypted under the AES key of a confidential token account
+///
+/// Confidential token accounts and instructions store this as the pod `DecryptableBalance`, which
+/// converts to and from this type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecryptableBalance(AeCiphertext);
+
+impl DecryptableBalance {
+    /// Encrypt `amount` under `aes_key`
+    pub fn encrypt(aes_key: &AeKey, amount: u64) -> Self {
+        Self(aes_key.encrypt(amount))
+    }
+
+    /// Decrypt the balance with `aes_key`, or `None` if it was encrypted under a different key
+    pub fn decrypt(&self, aes_key: &AeKey) -> Option<u64> {
+        aes_key.decrypt(&self.0)
+    }
+}
+
+impl From<AeCiphertext> for DecryptableBalance {
+    fn from(ciphertext: AeCiphertext) -> Self {
+        Self(ciphertext)
+    }
+}
+
+impl From<DecryptableBalance> for AeCiphertext {
+    fn from(balance: DecryptableBalance) -> Self {
+        balance.0
+    }
+}
+
+impl From<DecryptableBalance> for PodDecryptableBalance {
+    fn from(balance: DecryptableBalance) -> Self {
+        balance.0.into()
+    }
+}
+
+impl TryFrom<PodDecryptableBalance> for DecryptableBalance {
+    type Error = TokenError;
+
+    fn try_from(balance: PodDecryptableBalance) -> Result<Self, Self::Error> {
+        balance
+            .try_into()
+            .map(Self)
+            .map_err(|_| TokenError::AeBalanceDecryption)
+    }
+}
+
+/// Decrypted balances of a confidential token account
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConfidentialTokenAccountBalances {
+    pub pending_balance_lo: u64,
+    pub pending_balance_hi: u64,
+    pub available_balance: u64,
+    pub decryptable_available_balance: u64,
+}
+
+/// Summary of a confidential token account for display, e.g. in a wallet
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AccountSummary {
+    /// The available balance, which can be spent right away
+    pub available: u64,
+    /// The pending balance, which must be applied before it can be spent
+    pub pending: u64,
+    /// Whether the account has pending balance credits to apply
+    pub needs_apply: bool,
+    /// Whether the account accepts confidential deposits and transfers
+    pub credits_enabled: bool,
+    /// Whether the account accepts non-confidential transfers
+    pub non_confidential_credits_enabled: bool,
+}
+
+/// The output of a confidential transfer together with the transfer amount ciphertexts that were
+/// generated for it
+///
+/// Each transfer amount ciphertext embeds a decryption handle with respect to the auditor ElGamal
+/// public key of the mint, so the auditor can recover the transfer amount from the ciphertexts with
+/// `decrypt_transfer_amount_for_auditor` without fetching the transaction from the ledger.
+#[derive(Clone, Debug)]
+pub struct ConfidentialTransferWithCiphertexts<O> {
+    /// The output of processing the transfer transaction, e.g. its signature
+    pub output: O,
+    /// The ciphertext of the low 16 bits of the transfer amount
+    pub transfer_amount_ciphertext_lo: TransferAmountCiphertext,
+    /// The ciphertext of the high 32 bits of the transfer amount
+    pub transfer_amount_ciphertext_hi: TransferAmountCiphertext,
+}
+
+/// The output of a confidential withdraw or transfer together with the new decryptable available
+/// balance that was submitted for the source account
+///
+/// The new decryptable available balance lets clients update a cached balance without fetching
+/// the account again. It is only valid if the transaction succeeded.
+#[derive(Clone, Debug)]
+pub struct ConfidentialTransferWithDecryptableBalance<O> {
+    /// The output of processing the transaction, e.g. its signature
+    pub output: O,
+    /// The new decryptable available balance of the source account
+    pub new_decryptable_available_balance: DecryptableBalance,
+}
+
+/// The simulation of a confidential transfer together with the balances it predicts
+///
+/// The predicted balances are only valid if the simulation succeeded.
+#[derive(Clone, Debug)]
+pub struct ConfidentialTransferSimulation<S> {
+    /// The output of simulating the transaction, which reports whether the transfer would succeed
+    pub simulation: S,
+    /// The available balance of the source account after the transfer
+    pub source_available_balance: u64,
+    /// The pending balance of the destination account after the transfer, if the destination
+    /// ElGamal keypair was provided
+    pub destination_pending_balance: Option<u64>,
+}
+
+/// One leg of a confidential atomic swap: a confidential transfer of the tokens of one mint from
+/// one swap party to the other
+pub struct ConfidentialSwapLeg<'a> {
+    /// The source token account
+    pub source_account: &'a Pubkey,
+    /// The destination token account, owned by the owner of the source account of the other leg
+    pub destination_account: &'a Pubkey,
+    /// The owner of the source token account
+    pub source_authority: &'a Pubkey,
+    /// The amount to transfer
+    pub transfer_amount: u64,
+    /// The ElGamal keypair of the source token account
+    pub source_elgamal_keypair: &'a ElGamalKeypair,
+    /// The AES key of the source token account
+    pub source_aes_key: &'a AeKey,
+    /// The ElGamal public key of the destination token account
+    pub destination_elgamal_pubkey: &'a ElGamalPubkey,
+    /// The auditor ElGamal public key of the mint, if any
+    pub auditor_elgamal_pubkey: Option<&'a ElGamalPubkey>,
+    /// The account to verify the transfer proof of the leg into
+    pub proof_account: &'a dyn Signer,
+}
+
+/// The first component of a confidential token account that prevents the account from being
+/// closed
+///
+/// Decrypted amounts are `None` if they could not be decrypted with the account ElGamal keypair.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClosableBlocker {
+    /// The low bits of the pending balance are non-zero
+    PendingBalanceLo { amount: Option<u64> },
+    /// The high bits of the pending balance are non-zero
+    PendingBalanceHi { amount: Option<u64> },
+    /// The available balance is non-zero
+    AvailableBalance { amount: Option<u64> },
+    /// The available balance is zero, but it has not been cleared by `EmptyAccount`
+    AvailableBalanceNotEmptied,
+    /// The account holds withheld transfer fees
+    WithheldAmount,
+}
+
+impl fmt::Display for ClosableBlocker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClosableBlocker::PendingBalanceLo { .. } | ClosableBlocker::PendingBalanceHi { .. } => {
+                write!(f, "apply the pending balance first")
+            }
+            ClosableBlocker::AvailableBalance { .. } => {
+                write!(f, "withdraw the available balance first")
+            }
+            ClosableBlocker::AvailableBalanceNotEmptied => {
+                write!(f, "empty the account with `EmptyAccount` first")
+            }
+            ClosableBlocker::WithheldAmount => {
+                write!(f, "harvest the withheld fees to the mint first")
+            }
+        }
+    }
+}
+
+/// Compute units consumed by a confidential `Deposit` instruction, which performs a single
+/// ciphertext addition on the pending balance
+pub const CONFIDENTIAL_DEPOSIT_COMPUTE_UNITS: u32 = 20_000;
+
+/// Compute units consumed by a confidential `Withdraw` instruction, including the 112,454 units of
+/// the `VerifyWithdraw` proof instruction in the same transaction
+pub const CONFIDENTIAL_WITHDRAW_COMPUTE_UNITS: u32 = 130_000;
+
+/// Compute units consumed by a confidential `Transfer` instruction, including the 219,290 units of
+/// the `VerifyTransfer` proof instruction in the same transaction
+pub const CONFIDENTIAL_TRANSFER_COMPUTE_UNITS: u32 = 255_000;
+
+/// Compute units consumed by a confidential `Transfer` instruction on a mint with transfer fees,
+/// including the 407,121 units of the `VerifyTransferWithFee` proof instruction in the same
+/// transaction
+pub const CONFIDENTIAL_TRANSFER_WITH_FEE_COMPUTE_UNITS: u32 = 465_000;
+
+/// Compute units consumed by a confidential `ApplyPendingBalance` instruction, which combines the
+/// low and high pending balance ciphertexts into the available balance
+pub const CONFIDENTIAL_APPLY_PENDING_BALANCE_COMPUTE_UNITS: u32 = 25_000;
+
+/// Confidential transfer operations with a known compute unit cost
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfidentialOp {
+    Deposit,
+    Withdraw,
+    Transfer,
+    TransferWithFee,
+    ApplyPendingBalance,
+}
+
+/// Compute unit limit that is prepended to transactions with confidential transfer instructions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ComputeUnitLimit {
+    /// Do not prepend a compute unit limit
+    Default,
+    /// Prepend the given compute unit limit
+    Static(u32),
+    /// Prepend the sum of the compute unit estimates of the confidential transfer operations in the
+    /// transaction
+    Auto,
+}
+
+#[derive(Clone, Debug)]
 struct TokenMemo {
     text: String,
     signers: Vec<Pubkey>,
@@ -282,6 +628,11 @@ pub struct Token<T> {
     nonce_blockhash: Option<Hash>,
     memo: Arc<RwLock<Option<TokenMemo>>>,
     transfer_hook_accounts: Option<Vec<Pubkey>>,
+    retry_on_balance_mismatch: bool,
+    check_destination_elgamal_pubkey: bool,
+    compute_unit_limit: ComputeUnitLimit,
+    balance_decryptor: Arc<ConfidentialBalanceDecryptor>,
+    metrics_sink: Arc<dyn ConfidentialMetricsSink>,
 }
 
 impl<T> fmt::Debug for Token<T> {
@@ -299,6 +650,13 @@ impl<T> fmt::Debug for Token<T> {
             .field("nonce_blockhash", &self.nonce_blockhash)
             .field("memo", &self.memo.read().unwrap())
             .field("transfer_hook_accounts", &self.transfer_hook_accounts)
+            .field("retry_on_balance_mismatch", &self.retry_on_balance_mismatch)
+            .field(
+                "check_destination_elgamal_pubkey",
+                &self.check_destination_elgamal_pubkey,
+            )
+            .field("compute_unit_limit", &self.compute_unit_limit)
+            .field("balance_decryptor", &self.balance_decryptor)
             .finish()
     }
 }
@@ -323,6 +681,219 @@ fn native_mint_decimals(program_id: &Pubkey) -> u8 {
     }
 }
 
+/// Returns whether a transaction failed with `ConfidentialTransferBalanceMismatch`, which
+/// indicates that a proof was generated against an outdated available balance
+fn is_confidential_transfer_balance_mismatch(error: &TokenError) -> bool {
+    let client_error = match error {
+        TokenError::Client(client_error) => client_error,
+        _ => return false,
+    };
+    let transaction_error = if let Some(error) = client_error.downcast_ref::<BanksClientError>() {
+        match error {
+            BanksClientError::TransactionError(error)
+            | BanksClientError::SimulationError { err: error, .. } => Some(error.clone()),
+            _ => None,
+        }
+    } else if let Some(error) = client_error.downcast_ref::<RpcClientError>() {
+        error.get_transaction_error()
+    } else if let Some(TransportError::TransactionError(error)) =
+        client_error.downcast_ref::<TransportError>()
+    {
+        Some(error.clone())
+    } else {
+        None
+    };
+    matches!(
+        transaction_error,
+        Some(TransactionError::InstructionError(_, InstructionError::Custom(code)))
+            if code == spl_token_2022::error::TokenError::ConfidentialTransferBalanceMismatch as u32
+    )
+}
+
+/// Extensions that a token account needs to be configured for confidential transfers
+///
+/// The `ConfidentialTransferFeeAmount` extension is needed for mints with transfer fees, and the
+/// `MemoTransfer` extension if required memo transfers are enabled at the same time.
+fn confidential_transfer_account_extension_types(
+    mint_has_transfer_fee: bool,
+    require_memo_on_incoming: bool,
+) -> Vec<ExtensionType> {
+    let mut extension_types = vec![ExtensionType::ConfidentialTransferAccount];
+    if mint_has_transfer_fee {
+        extension_types.push(ExtensionType::ConfidentialTransferFeeAmount);
+    }
+    if require_memo_on_incoming {
+        extension_types.push(ExtensionType::MemoTransfer);
+    }
+    extension_types
+}
+
+/// Fail with `InsufficientConfidentialFunds` if the decryptable available balance of a
+/// confidential token account is less than the requested amount.
+///
+/// If the balance cannot be decrypted, the check is skipped and the failure is reported when the
+/// proofs for the instruction are generated.
+fn check_confidential_funds(
+    decryptable_available_balance: &PodDecryptableBalance,
+    requested: u64,
+    aes_key: &AeKey,
+) -> TokenResult<()> {
+    match DecryptableBalance::try_from(*decryptable_available_balance)
+        .ok()
+        .and_then(|balance| balance.decrypt(aes_key))
+    {
+        Some(available) if available < requested => {
+            Err(TokenError::InsufficientConfidentialFunds {
+                requested,
+                available,
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the ElGamal public key of a destination account is the key that a transfer proof
+/// is about to be generated for
+fn check_destination_elgamal_pubkey(
+    destination_account: &StateWithExtensionsOwned<Account>,
+    destination_elgamal_pubkey: &ElGamalPubkey,
+) -> TokenResult<()> {
+    let extension = destination_account.get_extension::<ConfidentialTransferAccount>()?;
+    if extension.elgamal_pubkey != PodElGamalPubkey::from(*destination_elgamal_pubkey) {
+        return Err(TokenError::DestinationElGamalPubkeyChanged);
+    }
+    Ok(())
+}
+
+/// Checks an ElGamal public key before it is submitted in a `PubkeyValidity` proof
+pub trait ElGamalPubkeyValidity {
+    /// Whether the public key passes the `PubkeyValidity` proof of `ConfigureAccount`
+    ///
+    /// A public key is valid if it is a point on the Ristretto curve other than the identity.
+    /// Decoding an `ElGamalPubkey` already guarantees that it is on the curve, so this only rejects
+    /// the identity, which is the public key of the zero secret key and cannot be proven valid.
+    fn is_valid_for_proof(&self) -> bool;
+}
+
+impl ElGamalPubkeyValidity for ElGamalPubkey {
+    fn is_valid_for_proof(&self) -> bool {
+        // the identity point compresses to all zero bytes
+        self.to_bytes() != [0; 32]
+    }
+}
+
+/// Decrypt the pending, available, and decryptable available balances of a confidential token
+/// account
+fn decrypt_confidential_balances(
+    decryptor: &ConfidentialBalanceDecryptor,
+    extension: &ConfidentialTransferAccount,
+    elgamal_keypair: &ElGamalKeypair,
+    aes_key: &AeKey,
+) -> TokenResult<ConfidentialTokenAccountBalances> {
+    let pending_balance_lo = decryptor
+        .decrypt(elgamal_keypair, &extension.pending_balance_lo)
+        .ok_or(TokenError::ElGamalBalanceDecryption)?;
+    let pending_balance_hi = decryptor
+        .decrypt(elgamal_keypair, &extension.pending_balance_hi)
+        .ok_or(TokenError::ElGamalBalanceDecryption)?;
+    let available_balance = decryptor
+        .decrypt(elgamal_keypair, &extension.available_balance)
+        .ok_or(TokenError::ElGamalBalanceDecryption)?;
+
+    let decryptable_available_balance =
+        DecryptableBalance::try_from(extension.decryptable_available_balance)?
+            .decrypt(aes_key)
+            .ok_or(TokenError::AeBalanceDecryption)?;
+
+    Ok(ConfidentialTokenAccountBalances {
+        pending_balance_lo,
+        pending_balance_hi,
+        available_balance,
+        decryptable_available_balance,
+    })
+}
+
+/// Decrypt the balances of several confidential token accounts, spreading the discrete log work
+/// over the available threads
+fn decrypt_confidential_balances_in_parallel(
+    decryptor: &ConfidentialBalanceDecryptor,
+    extensions: &[ConfidentialTransferAccount],
+    elgamal_keypair: &ElGamalKeypair,
+    aes_key: &AeKey,
+) -> Vec<TokenResult<ConfidentialTokenAccountBalances>> {
+    if extensions.is_empty() {
+        return vec![];
+    }
+    let threads = std::thread::available_parallelism()
+        .map(usize::from)
+        .unwrap_or(1);
+    let chunk_size = (extensions.len() + threads - 1) / threads;
+
+    std::thread::scope(|scope| {
+        extensions
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|extension| {
+                            decrypt_confidential_balances(
+                                decryptor,
+                                extension,
+                                elgamal_keypair,
+                                aes_key,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("balance decryption panicked"))
+            .collect()
+    })
+}
+
+/// Generate the proof data for a confidential withdraw
+///
+/// Proof generation is CPU-intensive and does not touch the network, so callers that cannot
+/// block, such as browser wallets, can run it separately, e.g. in a web worker, and submit the
+/// result with `confidential_transfer_withdraw_with_proof`.
+pub fn build_withdraw_proof_data(
+    account_info: &WithdrawAccountInfo,
+    withdraw_amount: u64,
+    elgamal_keypair: &ElGamalKeypair,
+    aes_key: &AeKey,
+) -> TokenResult<WithdrawData> {
+    account_info
+        .generate_proof_data(withdraw_amount, elgamal_keypair, aes_key)
+        .map_err(|_| TokenError::ProofGeneration)
+}
+
+/// Generate the proof data for a confidential transfer
+///
+/// Proof generation is CPU-intensive and does not touch the network, so callers that cannot
+/// block, such as browser wallets, can run it separately, e.g. in a web worker, and submit the
+/// result with `confidential_transfer_transfer_with_proof`.
+pub fn build_transfer_proof_data(
+    account_info: &TransferAccountInfo,
+    transfer_amount: u64,
+    source_elgamal_keypair: &ElGamalKeypair,
+    source_aes_key: &AeKey,
+    destination_elgamal_pubkey: &ElGamalPubkey,
+    auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+) -> TokenResult<TransferData> {
+    account_info
+        .generate_transfer_proof_data(
+            transfer_amount,
+            source_elgamal_keypair,
+            source_aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+        )
+        .map_err(|_| TokenError::ProofGeneration)
+}
+
 impl<T> Token<T>
 where
     T: SendTransaction + SimulateTransaction,
@@ -345,6 +916,11 @@ where
             nonce_blockhash: None,
             memo: Arc::new(RwLock::new(None)),
             transfer_hook_accounts: None,
+            retry_on_balance_mismatch: false,
+            check_destination_elgamal_pubkey: false,
+            compute_unit_limit: ComputeUnitLimit::Default,
+            balance_decryptor: Arc::new(ConfidentialBalanceDecryptor::new()),
+            metrics_sink: Arc::new(NoopConfidentialMetricsSink),
         }
     }
 
@@ -394,6 +970,85 @@ where
         self
     }
 
+    /// Set whether confidential transfers and withdrawals are retried once on a balance mismatch
+    ///
+    /// A confidential transfer or withdraw proof is generated against the available balance of
+    /// the source account at the time it is fetched. If the available balance changes on-chain
+    /// before the transaction is processed, for example due to a concurrent `ApplyPendingBalance`,
+    /// the transaction fails with `ConfidentialTransferBalanceMismatch`. If enabled,
+    /// `confidential_transfer_transfer` and `confidential_transfer_withdraw` then fetch the current
+    /// available balance, regenerate the proof, and retry once. The retry is disabled by default,
+    /// and is skipped for proofs in context state accounts, which cannot be regenerated, and for
+    /// durable nonce transactions, since the failed transaction advances the nonce.
+    pub fn with_retry_on_balance_mismatch(mut self, retry_on_balance_mismatch: bool) -> Self {
+        self.retry_on_balance_mismatch = retry_on_balance_mismatch;
+        self
+    }
+
+    /// Set whether confidential transfers check the destination ElGamal public key before the
+    /// proof is generated
+    ///
+    /// A transfer proof encrypts the transfer amount under the destination ElGamal public key that
+    /// the caller provides, which is typically fetched well before the transfer is submitted. If
+    /// the destination rotates its key in the meantime, the program rejects the transfer with
+    /// `ConfidentialTransferElGamalPubkeyMismatch` only after the proof was generated. When
+    /// enabled, `confidential_transfer_transfer` and `confidential_transfer_transfer_with_fee`
+    /// fetch the destination account right before the proof is generated and fail with
+    /// `DestinationElGamalPubkeyChanged` if its key differs from the provided one. Disabled by
+    /// default, since it costs an additional account fetch for transfers with fees.
+    pub fn with_destination_elgamal_pubkey_check(
+        mut self,
+        check_destination_elgamal_pubkey: bool,
+    ) -> Self {
+        self.check_destination_elgamal_pubkey = check_destination_elgamal_pubkey;
+        self
+    }
+
+    /// Prepend a `ComputeBudgetInstruction::SetComputeUnitLimit` instruction with the given limit
+    /// to every transaction with a confidential transfer instruction
+    ///
+    /// Transactions that already include a compute budget instruction are left untouched.
+    pub fn with_compute_unit_limit(mut self, compute_unit_limit: u32) -> Self {
+        self.compute_unit_limit = ComputeUnitLimit::Static(compute_unit_limit);
+        self
+    }
+
+    /// Prepend a `ComputeBudgetInstruction::SetComputeUnitLimit` instruction sized with
+    /// `confidential_transfer_estimate_compute_units` to every transaction with a confidential
+    /// transfer operation that has a known compute unit cost
+    ///
+    /// Transactions that already include a compute budget instruction are left untouched.
+    pub fn with_auto_compute_budget(mut self) -> Self {
+        self.compute_unit_limit = ComputeUnitLimit::Auto;
+        self
+    }
+
+    /// Cache up to `capacity` decrypted ElGamal ciphertexts of confidential balances
+    ///
+    /// Reading the balances of an account again only pays the discrete log cost for the
+    /// ciphertexts that changed since the last read, which speeds up tools that poll balances.
+    /// The cache is used by `confidential_transfer_get_balances`,
+    /// `confidential_transfer_get_balances_batch`, and `confidential_transfer_account_summary`.
+    pub fn with_decryption_cache(mut self, capacity: usize) -> Self {
+        self.balance_decryptor = Arc::new(ConfidentialBalanceDecryptor::with_cache(capacity));
+        self
+    }
+
+    /// Number of decrypted ciphertexts currently in the cache set with `with_decryption_cache`
+    pub fn decryption_cache_len(&self) -> usize {
+        self.balance_decryptor.cached_len()
+    }
+
+    /// Report the timings of proof generation, transaction submission, and balance decryption to
+    /// `metrics_sink`
+    ///
+    /// The sink is invoked by `confidential_transfer_transfer`, `confidential_transfer_withdraw`,
+    /// and `confidential_transfer_get_balances`.
+    pub fn with_metrics_sink(mut self, metrics_sink: Arc<dyn ConfidentialMetricsSink>) -> Self {
+        self.metrics_sink = metrics_sink;
+        self
+    }
+
     pub fn with_memo<M: AsRef<str>>(&self, memo: M, signers: Vec<Pubkey>) -> &Self {
         let mut w_memo = self.memo.write().unwrap();
         *w_memo = Some(TokenMemo {
@@ -473,6 +1128,12 @@ where
             }
         }
 
+        if let Some(compute_unit_limit) = self.compute_unit_limit_for(token_instructions) {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(
+                compute_unit_limit,
+            ));
+        }
+
         instructions.extend_from_slice(token_instructions);
 
         let (message, blockhash) =
@@ -518,6 +1179,87 @@ where
         Ok(transaction)
     }
 
+    /// Compute unit limit to prepend to a transaction with the given instructions, if any
+    fn compute_unit_limit_for(&self, token_instructions: &[Instruction]) -> Option<u32> {
+        if self.compute_unit_limit == ComputeUnitLimit::Default
+            || token_instructions
+                .iter()
+                .any(|instruction| instruction.program_id == compute_budget::id())
+        {
+            return None;
+        }
+
+        let confidential_transfer_instructions = token_instructions
+            .iter()
+            .filter(|instruction| {
+                instruction.program_id == self.program_id
+                    && matches!(
+                        instruction::TokenInstruction::unpack(&instruction.data),
+                        Ok(instruction::TokenInstruction::ConfidentialTransferExtension)
+                    )
+            })
+            .filter_map(|instruction| {
+                instruction
+                    .data
+                    .get(1)
+                    .and_then(|data| ConfidentialTransferInstruction::try_from(*data).ok())
+            })
+            .collect::<Vec<_>>();
+        if confidential_transfer_instructions.is_empty() {
+            return None;
+        }
+
+        match self.compute_unit_limit {
+            ComputeUnitLimit::Default => None,
+            ComputeUnitLimit::Static(compute_unit_limit) => Some(compute_unit_limit),
+            ComputeUnitLimit::Auto => {
+                let with_fee = token_instructions.iter().any(|instruction| {
+                    instruction.program_id
+                        == spl_token_2022::solana_zk_token_sdk::zk_token_proof_program::id()
+                        && ProofInstruction::instruction_type(&instruction.data)
+                            == Some(ProofInstruction::VerifyTransferWithFee)
+                });
+                let compute_unit_limit = confidential_transfer_instructions
+                    .into_iter()
+                    .filter_map(|instruction| match instruction {
+                        ConfidentialTransferInstruction::Deposit
+                        | ConfidentialTransferInstruction::DepositAndApply => {
+                            Some(ConfidentialOp::Deposit)
+                        }
+                        ConfidentialTransferInstruction::Withdraw
+                        | ConfidentialTransferInstruction::WithdrawTo => {
+                            Some(ConfidentialOp::Withdraw)
+                        }
+                        ConfidentialTransferInstruction::Transfer
+                        | ConfidentialTransferInstruction::TransferChecked
+                        | ConfidentialTransferInstruction::TransferWithReference
+                        | ConfidentialTransferInstruction::TransferWithAdditionalProofs
+                        | ConfidentialTransferInstruction::PullTransfer
+                        | ConfidentialTransferInstruction::DelegatedTransfer
+                            if with_fee =>
+                        {
+                            Some(ConfidentialOp::TransferWithFee)
+                        }
+                        ConfidentialTransferInstruction::Transfer
+                        | ConfidentialTransferInstruction::TransferChecked
+                        | ConfidentialTransferInstruction::TransferWithReference
+                        | ConfidentialTransferInstruction::TransferWithAdditionalProofs
+                        | ConfidentialTransferInstruction::PullTransfer
+                        | ConfidentialTransferInstruction::DelegatedTransfer => {
+                            Some(ConfidentialOp::Transfer)
+                        }
+                        ConfidentialTransferInstruction::ApplyPendingBalance => {
+                            Some(ConfidentialOp::ApplyPendingBalance)
+                        }
+                        _ => None,
+                    })
+                    .map(|operation| self.confidential_transfer_estimate_compute_units(operation))
+                    .fold(0u32, u32::saturating_add);
+                (compute_unit_limit > 0).then_some(compute_unit_limit)
+            }
+        }
+    }
+
     pub async fn simulate_ixs<S: Signers>(
         &self,
         token_instructions: &[Instruction],
@@ -644,6 +1386,77 @@ where
         get_associated_token_address_with_program_id(owner, &self.pubkey, &self.program_id)
     }
 
+    /// Get the address of the confidential token account of `owner` for this mint
+    ///
+    /// The address is not a program derived address. It is derived with
+    /// `Pubkey::create_with_seed` from `owner` as the base, the first `MAX_SEED_LEN` characters
+    /// of the base58 mint address as the seed, and the token program as the owning program, so
+    /// that it can be located from the owner and mint alone and differs from the associated token
+    /// account. No private key exists for the address, but `owner` must sign to create the
+    /// account, so the owner cannot be a multisig.
+    pub fn get_associated_confidential_account_address(&self, owner: &Pubkey) -> Pubkey {
+        Pubkey::create_with_seed(owner, &self.confidential_account_seed(), &self.program_id)
+            .unwrap()
+    }
+
+    fn confidential_account_seed(&self) -> String {
+        // a base58 encoded pubkey is at least 32 characters long
+        self.pubkey.to_string()[..MAX_SEED_LEN].to_string()
+    }
+
+    /// Create the confidential token account of `owner` at the address of
+    /// `get_associated_confidential_account_address` and configure it for confidential transfers
+    ///
+    /// The account is created with space for the confidential transfer extension in one
+    /// transaction, which `owner` must sign as the base of the address, and configured in a
+    /// second transaction as in `confidential_transfer_configure_token_account`.
+    pub async fn create_associated_confidential_account<S: Signers>(
+        &self,
+        owner: &Pubkey,
+        maximum_pending_balance_credit_counter: Option<u64>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let account = self.get_associated_confidential_account_address(owner);
+
+        let required_extensions = self
+            .required_account_extensions(vec![ExtensionType::ConfidentialTransferAccount])
+            .await?;
+        let space = ExtensionType::try_calculate_account_len::<Account>(&required_extensions)?;
+        let create_account_instruction = system_instruction::create_account_with_seed(
+            &self.payer.pubkey(),
+            &account,
+            owner,
+            &self.confidential_account_seed(),
+            self.client
+                .get_minimum_balance_for_rent_exemption(space)
+                .await
+                .map_err(TokenError::Client)?,
+            space as u64,
+            &self.program_id,
+        );
+        let instructions = self.initialize_token_account_instructions(
+            create_account_instruction,
+            &account,
+            owner,
+            &required_extensions,
+        )?;
+        self.process_ixs(&instructions, signing_keypairs).await?;
+
+        self.confidential_transfer_configure_token_account(
+            &account,
+            owner,
+            None,
+            maximum_pending_balance_credit_counter,
+            false,
+            elgamal_keypair,
+            aes_key,
+            signing_keypairs,
+        )
+        .await
+    }
+
     /// Create and initialize the associated account.
     pub async fn create_associated_token_account(&self, owner: &Pubkey) -> TokenResult<T::Output> {
         self.process_ixs::<[&dyn Signer; 0]>(
@@ -675,6 +1488,45 @@ where
         owner: &Pubkey,
         extensions: Vec<ExtensionType>,
     ) -> TokenResult<T::Output> {
+        let instructions = self
+            .create_auxiliary_token_account_instructions(&account.pubkey(), owner, extensions)
+            .await?;
+        self.process_ixs(&instructions, &[account]).await
+    }
+
+    async fn create_auxiliary_token_account_instructions(
+        &self,
+        account: &Pubkey,
+        owner: &Pubkey,
+        extensions: Vec<ExtensionType>,
+    ) -> TokenResult<Vec<Instruction>> {
+        let required_extensions = self.required_account_extensions(extensions).await?;
+        let space = ExtensionType::try_calculate_account_len::<Account>(&required_extensions)?;
+        let create_account_instruction = system_instruction::create_account(
+            &self.payer.pubkey(),
+            account,
+            self.client
+                .get_minimum_balance_for_rent_exemption(space)
+                .await
+                .map_err(TokenError::Client)?,
+            space as u64,
+            &self.program_id,
+        );
+
+        self.initialize_token_account_instructions(
+            create_account_instruction,
+            account,
+            owner,
+            &required_extensions,
+        )
+    }
+
+    /// The extensions of a new token account of this mint: those required by the mint, followed
+    /// by `extensions`
+    async fn required_account_extensions(
+        &self,
+        extensions: Vec<ExtensionType>,
+    ) -> TokenResult<Vec<ExtensionType>> {
         let state = self.get_mint_info().await?;
         let mint_extensions: Vec<ExtensionType> = state.get_extension_types()?;
         let mut required_extensions =
@@ -684,33 +1536,34 @@ where
                 required_extensions.push(extension_type);
             }
         }
-        let space = ExtensionType::try_calculate_account_len::<Account>(&required_extensions)?;
-        let mut instructions = vec![system_instruction::create_account(
-            &self.payer.pubkey(),
-            &account.pubkey(),
-            self.client
-                .get_minimum_balance_for_rent_exemption(space)
-                .await
-                .map_err(TokenError::Client)?,
-            space as u64,
-            &self.program_id,
-        )];
+        Ok(required_extensions)
+    }
+
+    /// Initialize a token account that is created by `create_account_instruction`
+    fn initialize_token_account_instructions(
+        &self,
+        create_account_instruction: Instruction,
+        account: &Pubkey,
+        owner: &Pubkey,
+        required_extensions: &[ExtensionType],
+    ) -> TokenResult<Vec<Instruction>> {
+        let mut instructions = vec![create_account_instruction];
 
         if required_extensions.contains(&ExtensionType::ImmutableOwner) {
             instructions.push(instruction::initialize_immutable_owner(
                 &self.program_id,
-                &account.pubkey(),
+                account,
             )?)
         }
 
         instructions.push(instruction::initialize_account(
             &self.program_id,
-            &account.pubkey(),
+            account,
             &self.pubkey,
             owner,
         )?);
 
-        self.process_ixs(&instructions, &[account]).await
+        Ok(instructions)
     }
 
     /// Retrieve a raw account
@@ -765,6 +1618,38 @@ where
         Ok(account)
     }
 
+    /// Retrieve several token accounts of this mint in a single request
+    ///
+    /// Fails with the first error that `get_account_info` would return for any of the accounts,
+    /// e.g. `AccountInvalidMint` if an account belongs to a different mint.
+    pub async fn get_account_infos(
+        &self,
+        accounts: &[Pubkey],
+    ) -> TokenResult<Vec<StateWithExtensionsOwned<Account>>> {
+        let mut account_infos = Vec::with_capacity(accounts.len());
+        for batch in accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let batch_accounts = self
+                .client
+                .get_multiple_accounts(batch)
+                .await
+                .map_err(TokenError::Client)?;
+
+            for account in batch_accounts {
+                let account = account.ok_or(TokenError::AccountNotFound)?;
+                if account.owner != self.program_id {
+                    return Err(TokenError::AccountInvalidOwner);
+                }
+                let account = StateWithExtensionsOwned::<Account>::unpack(account.data)?;
+                if account.base.mint != *self.get_address() {
+                    return Err(TokenError::AccountInvalidMint);
+                }
+                account_infos.push(account);
+            }
+        }
+
+        Ok(account_infos)
+    }
+
     /// Retrieve the associated account or create one if not found.
     pub async fn get_or_create_associated_account_info(
         &self,
@@ -1521,17 +2406,95 @@ where
         .await
     }
 
-    /// Prevent unsafe usage of token account through CPI
-    pub async fn enable_cpi_guard<S: Signers>(
+    /// Check that every confidential transfer in `token_instructions` into an account that
+    /// requires memos is immediately preceded by a memo instruction
+    ///
+    /// The instructions are checked as they would be submitted by `process_ixs`, including a memo
+    /// set with `with_memo`, which is not consumed. Fails with `MissingMemo` before submission
+    /// instead of letting the transaction fail with `NoMemo`. Destination accounts that cannot be
+    /// fetched are skipped and left for the program to reject.
+    pub async fn confidential_transfer_preflight_memo(
         &self,
-        account: &Pubkey,
-        authority: &Pubkey,
-        signing_keypairs: &S,
-    ) -> TokenResult<T::Output> {
-        let signing_pubkeys = signing_keypairs.pubkeys();
-        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+        token_instructions: &[Instruction],
+    ) -> TokenResult<()> {
+        let is_memo = |instruction: &Instruction| {
+            instruction.program_id == spl_memo::id() || instruction.program_id == spl_memo::v1::id()
+        };
+        let is_confidential_transfer = |instruction: &Instruction| {
+            instruction.program_id == self.program_id
+                && matches!(
+                    instruction::TokenInstruction::unpack(&instruction.data),
+                    Ok(instruction::TokenInstruction::ConfidentialTransferExtension)
+                )
+                && matches!(
+                    instruction
+                        .data
+                        .get(1)
+                        .map(|data| ConfidentialTransferInstruction::try_from(*data)),
+                    Some(Ok(ConfidentialTransferInstruction::Transfer
+                        | ConfidentialTransferInstruction::TransferChecked
+                        | ConfidentialTransferInstruction::TransferWithReference
+                        | ConfidentialTransferInstruction::TransferWithSplitProofs
+                        | ConfidentialTransferInstruction::TransferWithSplitValidityProofs
+                        | ConfidentialTransferInstruction::TransferWithAdditionalProofs
+                        | ConfidentialTransferInstruction::PullTransfer
+                        | ConfidentialTransferInstruction::DelegatedTransfer))
+                )
+        };
 
-        self.process_ixs(
+        let memo_instruction = self
+            .memo
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|memo| memo.to_instruction());
+        let instructions = memo_instruction
+            .iter()
+            .chain(token_instructions)
+            .collect::<Vec<_>>();
+
+        for (index, instruction) in instructions.iter().enumerate() {
+            if !is_confidential_transfer(instruction) {
+                continue;
+            }
+            if index > 0 && is_memo(instructions[index - 1]) {
+                continue;
+            }
+
+            // all confidential transfer instructions take the destination as the second account
+            let destination = match instruction.accounts.get(1) {
+                Some(meta) => meta.pubkey,
+                None => continue,
+            };
+            let memo_required = self
+                .get_account_info(&destination)
+                .await
+                .ok()
+                .and_then(|account| {
+                    account
+                        .get_extension::<memo_transfer::MemoTransfer>()
+                        .ok()
+                        .map(|extension| bool::from(extension.require_incoming_transfer_memos))
+                })
+                .unwrap_or(false);
+            if memo_required {
+                return Err(TokenError::MissingMemo(destination));
+            }
+        }
+        Ok(())
+    }
+
+    /// Prevent unsafe usage of token account through CPI
+    pub async fn enable_cpi_guard<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
             &[cpi_guard::instruction::enable_cpi_guard(
                 &self.program_id,
                 account,
@@ -1659,9 +2622,97 @@ where
         .await
     }
 
+    /// Update the ElGamal public keys of the additional auditors of the mint
+    pub async fn confidential_transfer_update_auditors<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        auditor_elgamal_pubkeys: &[PodElGamalPubkey],
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[confidential_transfer::instruction::update_auditors(
+                &self.program_id,
+                &self.pubkey,
+                authority,
+                &multisig_signers,
+                auditor_elgamal_pubkeys,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Update the maximum amount of a single confidential transfer of the mint
+    pub async fn confidential_transfer_update_maximum_transfer_amount<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        maximum_transfer_amount: u64,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[
+                confidential_transfer::instruction::update_maximum_transfer_amount(
+                    &self.program_id,
+                    &self.pubkey,
+                    authority,
+                    &multisig_signers,
+                    maximum_transfer_amount,
+                )?,
+            ],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Update confidential transfer mint, leaving the auditor ElGamal public key unchanged unless
+    /// requested
+    ///
+    /// The auditor is left unchanged if `auditor_elgamal_pubkey` is `None`, cleared if it is
+    /// `Some(None)`, and set if it is `Some(Some(_))`.
+    pub async fn confidential_transfer_update_mint_partial<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        auto_approve_new_account: bool,
+        auditor_elgamal_pubkey: Option<Option<PodElGamalPubkey>>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[confidential_transfer::instruction::update_mint_partial(
+                &self.program_id,
+                &self.pubkey,
+                authority,
+                &multisig_signers,
+                auto_approve_new_account,
+                auditor_elgamal_pubkey,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
     /// Configures confidential transfers for a token account. If the maximum pending balance
     /// credit counter for the extension is not provided, then it is set to be a default value of
     /// `2^16`.
+    ///
+    /// If the token account is not large enough to hold the confidential transfer extensions, it
+    /// is reallocated in the same transaction.
+    ///
+    /// If `require_memo_on_incoming` is set, required memo transfers are also enabled for the
+    /// account in the same transaction, and the `MemoTransfer` extension is included in the
+    /// reallocation.
+    ///
+    /// The ElGamal public key is checked with `ElGamalPubkeyValidity::is_valid_for_proof` before
+    /// the proof is generated, and `InvalidElGamalPubkey` is returned without sending a
+    /// transaction if it fails.
     #[allow(clippy::too_many_arguments)]
     pub async fn confidential_transfer_configure_token_account<S: Signers>(
         &self,
@@ -1669,11 +2720,14 @@ where
         authority: &Pubkey,
         context_state_account: Option<&Pubkey>,
         maximum_pending_balance_credit_counter: Option<u64>,
+        require_memo_on_incoming: bool,
         elgamal_keypair: &ElGamalKeypair,
         aes_key: &AeKey,
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
-        const DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER: u64 = 65536;
+        if !elgamal_keypair.pubkey().is_valid_for_proof() {
+            return Err(TokenError::InvalidElGamalPubkey);
+        }
 
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
@@ -1697,22 +2751,178 @@ where
             ProofLocation::ContextStateAccount(context_state_account)
         };
 
-        let decryptable_balance = aes_key.encrypt(0);
+        let decryptable_balance = DecryptableBalance::encrypt(aes_key, 0);
 
-        self.process_ixs(
-            &confidential_transfer::instruction::configure_account(
+        let mut instructions = vec![];
+        if let Some(reallocate_instruction) = self
+            .confidential_transfer_reallocate_instruction(
+                account,
+                authority,
+                &multisig_signers,
+                require_memo_on_incoming,
+            )
+            .await?
+        {
+            instructions.push(reallocate_instruction);
+        }
+
+        instructions.extend(confidential_transfer::instruction::configure_account(
+            &self.program_id,
+            account,
+            &self.pubkey,
+            decryptable_balance.into(),
+            maximum_pending_balance_credit_counter,
+            authority,
+            &multisig_signers,
+            proof_location,
+        )?);
+
+        if require_memo_on_incoming {
+            instructions.push(memo_transfer::instruction::enable_required_transfer_memos(
                 &self.program_id,
                 account,
-                &self.pubkey,
-                decryptable_balance,
-                maximum_pending_balance_credit_counter,
                 authority,
                 &multisig_signers,
-                proof_location,
-            )?,
-            signing_keypairs,
-        )
-        .await
+            )?);
+        }
+
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Reallocate a token account to be large enough for the confidential transfer extensions
+    ///
+    /// The `ConfidentialTransferFeeAmount` extension is included if the mint is extended for
+    /// transfer fees. If the account is already large enough, no transaction is submitted and
+    /// `None` is returned.
+    pub async fn confidential_transfer_reallocate_for_account<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<Option<T::Output>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        if let Some(reallocate_instruction) = self
+            .confidential_transfer_reallocate_instruction(
+                account,
+                authority,
+                &multisig_signers,
+                false,
+            )
+            .await?
+        {
+            self.process_ixs(&[reallocate_instruction], signing_keypairs)
+                .await
+                .map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Create a `Reallocate` instruction for the confidential transfer extensions of a token
+    /// account, or `None` if the account is already large enough to hold them
+    async fn confidential_transfer_reallocate_instruction(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        multisig_signers: &[&Pubkey],
+        require_memo_on_incoming: bool,
+    ) -> TokenResult<Option<Instruction>> {
+        let mint_state = self.get_mint_info().await?;
+        let extension_types = confidential_transfer_account_extension_types(
+            mint_state
+                .get_extension::<transfer_fee::TransferFeeConfig>()
+                .is_ok(),
+            require_memo_on_incoming,
+        );
+
+        let account_data = self.get_account(*account).await?.data;
+        let current_account_len = account_data.len();
+        let account_state = StateWithExtensionsOwned::<Account>::unpack(account_data)?;
+
+        // `ExtensionType::try_calculate_account_len` dedupes types, so the new types can simply be
+        // appended to the current types
+        let mut required_extension_types = account_state.get_extension_types()?;
+        required_extension_types.extend_from_slice(&extension_types);
+        let required_account_len =
+            ExtensionType::try_calculate_account_len::<Account>(&required_extension_types)?;
+
+        if current_account_len >= required_account_len {
+            return Ok(None);
+        }
+
+        Ok(Some(instruction::reallocate(
+            &self.program_id,
+            account,
+            &self.payer.pubkey(),
+            authority,
+            multisig_signers,
+            &extension_types,
+        )?))
+    }
+
+    /// Create a new token account with the `ConfidentialTransferAccount` extension and configure
+    /// it for confidential transfers in a single transaction
+    ///
+    /// The pubkey validity proof is included in the same transaction. If `approve_authority` is
+    /// provided, the account is also approved by the confidential transfer mint authority.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_and_configure_confidential_account(
+        &self,
+        account: &dyn Signer,
+        owner: &dyn Signer,
+        maximum_pending_balance_credit_counter: Option<u64>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        approve_authority: Option<&dyn Signer>,
+    ) -> TokenResult<T::Output> {
+        let account_pubkey = account.pubkey();
+        let owner_pubkey = owner.pubkey();
+
+        let mut instructions = self
+            .create_auxiliary_token_account_instructions(
+                &account_pubkey,
+                &owner_pubkey,
+                vec![ExtensionType::ConfidentialTransferAccount],
+            )
+            .await?;
+
+        let maximum_pending_balance_credit_counter = maximum_pending_balance_credit_counter
+            .unwrap_or(DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER);
+
+        let proof_data =
+            confidential_transfer::instruction::PubkeyValidityData::new(elgamal_keypair)
+                .map_err(|_| TokenError::ProofGeneration)?;
+        let proof_location = ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data);
+
+        let decryptable_balance = DecryptableBalance::encrypt(aes_key, 0);
+
+        instructions.extend(confidential_transfer::instruction::configure_account(
+            &self.program_id,
+            &account_pubkey,
+            &self.pubkey,
+            decryptable_balance.into(),
+            maximum_pending_balance_credit_counter,
+            &owner_pubkey,
+            &[],
+            proof_location,
+        )?);
+
+        let mut signing_keypairs = vec![account, owner];
+
+        if let Some(approve_authority) = approve_authority {
+            instructions.push(confidential_transfer::instruction::approve_account(
+                &self.program_id,
+                &account_pubkey,
+                &self.pubkey,
+                &approve_authority.pubkey(),
+                &[],
+            )?);
+            signing_keypairs.push(approve_authority);
+        }
+
+        self.process_ixs(&instructions, &signing_keypairs).await
     }
 
     /// Approves a token account for confidential transfers
@@ -1738,6 +2948,187 @@ where
         .await
     }
 
+    /// Approve several token accounts for confidential transfers, batching as many approvals per
+    /// transaction as fit
+    ///
+    /// Accounts that are already approved are skipped. If a transaction fails, the approvals that
+    /// it contained are retried one account per transaction, so that a single invalid account does
+    /// not prevent the rest of its batch from being approved.
+    ///
+    /// Returns a result for every account, in order: `Ok(true)` if the account was approved by
+    /// this call, `Ok(false)` if it was already approved, or the error that prevented approval.
+    pub async fn confidential_transfer_approve_accounts<S: Signers>(
+        &self,
+        accounts: &[Pubkey],
+        authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<Vec<(Pubkey, TokenResult<bool>)>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+        let approve_account_instruction = |account: &Pubkey| {
+            confidential_transfer::instruction::approve_account(
+                &self.program_id,
+                account,
+                &self.pubkey,
+                authority,
+                &multisig_signers,
+            )
+        };
+        let transaction_size = |instructions: &[Instruction]| {
+            let message = Message::new(instructions, Some(&self.payer.pubkey()));
+            // a signature count below 128 is serialized as a single byte
+            1 + message.header.num_required_signatures as usize * 64 + message.serialize().len()
+        };
+
+        // accounts that need approval are marked as approved, and overwritten if approval fails
+        let mut results: Vec<TokenResult<bool>> = Vec::with_capacity(accounts.len());
+        let mut unapproved_accounts = vec![];
+        for batch in accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let batch_accounts = self
+                .client
+                .get_multiple_accounts(batch)
+                .await
+                .map_err(TokenError::Client)?;
+
+            for (address, account) in batch.iter().zip(batch_accounts) {
+                let approved = match account {
+                    None => Err(TokenError::AccountNotFound),
+                    Some(account) if account.owner != self.program_id => {
+                        Err(TokenError::AccountInvalidOwner)
+                    }
+                    Some(account) => StateWithExtensionsOwned::<Account>::unpack(account.data)
+                        .map_err(TokenError::from)
+                        .and_then(|state| {
+                            if state.base.mint != self.pubkey {
+                                return Err(TokenError::AccountInvalidMint);
+                            }
+                            let extension = state.get_extension::<ConfidentialTransferAccount>()?;
+                            Ok(bool::from(&extension.approved))
+                        }),
+                };
+                match approved {
+                    Ok(true) => results.push(Ok(false)),
+                    Ok(false) => {
+                        unapproved_accounts.push((results.len(), *address));
+                        results.push(Ok(true));
+                    }
+                    Err(error) => results.push(Err(error)),
+                }
+            }
+        }
+
+        let mut batches: Vec<(Vec<(usize, Pubkey)>, Vec<Instruction>)> = vec![];
+        for (index, account) in unapproved_accounts {
+            let instruction = approve_account_instruction(&account)?;
+            if let Some((batch_accounts, batch_instructions)) = batches.last_mut() {
+                let mut candidate_instructions = batch_instructions.clone();
+                candidate_instructions.push(instruction.clone());
+                if transaction_size(&candidate_instructions) <= PACKET_DATA_SIZE {
+                    batch_accounts.push((index, account));
+                    *batch_instructions = candidate_instructions;
+                    continue;
+                }
+            }
+            batches.push((vec![(index, account)], vec![instruction]));
+        }
+
+        for (batch_accounts, batch_instructions) in batches {
+            let result = self
+                .process_ixs(&batch_instructions, signing_keypairs)
+                .await;
+            match result {
+                Ok(_) => {}
+                Err(error) if batch_accounts.len() == 1 => {
+                    results[batch_accounts[0].0] = Err(error);
+                }
+                Err(_) => {
+                    for (index, account) in batch_accounts {
+                        let result = self
+                            .process_ixs(
+                                &[approve_account_instruction(&account)?],
+                                signing_keypairs,
+                            )
+                            .await;
+                        if let Err(error) = result {
+                            results[index] = Err(error);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(accounts.iter().copied().zip(results).collect())
+    }
+
+    /// Check whether a token account is approved for confidential transfers
+    ///
+    /// If `auto_approve_new_accounts` is disabled on the mint, a newly configured account cannot
+    /// deposit, transfer, or receive confidential tokens until the confidential transfer mint
+    /// authority approves it.
+    pub async fn confidential_transfer_is_approved(&self, account: &Pubkey) -> TokenResult<bool> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+        Ok(bool::from(&extension.approved))
+    }
+
+    /// Configure an existing token account for confidential transfers and approve it with the
+    /// confidential transfer mint authority in a single transaction
+    ///
+    /// If the token account is not large enough to hold the confidential transfer extensions, it
+    /// is reallocated in the same transaction.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_request_and_approve(
+        &self,
+        account: &Pubkey,
+        owner: &dyn Signer,
+        maximum_pending_balance_credit_counter: Option<u64>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        approve_authority: &dyn Signer,
+    ) -> TokenResult<T::Output> {
+        let owner_pubkey = owner.pubkey();
+
+        let maximum_pending_balance_credit_counter = maximum_pending_balance_credit_counter
+            .unwrap_or(DEFAULT_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER);
+
+        let proof_data =
+            confidential_transfer::instruction::PubkeyValidityData::new(elgamal_keypair)
+                .map_err(|_| TokenError::ProofGeneration)?;
+        let proof_location = ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data);
+
+        let decryptable_balance = DecryptableBalance::encrypt(aes_key, 0);
+
+        let mut instructions = vec![];
+        if let Some(reallocate_instruction) = self
+            .confidential_transfer_reallocate_instruction(account, &owner_pubkey, &[], false)
+            .await?
+        {
+            instructions.push(reallocate_instruction);
+        }
+
+        instructions.extend(confidential_transfer::instruction::configure_account(
+            &self.program_id,
+            account,
+            &self.pubkey,
+            decryptable_balance.into(),
+            maximum_pending_balance_credit_counter,
+            &owner_pubkey,
+            &[],
+            proof_location,
+        )?);
+
+        instructions.push(confidential_transfer::instruction::approve_account(
+            &self.program_id,
+            account,
+            &self.pubkey,
+            &approve_authority.pubkey(),
+            &[],
+        )?);
+
+        self.process_ixs(&instructions, &[owner, approve_authority])
+            .await
+    }
+
     /// Prepare a token account with the confidential transfer extension for closing
     pub async fn confidential_transfer_empty_account<S: Signers>(
         &self,
@@ -1790,22 +3181,512 @@ where
         .await
     }
 
-    /// Fetch and decrypt the available balance of a confidential token account using the uniquely
-    /// derived decryption key from a signer
-    #[cfg(feature = "proof-program")]
-    pub async fn confidential_transfer_get_available_balance<S: Signer>(
+    /// Prepare a token account with the confidential transfer extension for closing, emptying both
+    /// the available and pending balances with a single zero-balance proof
+    pub async fn confidential_transfer_empty_account_full<S: Signers>(
         &self,
-        token_account: &Pubkey,
-        authority: &S,
-    ) -> TokenResult<u64> {
-        let authenticated_encryption_key =
-            AeKey::new(authority, token_account).map_err(TokenError::Key)?;
+        account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        account_info: Option<EmptyAccountFullAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
-        self.confidential_transfer_get_available_balance_with_key(
-            token_account,
-            &authenticated_encryption_key,
-        )
-        .await
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .empty_account_full_account_info()
+        };
+
+        let proof_data = if context_state_account.is_some() {
+            None
+        } else {
+            Some(
+                account_info
+                    .generate_proof_data(elgamal_keypair)
+                    .map_err(|_| TokenError::ProofGeneration)?,
+            )
+        };
+
+        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
+        } else {
+            let context_state_account = context_state_account.unwrap();
+            ProofLocation::ContextStateAccount(context_state_account)
+        };
+
+        self.process_ixs(
+            &confidential_transfer::instruction::empty_account_full(
+                &self.program_id,
+                account,
+                authority,
+                &multisig_signers,
+                proof_location,
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Estimate the compute units needed by a confidential transfer operation
+    ///
+    /// The estimates include the zero-knowledge proof verification when the proof is submitted in
+    /// the same transaction and are meant to size a `ComputeBudgetInstruction::SetComputeUnitLimit`
+    /// instruction, since most of these operations exceed the default per-instruction budget.
+    pub fn confidential_transfer_estimate_compute_units(&self, operation: ConfidentialOp) -> u32 {
+        match operation {
+            ConfidentialOp::Deposit => CONFIDENTIAL_DEPOSIT_COMPUTE_UNITS,
+            ConfidentialOp::Withdraw => CONFIDENTIAL_WITHDRAW_COMPUTE_UNITS,
+            ConfidentialOp::Transfer => CONFIDENTIAL_TRANSFER_COMPUTE_UNITS,
+            ConfidentialOp::TransferWithFee => CONFIDENTIAL_TRANSFER_WITH_FEE_COMPUTE_UNITS,
+            ConfidentialOp::ApplyPendingBalance => CONFIDENTIAL_APPLY_PENDING_BALANCE_COMPUTE_UNITS,
+        }
+    }
+
+    /// Fetch and decrypt the pending, available, and decryptable available balances of a
+    /// confidential token account
+    ///
+    /// Fails with `ElGamalBalanceDecryption` if an ElGamal ciphertext cannot be decrypted, which
+    /// usually means the wrong ElGamal keypair was supplied, and with `AeBalanceDecryption` if the
+    /// decryptable available balance cannot be decrypted with the authenticated encryption key.
+    pub async fn confidential_transfer_get_balances(
+        &self,
+        account: &Pubkey,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+    ) -> TokenResult<ConfidentialTokenAccountBalances> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+        measure(
+            self.metrics_sink.as_ref(),
+            "get_balances",
+            ConfidentialPhase::Decryption,
+            || {
+                decrypt_confidential_balances(
+                    &self.balance_decryptor,
+                    extension,
+                    elgamal_keypair,
+                    aes_key,
+                )
+            },
+        )
+    }
+
+    /// Fetch a confidential token account and summarize its balances and settings for display
+    ///
+    /// The available balance is decrypted from the decryptable available balance with the
+    /// authenticated encryption key, and the pending balance from its low and high ElGamal
+    /// ciphertexts. Fails with `ElGamalBalanceDecryption` or `AeBalanceDecryption` if a balance
+    /// cannot be decrypted with the supplied keys.
+    pub async fn confidential_transfer_account_summary(
+        &self,
+        account: &Pubkey,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+    ) -> TokenResult<AccountSummary> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+
+        let available = DecryptableBalance::try_from(extension.decryptable_available_balance)?
+            .decrypt(aes_key)
+            .ok_or(TokenError::AeBalanceDecryption)?;
+
+        let pending_balance_lo = self
+            .balance_decryptor
+            .decrypt(elgamal_keypair, &extension.pending_balance_lo)
+            .ok_or(TokenError::ElGamalBalanceDecryption)?;
+        let pending_balance_hi = self
+            .balance_decryptor
+            .decrypt(elgamal_keypair, &extension.pending_balance_hi)
+            .ok_or(TokenError::ElGamalBalanceDecryption)?;
+        let pending = pending_balance_hi
+            .checked_shl(confidential_transfer::PENDING_BALANCE_LO_BIT_LENGTH)
+            .and_then(|pending_balance_hi| pending_balance_hi.checked_add(pending_balance_lo))
+            .ok_or(TokenError::ElGamalBalanceDecryption)?;
+
+        Ok(AccountSummary {
+            available,
+            pending,
+            needs_apply: u64::from(extension.pending_balance_credit_counter) > 0,
+            credits_enabled: bool::from(extension.allow_confidential_credits),
+            non_confidential_credits_enabled: bool::from(extension.allow_non_confidential_credits),
+        })
+    }
+
+    /// Compare a cached available balance ciphertext against the live one of a confidential token
+    /// account
+    ///
+    /// Returns `true` if the available balance changed since it was cached, in which case any proof
+    /// generated from the cached ciphertext is stale and must be regenerated.
+    pub async fn confidential_transfer_available_balance_changed(
+        &self,
+        account: &Pubkey,
+        cached_available_balance: &EncryptedBalance,
+    ) -> TokenResult<bool> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+
+        Ok(!extension
+            .available_balance
+            .ct_eq_bytes(cached_available_balance))
+    }
+
+    /// Attest that the total available balance of several confidential token accounts is at
+    /// least `threshold`, without revealing the balances or their total
+    ///
+    /// Each available balance is decrypted from the decryptable available balance with the
+    /// account's authenticated encryption key. Fails with `NotEnoughFunds` if the total is below
+    /// the threshold.
+    pub async fn confidential_transfer_prove_reserves(
+        &self,
+        accounts: &[(Pubkey, &ElGamalKeypair, &AeKey)],
+        threshold: u64,
+    ) -> TokenResult<ReservesAttestation> {
+        let mut balances = Vec::with_capacity(accounts.len());
+        for (account, elgamal_keypair, aes_key) in accounts {
+            let account_info = self.get_account_info(account).await?;
+            let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+            let decrypted_available_balance =
+                DecryptableBalance::try_from(extension.decryptable_available_balance)?
+                    .decrypt(aes_key)
+                    .ok_or(TokenError::AeBalanceDecryption)?;
+            balances.push((
+                *elgamal_keypair,
+                extension.available_balance,
+                decrypted_available_balance,
+            ));
+        }
+
+        let balances = balances
+            .iter()
+            .map(
+                |(elgamal_keypair, available_balance, decrypted_available_balance)| {
+                    (
+                        *elgamal_keypair,
+                        available_balance,
+                        *decrypted_available_balance,
+                    )
+                },
+            )
+            .collect::<Vec<_>>();
+        ReservesAttestation::new(&balances, threshold).map_err(|e| match e {
+            spl_token_2022::error::TokenError::InsufficientFunds => TokenError::NotEnoughFunds,
+            _ => TokenError::ProofGeneration,
+        })
+    }
+
+    /// Verify an attestation created with `confidential_transfer_prove_reserves` against the
+    /// current state of the attested accounts, in the same order
+    ///
+    /// Any change to an attested available balance since the attestation was created causes
+    /// verification to fail.
+    pub async fn confidential_transfer_verify_reserves(
+        &self,
+        accounts: &[Pubkey],
+        attestation: &ReservesAttestation,
+    ) -> TokenResult<()> {
+        let mut extensions = Vec::with_capacity(accounts.len());
+        for account in accounts {
+            let account_info = self.get_account_info(account).await?;
+            extensions.push(*account_info.get_extension::<ConfidentialTransferAccount>()?);
+        }
+
+        let accounts = extensions
+            .iter()
+            .map(|extension| (&extension.elgamal_pubkey, &extension.available_balance))
+            .collect::<Vec<_>>();
+        attestation
+            .verify(&accounts)
+            .map_err(|e| TokenError::Program(e.into()))
+    }
+
+    /// Fetch and decrypt the balances of several confidential token accounts that share the same
+    /// ElGamal keypair and authenticated encryption key
+    ///
+    /// Accounts are fetched in batches of `MAX_MULTIPLE_ACCOUNTS` with a single
+    /// `get_multiple_accounts` request per batch, and their balances are decrypted in parallel.
+    /// The results are returned in the order of `accounts`. An account that does not exist, is not
+    /// a confidential token account of this mint, or cannot be decrypted only fails its own entry;
+    /// the outer error is reserved for failed requests.
+    pub async fn confidential_transfer_get_balances_batch(
+        &self,
+        accounts: &[Pubkey],
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+    ) -> TokenResult<Vec<TokenResult<ConfidentialTokenAccountBalances>>> {
+        let mut balances = Vec::with_capacity(accounts.len());
+        for batch in accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let batch_accounts = self
+                .client
+                .get_multiple_accounts(batch)
+                .await
+                .map_err(TokenError::Client)?;
+
+            let extensions = batch_accounts
+                .into_iter()
+                .map(|account| {
+                    let account = account.ok_or(TokenError::AccountNotFound)?;
+                    if account.owner != self.program_id {
+                        return Err(TokenError::AccountInvalidOwner);
+                    }
+                    let account = StateWithExtensionsOwned::<Account>::unpack(account.data)?;
+                    if account.base.mint != self.pubkey {
+                        return Err(TokenError::AccountInvalidMint);
+                    }
+                    Ok(*account.get_extension::<ConfidentialTransferAccount>()?)
+                })
+                .collect::<Vec<_>>();
+
+            let decryptable_extensions = extensions
+                .iter()
+                .filter_map(|extension| extension.as_ref().ok().copied())
+                .collect::<Vec<_>>();
+            let mut decrypted = decrypt_confidential_balances_in_parallel(
+                &self.balance_decryptor,
+                &decryptable_extensions,
+                elgamal_keypair,
+                aes_key,
+            )
+            .into_iter();
+
+            balances.extend(extensions.into_iter().map(|extension| {
+                extension.and_then(|_| decrypted.next().expect("one result per extension"))
+            }));
+        }
+        Ok(balances)
+    }
+
+    /// Fetch and decrypt the available balance of a confidential token account, searching only
+    /// for balances of at most `maximum_balance`
+    ///
+    /// Decrypting an ElGamal ciphertext requires solving a discrete log, which takes longer the
+    /// larger the balance. Returns `None` instead of searching further if the available balance
+    /// exceeds `maximum_balance` or cannot be decrypted with `elgamal_secret_key`.
+    pub async fn confidential_transfer_get_available_balance_bounded(
+        &self,
+        account: &Pubkey,
+        elgamal_secret_key: &ElGamalSecretKey,
+        maximum_balance: u64,
+    ) -> TokenResult<Option<u64>> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+
+        let available_balance = extension
+            .available_balance
+            .try_into()
+            .map_err(|_| TokenError::ElGamalBalanceDecryption)?;
+
+        Ok(confidential_transfer::account_info::decrypt_u32_with_max(
+            &available_balance,
+            elgamal_secret_key,
+            maximum_balance,
+        ))
+    }
+
+    /// Explain why a confidential token account cannot be closed
+    ///
+    /// The program only closes a confidential token account if its pending balance, available
+    /// balance, and withheld amount ciphertexts are all cleared, and otherwise fails with an error
+    /// that does not say which of them is set. This returns the first component that blocks the
+    /// account from being closed, with its decrypted amount where available, or `None` if the
+    /// account is closable. The withheld amount is encrypted under the withdraw withheld authority
+    /// ElGamal public key of the mint, so it is reported without an amount.
+    pub async fn confidential_transfer_why_not_closable(
+        &self,
+        account: &Pubkey,
+        elgamal_keypair: &ElGamalKeypair,
+    ) -> TokenResult<Option<ClosableBlocker>> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+
+        let is_cleared = |ciphertext: &EncryptedBalance| ciphertext.0 == [0; 64];
+
+        if !is_cleared(&extension.pending_balance_lo) {
+            return Ok(Some(ClosableBlocker::PendingBalanceLo {
+                amount: extension
+                    .pending_balance_lo
+                    .decrypt(elgamal_keypair.secret()),
+            }));
+        }
+        if !is_cleared(&extension.pending_balance_hi) {
+            return Ok(Some(ClosableBlocker::PendingBalanceHi {
+                amount: extension
+                    .pending_balance_hi
+                    .decrypt(elgamal_keypair.secret()),
+            }));
+        }
+        if !is_cleared(&extension.available_balance) {
+            let amount = extension
+                .available_balance
+                .decrypt(elgamal_keypair.secret());
+            if amount == Some(0) {
+                return Ok(Some(ClosableBlocker::AvailableBalanceNotEmptied));
+            }
+            return Ok(Some(ClosableBlocker::AvailableBalance { amount }));
+        }
+
+        if let Ok(fee_amount) = account_info.get_extension::<ConfidentialTransferFeeAmount>() {
+            if fee_amount.closable().is_err() {
+                return Ok(Some(ClosableBlocker::WithheldAmount));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Check whether `EmptyAccount` would succeed on a confidential token account
+    ///
+    /// Given a valid zero-balance proof, the program clears the available balance ciphertext and
+    /// then fails with `ConfidentialTransferAccountHasBalance` unless the pending balance
+    /// ciphertexts are also cleared. This applies the same check to the fetched account and returns
+    /// the same error. Whether the available balance encrypts zero is only checked when the proof
+    /// is generated.
+    pub async fn confidential_transfer_can_empty(&self, account: &Pubkey) -> TokenResult<()> {
+        let account_info = self.get_account_info(account).await?;
+        let mut extension = *account_info.get_extension::<ConfidentialTransferAccount>()?;
+
+        extension.available_balance = EncryptedBalance::zeroed();
+        extension.closable()?;
+        Ok(())
+    }
+
+    /// Number of deposits and transfers a confidential token account can still receive before its
+    /// pending balance must be applied
+    ///
+    /// This is bounded by both the account's `maximum_pending_balance_credit_counter` and
+    /// `MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER`, past which the pending balance could no
+    /// longer be decrypted.
+    pub async fn confidential_transfer_remaining_pending_balance_credits(
+        &self,
+        account: &Pubkey,
+    ) -> TokenResult<u64> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+        Ok(extension.remaining_pending_balance_credits())
+    }
+
+    /// Check whether a confidential token account can receive another deposit or transfer
+    ///
+    /// The account must be approved, accept confidential credits, and have a pending balance credit
+    /// counter below both its `maximum_pending_balance_credit_counter` and
+    /// `MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER`. Senders can check this before generating a
+    /// transfer proof that the destination would reject. A slot set by
+    /// `EnableConfidentialCreditsUntilSlot` is not checked, since the slot at which the transfer is
+    /// processed is not known ahead of time.
+    pub async fn confidential_transfer_destination_can_receive(
+        &self,
+        destination: &Pubkey,
+    ) -> TokenResult<bool> {
+        let account_info = self.get_account_info(destination).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+        Ok(bool::from(extension.approved)
+            && bool::from(extension.allow_confidential_credits)
+            && extension.remaining_pending_balance_credits() > 0)
+    }
+
+    /// Wait until the pending balance credit counter of a confidential token account exceeds
+    /// `from_counter`, returning the new counter
+    ///
+    /// This detects that *a* deposit or transfer was credited to the account, not its amount or
+    /// sender, which stay hidden. Read the counter before requesting a payment and pass it as
+    /// `from_counter`. The counter is reset to zero by `ApplyPendingBalance`, so the account should
+    /// not apply its pending balance while waiting. Fails with a `TimedOut` client error if no
+    /// credit arrives within `timeout`.
+    pub async fn confidential_transfer_await_credit(
+        &self,
+        account: &Pubkey,
+        from_counter: u64,
+        timeout: Duration,
+    ) -> TokenResult<u64> {
+        let start = Instant::now();
+        loop {
+            let account_info = self.get_account_info(account).await?;
+            let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+            let counter = u64::from(extension.pending_balance_credit_counter);
+            if counter > from_counter {
+                return Ok(counter);
+            }
+
+            if start.elapsed() >= timeout {
+                return Err(TokenError::Client(Box::new(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!(
+                        "No credit to {} after {}ms, pending balance credit counter is {}",
+                        account,
+                        start.elapsed().as_millis(),
+                        counter,
+                    ),
+                ))));
+            }
+            time::sleep(Duration::from_millis(200)).await;
+        }
+    }
+
+    /// Find the confidential token accounts of this mint that have pending balance credits to apply
+    ///
+    /// Only the plaintext pending balance credit counter is inspected, so no keys are required.
+    /// The counter is reset to zero by `ApplyPendingBalance`, so an account needs to apply its
+    /// pending balance whenever the counter is nonzero. Accounts are fetched in batches of
+    /// `MAX_MULTIPLE_ACCOUNTS`; accounts that do not exist, are not token accounts of this mint, or
+    /// are not configured for confidential transfers are skipped.
+    pub async fn confidential_accounts_needing_apply(
+        &self,
+        accounts: &[Pubkey],
+    ) -> TokenResult<Vec<Pubkey>> {
+        let mut accounts_needing_apply = vec![];
+        for batch in accounts.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let batch_accounts = self
+                .client
+                .get_multiple_accounts(batch)
+                .await
+                .map_err(TokenError::Client)?;
+
+            for (address, account) in batch.iter().zip(batch_accounts) {
+                let needs_apply = account
+                    .filter(|account| account.owner == self.program_id)
+                    .and_then(|account| {
+                        StateWithExtensionsOwned::<Account>::unpack(account.data).ok()
+                    })
+                    .filter(|state| state.base.mint == self.pubkey)
+                    .and_then(|state| {
+                        state
+                            .get_extension::<ConfidentialTransferAccount>()
+                            .ok()
+                            .map(|extension| {
+                                u64::from(extension.pending_balance_credit_counter) > 0
+                            })
+                    })
+                    .unwrap_or(false);
+                if needs_apply {
+                    accounts_needing_apply.push(*address);
+                }
+            }
+        }
+        Ok(accounts_needing_apply)
+    }
+
+    /// Fetch and decrypt the available balance of a confidential token account using the uniquely
+    /// derived decryption key from a signer
+    #[cfg(feature = "proof-program")]
+    pub async fn confidential_transfer_get_available_balance<S: Signer>(
+        &self,
+        token_account: &Pubkey,
+        authority: &S,
+    ) -> TokenResult<u64> {
+        let authenticated_encryption_key =
+            AeKey::new(authority, token_account).map_err(TokenError::Key)?;
+
+        self.confidential_transfer_get_available_balance_with_key(
+            token_account,
+            &authenticated_encryption_key,
+        )
+        .await
     }
 
     /// Fetch and decrypt the available balance of a confidential token account using a custom
@@ -1935,6 +3816,29 @@ where
         Ok(elgamal_pubkey)
     }
 
+    /// Fetch the current auditor ElGamal public key of a confidential token mint
+    ///
+    /// Transfer proofs must be generated for the auditor ElGamal public key that is stored in the
+    /// mint at the time the transfer is processed, so clients should fetch it with this function
+    /// rather than cache it.
+    pub async fn confidential_transfer_current_auditor(
+        &self,
+    ) -> TokenResult<Option<ElGamalPubkey>> {
+        let mint_state = self.get_mint_info().await?;
+        let ct_mint =
+            mint_state.get_extension::<confidential_transfer::ConfidentialTransferMint>()?;
+        let auditor_elgamal_pubkey: Option<PodElGamalPubkey> =
+            ct_mint.auditor_elgamal_pubkey.into();
+
+        auditor_elgamal_pubkey
+            .map(|elgamal_pubkey| {
+                elgamal_pubkey
+                    .try_into()
+                    .map_err(|_| TokenError::Program(ProgramError::InvalidAccountData))
+            })
+            .transpose()
+    }
+
     /// Fetch the ElGamal pubkey key of the auditor associated with a confidential token mint
     #[cfg(feature = "proof-program")]
     pub async fn confidential_transfer_get_auditor_elgamal_pubkey<S: Signer>(
@@ -1976,6 +3880,9 @@ where
     }
 
     /// Deposit SPL Tokens into the pending balance of a confidential token account
+    ///
+    /// Fails with `AccountInvalidMint` without sending a transaction if the account belongs to a
+    /// different mint.
     pub async fn confidential_transfer_deposit<S: Signers>(
         &self,
         account: &Pubkey,
@@ -1987,6 +3894,8 @@ where
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
+        self.get_account_info(account).await?;
+
         self.process_ixs(
             &[confidential_transfer::instruction::deposit(
                 &self.program_id,
@@ -2002,17 +3911,73 @@ where
         .await
     }
 
-    /// Withdraw SPL Tokens from the available balance of a confidential token account
+    /// Deposit SPL Tokens into the pending balance of a confidential token account, applying the
+    /// pending balance first if the account cannot be credited again
+    ///
+    /// The account is fetched to check its pending balance credit counter against its maximum. If
+    /// the deposit would exceed it, an `ApplyPendingBalance` instruction is prepended to the
+    /// deposit in the same transaction, so that repeated deposits do not fail with
+    /// `MaximumPendingBalanceCreditCounterExceeded`. The ElGamal secret key and AES key of the
+    /// account are only used to compute the new decryptable available balance of the apply.
     #[allow(clippy::too_many_arguments)]
-    pub async fn confidential_transfer_withdraw<S: Signers>(
+    pub async fn confidential_transfer_deposit_with_auto_apply<S: Signers>(
         &self,
         account: &Pubkey,
         authority: &Pubkey,
-        context_state_account: Option<&Pubkey>,
-        withdraw_amount: u64,
+        amount: u64,
+        decimals: u8,
+        elgamal_secret_key: &ElGamalSecretKey,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let account_state = self.get_account_info(account).await?;
+        let extension = account_state.get_extension::<ConfidentialTransferAccount>()?;
+
+        let mut instructions = vec![];
+        if extension.remaining_pending_balance_credits() == 0 {
+            let account_info = extension.apply_pending_balance_account_info();
+            let new_decryptable_available_balance = account_info
+                .new_decryptable_available_balance(elgamal_secret_key, aes_key)
+                .map_err(|_| TokenError::AccountDecryption)?;
+
+            instructions.push(confidential_transfer::instruction::apply_pending_balance(
+                &self.program_id,
+                account,
+                account_info.pending_balance_credit_counter(),
+                new_decryptable_available_balance,
+                authority,
+                &multisig_signers,
+            )?);
+        }
+
+        instructions.push(confidential_transfer::instruction::deposit(
+            &self.program_id,
+            account,
+            &self.pubkey,
+            amount,
+            decimals,
+            authority,
+            &multisig_signers,
+        )?);
+
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Deposit SPL Tokens directly into the available balance of a confidential token account
+    ///
+    /// The new decryptable available balance is computed from the current decryptable available
+    /// balance of the account, which is fetched if `account_info` is `None`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_deposit_and_apply<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
         decimals: u8,
         account_info: Option<WithdrawAccountInfo>,
-        elgamal_keypair: &ElGamalKeypair,
         aes_key: &AeKey,
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
@@ -2028,93 +3993,2491 @@ where
                 .withdraw_account_info()
         };
 
-        let proof_data = if context_state_account.is_some() {
-            None
-        } else {
-            Some(
-                account_info
-                    .generate_proof_data(withdraw_amount, elgamal_keypair, aes_key)
-                    .map_err(|_| TokenError::ProofGeneration)?,
-            )
-        };
-
-        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
-            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
-        } else {
-            let context_state_account = context_state_account.unwrap();
-            ProofLocation::ContextStateAccount(context_state_account)
-        };
-
-        let new_decryptable_available_balance = account_info
-            .new_decryptable_available_balance(withdraw_amount, aes_key)
-            .map_err(|_| TokenError::AccountDecryption)?;
+        let new_decrypted_available_balance =
+            DecryptableBalance::try_from(account_info.decryptable_available_balance)?
+                .decrypt(aes_key)
+                .ok_or(TokenError::AeBalanceDecryption)?
+                .checked_add(amount)
+                .ok_or(TokenError::MaximumAvailableBalanceExceeded)?;
+        let new_decryptable_available_balance =
+            DecryptableBalance::encrypt(aes_key, new_decrypted_available_balance);
 
         self.process_ixs(
-            &confidential_transfer::instruction::withdraw(
+            &[confidential_transfer::instruction::deposit_and_apply(
                 &self.program_id,
                 account,
                 &self.pubkey,
-                withdraw_amount,
+                amount,
                 decimals,
-                new_decryptable_available_balance,
+                new_decryptable_available_balance.into(),
                 authority,
                 &multisig_signers,
-                proof_location,
-            )?,
+            )?],
             signing_keypairs,
         )
         .await
     }
 
-    /// Withdraw SPL Tokens from the available balance of a confidential token account using custom
-    /// keys
-    #[allow(clippy::too_many_arguments)]
-    #[cfg(feature = "proof-program")]
-    pub async fn confidential_transfer_withdraw_with_key<S: Signer>(
+    /// Deposit the entire public balance of a token account into its pending balance
+    ///
+    /// A single deposit carries at most `MAXIMUM_DEPOSIT_TRANSFER_AMOUNT`, so larger balances are
+    /// split across several deposits, each submitted in its own transaction. The pending balance is
+    /// applied first whenever another deposit would exceed the remaining pending balance credits of
+    /// the account, or would grow the high bits of the pending balance beyond what can be
+    /// decrypted. Returns the outputs of all submitted transactions, in order.
+    pub async fn confidential_transfer_deposit_all<S: Signers>(
         &self,
-        token_account: &Pubkey,
-        token_authority: &S,
-        amount: u64,
+        account: &Pubkey,
+        authority: &Pubkey,
         decimals: u8,
-        available_balance: u64,
-        available_balance_ciphertext: &ElGamalCiphertext,
-        elgamal_keypair: &ElGamalKeypair,
-        authenticated_encryption_key: &AeKey,
-    ) -> TokenResult<T::Output> {
-        let proof_data = confidential_transfer::instruction::WithdrawData::new(
-            amount,
-            elgamal_keypair,
-            available_balance,
-            available_balance_ciphertext,
+        elgamal_secret_key: &ElGamalSecretKey,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<Vec<T::Output>> {
+        let state = self.get_account_info(account).await?;
+        let extension = state.get_extension::<ConfidentialTransferAccount>()?;
+
+        let maximum_credits = u64::from(extension.maximum_pending_balance_credit_counter)
+            .min(confidential_transfer::MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER);
+        let mut remaining_credits = extension.remaining_pending_balance_credits();
+        let mut pending_balance_hi = if u64::from(extension.pending_balance_credit_counter) == 0 {
+            0
+        } else {
+            extension
+                .pending_balance_hi
+                .decrypt(elgamal_secret_key)
+                .ok_or(TokenError::ElGamalBalanceDecryption)?
+        };
+
+        let mut remaining_amount = state.base.amount;
+        let mut outputs = vec![];
+        while remaining_amount > 0 {
+            let deposit_amount =
+                remaining_amount.min(confidential_transfer::MAXIMUM_DEPOSIT_TRANSFER_AMOUNT);
+            let deposit_amount_hi =
+                deposit_amount >> confidential_transfer::PENDING_BALANCE_LO_BIT_LENGTH;
+
+            if remaining_credits == 0 || pending_balance_hi + deposit_amount_hi > u32::MAX as u64 {
+                if maximum_credits == 0 {
+                    return Err(ProgramError::from(
+                        spl_token_2022::error::TokenError::MaximumPendingBalanceCreditCounterExceeded,
+                    )
+                    .into());
+                }
+                outputs.push(
+                    self.confidential_transfer_apply_pending_balance(
+                        account,
+                        authority,
+                        None,
+                        elgamal_secret_key,
+                        aes_key,
+                        signing_keypairs,
+                    )
+                    .await?,
+                );
+                remaining_credits = maximum_credits;
+                pending_balance_hi = 0;
+            }
+
+            outputs.push(
+                self.confidential_transfer_deposit(
+                    account,
+                    authority,
+                    deposit_amount,
+                    decimals,
+                    signing_keypairs,
+                )
+                .await?,
+            );
+            remaining_amount -= deposit_amount;
+            remaining_credits -= 1;
+            pending_balance_hi += deposit_amount_hi;
+        }
+
+        Ok(outputs)
+    }
+
+    /// Mint SPL Tokens directly into the pending balance of a confidential token account
+    ///
+    /// The mint amount is encrypted under the destination and supply ElGamal public keys. The
+    /// ciphertext validity and range proofs for the mint amount are each verified into a separate
+    /// context state account before the mint instruction is submitted. The context state accounts
+    /// are left open and can be closed by the context state authority afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_mint<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        ciphertext_validity_proof_account: &dyn Signer,
+        range_proof_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        amount: u64,
+        decimals: u8,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        supply_elgamal_pubkey: &ElGamalPubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let MintConfidentialProofData {
+            ciphertext_validity_proof_data,
+            range_proof_data,
+        } = generate_mint_confidential_proof_data(
+            amount,
+            destination_elgamal_pubkey,
+            supply_elgamal_pubkey,
+        )
+        .map_err(|e| TokenError::Program(e.into()))?;
+
+        self.create_proof_context_state_account(
+            ciphertext_validity_proof_account,
+            size_of::<ProofContextState<BatchedGroupedCiphertext2HandlesValidityProofContext>>(),
+            ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &ciphertext_validity_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &ciphertext_validity_proof_data,
+            ),
+        )
+        .await?;
+
+        self.create_proof_context_state_account(
+            range_proof_account,
+            size_of::<ProofContextState<BatchedRangeProofContext>>(),
+            ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &range_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &range_proof_data,
+            ),
+        )
+        .await?;
+
+        self.process_ixs(
+            &[confidential_transfer::instruction::mint_confidential(
+                &self.program_id,
+                account,
+                &self.pubkey,
+                decimals,
+                authority,
+                &multisig_signers,
+                &ciphertext_validity_proof_account.pubkey(),
+                &range_proof_account.pubkey(),
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Create a context state account and verify a zero-knowledge proof into it.
+    ///
+    /// The account is allocated and the proof is verified in separate transactions since larger
+    /// proofs do not fit in a single transaction together with the account creation.
+    async fn create_proof_context_state_account(
+        &self,
+        context_state_account: &dyn Signer,
+        space: usize,
+        verify_proof_instruction: Instruction,
+    ) -> TokenResult<T::Output> {
+        self.process_ixs(
+            &[system_instruction::create_account(
+                &self.payer.pubkey(),
+                &context_state_account.pubkey(),
+                self.client
+                    .get_minimum_balance_for_rent_exemption(space)
+                    .await
+                    .map_err(TokenError::Client)?,
+                space as u64,
+                &spl_token_2022::solana_zk_token_sdk::zk_token_proof_program::id(),
+            )],
+            &[context_state_account],
+        )
+        .await?;
+
+        self.process_ixs(&[verify_proof_instruction], &[] as &[&dyn Signer; 0])
+            .await
+    }
+
+    /// Withdraw SPL Tokens from the available balance of a confidential token account
+    ///
+    /// If the available balance changed since `account_info` was fetched, the withdrawal is retried
+    /// once with the current account information if enabled with
+    /// `with_retry_on_balance_mismatch`.
+    ///
+    /// Fails with `AccountInvalidMint` before the proof is generated if the account belongs to a
+    /// different mint.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_withdraw<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        withdraw_amount: u64,
+        decimals: u8,
+        account_info: Option<WithdrawAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        self.confidential_transfer_withdraw_with_decryptable_balance(
+            account,
+            authority,
+            context_state_account,
+            withdraw_amount,
+            decimals,
+            account_info,
+            elgamal_keypair,
+            aes_key,
+            signing_keypairs,
+        )
+        .await
+        .map(|withdraw| withdraw.output)
+    }
+
+    /// Withdraw SPL Tokens from the available balance of a confidential token account, as in
+    /// `confidential_transfer_withdraw`
+    ///
+    /// Returns the new decryptable available balance that was submitted for the account along
+    /// with the transaction output. The balance is only valid if the transaction succeeded.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_withdraw_with_decryptable_balance<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        withdraw_amount: u64,
+        decimals: u8,
+        account_info: Option<WithdrawAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithDecryptableBalance<T::Output>> {
+        // the account is checked against the mint before the proof is generated
+        let account_state = self.get_account_info(account).await?;
+        let account_info = match account_info {
+            Some(account_info) => account_info,
+            None => account_state
+                .get_extension::<ConfidentialTransferAccount>()?
+                .withdraw_account_info(),
+        };
+
+        let memo = self.memo.read().unwrap().clone();
+        match self
+            .confidential_transfer_withdraw_once(
+                account,
+                authority,
+                context_state_account,
+                withdraw_amount,
+                decimals,
+                Some(account_info),
+                elgamal_keypair,
+                aes_key,
+                signing_keypairs,
+            )
+            .await
+        {
+            Err(error) if self.should_retry_on_balance_mismatch(context_state_account, &error) => {
+                *self.memo.write().unwrap() = memo;
+                self.confidential_transfer_withdraw_once(
+                    account,
+                    authority,
+                    context_state_account,
+                    withdraw_amount,
+                    decimals,
+                    None,
+                    elgamal_keypair,
+                    aes_key,
+                    signing_keypairs,
+                )
+                .await
+            }
+            result => result,
+        }
+    }
+
+    async fn confidential_transfer_withdraw_once<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        withdraw_amount: u64,
+        decimals: u8,
+        account_info: Option<WithdrawAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithDecryptableBalance<T::Output>> {
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .withdraw_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            withdraw_amount,
+            aes_key,
+        )?;
+
+        let proof_data = if context_state_account.is_some() {
+            None
+        } else {
+            Some(measure(
+                self.metrics_sink.as_ref(),
+                "withdraw",
+                ConfidentialPhase::ProofGeneration,
+                || {
+                    build_withdraw_proof_data(
+                        &account_info,
+                        withdraw_amount,
+                        elgamal_keypair,
+                        aes_key,
+                    )
+                },
+            )?)
+        };
+
+        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
+        } else {
+            let context_state_account = context_state_account.unwrap();
+            ProofLocation::ContextStateAccount(context_state_account)
+        };
+
+        measure_async(
+            self.metrics_sink.as_ref(),
+            "withdraw",
+            ConfidentialPhase::Submission,
+            self.confidential_transfer_withdraw_with_proof(
+                account,
+                authority,
+                proof_location,
+                withdraw_amount,
+                decimals,
+                &account_info,
+                aes_key,
+                signing_keypairs,
+            ),
+        )
+        .await
+    }
+
+    /// Withdraw SPL Tokens from the available balance of a confidential token account, using a
+    /// proof that was already generated
+    ///
+    /// `account_info` must be the same account information that the proof was generated from.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_withdraw_with_proof<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        proof_location: ProofLocation<'_, WithdrawData>,
+        withdraw_amount: u64,
+        decimals: u8,
+        account_info: &WithdrawAccountInfo,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithDecryptableBalance<T::Output>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(withdraw_amount, aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let output = self
+            .process_ixs(
+                &confidential_transfer::instruction::withdraw(
+                    &self.program_id,
+                    account,
+                    &self.pubkey,
+                    withdraw_amount,
+                    decimals,
+                    new_decryptable_available_balance,
+                    authority,
+                    &multisig_signers,
+                    proof_location,
+                )?,
+                signing_keypairs,
+            )
+            .await?;
+
+        Ok(ConfidentialTransferWithDecryptableBalance {
+            output,
+            new_decryptable_available_balance: new_decryptable_available_balance.into(),
+        })
+    }
+
+    /// Withdraw SPL Tokens from the available balance of a confidential token account into the
+    /// non-confidential balance of a different token account
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_withdraw_to<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        withdraw_amount: u64,
+        decimals: u8,
+        account_info: Option<WithdrawAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .withdraw_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            withdraw_amount,
+            aes_key,
+        )?;
+
+        let proof_data = if context_state_account.is_some() {
+            None
+        } else {
+            Some(
+                account_info
+                    .generate_proof_data(withdraw_amount, elgamal_keypair, aes_key)
+                    .map_err(|_| TokenError::ProofGeneration)?,
+            )
+        };
+
+        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
+        } else {
+            let context_state_account = context_state_account.unwrap();
+            ProofLocation::ContextStateAccount(context_state_account)
+        };
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(withdraw_amount, aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.process_ixs(
+            &confidential_transfer::instruction::withdraw_to(
+                &self.program_id,
+                source_account,
+                destination_account,
+                &self.pubkey,
+                withdraw_amount,
+                decimals,
+                new_decryptable_available_balance,
+                authority,
+                &multisig_signers,
+                proof_location,
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Burn SPL Tokens from the available balance of a confidential token account
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_burn<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        burn_amount: u64,
+        decimals: u8,
+        account_info: Option<WithdrawAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .withdraw_account_info()
+        };
+
+        let proof_data = if context_state_account.is_some() {
+            None
+        } else {
+            Some(
+                account_info
+                    .generate_proof_data(burn_amount, elgamal_keypair, aes_key)
+                    .map_err(|_| TokenError::ProofGeneration)?,
+            )
+        };
+
+        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
+        } else {
+            let context_state_account = context_state_account.unwrap();
+            ProofLocation::ContextStateAccount(context_state_account)
+        };
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(burn_amount, aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.process_ixs(
+            &confidential_transfer::instruction::burn_confidential(
+                &self.program_id,
+                account,
+                &self.pubkey,
+                burn_amount,
+                decimals,
+                new_decryptable_available_balance,
+                authority,
+                &multisig_signers,
+                proof_location,
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Rotate the ElGamal public key of a confidential token account
+    ///
+    /// The pending balance of the account must be applied before the key can be rotated.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_rotate_elgamal_key<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        account_info: Option<RotateElGamalKeyAccountInfo>,
+        current_elgamal_keypair: &ElGamalKeypair,
+        current_aes_key: &AeKey,
+        new_elgamal_keypair: &ElGamalKeypair,
+        new_aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .rotate_elgamal_key_account_info()
+        };
+
+        let proof_data = if context_state_account.is_some() {
+            None
+        } else {
+            Some(
+                account_info
+                    .generate_proof_data(
+                        current_elgamal_keypair,
+                        current_aes_key,
+                        new_elgamal_keypair.pubkey(),
+                    )
+                    .map_err(|_| TokenError::ProofGeneration)?,
+            )
+        };
+
+        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
+        } else {
+            let context_state_account = context_state_account.unwrap();
+            ProofLocation::ContextStateAccount(context_state_account)
+        };
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(current_aes_key, new_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.process_ixs(
+            &confidential_transfer::instruction::rotate_elgamal_key(
+                &self.program_id,
+                account,
+                (*new_elgamal_keypair.pubkey()).into(),
+                new_decryptable_available_balance,
+                authority,
+                &multisig_signers,
+                proof_location,
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Forward the entire pending balance of a confidential token account to the pending balance
+    /// of another account without applying it first
+    ///
+    /// The pending balance cannot be forwarded in part, and the source must be a different account
+    /// than the destination. The pending balance is re-encrypted under the destination ElGamal
+    /// public key, and the equality proofs for its low and high bits are each verified into a
+    /// separate context state account before the forward instruction is submitted, since both
+    /// proofs do not fit in a single transaction. The context state accounts are left open and can
+    /// be closed by the context state authority afterwards.
+    ///
+    /// Returns the forwarded amount.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_forward_pending<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        equality_proof_lo_account: &dyn Signer,
+        equality_proof_hi_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        account_info: Option<ForwardPendingBalanceAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<u64> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .forward_pending_balance_account_info()
+        };
+
+        let ForwardPendingBalanceProofData {
+            equality_proof_data_lo,
+            equality_proof_data_hi,
+            forwarded_amount,
+        } = account_info
+            .generate_proof_data(source_elgamal_keypair, destination_elgamal_pubkey)
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        for (equality_proof_account, equality_proof_data) in [
+            (equality_proof_lo_account, &equality_proof_data_lo),
+            (equality_proof_hi_account, &equality_proof_data_hi),
+        ] {
+            self.create_proof_context_state_account(
+                equality_proof_account,
+                size_of::<ProofContextState<CiphertextCiphertextEqualityProofContext>>(),
+                ProofInstruction::VerifyCiphertextCiphertextEquality.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &equality_proof_account.pubkey(),
+                        context_state_authority,
+                    }),
+                    equality_proof_data,
+                ),
+            )
+            .await?;
+        }
+
+        self.process_ixs(
+            &confidential_transfer::instruction::forward_pending_balance(
+                &self.program_id,
+                source_account,
+                destination_account,
+                &self.pubkey,
+                source_authority,
+                &multisig_signers,
+                ProofLocation::ContextStateAccount(&equality_proof_lo_account.pubkey()),
+                ProofLocation::ContextStateAccount(&equality_proof_hi_account.pubkey()),
+            )?,
+            signing_keypairs,
+        )
+        .await?;
+
+        Ok(forwarded_amount)
+    }
+
+    /// Withdraw SPL Tokens from the available balance of a confidential token account using custom
+    /// keys
+    #[allow(clippy::too_many_arguments)]
+    #[cfg(feature = "proof-program")]
+    pub async fn confidential_transfer_withdraw_with_key<S: Signer>(
+        &self,
+        token_account: &Pubkey,
+        token_authority: &S,
+        amount: u64,
+        decimals: u8,
+        available_balance: u64,
+        available_balance_ciphertext: &ElGamalCiphertext,
+        elgamal_keypair: &ElGamalKeypair,
+        authenticated_encryption_key: &AeKey,
+    ) -> TokenResult<T::Output> {
+        let proof_data = confidential_transfer::instruction::WithdrawData::new(
+            amount,
+            elgamal_keypair,
+            available_balance,
+            available_balance_ciphertext,
+        )
+        .map_err(TokenError::Proof)?;
+
+        let remaining_balance = available_balance
+            .checked_sub(amount)
+            .ok_or(TokenError::NotEnoughFunds)?;
+        let new_decryptable_available_balance =
+            authenticated_encryption_key.encrypt(remaining_balance);
+
+        self.process_ixs(
+            &confidential_transfer::instruction::withdraw(
+                &self.program_id,
+                token_account,
+                &self.pubkey,
+                amount,
+                decimals,
+                new_decryptable_available_balance,
+                &token_authority.pubkey(),
+                &[],
+                &proof_data,
+            )?,
+            &[token_authority],
+        )
+        .await
+    }
+
+    /// Transfer tokens confidentially
+    ///
+    /// If the available balance of the source account changed since `account_info` was fetched,
+    /// the transfer is retried once with the current account information if enabled with
+    /// `with_retry_on_balance_mismatch`.
+    ///
+    /// The source and destination accounts are fetched in a single request and the transfer fails
+    /// with `AccountInvalidMint` before the proof is generated if either belongs to a different
+    /// mint. If enabled with `with_destination_elgamal_pubkey_check`, it also fails with
+    /// `DestinationElGamalPubkeyChanged` if the destination key is not `destination_elgamal_pubkey`.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        self.confidential_transfer_transfer_with_decryptable_balance(
+            source_account,
+            destination_account,
+            source_authority,
+            context_state_account,
+            transfer_amount,
+            account_info,
+            source_elgamal_keypair,
+            source_aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+            signing_keypairs,
+        )
+        .await
+        .map(|transfer| transfer.output)
+    }
+
+    /// Transfer tokens confidentially, as in `confidential_transfer_transfer`
+    ///
+    /// Returns the new decryptable available balance that was submitted for the source account
+    /// along with the transaction output. The balance is only valid if the transaction succeeded.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_decryptable_balance<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithDecryptableBalance<T::Output>> {
+        // both accounts are checked against the mint before the proof is generated
+        let account_states = self
+            .get_account_infos(&[*source_account, *destination_account])
+            .await?;
+        if self.check_destination_elgamal_pubkey {
+            check_destination_elgamal_pubkey(&account_states[1], destination_elgamal_pubkey)?;
+        }
+        let account_info = match account_info {
+            Some(account_info) => account_info,
+            None => account_states[0]
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info(),
+        };
+
+        let memo = self.memo.read().unwrap().clone();
+        match self
+            .confidential_transfer_transfer_once(
+                source_account,
+                destination_account,
+                source_authority,
+                context_state_account,
+                transfer_amount,
+                Some(account_info),
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+                signing_keypairs,
+            )
+            .await
+        {
+            Err(error) if self.should_retry_on_balance_mismatch(context_state_account, &error) => {
+                *self.memo.write().unwrap() = memo;
+                self.confidential_transfer_transfer_once(
+                    source_account,
+                    destination_account,
+                    source_authority,
+                    context_state_account,
+                    transfer_amount,
+                    None,
+                    source_elgamal_keypair,
+                    source_aes_key,
+                    destination_elgamal_pubkey,
+                    auditor_elgamal_pubkey,
+                    signing_keypairs,
+                )
+                .await
+            }
+            result => result,
+        }
+    }
+
+    async fn confidential_transfer_transfer_once<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        context_state_account: Option<&Pubkey>,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithDecryptableBalance<T::Output>> {
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let proof_data = if context_state_account.is_some() {
+            None
+        } else {
+            Some(measure(
+                self.metrics_sink.as_ref(),
+                "transfer",
+                ConfidentialPhase::ProofGeneration,
+                || {
+                    build_transfer_proof_data(
+                        &account_info,
+                        transfer_amount,
+                        source_elgamal_keypair,
+                        source_aes_key,
+                        destination_elgamal_pubkey,
+                        auditor_elgamal_pubkey,
+                    )
+                },
+            )?)
+        };
+
+        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
+        } else {
+            let context_state_account = context_state_account.unwrap();
+            ProofLocation::ContextStateAccount(context_state_account)
+        };
+
+        measure_async(
+            self.metrics_sink.as_ref(),
+            "transfer",
+            ConfidentialPhase::Submission,
+            self.confidential_transfer_transfer_with_proof(
+                source_account,
+                destination_account,
+                source_authority,
+                proof_location,
+                transfer_amount,
+                &account_info,
+                source_aes_key,
+                signing_keypairs,
+            ),
+        )
+        .await
+    }
+
+    /// Transfer tokens confidentially from one source account to many recipients
+    ///
+    /// Each recipient is given as `(destination_account, transfer_amount,
+    /// destination_elgamal_pubkey)`. A transfer together with its proof instruction fills most of a
+    /// transaction, so every recipient is paid in a transaction of its own and the output of each
+    /// transaction is returned in the order of `recipients`.
+    ///
+    /// All proofs are generated before the first transaction is sent. A transfer proof is generated
+    /// against the source available balance that the program sees when the transfer is processed,
+    /// so each proof is generated against the new source balance ciphertext of the proof before it,
+    /// and the transactions must be processed in the order of `recipients`. A self-transfer adds
+    /// the transfer amount back to the available balance. If the source account is debited by
+    /// anything else while the batch is being processed, the remaining transfers fail with a
+    /// balance mismatch.
+    ///
+    /// If a transaction fails, the transfers before it have already been processed and the
+    /// remaining transfers are not sent.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_batch<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        source_authority: &Pubkey,
+        recipients: &[(Pubkey, u64, ElGamalPubkey)],
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<Vec<T::Output>> {
+        let mut account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        let total_debit_amount = recipients
+            .iter()
+            .filter(|(destination_account, _, _)| destination_account != source_account)
+            .try_fold(0_u64, |total, (_, transfer_amount, _)| {
+                total.checked_add(*transfer_amount)
+            })
+            .ok_or(TokenError::Program(ProgramError::ArithmeticOverflow))?;
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            total_debit_amount,
+            source_aes_key,
+        )?;
+
+        // chain the proofs through the source balance that each transfer leaves behind
+        let mut transfers = Vec::with_capacity(recipients.len());
+        for (destination_account, transfer_amount, destination_elgamal_pubkey) in recipients {
+            let proof_data = build_transfer_proof_data(
+                &account_info,
+                *transfer_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+            )?;
+
+            let next_account_info = account_info
+                .after_transfer(
+                    *transfer_amount,
+                    &proof_data,
+                    source_aes_key,
+                    destination_account == source_account,
+                )
+                .map_err(|_| TokenError::AccountDecryption)?;
+
+            transfers.push((
+                destination_account,
+                *transfer_amount,
+                account_info,
+                proof_data,
+            ));
+            account_info = next_account_info;
+        }
+
+        let mut outputs = Vec::with_capacity(transfers.len());
+        for (destination_account, transfer_amount, account_info, proof_data) in transfers.iter() {
+            let transfer = self
+                .confidential_transfer_transfer_with_proof(
+                    source_account,
+                    destination_account,
+                    source_authority,
+                    ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data),
+                    *transfer_amount,
+                    account_info,
+                    source_aes_key,
+                    signing_keypairs,
+                )
+                .await?;
+            outputs.push(transfer.output);
+        }
+
+        Ok(outputs)
+    }
+
+    /// Transfer tokens confidentially, using a proof that was already generated
+    ///
+    /// `account_info` must be the same account information that the proof was generated from.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_proof<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        proof_location: ProofLocation<'_, TransferData>,
+        transfer_amount: u64,
+        account_info: &TransferAccountInfo,
+        source_aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithDecryptableBalance<T::Output>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let debit_amount = if source_account == destination_account {
+            0
+        } else {
+            transfer_amount
+        };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let output = self
+            .process_ixs(
+                &confidential_transfer::instruction::transfer(
+                    &self.program_id,
+                    source_account,
+                    destination_account,
+                    &self.pubkey,
+                    new_decryptable_available_balance,
+                    source_authority,
+                    &multisig_signers,
+                    proof_location,
+                )?,
+                signing_keypairs,
+            )
+            .await?;
+
+        Ok(ConfidentialTransferWithDecryptableBalance {
+            output,
+            new_decryptable_available_balance: new_decryptable_available_balance.into(),
+        })
+    }
+
+    /// Transfer tokens confidentially to a mint with additional auditors or a maximum transfer
+    /// amount
+    ///
+    /// The transfer proof is verified into the context state account `transfer_proof_account`. For
+    /// each additional auditor of the mint, the low and high bits of the transfer amount are
+    /// re-encrypted under the auditor ElGamal public key and the equality proofs are verified into
+    /// the context state accounts of `additional_proof_accounts`, two per auditor in the order of
+    /// the `ConfidentialTransferAuditors` extension. If the mint has a maximum transfer amount, the
+    /// equality and range proofs that the transfer amount does not exceed it are verified into the
+    /// last two context state accounts. The context state accounts are left open and can be closed
+    /// by the context state authority afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_additional_proofs<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        transfer_proof_account: &dyn Signer,
+        additional_proof_accounts: &[&dyn Signer],
+        context_state_authority: &Pubkey,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let mint_state = self.get_mint_info().await?;
+        let additional_auditor_elgamal_pubkeys = mint_state
+            .get_extension::<ConfidentialTransferAuditors>()
+            .map(|auditors| auditors.auditor_elgamal_pubkeys().collect::<Vec<_>>())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|auditor_elgamal_pubkey| {
+                auditor_elgamal_pubkey
+                    .try_into()
+                    .map_err(|_| TokenError::Program(ProgramError::InvalidAccountData))
+            })
+            .collect::<TokenResult<Vec<ElGamalPubkey>>>()?;
+        let maximum_transfer_amount = mint_state
+            .get_extension::<ConfidentialTransferMaximumTransferAmount>()
+            .ok()
+            .map(|extension| u64::from(extension.maximum_transfer_amount));
+
+        // two proofs for each additional auditor, followed by two for the maximum transfer amount
+        let auditor_proof_accounts_len = additional_auditor_elgamal_pubkeys.len() * 2;
+        let maximum_transfer_amount_proof_accounts_len =
+            maximum_transfer_amount.map(|_| 2).unwrap_or(0);
+        if additional_proof_accounts.len()
+            != auditor_proof_accounts_len + maximum_transfer_amount_proof_accounts_len
+        {
+            return Err(TokenError::Program(ProgramError::NotEnoughAccountKeys));
+        }
+        let (auditor_proof_accounts, maximum_transfer_amount_proof_accounts) =
+            additional_proof_accounts.split_at(auditor_proof_accounts_len);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let proof_data = account_info
+            .generate_transfer_proof_data(
+                transfer_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        self.create_proof_context_state_account(
+            transfer_proof_account,
+            size_of::<ProofContextState<TransferProofContext>>(),
+            ProofInstruction::VerifyTransfer.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &transfer_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &proof_data,
+            ),
+        )
+        .await?;
+
+        for (auditor_elgamal_pubkey, auditor_proof_accounts) in additional_auditor_elgamal_pubkeys
+            .iter()
+            .zip(auditor_proof_accounts.chunks(2))
+        {
+            let AuditorProofData {
+                equality_proof_data_lo,
+                equality_proof_data_hi,
+            } = generate_auditor_proof_data(
+                &proof_data,
+                transfer_amount,
+                source_elgamal_keypair,
+                auditor_elgamal_pubkey,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+            for (auditor_proof_account, equality_proof_data) in auditor_proof_accounts
+                .iter()
+                .zip([&equality_proof_data_lo, &equality_proof_data_hi])
+            {
+                self.create_proof_context_state_account(
+                    *auditor_proof_account,
+                    size_of::<ProofContextState<CiphertextCiphertextEqualityProofContext>>(),
+                    ProofInstruction::VerifyCiphertextCiphertextEquality.encode_verify_proof(
+                        Some(ContextStateInfo {
+                            context_state_account: &auditor_proof_account.pubkey(),
+                            context_state_authority,
+                        }),
+                        equality_proof_data,
+                    ),
+                )
+                .await?;
+            }
+        }
+
+        if let Some(maximum_transfer_amount) = maximum_transfer_amount {
+            let MaximumTransferAmountProofData {
+                equality_proof_data,
+                range_proof_data,
+            } = generate_maximum_transfer_amount_proof_data(
+                &proof_data,
+                transfer_amount,
+                maximum_transfer_amount,
+                source_elgamal_keypair,
+            )
+            .map_err(|e| TokenError::Program(e.into()))?;
+
+            let equality_proof_account = maximum_transfer_amount_proof_accounts[0];
+            self.create_proof_context_state_account(
+                equality_proof_account,
+                size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+                ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &equality_proof_account.pubkey(),
+                        context_state_authority,
+                    }),
+                    &equality_proof_data,
+                ),
+            )
+            .await?;
+
+            let range_proof_account = maximum_transfer_amount_proof_accounts[1];
+            self.create_proof_context_state_account(
+                range_proof_account,
+                size_of::<ProofContextState<BatchedRangeProofContext>>(),
+                ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &range_proof_account.pubkey(),
+                        context_state_authority,
+                    }),
+                    &range_proof_data,
+                ),
+            )
+            .await?;
+        }
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(transfer_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let additional_proof_context_state_accounts = additional_proof_accounts
+            .chunks(2)
+            .map(|accounts| (accounts[0].pubkey(), accounts[1].pubkey()))
+            .collect::<Vec<_>>();
+
+        self.process_ixs(
+            &confidential_transfer::instruction::transfer_with_additional_proofs(
+                &self.program_id,
+                source_account,
+                destination_account,
+                &self.pubkey,
+                new_decryptable_available_balance,
+                source_authority,
+                &multisig_signers,
+                ProofLocation::ContextStateAccount(&transfer_proof_account.pubkey()),
+                &additional_proof_context_state_accounts,
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Transfer tokens confidentially with an encrypted transfer reference
+    ///
+    /// The reference is encrypted with `EncryptedTransferReference::encrypt` under a key that the
+    /// recipient shares with its payers, and decrypted by the recipient from the instruction data
+    /// with `decode_transfer_reference` or from the logged `ConfidentialTransferReferenceEvent`. It
+    /// is not part of the zero-knowledge proof of the transfer, so it is not validated by the
+    /// program.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_reference<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        transfer_amount: u64,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        reference: &EncryptedTransferReference,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = self
+            .get_account_info(source_account)
+            .await?
+            .get_extension::<ConfidentialTransferAccount>()?
+            .transfer_account_info();
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let proof_data = build_transfer_proof_data(
+            &account_info,
+            transfer_amount,
+            source_elgamal_keypair,
+            source_aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+        )?;
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let debit_amount = if source_account == destination_account {
+            0
+        } else {
+            transfer_amount
+        };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.process_ixs(
+            &confidential_transfer::instruction::transfer_with_reference(
+                &self.program_id,
+                source_account,
+                destination_account,
+                &self.pubkey,
+                new_decryptable_available_balance,
+                source_authority,
+                &multisig_signers,
+                reference,
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Simulate a confidential transfer and predict the resulting balances without submitting it
+    ///
+    /// The transfer instruction is built as in `confidential_transfer_transfer`, with the proof
+    /// included in the transaction, and run through `simulate_transaction`. The simulation output
+    /// of the client does not carry the resulting account states, so the balances are predicted by
+    /// decrypting the current account states with the provided keys and applying the transfer to
+    /// them, which is exactly what the program does homomorphically. The destination pending
+    /// balance is only predicted if `destination_elgamal_keypair` is provided. Mints extended for
+    /// transfer fees are not supported by this helper.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn simulate_confidential_transfer<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        transfer_amount: u64,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        destination_elgamal_keypair: Option<&ElGamalKeypair>,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferSimulation<T::SimulationOutput>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = self
+            .get_account_info(source_account)
+            .await?
+            .get_extension::<ConfidentialTransferAccount>()?
+            .transfer_account_info();
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+        let source_available_balance =
+            DecryptableBalance::try_from(account_info.decryptable_available_balance)?
+                .decrypt(source_aes_key)
+                .ok_or(TokenError::AeBalanceDecryption)?;
+
+        let proof_data = build_transfer_proof_data(
+            &account_info,
+            transfer_amount,
+            source_elgamal_keypair,
+            source_aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+        )?;
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let is_self_transfer = source_account == destination_account;
+        let debit_amount = if is_self_transfer { 0 } else { transfer_amount };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let destination_pending_balance =
+            if let Some(destination_elgamal_keypair) = destination_elgamal_keypair {
+                let destination_account_info = self.get_account_info(destination_account).await?;
+                let extension =
+                    destination_account_info.get_extension::<ConfidentialTransferAccount>()?;
+                let pending_balance_lo = self
+                    .balance_decryptor
+                    .decrypt(destination_elgamal_keypair, &extension.pending_balance_lo)
+                    .ok_or(TokenError::ElGamalBalanceDecryption)?;
+                let pending_balance_hi = self
+                    .balance_decryptor
+                    .decrypt(destination_elgamal_keypair, &extension.pending_balance_hi)
+                    .ok_or(TokenError::ElGamalBalanceDecryption)?;
+                let credit_amount = if is_self_transfer { 0 } else { transfer_amount };
+                Some(
+                    pending_balance_hi
+                        .checked_shl(confidential_transfer::PENDING_BALANCE_LO_BIT_LENGTH)
+                        .and_then(|pending_balance_hi| {
+                            pending_balance_hi.checked_add(pending_balance_lo)
+                        })
+                        .and_then(|pending_balance| pending_balance.checked_add(credit_amount))
+                        .ok_or(TokenError::ElGamalBalanceDecryption)?,
+                )
+            } else {
+                None
+            };
+
+        let simulation = self
+            .simulate_ixs(
+                &confidential_transfer::instruction::transfer(
+                    &self.program_id,
+                    source_account,
+                    destination_account,
+                    &self.pubkey,
+                    new_decryptable_available_balance,
+                    source_authority,
+                    &multisig_signers,
+                    ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+                )?,
+                signing_keypairs,
+            )
+            .await?;
+
+        Ok(ConfidentialTransferSimulation {
+            simulation,
+            source_available_balance: source_available_balance - debit_amount,
+            destination_pending_balance,
+        })
+    }
+
+    /// Transfer tokens confidentially and return the generated transfer amount ciphertexts
+    ///
+    /// This is equivalent to `confidential_transfer_transfer` with the proof included in the
+    /// transaction, but also returns the low and high transfer amount ciphertexts of the proof so
+    /// that they can be stored and later decrypted by the auditor.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_auditor_decrypt_handle<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<ConfidentialTransferWithCiphertexts<T::Output>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let proof_data = account_info
+            .generate_transfer_proof_data(
+                transfer_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let debit_amount = if source_account == destination_account {
+            0
+        } else {
+            transfer_amount
+        };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        let output = self
+            .process_ixs(
+                &confidential_transfer::instruction::transfer(
+                    &self.program_id,
+                    source_account,
+                    destination_account,
+                    &self.pubkey,
+                    new_decryptable_available_balance,
+                    source_authority,
+                    &multisig_signers,
+                    ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+                )?,
+                signing_keypairs,
+            )
+            .await?;
+
+        Ok(ConfidentialTransferWithCiphertexts {
+            output,
+            transfer_amount_ciphertext_lo: proof_data.context.ciphertext_lo,
+            transfer_amount_ciphertext_hi: proof_data.context.ciphertext_hi,
+        })
+    }
+
+    /// Approve `delegate` to transfer up to `amount` out of the confidential balance of an account
+    ///
+    /// The delegate transfers with `confidential_transfer_delegated_transfer`. The account is
+    /// reallocated for the `ConfidentialTransferDelegateAllowance` extension if needed.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_approve_delegate<S: Signers>(
+        &self,
+        account: &Pubkey,
+        delegate: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let account_state = self.get_account_info(account).await?;
+
+        let mut instructions = vec![];
+        if account_state
+            .get_extension::<ConfidentialTransferDelegateAllowance>()
+            .is_err()
+        {
+            instructions.push(instruction::reallocate(
+                &self.program_id,
+                account,
+                &self.payer.pubkey(),
+                authority,
+                &multisig_signers,
+                &[ExtensionType::ConfidentialTransferDelegateAllowance],
+            )?);
+        }
+
+        instructions.push(
+            confidential_transfer::instruction::approve_confidential_delegate(
+                &self.program_id,
+                account,
+                delegate,
+                amount,
+                aes_key.encrypt(amount),
+                authority,
+                &multisig_signers,
+            )?,
+        );
+
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Transfer tokens confidentially out of an account as the delegate approved with
+    /// `confidential_transfer_approve_delegate`
+    ///
+    /// The delegate generates the proofs for the source account, so it must hold the ElGamal
+    /// keypair and AES key of the source account. The equality and range proofs that the transfer
+    /// amount does not exceed the remaining allowance are verified into the context state accounts
+    /// `equality_proof_account` and `range_proof_account`, which are left open and can be closed
+    /// by the context state authority afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_delegated_transfer<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        delegate: &Pubkey,
+        equality_proof_account: &dyn Signer,
+        range_proof_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        transfer_amount: u64,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(delegate, &signing_pubkeys);
+
+        let source_account_state = self.get_account_info(source_account).await?;
+        let account_info = source_account_state
+            .get_extension::<ConfidentialTransferAccount>()?
+            .transfer_account_info();
+        let delegate_allowance =
+            source_account_state.get_extension::<ConfidentialTransferDelegateAllowance>()?;
+        let decryptable_allowance = delegate_allowance
+            .decryptable_allowance
+            .try_into()
+            .map_err(|_| TokenError::AeBalanceDecryption)?;
+        let allowance = source_aes_key
+            .decrypt(&decryptable_allowance)
+            .ok_or(TokenError::AeBalanceDecryption)?;
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let proof_data = account_info
+            .generate_transfer_proof_data(
+                transfer_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        let DelegateAllowanceProofData {
+            equality_proof_data,
+            range_proof_data,
+        } = generate_delegate_allowance_proof_data(
+            &proof_data,
+            transfer_amount,
+            &delegate_allowance.allowance,
+            allowance,
+            source_elgamal_keypair,
+        )
+        .map_err(|e| TokenError::Program(e.into()))?;
+
+        self.create_proof_context_state_account(
+            equality_proof_account,
+            size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+            ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &equality_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &equality_proof_data,
+            ),
+        )
+        .await?;
+
+        self.create_proof_context_state_account(
+            range_proof_account,
+            size_of::<ProofContextState<BatchedRangeProofContext>>(),
+            ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &range_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &range_proof_data,
+            ),
+        )
+        .await?;
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let debit_amount = if source_account == destination_account {
+            0
+        } else {
+            transfer_amount
+        };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.process_ixs(
+            &confidential_transfer::instruction::delegated_transfer(
+                &self.program_id,
+                source_account,
+                destination_account,
+                &self.pubkey,
+                new_decryptable_available_balance,
+                source_aes_key.encrypt(allowance - transfer_amount),
+                &equality_proof_account.pubkey(),
+                &range_proof_account.pubkey(),
+                delegate,
+                &multisig_signers,
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Authorize `puller` to pull a single confidential transfer from an account
+    ///
+    /// The transfer proof is generated in advance by the source account owner, for example with
+    /// `build_transfer_proof_data`, and must be handed to the puller, who submits it with
+    /// `confidential_transfer_pull_transfer`. The account is reallocated for the
+    /// `ConfidentialTransferPullAuthorization` extension if needed.
+    ///
+    /// The pull transfer leaves the decryptable available balance of the source account unchanged,
+    /// so the owner refreshes it with `confidential_transfer_repair_decryptable_balance` after the
+    /// pull before transferring, withdrawing, or burning from the account again.
+    pub async fn confidential_transfer_approve_pull_transfer<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        puller: &Pubkey,
+        source_authority: &Pubkey,
+        proof_data: &TransferData,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account = self.get_account_info(source_account).await?;
+
+        let mut instructions = vec![];
+        if account
+            .get_extension::<ConfidentialTransferPullAuthorization>()
+            .is_err()
+        {
+            instructions.push(instruction::reallocate(
+                &self.program_id,
+                source_account,
+                &self.payer.pubkey(),
+                source_authority,
+                &multisig_signers,
+                &[ExtensionType::ConfidentialTransferPullAuthorization],
+            )?);
+        }
+
+        instructions.push(confidential_transfer::instruction::approve_pull_transfer(
+            &self.program_id,
+            source_account,
+            puller,
+            proof_data,
+            source_authority,
+            &multisig_signers,
+        )?);
+
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Pull a confidential transfer that was authorized by the source account owner with
+    /// `confidential_transfer_approve_pull_transfer`
+    pub async fn confidential_transfer_pull_transfer<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        puller: &Pubkey,
+        proof_location: ProofLocation<'_, TransferData>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(puller, &signing_pubkeys);
+
+        self.process_ixs(
+            &confidential_transfer::instruction::pull_transfer(
+                &self.program_id,
+                source_account,
+                destination_account,
+                &self.pubkey,
+                puller,
+                &multisig_signers,
+                proof_location,
+            )?,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Atomically swap confidential balances of this mint and the mint of `counterparty_token`
+    ///
+    /// `leg` transfers tokens of this mint and `counterparty_leg` transfers tokens of the
+    /// counterparty mint in the opposite direction. The transfer proof of each leg is verified into
+    /// its `proof_account` before the swap instruction is submitted, and the swap is signed by the
+    /// source authorities of both legs. The context state accounts are left open and can be closed
+    /// by the context state authority afterwards. Mints extended for transfer fees are not
+    /// supported by this helper.
+    pub async fn confidential_atomic_swap<S: Signers>(
+        &self,
+        counterparty_token: &Token<T>,
+        leg: ConfidentialSwapLeg<'_>,
+        counterparty_leg: ConfidentialSwapLeg<'_>,
+        context_state_authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let new_decryptable_available_balance = self
+            .verify_confidential_swap_leg(&leg, context_state_authority)
+            .await?;
+        let counterparty_new_decryptable_available_balance = counterparty_token
+            .verify_confidential_swap_leg(&counterparty_leg, context_state_authority)
+            .await?;
+
+        self.process_ixs(
+            &[confidential_transfer::instruction::atomic_swap(
+                &self.program_id,
+                leg.source_account,
+                leg.destination_account,
+                &self.pubkey,
+                &leg.proof_account.pubkey(),
+                leg.source_authority,
+                new_decryptable_available_balance.into(),
+                counterparty_leg.source_account,
+                counterparty_leg.destination_account,
+                &counterparty_token.pubkey,
+                &counterparty_leg.proof_account.pubkey(),
+                counterparty_leg.source_authority,
+                counterparty_new_decryptable_available_balance.into(),
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Verify the transfer proof of a confidential swap leg into its proof account and return the
+    /// new decryptable available balance of its source account
+    async fn verify_confidential_swap_leg(
+        &self,
+        leg: &ConfidentialSwapLeg<'_>,
+        context_state_authority: &Pubkey,
+    ) -> TokenResult<AeCiphertext> {
+        let account_info = self
+            .get_account_info(leg.source_account)
+            .await?
+            .get_extension::<ConfidentialTransferAccount>()?
+            .transfer_account_info();
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            leg.transfer_amount,
+            leg.source_aes_key,
+        )?;
+
+        let proof_data = build_transfer_proof_data(
+            &account_info,
+            leg.transfer_amount,
+            leg.source_elgamal_keypair,
+            leg.source_aes_key,
+            leg.destination_elgamal_pubkey,
+            leg.auditor_elgamal_pubkey,
+        )?;
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(leg.transfer_amount, leg.source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.create_proof_context_state_account(
+            leg.proof_account,
+            size_of::<ProofContextState<TransferProofContext>>(),
+            ProofInstruction::VerifyTransfer.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &leg.proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &proof_data,
+            ),
+        )
+        .await?;
+
+        Ok(new_decryptable_available_balance)
+    }
+
+    /// Transfer tokens confidentially using split proofs
+    ///
+    /// The equality, ciphertext validity, range, and source transfer amount proofs for the transfer
+    /// are each verified into a separate context state account before the transfer instruction is
+    /// submitted. The context state accounts are left open and can be closed by the context state
+    /// authority afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_split_proofs<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        equality_proof_account: &dyn Signer,
+        ciphertext_validity_proof_account: &dyn Signer,
+        range_proof_account: &dyn Signer,
+        source_amount_lo_proof_account: &dyn Signer,
+        source_amount_hi_proof_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let TransferSplitProofData {
+            equality_proof_data,
+            ciphertext_validity_proof_data,
+            range_proof_data,
+            source_amount_equality_proof_data_lo,
+            source_amount_equality_proof_data_hi,
+        } = account_info
+            .generate_split_transfer_proof_data(
+                transfer_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let debit_amount = if source_account == destination_account {
+            0
+        } else {
+            transfer_amount
+        };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.create_proof_context_state_account(
+            equality_proof_account,
+            size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+            ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &equality_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &equality_proof_data,
+            ),
+        )
+        .await?;
+
+        self.create_proof_context_state_account(
+            ciphertext_validity_proof_account,
+            size_of::<ProofContextState<BatchedGroupedCiphertext2HandlesValidityProofContext>>(),
+            ProofInstruction::VerifyBatchedGroupedCiphertext2HandlesValidity.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &ciphertext_validity_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &ciphertext_validity_proof_data,
+            ),
+        )
+        .await?;
+
+        self.create_proof_context_state_account(
+            range_proof_account,
+            size_of::<ProofContextState<BatchedRangeProofContext>>(),
+            ProofInstruction::VerifyBatchedRangeProofU128.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &range_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &range_proof_data,
+            ),
+        )
+        .await?;
+
+        for (source_amount_proof_account, source_amount_equality_proof_data) in [
+            (
+                source_amount_lo_proof_account,
+                &source_amount_equality_proof_data_lo,
+            ),
+            (
+                source_amount_hi_proof_account,
+                &source_amount_equality_proof_data_hi,
+            ),
+        ] {
+            self.create_proof_context_state_account(
+                source_amount_proof_account,
+                size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+                ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &source_amount_proof_account.pubkey(),
+                        context_state_authority,
+                    }),
+                    source_amount_equality_proof_data,
+                ),
+            )
+            .await?;
+        }
+
+        self.process_ixs(
+            &[
+                confidential_transfer::instruction::transfer_with_split_proofs(
+                    &self.program_id,
+                    source_account,
+                    destination_account,
+                    &self.pubkey,
+                    new_decryptable_available_balance,
+                    source_authority,
+                    &multisig_signers,
+                    &equality_proof_account.pubkey(),
+                    &ciphertext_validity_proof_account.pubkey(),
+                    &range_proof_account.pubkey(),
+                    &source_amount_lo_proof_account.pubkey(),
+                    &source_amount_hi_proof_account.pubkey(),
+                )?,
+            ],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Transfer tokens confidentially using split proofs with separate lo and hi validity proofs
+    ///
+    /// The equality, range, and source transfer amount proofs for the transfer are each verified
+    /// into a separate context state account, while the ciphertext validity proofs for the low and
+    /// high bits of the transfer amount are included in the transfer transaction. The context
+    /// state accounts are left open and can be closed by the context state authority afterwards.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_split_validity_proofs<S: Signers>(
+        &self,
+        source_account: &Pubkey,
+        destination_account: &Pubkey,
+        source_authority: &Pubkey,
+        equality_proof_account: &dyn Signer,
+        range_proof_account: &dyn Signer,
+        source_amount_lo_proof_account: &dyn Signer,
+        source_amount_hi_proof_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
+        let TransferSplitValidityProofData {
+            equality_proof_data,
+            ciphertext_validity_proof_data_lo,
+            ciphertext_validity_proof_data_hi,
+            range_proof_data,
+            source_amount_equality_proof_data_lo,
+            source_amount_equality_proof_data_hi,
+        } = account_info
+            .generate_split_validity_transfer_proof_data(
+                transfer_amount,
+                source_elgamal_keypair,
+                source_aes_key,
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        // a self-transfer is credited back to the available balance, leaving it unchanged
+        let debit_amount = if source_account == destination_account {
+            0
+        } else {
+            transfer_amount
+        };
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(debit_amount, source_aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.create_proof_context_state_account(
+            equality_proof_account,
+            size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+            ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &equality_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &equality_proof_data,
+            ),
         )
-        .map_err(TokenError::Proof)?;
+        .await?;
+
+        self.create_proof_context_state_account(
+            range_proof_account,
+            size_of::<ProofContextState<BatchedRangeProofContext>>(),
+            ProofInstruction::VerifyBatchedRangeProofU128.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &range_proof_account.pubkey(),
+                    context_state_authority,
+                }),
+                &range_proof_data,
+            ),
+        )
+        .await?;
 
-        let remaining_balance = available_balance
-            .checked_sub(amount)
-            .ok_or(TokenError::NotEnoughFunds)?;
-        let new_decryptable_available_balance =
-            authenticated_encryption_key.encrypt(remaining_balance);
+        for (source_amount_proof_account, source_amount_equality_proof_data) in [
+            (
+                source_amount_lo_proof_account,
+                &source_amount_equality_proof_data_lo,
+            ),
+            (
+                source_amount_hi_proof_account,
+                &source_amount_equality_proof_data_hi,
+            ),
+        ] {
+            self.create_proof_context_state_account(
+                source_amount_proof_account,
+                size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+                ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &source_amount_proof_account.pubkey(),
+                        context_state_authority,
+                    }),
+                    source_amount_equality_proof_data,
+                ),
+            )
+            .await?;
+        }
 
         self.process_ixs(
-            &confidential_transfer::instruction::withdraw(
+            &confidential_transfer::instruction::transfer_with_split_validity_proofs(
                 &self.program_id,
-                token_account,
+                source_account,
+                destination_account,
                 &self.pubkey,
-                amount,
-                decimals,
                 new_decryptable_available_balance,
-                &token_authority.pubkey(),
-                &[],
-                &proof_data,
+                source_authority,
+                &multisig_signers,
+                &equality_proof_account.pubkey(),
+                ProofLocation::InstructionOffset(
+                    1.try_into().unwrap(),
+                    &ciphertext_validity_proof_data_lo,
+                ),
+                ProofLocation::InstructionOffset(
+                    2.try_into().unwrap(),
+                    &ciphertext_validity_proof_data_hi,
+                ),
+                &range_proof_account.pubkey(),
+                &source_amount_lo_proof_account.pubkey(),
+                &source_amount_hi_proof_account.pubkey(),
             )?,
-            &[token_authority],
+            signing_keypairs,
         )
         .await
     }
 
-    /// Transfer tokens confidentially
+    /// Verify the proof of a `ConfigureAccount` instruction into a context state account
+    ///
+    /// The context state account is paid for by the payer of this token client, while
+    /// `context_state_authority` is the only account that can close it. This lets a relayer create
+    /// the context state for an owner that keeps control over it. Pass the context state account
+    /// to `confidential_transfer_configure_token_account` to use the proof.
+    pub async fn confidential_transfer_create_configure_account_proof_context(
+        &self,
+        context_state_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        elgamal_keypair: &ElGamalKeypair,
+    ) -> TokenResult<T::Output> {
+        let proof_data =
+            confidential_transfer::instruction::PubkeyValidityData::new(elgamal_keypair)
+                .map_err(|_| TokenError::ProofGeneration)?;
+
+        self.create_proof_context_state_account(
+            context_state_account,
+            size_of::<ProofContextState<PubkeyValidityProofContext>>(),
+            ProofInstruction::VerifyPubkeyValidity.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &context_state_account.pubkey(),
+                    context_state_authority,
+                }),
+                &proof_data,
+            ),
+        )
+        .await
+    }
+
+    /// Verify the proof of an `EmptyAccount` instruction into a context state account that can
+    /// only be closed by `context_state_authority`
+    ///
+    /// Pass the context state account to `confidential_transfer_empty_account` to use the proof.
+    pub async fn confidential_transfer_create_empty_account_proof_context(
+        &self,
+        account: &Pubkey,
+        context_state_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        account_info: Option<EmptyAccountAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+    ) -> TokenResult<T::Output> {
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .empty_account_account_info()
+        };
+
+        let proof_data = account_info
+            .generate_proof_data(elgamal_keypair)
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        self.create_proof_context_state_account(
+            context_state_account,
+            size_of::<ProofContextState<ZeroBalanceProofContext>>(),
+            ProofInstruction::VerifyZeroBalance.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &context_state_account.pubkey(),
+                    context_state_authority,
+                }),
+                &proof_data,
+            ),
+        )
+        .await
+    }
+
+    /// Verify the proof of a `Withdraw` instruction into a context state account that can only be
+    /// closed by `context_state_authority`
+    ///
+    /// Pass the context state account to `confidential_transfer_withdraw` with the same
+    /// `withdraw_amount` to use the proof.
     #[allow(clippy::too_many_arguments)]
-    pub async fn confidential_transfer_transfer<S: Signers>(
+    pub async fn confidential_transfer_create_withdraw_proof_context(
+        &self,
+        account: &Pubkey,
+        context_state_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        withdraw_amount: u64,
+        account_info: Option<WithdrawAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+    ) -> TokenResult<T::Output> {
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .withdraw_account_info()
+        };
+
+        let proof_data = account_info
+            .generate_proof_data(withdraw_amount, elgamal_keypair, aes_key)
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        self.create_proof_context_state_account(
+            context_state_account,
+            size_of::<ProofContextState<WithdrawProofContext>>(),
+            ProofInstruction::VerifyWithdraw.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &context_state_account.pubkey(),
+                    context_state_authority,
+                }),
+                &proof_data,
+            ),
+        )
+        .await
+    }
+
+    /// Verify the proof of a `Transfer` instruction into a context state account that can only be
+    /// closed by `context_state_authority`
+    ///
+    /// Pass the context state account to `confidential_transfer_transfer` with the same
+    /// `transfer_amount` to use the proof.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_create_transfer_proof_context(
+        &self,
+        source_account: &Pubkey,
+        context_state_account: &dyn Signer,
+        context_state_authority: &Pubkey,
+        transfer_amount: u64,
+        account_info: Option<TransferAccountInfo>,
+        source_elgamal_keypair: &ElGamalKeypair,
+        source_aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+    ) -> TokenResult<T::Output> {
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(source_account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info()
+        };
+
+        let proof_data = build_transfer_proof_data(
+            &account_info,
+            transfer_amount,
+            source_elgamal_keypair,
+            source_aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+        )?;
+
+        self.create_proof_context_state_account(
+            context_state_account,
+            size_of::<ProofContextState<TransferProofContext>>(),
+            ProofInstruction::VerifyTransfer.encode_verify_proof(
+                Some(ContextStateInfo {
+                    context_state_account: &context_state_account.pubkey(),
+                    context_state_authority,
+                }),
+                &proof_data,
+            ),
+        )
+        .await
+    }
+
+    /// Verify a zero-knowledge proof into a context state account that may already hold an
+    /// earlier proof
+    ///
+    /// A sequence of operations by the same context state authority can use one context state
+    /// account for all of its proofs instead of creating a new account per proof. If the account
+    /// already holds a proof, it is closed and recreated at the same address in a single
+    /// transaction, with its rent lamports returned to the payer, and the new proof is then
+    /// verified into it. If the account does not exist yet or is still uninitialized, it is
+    /// created or used as is.
+    ///
+    /// Reusing an account is only safe once every instruction that consumes the earlier proof has
+    /// been processed: closing the account destroys that proof, and a pending transaction that
+    /// still refers to the account is processed against the new proof, which it does not match.
+    /// Operations that share a context state account must therefore be submitted one after the
+    /// other. Both the account keypair and `context_state_authority` must sign to recreate the
+    /// account.
+    pub async fn confidential_transfer_reuse_proof_context<P, C>(
+        &self,
+        context_state_account: &dyn Signer,
+        context_state_authority: &dyn Signer,
+        proof_instruction: ProofInstruction,
+        proof_data: &P,
+    ) -> TokenResult<T::Output>
+    where
+        P: Pod + ZkProofData<C>,
+        C: Pod,
+    {
+        let context_state_pubkey = context_state_account.pubkey();
+        let context_state_authority_pubkey = context_state_authority.pubkey();
+        let space = size_of::<ProofContextState<C>>();
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_pubkey,
+            context_state_authority: &context_state_authority_pubkey,
+        };
+        let verify_proof_instruction =
+            proof_instruction.encode_verify_proof(Some(context_state_info), proof_data);
+
+        let existing_account = self
+            .client
+            .get_account(context_state_pubkey)
+            .await
+            .map_err(TokenError::Client)?;
+        let existing_account = match existing_account {
+            Some(account) => account,
+            None => {
+                return self
+                    .create_proof_context_state_account(
+                        context_state_account,
+                        space,
+                        verify_proof_instruction,
+                    )
+                    .await;
+            }
+        };
+
+        if existing_account.owner != zk_token_proof_program::id() {
+            return Err(TokenError::AccountInvalidOwner);
+        }
+
+        // an account that was allocated but never verified into can be used directly if it has
+        // the right size, while an account holding a proof must be closed by its authority first
+        let is_uninitialized = existing_account.data.iter().all(|byte| *byte == 0);
+        if is_uninitialized {
+            if existing_account.data.len() != space {
+                return Err(TokenError::Program(ProgramError::InvalidAccountData));
+            }
+        } else {
+            self.process_ixs(
+                &[
+                    close_context_state(context_state_info, &self.payer.pubkey()),
+                    system_instruction::create_account(
+                        &self.payer.pubkey(),
+                        &context_state_pubkey,
+                        self.client
+                            .get_minimum_balance_for_rent_exemption(space)
+                            .await
+                            .map_err(TokenError::Client)?,
+                        space as u64,
+                        &zk_token_proof_program::id(),
+                    ),
+                ],
+                &[context_state_account, context_state_authority],
+            )
+            .await?;
+        }
+
+        self.process_ixs(&[verify_proof_instruction], &[] as &[&dyn Signer; 0])
+            .await
+    }
+
+    /// Whether a failed confidential transfer or withdraw should be retried with a regenerated
+    /// proof
+    fn should_retry_on_balance_mismatch(
+        &self,
+        context_state_account: Option<&Pubkey>,
+        error: &TokenError,
+    ) -> bool {
+        self.retry_on_balance_mismatch
+            && context_state_account.is_none()
+            && self.nonce_account.is_none()
+            && is_confidential_transfer_balance_mismatch(error)
+    }
+
+    /// Fetch the transfer fee of the mint that is in effect for the current epoch
+    ///
+    /// The `maximum_fee` and `transfer_fee_basis_points` of the returned fee are the fee
+    /// parameters that a transfer with fee proof must be generated with to be accepted in the
+    /// current epoch.
+    pub async fn confidential_transfer_current_epoch_fee(
+        &self,
+    ) -> TokenResult<transfer_fee::TransferFee> {
+        let mint_state = self.get_mint_info().await?;
+        let transfer_fee_config = mint_state.get_extension::<transfer_fee::TransferFeeConfig>()?;
+
+        let clock_account = self.get_account(sysvar::clock::id()).await?;
+        let clock = from_account::<Clock, _>(&clock_account)
+            .ok_or(TokenError::Program(ProgramError::InvalidAccountData))?;
+
+        Ok(*transfer_fee_config.get_epoch_fee(clock.epoch))
+    }
+
+    /// Transfer tokens confidentially with fee
+    ///
+    /// A self-transfer does not incur a fee, so it is submitted as in
+    /// `confidential_transfer_transfer` and the fee parameters are ignored. A context state
+    /// account for a self-transfer must then hold a transfer proof instead of a transfer with fee
+    /// proof.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_transfer_with_fee<S: Signers>(
         &self,
         source_account: &Pubkey,
         destination_account: &Pubkey,
@@ -2126,8 +6489,29 @@ where
         source_aes_key: &AeKey,
         destination_elgamal_pubkey: &ElGamalPubkey,
         auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+        withdraw_withheld_authority_elgamal_pubkey: &ElGamalPubkey,
+        fee_rate_basis_points: u16,
+        maximum_fee: u64,
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
+        if source_account == destination_account {
+            return self
+                .confidential_transfer_transfer(
+                    source_account,
+                    destination_account,
+                    source_authority,
+                    context_state_account,
+                    transfer_amount,
+                    account_info,
+                    source_elgamal_keypair,
+                    source_aes_key,
+                    destination_elgamal_pubkey,
+                    auditor_elgamal_pubkey,
+                    signing_keypairs,
+                )
+                .await;
+        }
+
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
 
@@ -2140,17 +6524,31 @@ where
                 .transfer_account_info()
         };
 
+        if self.check_destination_elgamal_pubkey {
+            let destination_account = self.get_account_info(destination_account).await?;
+            check_destination_elgamal_pubkey(&destination_account, destination_elgamal_pubkey)?;
+        }
+
+        check_confidential_funds(
+            &account_info.decryptable_available_balance,
+            transfer_amount,
+            source_aes_key,
+        )?;
+
         let proof_data = if context_state_account.is_some() {
             None
         } else {
             Some(
                 account_info
-                    .generate_transfer_proof_data(
+                    .generate_transfer_with_fee_proof_data(
                         transfer_amount,
                         source_elgamal_keypair,
                         source_aes_key,
                         destination_elgamal_pubkey,
                         auditor_elgamal_pubkey,
+                        withdraw_withheld_authority_elgamal_pubkey,
+                        fee_rate_basis_points,
+                        maximum_fee,
                     )
                     .map_err(|_| TokenError::ProofGeneration)?,
             )
@@ -2168,7 +6566,7 @@ where
             .map_err(|_| TokenError::AccountDecryption)?;
 
         self.process_ixs(
-            &confidential_transfer::instruction::transfer(
+            &confidential_transfer::instruction::transfer_with_fee(
                 &self.program_id,
                 source_account,
                 destination_account,
@@ -2183,141 +6581,353 @@ where
         .await
     }
 
-    /// Transfer tokens confidentially with fee
+    /// Transfer tokens confidentially, with or without a fee as required by the mint
+    ///
+    /// The program requires a transfer with fee proof if the mint has the `TransferFeeConfig`
+    /// extension and the transfer is not a self-transfer, and a transfer proof otherwise. This
+    /// function reads the mint to make the same choice, so that callers do not need to know
+    /// whether the mint charges fees. The proofs are generated for the auditor ElGamal public key
+    /// of the mint and, for transfers with fee, for the withdraw withheld authority ElGamal public
+    /// key of the mint and the transfer fee in effect for the current epoch.
     #[allow(clippy::too_many_arguments)]
-    pub async fn confidential_transfer_transfer_with_fee<S: Signers>(
+    pub async fn confidential_transfer_send<S: Signers>(
         &self,
         source_account: &Pubkey,
         destination_account: &Pubkey,
         source_authority: &Pubkey,
-        context_state_account: Option<&Pubkey>,
         transfer_amount: u64,
         account_info: Option<TransferAccountInfo>,
         source_elgamal_keypair: &ElGamalKeypair,
         source_aes_key: &AeKey,
         destination_elgamal_pubkey: &ElGamalPubkey,
-        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
-        withdraw_withheld_authority_elgamal_pubkey: &ElGamalPubkey,
-        fee_rate_basis_points: u16,
-        maximum_fee: u64,
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
-        let signing_pubkeys = signing_keypairs.pubkeys();
-        let multisig_signers = self.get_multisig_signers(source_authority, &signing_pubkeys);
+        let auditor_elgamal_pubkey = self.confidential_transfer_current_auditor().await?;
+
+        let mint_state = self.get_mint_info().await?;
+        if mint_state
+            .get_extension::<transfer_fee::TransferFeeConfig>()
+            .is_err()
+            || source_account == destination_account
+        {
+            return self
+                .confidential_transfer_transfer(
+                    source_account,
+                    destination_account,
+                    source_authority,
+                    None,
+                    transfer_amount,
+                    account_info,
+                    source_elgamal_keypair,
+                    source_aes_key,
+                    destination_elgamal_pubkey,
+                    auditor_elgamal_pubkey.as_ref(),
+                    signing_keypairs,
+                )
+                .await;
+        }
+
+        let withdraw_withheld_authority_elgamal_pubkey: ElGamalPubkey = mint_state
+            .get_extension::<ConfidentialTransferFeeConfig>()?
+            .withdraw_withheld_authority_elgamal_pubkey
+            .try_into()
+            .map_err(|_| TokenError::Program(ProgramError::InvalidAccountData))?;
+        let fee = self.confidential_transfer_current_epoch_fee().await?;
+
+        self.confidential_transfer_transfer_with_fee(
+            source_account,
+            destination_account,
+            source_authority,
+            None,
+            transfer_amount,
+            account_info,
+            source_elgamal_keypair,
+            source_aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey.as_ref(),
+            &withdraw_withheld_authority_elgamal_pubkey,
+            u16::from(fee.transfer_fee_basis_points),
+            u64::from(fee.maximum_fee),
+            signing_keypairs,
+        )
+        .await
+    }
 
+    /// Applies the confidential transfer pending balance to the available balance
+    ///
+    /// The new decryptable available balance is always computed from the account: the available
+    /// balance is decrypted under `aes_key`, the pending balance under `elgamal_secret_key`, and
+    /// their sum is encrypted under `aes_key`. The program stores the decryptable available balance
+    /// as given, so computing it here keeps it in sync with the available balance ciphertext.
+    pub async fn confidential_transfer_apply_pending_balance<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        account_info: Option<ApplyPendingBalanceAccountInfo>,
+        elgamal_secret_key: &ElGamalSecretKey,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
         let account_info = if let Some(account_info) = account_info {
             account_info
         } else {
-            self.get_account_info(source_account)
+            self.get_account_info(account)
                 .await?
                 .get_extension::<ConfidentialTransferAccount>()?
-                .transfer_account_info()
+                .apply_pending_balance_account_info()
         };
 
-        let proof_data = if context_state_account.is_some() {
-            None
-        } else {
-            Some(
-                account_info
-                    .generate_transfer_with_fee_proof_data(
-                        transfer_amount,
-                        source_elgamal_keypair,
-                        source_aes_key,
-                        destination_elgamal_pubkey,
-                        auditor_elgamal_pubkey,
-                        withdraw_withheld_authority_elgamal_pubkey,
-                        fee_rate_basis_points,
-                        maximum_fee,
-                    )
-                    .map_err(|_| TokenError::ProofGeneration)?,
-            )
-        };
+        let expected_pending_balance_credit_counter = account_info.pending_balance_credit_counter();
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(elgamal_secret_key, aes_key)
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+        self.confidential_transfer_apply_pending_balance_with_decryptable_balance(
+            account,
+            authority,
+            expected_pending_balance_credit_counter,
+            new_decryptable_available_balance,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Applies the confidential transfer pending balance to the available balance with a
+    /// caller-provided decryptable available balance
+    ///
+    /// The program does not check that `new_decryptable_available_balance` encrypts the sum of
+    /// the available and pending balances, so a wrong value leaves the decryptable available
+    /// balance out of sync with the available balance ciphertext. Prefer
+    /// `confidential_transfer_apply_pending_balance`, which computes it from the account.
+    pub async fn confidential_transfer_apply_pending_balance_with_decryptable_balance<
+        S: Signers,
+    >(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        expected_pending_balance_credit_counter: u64,
+        new_decryptable_available_balance: AeCiphertext,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[confidential_transfer::instruction::apply_pending_balance(
+                &self.program_id,
+                account,
+                expected_pending_balance_credit_counter,
+                new_decryptable_available_balance,
+                authority,
+                &multisig_signers,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Applies the confidential transfer pending balance to the available balance, using the
+    /// pending balance credit counter and balances that are currently stored in the account
+    pub async fn confidential_transfer_apply_pending_balance_with_fetch<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        elgamal_secret_key: &ElGamalSecretKey,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        self.confidential_transfer_apply_pending_balance(
+            account,
+            authority,
+            None,
+            elgamal_secret_key,
+            aes_key,
+            signing_keypairs,
+        )
+        .await
+    }
 
-        let proof_location = if let Some(proof_data_temp) = proof_data.as_ref() {
-            ProofLocation::InstructionOffset(1.try_into().unwrap(), proof_data_temp)
-        } else {
-            let context_state_account = context_state_account.unwrap();
-            ProofLocation::ContextStateAccount(context_state_account)
-        };
+    /// Applies the confidential transfer pending balance to the available balance only if the
+    /// resulting available balance is guaranteed to stay at or below `maximum_available_balance`
+    ///
+    /// The available balance ciphertext is decrypted by solving a discrete log, so its decryption
+    /// time grows with the balance. Since the pending balance ciphertexts are not decrypted ahead of
+    /// time, the pending balance is bounded by the number of pending credits times
+    /// `maximum_credit_amount`, the largest amount that a single deposit or transfer to the account
+    /// is expected to carry. Fails with `MaximumAvailableBalanceExceeded` if the bound exceeds
+    /// `maximum_available_balance`, in which case the pending balance is left untouched.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn confidential_transfer_apply_pending_balance_with_limit<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        maximum_credit_amount: u64,
+        maximum_available_balance: u64,
+        elgamal_secret_key: &ElGamalSecretKey,
+        aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let state = self.get_account_info(account).await?;
+        let extension = state.get_extension::<ConfidentialTransferAccount>()?;
+
+        let available_balance =
+            DecryptableBalance::try_from(extension.decryptable_available_balance)?
+                .decrypt(aes_key)
+                .ok_or(TokenError::AeBalanceDecryption)?;
+
+        let pending_balance_credit_counter = u64::from(extension.pending_balance_credit_counter);
+        let new_available_balance_bound = pending_balance_credit_counter
+            .checked_mul(maximum_credit_amount)
+            .and_then(|pending_balance_bound| pending_balance_bound.checked_add(available_balance));
+        if !matches!(
+            new_available_balance_bound,
+            Some(bound) if bound <= maximum_available_balance
+        ) {
+            return Err(TokenError::MaximumAvailableBalanceExceeded);
+        }
 
-        let new_decryptable_available_balance = account_info
-            .new_decryptable_available_balance(transfer_amount, source_aes_key)
-            .map_err(|_| TokenError::AccountDecryption)?;
+        self.confidential_transfer_apply_pending_balance(
+            account,
+            authority,
+            Some(extension.apply_pending_balance_account_info()),
+            elgamal_secret_key,
+            aes_key,
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Enable confidential transfer `Deposit` and `Transfer` instructions for a token account
+    pub async fn confidential_transfer_enable_confidential_credits<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
         self.process_ixs(
-            &confidential_transfer::instruction::transfer_with_fee(
-                &self.program_id,
-                source_account,
-                destination_account,
-                &self.pubkey,
-                new_decryptable_available_balance,
-                source_authority,
-                &multisig_signers,
-                proof_location,
-            )?,
+            &[
+                confidential_transfer::instruction::enable_confidential_credits(
+                    &self.program_id,
+                    account,
+                    authority,
+                    &multisig_signers,
+                )?,
+            ],
             signing_keypairs,
         )
         .await
     }
 
-    /// Applies the confidential transfer pending balance to the available balance
-    pub async fn confidential_transfer_apply_pending_balance<S: Signers>(
+    /// Enable confidential transfer `Deposit` and `Transfer` instructions for a token account until
+    /// the given slot
+    ///
+    /// The account is reallocated for the `ConfidentialTransferCreditsUntilSlot` extension if
+    /// needed.
+    pub async fn confidential_transfer_enable_confidential_credits_until_slot<S: Signers>(
         &self,
         account: &Pubkey,
         authority: &Pubkey,
-        account_info: Option<ApplyPendingBalanceAccountInfo>,
-        elgamal_secret_key: &ElGamalSecretKey,
-        aes_key: &AeKey,
+        slot: u64,
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
-        let account_info = if let Some(account_info) = account_info {
-            account_info
-        } else {
-            self.get_account_info(account)
+        let mut instructions = vec![];
+        if slot != 0
+            && self
+                .get_account_info(account)
                 .await?
-                .get_extension::<ConfidentialTransferAccount>()?
-                .apply_pending_balance_account_info()
-        };
-
-        let expected_pending_balance_credit_counter = account_info.pending_balance_credit_counter();
-        let new_decryptable_available_balance = account_info
-            .new_decryptable_available_balance(elgamal_secret_key, aes_key)
-            .map_err(|_| TokenError::AccountDecryption)?;
+                .get_extension::<ConfidentialTransferCreditsUntilSlot>()
+                .is_err()
+        {
+            instructions.push(instruction::reallocate(
+                &self.program_id,
+                account,
+                &self.payer.pubkey(),
+                authority,
+                &multisig_signers,
+                &[ExtensionType::ConfidentialTransferCreditsUntilSlot],
+            )?);
+        }
 
-        self.process_ixs(
-            &[confidential_transfer::instruction::apply_pending_balance(
+        instructions.push(
+            confidential_transfer::instruction::enable_confidential_credits_until_slot(
                 &self.program_id,
                 account,
-                expected_pending_balance_credit_counter,
-                new_decryptable_available_balance,
                 authority,
                 &multisig_signers,
-            )?],
+                slot,
+            )?,
+        );
+
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Update the maximum number of confidential transfer `Deposit` and `Transfer` instructions
+    /// that can credit the pending balance of a token account before it must be applied
+    pub async fn confidential_transfer_update_max_pending_credit_counter<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        maximum_pending_balance_credit_counter: u64,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[
+                confidential_transfer::instruction::update_max_pending_balance_credit_counter(
+                    &self.program_id,
+                    account,
+                    authority,
+                    &multisig_signers,
+                    maximum_pending_balance_credit_counter,
+                )?,
+            ],
             signing_keypairs,
         )
         .await
     }
 
-    /// Enable confidential transfer `Deposit` and `Transfer` instructions for a token account
-    pub async fn confidential_transfer_enable_confidential_credits<S: Signers>(
+    /// Repair the decryptable available balance of a token account from its available balance
+    ///
+    /// The available balance is decrypted with the ElGamal keypair, which is authoritative but
+    /// slow for large balances, and re-encrypted under the authenticated encryption key. Only the
+    /// decryptable available balance is updated by the submitted `UpdateDecryptableBalance`
+    /// instruction.
+    pub async fn confidential_transfer_repair_decryptable_balance<S: Signers>(
         &self,
         account: &Pubkey,
         authority: &Pubkey,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+        let available_balance = extension
+            .available_balance
+            .decrypt(elgamal_keypair.secret())
+            .ok_or(TokenError::ElGamalBalanceDecryption)?;
+        let new_decryptable_available_balance =
+            DecryptableBalance::encrypt(aes_key, available_balance);
+
         self.process_ixs(
             &[
-                confidential_transfer::instruction::enable_confidential_credits(
+                confidential_transfer::instruction::update_decryptable_balance(
                     &self.program_id,
                     account,
                     authority,
                     &multisig_signers,
+                    new_decryptable_available_balance.into(),
                 )?,
             ],
             signing_keypairs,
@@ -2325,6 +6935,37 @@ where
         .await
     }
 
+    /// Rotate the authenticated encryption key of a token account, keeping its ElGamal keypair
+    ///
+    /// The decryptable available balance must decrypt under `old_aes_key`, but the new decryptable
+    /// available balance is encrypted under `new_aes_key` from the decrypted available balance,
+    /// which is authoritative. No zero-knowledge proof is needed since the ElGamal ciphertexts are
+    /// unchanged.
+    pub async fn confidential_transfer_rotate_aes_key<S: Signers>(
+        &self,
+        account: &Pubkey,
+        authority: &Pubkey,
+        elgamal_keypair: &ElGamalKeypair,
+        old_aes_key: &AeKey,
+        new_aes_key: &AeKey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let account_info = self.get_account_info(account).await?;
+        let extension = account_info.get_extension::<ConfidentialTransferAccount>()?;
+        DecryptableBalance::try_from(extension.decryptable_available_balance)?
+            .decrypt(old_aes_key)
+            .ok_or(TokenError::AeBalanceDecryption)?;
+
+        self.confidential_transfer_repair_decryptable_balance(
+            account,
+            authority,
+            elgamal_keypair,
+            new_aes_key,
+            signing_keypairs,
+        )
+        .await
+    }
+
     /// Disable confidential transfer `Deposit` and `Transfer` instructions for a token account
     pub async fn confidential_transfer_disable_confidential_credits<S: Signers>(
         &self,
@@ -2413,27 +7054,38 @@ where
                 .map_err(TokenError::Key)?;
 
         self.confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
-            withdraw_withheld_authority,
+            &withdraw_withheld_authority.pubkey(),
             destination_token_account,
             destination_elgamal_pubkey,
             withheld_amount,
             withheld_amount_ciphertext,
             &withdraw_withheld_authority_elgamal_keypair,
+            &[withdraw_withheld_authority],
         )
         .await
     }
 
     /// Withdraw withheld confidential tokens from mint using a custom decryption key
+    ///
+    /// The withdraw withheld authority may be a multisig, in which case `signing_keypairs` holds
+    /// its signers. The proof is generated with `withdraw_withheld_authority_elgamal_keypair`
+    /// alone, so the decryption key does not depend on which signers approve the withdrawal.
+    #[allow(clippy::too_many_arguments)]
     #[cfg(feature = "proof-program")]
-    pub async fn confidential_transfer_withdraw_withheld_tokens_from_mint_with_key<S: Signer>(
+    pub async fn confidential_transfer_withdraw_withheld_tokens_from_mint_with_key<S: Signers>(
         &self,
-        withdraw_withheld_authority: &S,
+        withdraw_withheld_authority: &Pubkey,
         destination_token_account: &Pubkey,
         destination_elgamal_pubkey: &ElGamalPubkey,
         withheld_amount: u64,
         withheld_amount_ciphertext: &ElGamalCiphertext,
         withdraw_withheld_authority_elgamal_keypair: &ElGamalKeypair,
+        signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers =
+            self.get_multisig_signers(withdraw_withheld_authority, &signing_pubkeys);
+
         let proof_data = confidential_transfer::instruction::WithdrawWithheldTokensData::new(
             withdraw_withheld_authority_elgamal_keypair,
             destination_elgamal_pubkey,
@@ -2447,15 +7099,80 @@ where
                 &self.program_id,
                 &self.pubkey,
                 destination_token_account,
-                &withdraw_withheld_authority.pubkey(),
-                &[],
+                withdraw_withheld_authority,
+                &multisig_signers,
                 &proof_data,
             )?,
-            &[withdraw_withheld_authority],
+            signing_keypairs,
         )
         .await
     }
 
+    /// Withdraw withheld confidential tokens from several mints, batching as many mints per
+    /// transaction as fit
+    ///
+    /// Each entry consists of a mint, the destination token account for that mint, and the proof
+    /// data for the withheld amount of that mint. The mints must all belong to the same token
+    /// program as this `Token`. The instructions for a single mint are never split across
+    /// transactions.
+    ///
+    /// Returns the result of every submitted transaction along with the mints that it covered. A
+    /// failed transaction does not prevent the remaining batches from being submitted.
+    #[cfg(feature = "proof-program")]
+    pub async fn confidential_transfer_withdraw_withheld_from_mints<S: Signers>(
+        &self,
+        withdraw_withheld_authority: &Pubkey,
+        mints: &[(
+            Pubkey,
+            Pubkey,
+            confidential_transfer::instruction::WithdrawWithheldTokensData,
+        )],
+        signing_keypairs: &S,
+    ) -> TokenResult<Vec<(Vec<Pubkey>, TokenResult<T::Output>)>> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers =
+            self.get_multisig_signers(withdraw_withheld_authority, &signing_pubkeys);
+        let transaction_size = |instructions: &[Instruction]| {
+            let message = Message::new(instructions, Some(&self.payer.pubkey()));
+            // a signature count below 128 is serialized as a single byte
+            1 + message.header.num_required_signatures as usize * 64 + message.serialize().len()
+        };
+
+        let mut batches: Vec<(Vec<Pubkey>, Vec<Instruction>)> = vec![];
+        for (mint, destination_token_account, proof_data) in mints {
+            let instructions =
+                confidential_transfer_fee::instruction::withdraw_withheld_tokens_from_mint(
+                    &self.program_id,
+                    mint,
+                    destination_token_account,
+                    withdraw_withheld_authority,
+                    &multisig_signers,
+                    proof_data,
+                )?;
+
+            if let Some((batch_mints, batch_instructions)) = batches.last_mut() {
+                let mut candidate_instructions = batch_instructions.clone();
+                candidate_instructions.extend_from_slice(&instructions);
+                if transaction_size(&candidate_instructions) <= PACKET_DATA_SIZE {
+                    batch_mints.push(*mint);
+                    *batch_instructions = candidate_instructions;
+                    continue;
+                }
+            }
+            batches.push((vec![*mint], instructions));
+        }
+
+        let mut results = Vec::with_capacity(batches.len());
+        for (batch_mints, batch_instructions) in batches {
+            let result = self
+                .process_ixs(&batch_instructions, signing_keypairs)
+                .await;
+            results.push((batch_mints, result));
+        }
+
+        Ok(results)
+    }
+
     /// Withdraw withheld confidential tokens from accounts using the uniquely derived decryption
     /// key
     #[cfg(feature = "proof-program")]
@@ -2473,32 +7190,41 @@ where
                 .map_err(TokenError::Key)?;
 
         self.confidential_transfer_withdraw_withheld_tokens_from_accounts_with_key(
-            withdraw_withheld_authority,
+            &withdraw_withheld_authority.pubkey(),
             destination_token_account,
             destination_elgamal_pubkey,
             aggregate_withheld_amount,
             aggregate_withheld_amount_ciphertext,
             &withdraw_withheld_authority_elgamal_keypair,
             sources,
+            &[withdraw_withheld_authority],
         )
         .await
     }
 
     /// Withdraw withheld confidential tokens from accounts using a custom decryption key
+    ///
+    /// As with `confidential_transfer_withdraw_withheld_tokens_from_mint_with_key`, the withdraw
+    /// withheld authority may be a multisig whose signers are in `signing_keypairs`.
     #[allow(clippy::too_many_arguments)]
     #[cfg(feature = "proof-program")]
     pub async fn confidential_transfer_withdraw_withheld_tokens_from_accounts_with_key<
-        S: Signer,
+        S: Signers,
     >(
         &self,
-        withdraw_withheld_authority: &S,
+        withdraw_withheld_authority: &Pubkey,
         destination_token_account: &Pubkey,
         destination_elgamal_pubkey: &ElGamalPubkey,
         aggregate_withheld_amount: u64,
         aggregate_withheld_amount_ciphertext: &ElGamalCiphertext,
         withdraw_withheld_authority_elgamal_keypair: &ElGamalKeypair,
         sources: &[&Pubkey],
+        signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers =
+            self.get_multisig_signers(withdraw_withheld_authority, &signing_pubkeys);
+
         let proof_data = confidential_transfer::instruction::WithdrawWithheldTokensData::new(
             withdraw_withheld_authority_elgamal_keypair,
             destination_elgamal_pubkey,
@@ -2512,31 +7238,194 @@ where
                 &self.program_id,
                 &self.pubkey,
                 destination_token_account,
-                &withdraw_withheld_authority.pubkey(),
-                &[],
+                withdraw_withheld_authority,
+                &multisig_signers,
                 sources,
                 &proof_data,
             )?,
-            &[withdraw_withheld_authority],
+            signing_keypairs,
         )
         .await
     }
 
     /// Harvest withheld confidential tokens to mint
+    ///
+    /// Sources that cannot be harvested, because they do not exist, belong to a different mint, or
+    /// lack the `ConfidentialTransferFeeAmount` extension, are skipped. Returns the transaction
+    /// output along with the sources that were actually harvested, so that callers can retry the
+    /// rest.
     #[cfg(feature = "proof-program")]
     pub async fn confidential_transfer_harvest_withheld_tokens_to_mint(
         &self,
         sources: &[&Pubkey],
+    ) -> TokenResult<(T::Output, Vec<Pubkey>)> {
+        let mut harvested_sources = Vec::with_capacity(sources.len());
+        for source in sources {
+            let harvestable = self
+                .get_account_info(source)
+                .await
+                .map(|account| {
+                    account
+                        .get_extension::<ConfidentialTransferFeeAmount>()
+                        .is_ok()
+                })
+                .unwrap_or(false);
+            if harvestable {
+                harvested_sources.push(**source);
+            }
+        }
+        let harvested_source_refs = harvested_sources.iter().collect::<Vec<_>>();
+
+        let output = self
+            .process_ixs::<[&dyn Signer; 0]>(
+                &[
+                    confidential_transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                        &self.program_id,
+                        &self.pubkey,
+                        &harvested_source_refs,
+                    )?,
+                ],
+                &[],
+            )
+            .await?;
+
+        Ok((output, harvested_sources))
+    }
+
+    /// Harvest the withheld confidential tokens of a token account to the mint, empty its
+    /// confidential balance, and close it in a single transaction
+    ///
+    /// `HarvestWithheldTokensToMint` is permissionless, so neither the withdraw withheld authority
+    /// nor its signature is needed to clear the withheld fees that would otherwise keep the account
+    /// from being closed. The available balance must already be zero and the pending balance must
+    /// already be applied.
+    #[cfg(feature = "proof-program")]
+    pub async fn confidential_transfer_close_with_fees<S: Signers>(
+        &self,
+        account: &Pubkey,
+        lamports_destination: &Pubkey,
+        authority: &Pubkey,
+        account_info: Option<EmptyAccountAccountInfo>,
+        elgamal_keypair: &ElGamalKeypair,
+        signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
-        self.process_ixs::<[&dyn Signer; 0]>(
-            &[
-                confidential_transfer::instruction::harvest_withheld_tokens_to_mint(
-                    &self.program_id,
-                    &self.pubkey,
-                    sources,
-                )?,
-            ],
-            &[],
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        let account_info = if let Some(account_info) = account_info {
+            account_info
+        } else {
+            self.get_account_info(account)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .empty_account_account_info()
+        };
+
+        let proof_data = account_info
+            .generate_proof_data(elgamal_keypair)
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        let mut instructions = vec![
+            confidential_transfer_fee::instruction::harvest_withheld_tokens_to_mint(
+                &self.program_id,
+                &self.pubkey,
+                &[account],
+            )?,
+        ];
+        instructions.extend(confidential_transfer::instruction::empty_account(
+            &self.program_id,
+            account,
+            authority,
+            &multisig_signers,
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+        )?);
+        instructions.push(instruction::close_account(
+            &self.program_id,
+            account,
+            lamports_destination,
+            authority,
+            &multisig_signers,
+        )?);
+
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Harvest withheld confidential tokens from `sources` to the mint, then withdraw the
+    /// aggregated withheld amount of the mint to `destination_token_account`
+    ///
+    /// The withheld amount of the mint is decrypted with
+    /// `withdraw_withheld_authority_elgamal_keypair` to generate the withdraw proof. Returns the
+    /// output of the harvest transaction, the sources that were actually harvested, and the output
+    /// of the withdraw transaction, which is `None` if the mint holds no withheld tokens after the
+    /// harvest and the withdraw was skipped.
+    ///
+    /// Harvesting is permissionless, so only the withdraw is signed by `signing_keypairs`, which
+    /// holds the signers of a multisig withdraw withheld authority.
+    #[cfg(feature = "proof-program")]
+    #[allow(clippy::type_complexity)]
+    pub async fn confidential_transfer_sweep_fees<S: Signers>(
+        &self,
+        sources: &[&Pubkey],
+        withdraw_withheld_authority: &Pubkey,
+        withdraw_withheld_authority_elgamal_keypair: &ElGamalKeypair,
+        destination_token_account: &Pubkey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<(T::Output, Vec<Pubkey>, Option<T::Output>)> {
+        let (harvest_output, harvested_sources) = self
+            .confidential_transfer_harvest_withheld_tokens_to_mint(sources)
+            .await?;
+
+        let mint_state = self.get_mint_info().await?;
+        let fee_config = mint_state
+            .get_extension::<confidential_transfer_fee::ConfidentialTransferFeeConfig>()?;
+        let withheld_amount_ciphertext: ElGamalCiphertext =
+            fee_config
+                .withheld_amount
+                .try_into()
+                .map_err(|_| TokenError::AccountDecryption)?;
+        let withheld_amount = withheld_amount_ciphertext
+            .decrypt_u32(withdraw_withheld_authority_elgamal_keypair.secret())
+            .ok_or(TokenError::AccountDecryption)?;
+
+        if withheld_amount == 0 {
+            return Ok((harvest_output, harvested_sources, None));
+        }
+
+        let withdraw_output = self
+            .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
+                withdraw_withheld_authority,
+                destination_token_account,
+                destination_elgamal_pubkey,
+                withheld_amount,
+                &withheld_amount_ciphertext,
+                withdraw_withheld_authority_elgamal_keypair,
+                signing_keypairs,
+            )
+            .await?;
+
+        Ok((harvest_output, harvested_sources, Some(withdraw_output)))
+    }
+
+    /// Close a zk-token-proof context state account and reclaim its rent lamports
+    pub async fn confidential_transfer_close_context_state<S: Signers>(
+        &self,
+        context_state_account: &Pubkey,
+        lamport_destination_account: &Pubkey,
+        context_state_authority: &Pubkey,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let context_state_info = ContextStateInfo {
+            context_state_account,
+            context_state_authority,
+        };
+
+        self.process_ixs(
+            &[close_context_state(
+                context_state_info,
+                lamport_destination_account,
+            )],
+            signing_keypairs,
         )
         .await
     }
@@ -6,6 +6,7 @@ use {
     program_test::{TestContext, TokenContext},
     solana_program_test::tokio,
     solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
         instruction::InstructionError,
         pubkey::Pubkey,
         signature::Signer,
@@ -18,12 +19,24 @@ use {
         error::TokenError,
         extension::{
             confidential_transfer::{
-                self, ConfidentialTransferAccount, ConfidentialTransferMint,
-                MAXIMUM_DEPOSIT_TRANSFER_AMOUNT,
+                self,
+                account_info::{
+                    decrypt_transfer_amount_for_additional_auditor,
+                    decrypt_transfer_amount_for_auditor,
+                    generate_maximum_transfer_amount_proof_data, verify_confidential_supply,
+                    verify_decryptable_zero_balance, MaximumTransferAmountProofData,
+                },
+                ConfidentialMintSupply, ConfidentialTransferAccount,
+                ConfidentialTransferCreditsUntilSlot, ConfidentialTransferDelegateAllowance,
+                ConfidentialTransferMint, ConfidentialTransferPullAuthorization,
+                EncryptedTransferReference, MAXIMUM_DEPOSIT_TRANSFER_AMOUNT,
             },
+            memo_transfer::MemoTransfer,
             BaseStateWithExtensions, ExtensionType,
         },
         instruction,
+        pod::pod_from_bytes,
+        proof::ProofLocation,
         solana_zk_token_sdk::{
             encryption::{auth_encryption::*, elgamal::*},
             zk_token_elgamal::pod::{self, Zeroable},
@@ -31,20 +44,30 @@ use {
             zk_token_proof_program,
             zk_token_proof_state::ProofContextState,
         },
+        state::Account,
     },
     spl_token_client::{
         client::{SendTransaction, SimulateTransaction},
-        token::{ExtensionInitializationParams, Token, TokenError as TokenClientError},
+        metrics::{ConfidentialMetricsSink, ConfidentialPhase},
+        token::{
+            build_transfer_proof_data, build_withdraw_proof_data, AccountSummary, ClosableBlocker,
+            ConfidentialOp, ConfidentialSwapLeg, ConfidentialTokenAccountBalances,
+            DecryptableBalance, ElGamalPubkeyValidity, ExtensionInitializationParams, Token,
+            TokenError as TokenClientError,
+        },
+    },
+    std::{
+        convert::TryInto,
+        mem::size_of,
+        sync::{Arc, Mutex},
+        time::Duration,
     },
-    std::{convert::TryInto, mem::size_of},
 };
 
 #[cfg(all(feature = "zk-ops", feature = "proof-program"))]
 use solana_sdk::epoch_info::EpochInfo;
 
-#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
 const TEST_MAXIMUM_FEE: u64 = 100;
-#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
 const TEST_FEE_BASIS_POINTS: u16 = 250;
 #[cfg(feature = "proof-program")]
 const TEST_MAXIMUM_PENDING_BALANCE_CREDIT_COUNTER: u64 = 2;
@@ -93,6 +116,7 @@ impl ConfidentialTokenAccountMeta {
                 &owner.pubkey(),
                 None,
                 None,
+                false,
                 &elgamal_keypair,
                 &aes_key,
                 &[owner],
@@ -136,6 +160,7 @@ impl ConfidentialTokenAccountMeta {
                 &owner.pubkey(),
                 None,
                 Some(maximum_pending_balance_credit_counter),
+                false,
                 &elgamal_keypair,
                 &aes_key,
                 &[owner],
@@ -160,10 +185,7 @@ impl ConfidentialTokenAccountMeta {
             .create_auxiliary_token_account_with_extension_space(
                 &token_account_keypair,
                 &owner.pubkey(),
-                vec![
-                    ExtensionType::ConfidentialTransferAccount,
-                    ExtensionType::MemoTransfer,
-                ],
+                vec![],
             )
             .await
             .unwrap();
@@ -179,6 +201,7 @@ impl ConfidentialTokenAccountMeta {
                 &owner.pubkey(),
                 None,
                 None,
+                true,
                 &elgamal_keypair,
                 &aes_key,
                 &[owner],
@@ -186,11 +209,6 @@ impl ConfidentialTokenAccountMeta {
             .await
             .unwrap();
 
-        token
-            .enable_required_transfer_memos(&token_account, &owner.pubkey(), &[owner])
-            .await
-            .unwrap();
-
         Self {
             token_account,
             elgamal_keypair,
@@ -251,49 +269,18 @@ impl ConfidentialTokenAccountMeta {
     where
         T: SendTransaction + SimulateTransaction,
     {
-        let state = token.get_account_info(&self.token_account).await.unwrap();
-        let extension = state
-            .get_extension::<ConfidentialTransferAccount>()
+        let balances = token
+            .confidential_transfer_get_balances(
+                &self.token_account,
+                &self.elgamal_keypair,
+                &self.aes_key,
+            )
+            .await
             .unwrap();
-
-        assert_eq!(
-            extension
-                .pending_balance_lo
-                .decrypt(self.elgamal_keypair.secret())
-                .unwrap(),
-            expected.pending_balance_lo,
-        );
-        assert_eq!(
-            extension
-                .pending_balance_hi
-                .decrypt(self.elgamal_keypair.secret())
-                .unwrap(),
-            expected.pending_balance_hi,
-        );
-        assert_eq!(
-            extension
-                .available_balance
-                .decrypt(self.elgamal_keypair.secret())
-                .unwrap(),
-            expected.available_balance,
-        );
-        assert_eq!(
-            self.aes_key
-                .decrypt(&extension.decryptable_available_balance.try_into().unwrap())
-                .unwrap(),
-            expected.decryptable_available_balance,
-        );
+        assert_eq!(balances, expected);
     }
 }
 
-#[cfg(feature = "zk-ops")]
-struct ConfidentialTokenAccountBalances {
-    pending_balance_lo: u64,
-    pending_balance_hi: u64,
-    available_balance: u64,
-    decryptable_available_balance: u64,
-}
-
 #[cfg(all(feature = "zk-ops", feature = "proof-program"))]
 async fn check_withheld_amount_in_mint<T>(
     token: &Token<T>,
@@ -450,6 +437,77 @@ async fn confidential_transfer_initialize_and_update_mint() {
     assert_eq!(extension.authority, None.try_into().unwrap());
 }
 
+#[tokio::test]
+async fn confidential_transfer_update_mint_partial() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, .. } = context.token_context.unwrap();
+
+    // only flip `auto_approve_new_accounts`, leaving the auditor in place
+    token
+        .confidential_transfer_update_mint_partial(&authority.pubkey(), false, None, &[&authority])
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<ConfidentialTransferMint>().unwrap();
+    assert_eq!(extension.auto_approve_new_accounts, false.into());
+    assert_eq!(
+        extension.auditor_elgamal_pubkey,
+        Some(auditor_elgamal_pubkey).try_into().unwrap(),
+    );
+
+    // set a new auditor
+    let new_auditor_elgamal_pubkey = (*ElGamalKeypair::new_rand().pubkey()).into();
+    token
+        .confidential_transfer_update_mint_partial(
+            &authority.pubkey(),
+            false,
+            Some(Some(new_auditor_elgamal_pubkey)),
+            &[&authority],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<ConfidentialTransferMint>().unwrap();
+    assert_eq!(
+        extension.auditor_elgamal_pubkey,
+        Some(new_auditor_elgamal_pubkey).try_into().unwrap(),
+    );
+
+    // clear the auditor
+    token
+        .confidential_transfer_update_mint_partial(
+            &authority.pubkey(),
+            true,
+            Some(None),
+            &[&authority],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<ConfidentialTransferMint>().unwrap();
+    assert_eq!(extension.auto_approve_new_accounts, true.into());
+    assert_eq!(extension.auditor_elgamal_pubkey, None.try_into().unwrap());
+}
+
 #[tokio::test]
 async fn confidential_transfer_configure_token_account() {
     let authority = Keypair::new();
@@ -481,12 +539,16 @@ async fn confidential_transfer_configure_token_account() {
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
     assert!(!bool::from(&extension.approved));
+    assert!(!token
+        .confidential_transfer_is_approved(&alice_meta.token_account)
+        .await
+        .unwrap());
     assert!(bool::from(&extension.allow_confidential_credits));
     assert_eq!(extension.elgamal_pubkey, alice_elgamal_pubkey);
     assert_eq!(
-        alice_meta
-            .aes_key
-            .decrypt(&(extension.decryptable_available_balance.try_into().unwrap()))
+        DecryptableBalance::try_from(extension.decryptable_available_balance)
+            .unwrap()
+            .decrypt(&alice_meta.aes_key)
             .unwrap(),
         0
     );
@@ -508,6 +570,10 @@ async fn confidential_transfer_configure_token_account() {
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
     assert!(bool::from(&extension.approved));
+    assert!(token
+        .confidential_transfer_is_approved(&alice_meta.token_account)
+        .await
+        .unwrap());
 
     // Configuring an already initialized account should produce an error
     let err = token
@@ -516,6 +582,7 @@ async fn confidential_transfer_configure_token_account() {
             &alice.pubkey(),
             None,
             None,
+            false,
             &alice_meta.elgamal_keypair,
             &alice_meta.aes_key,
             &[&alice],
@@ -535,11 +602,10 @@ async fn confidential_transfer_configure_token_account() {
 }
 
 #[tokio::test]
-async fn confidential_transfer_enable_disable_confidential_credits() {
+async fn confidential_transfer_approve_accounts() {
     let authority = Keypair::new();
-    let auto_approve_new_accounts = true;
-    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
-    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let auto_approve_new_accounts = false;
+    let auditor_elgamal_pubkey = None;
 
     let mut context = TestContext::new().await;
     context
@@ -547,104 +613,85 @@ async fn confidential_transfer_enable_disable_confidential_credits() {
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+                auditor_elgamal_pubkey,
             },
         ])
         .await
         .unwrap();
 
     let TokenContext {
-        token,
-        alice,
-        mint_authority,
-        decimals,
-        ..
+        token, alice, bob, ..
     } = context.token_context.unwrap();
     let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
 
+    // an account that is not configured for confidential transfers
+    let unconfigured_account = Keypair::new();
     token
-        .confidential_transfer_disable_confidential_credits(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            &[&alice],
-        )
+        .create_auxiliary_token_account(&unconfigured_account, &alice.pubkey())
         .await
         .unwrap();
-    let state = token
-        .get_account_info(&alice_meta.token_account)
-        .await
-        .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
-    assert!(!bool::from(&extension.allow_confidential_credits));
 
     token
-        .mint_to(
+        .confidential_transfer_approve_account(
             &alice_meta.token_account,
-            &mint_authority.pubkey(),
-            10,
-            &[&mint_authority],
+            &authority.pubkey(),
+            &[&authority],
         )
         .await
         .unwrap();
 
-    let err = token
-        .confidential_transfer_deposit(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            10,
-            decimals,
-            &[&alice],
+    let missing_account = Pubkey::new_unique();
+    let results = token
+        .confidential_transfer_approve_accounts(
+            &[
+                alice_meta.token_account,
+                bob_meta.token_account,
+                unconfigured_account.pubkey(),
+                missing_account,
+            ],
+            &authority.pubkey(),
+            &[&authority],
         )
         .await
-        .unwrap_err();
-
+        .unwrap();
+    assert_eq!(results.len(), 4);
+    assert_eq!(results[0], (alice_meta.token_account, Ok(false)));
+    assert_eq!(results[1], (bob_meta.token_account, Ok(true)));
+    assert_eq!(results[2].0, unconfigured_account.pubkey());
+    assert!(results[2].1.is_err());
     assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(
-                0,
-                InstructionError::Custom(
-                    TokenError::ConfidentialTransferDepositsAndTransfersDisabled as u32
-                )
-            )
-        )))
+        results[3],
+        (missing_account, Err(TokenClientError::AccountNotFound))
     );
 
-    token
-        .confidential_transfer_enable_confidential_credits(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            &[&alice],
-        )
-        .await
-        .unwrap();
-    let state = token
-        .get_account_info(&alice_meta.token_account)
+    assert!(token
+        .confidential_transfer_is_approved(&bob_meta.token_account)
         .await
-        .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
-    assert!(bool::from(&extension.allow_confidential_credits));
+        .unwrap());
 
-    token
-        .confidential_transfer_deposit(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            10,
-            decimals,
-            &[&alice],
+    // approving again skips every account
+    let results = token
+        .confidential_transfer_approve_accounts(
+            &[alice_meta.token_account, bob_meta.token_account],
+            &authority.pubkey(),
+            &[&authority],
         )
         .await
         .unwrap();
+    assert_eq!(
+        results,
+        vec![
+            (alice_meta.token_account, Ok(false)),
+            (bob_meta.token_account, Ok(false)),
+        ]
+    );
 }
 
 #[tokio::test]
-async fn confidential_transfer_enable_disable_non_confidential_credits() {
+async fn confidential_transfer_request_and_approve() {
     let authority = Keypair::new();
-    let auto_approve_new_accounts = true;
+    let auto_approve_new_accounts = false;
     let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
     let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
@@ -660,138 +707,124 @@ async fn confidential_transfer_enable_disable_non_confidential_credits() {
         .await
         .unwrap();
 
-    let TokenContext {
-        token,
-        alice,
-        bob,
-        mint_authority,
-        ..
-    } = context.token_context.unwrap();
-    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
-    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
 
+    // the account is created without the confidential transfer extension, so it is reallocated
+    let token_account_keypair = Keypair::new();
     token
-        .mint_to(
-            &alice_meta.token_account,
-            &mint_authority.pubkey(),
-            10,
-            &[&mint_authority],
-        )
+        .create_auxiliary_token_account(&token_account_keypair, &alice.pubkey())
         .await
         .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
 
     token
-        .confidential_transfer_disable_non_confidential_credits(
-            &bob_meta.token_account,
-            &bob.pubkey(),
-            &[&bob],
+        .confidential_transfer_request_and_approve(
+            &token_account,
+            &alice,
+            None,
+            &elgamal_keypair,
+            &aes_key,
+            &authority,
         )
         .await
         .unwrap();
-    let state = token
-        .get_account_info(&bob_meta.token_account)
-        .await
-        .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
-    assert!(!bool::from(&extension.allow_non_confidential_credits));
 
-    let err = token
-        .transfer(
-            &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice.pubkey(),
-            10,
-            &[&alice],
-        )
+    assert!(token
+        .confidential_transfer_is_approved(&token_account)
         .await
-        .unwrap_err();
-
-    assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(
-                0,
-                InstructionError::Custom(TokenError::NonConfidentialTransfersDisabled as u32)
-            )
-        )))
-    );
+        .unwrap());
 
-    token
-        .confidential_transfer_enable_non_confidential_credits(
-            &bob_meta.token_account,
-            &bob.pubkey(),
-            &[&bob],
-        )
-        .await
-        .unwrap();
-    let state = token
-        .get_account_info(&bob_meta.token_account)
-        .await
-        .unwrap();
+    let state = token.get_account_info(&token_account).await.unwrap();
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
-    assert!(bool::from(&extension.allow_non_confidential_credits));
-
-    token
-        .transfer(
-            &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice.pubkey(),
-            10,
-            &[&alice],
-        )
-        .await
-        .unwrap();
+    assert_eq!(extension.elgamal_pubkey, (*elgamal_keypair.pubkey()).into());
 }
 
 #[tokio::test]
-async fn confidential_transfer_empty_account() {
+async fn confidential_transfer_configure_token_account_with_nonzero_decryptable_balance() {
     let authority = Keypair::new();
     let auto_approve_new_accounts = true;
-    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
-    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
-
-    // newly created confidential transfer account should hold no balance and therefore,
-    // immediately closable
     context
         .init_token_with_mint(vec![
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+                auditor_elgamal_pubkey: None,
             },
         ])
         .await
         .unwrap();
 
     let TokenContext { token, alice, .. } = context.token_context.unwrap();
-    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
 
+    let token_account_keypair = Keypair::new();
     token
-        .confidential_transfer_empty_account(
-            &alice_meta.token_account,
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
             &alice.pubkey(),
-            None,
-            None,
-            &alice_meta.elgamal_keypair,
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair = ElGamalKeypair::new_rand();
+    let aes_key = AeKey::new_rand();
+
+    let proof_data =
+        confidential_transfer::instruction::PubkeyValidityData::new(&elgamal_keypair).unwrap();
+    let decryptable_nonzero_balance = aes_key.encrypt(1);
+
+    // the client check rejects a decryptable balance that does not encrypt zero
+    assert_eq!(
+        verify_decryptable_zero_balance(&aes_key, &decryptable_nonzero_balance.into()),
+        Err(TokenError::ConfidentialTransferBalanceMismatch)
+    );
+    assert_eq!(
+        verify_decryptable_zero_balance(&aes_key, &aes_key.encrypt(0).into()),
+        Ok(())
+    );
+
+    // the program cannot check the decryptable balance and accepts it as is
+    token
+        .process_ixs(
+            &confidential_transfer::instruction::configure_account(
+                &spl_token_2022::id(),
+                &token_account,
+                token.get_address(),
+                decryptable_nonzero_balance,
+                2,
+                &alice.pubkey(),
+                &[],
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )
+            .unwrap(),
             &[&alice],
         )
         .await
         .unwrap();
+
+    let state = token.get_account_info(&token_account).await.unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(
+        verify_decryptable_zero_balance(&aes_key, &extension.decryptable_available_balance),
+        Err(TokenError::ConfidentialTransferBalanceMismatch)
+    );
 }
 
-#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_deposit() {
+async fn confidential_transfer_create_and_configure_account() {
     let authority = Keypair::new();
-    let auto_approve_new_accounts = true;
-    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
-    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let auto_approve_new_accounts = false;
 
     let mut context = TestContext::new().await;
     context
@@ -799,125 +832,185 @@ async fn confidential_transfer_deposit() {
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+                auditor_elgamal_pubkey: None,
             },
         ])
         .await
         .unwrap();
 
-    let TokenContext {
-        token,
-        alice,
-        mint_authority,
-        decimals,
-        ..
-    } = context.token_context.unwrap();
-    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
-        &token, &alice, 2,
-    )
-    .await;
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+
+    let alice_account = Keypair::new();
+    let alice_elgamal_keypair = ElGamalKeypair::new_rand();
+    let alice_aes_key = AeKey::new_rand();
 
     token
-        .mint_to(
-            &alice_meta.token_account,
-            &mint_authority.pubkey(),
-            65537,
-            &[&mint_authority],
+        .create_and_configure_confidential_account(
+            &alice_account,
+            &alice,
+            None,
+            &alice_elgamal_keypair,
+            &alice_aes_key,
+            Some(&authority),
         )
         .await
         .unwrap();
 
     let state = token
-        .get_account_info(&alice_meta.token_account)
+        .get_account_info(&alice_account.pubkey())
         .await
         .unwrap();
-    assert_eq!(state.base.amount, 65537);
+    assert_eq!(state.base.owner, alice.pubkey());
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
-    assert_eq!(extension.pending_balance_credit_counter, 0.into());
-    assert_eq!(extension.expected_pending_balance_credit_counter, 0.into());
-    assert_eq!(extension.actual_pending_balance_credit_counter, 0.into());
-    assert_eq!(
-        extension.pending_balance_lo,
-        pod::ElGamalCiphertext::zeroed()
-    );
+    assert!(bool::from(&extension.approved));
     assert_eq!(
-        extension.pending_balance_hi,
-        pod::ElGamalCiphertext::zeroed()
+        extension.elgamal_pubkey,
+        (*alice_elgamal_keypair.pubkey()).into()
     );
     assert_eq!(
-        extension.available_balance,
-        pod::ElGamalCiphertext::zeroed()
+        DecryptableBalance::try_from(extension.decryptable_available_balance)
+            .unwrap()
+            .decrypt(&alice_aes_key)
+            .unwrap(),
+        0
     );
+}
+
+#[tokio::test]
+async fn confidential_transfer_reallocate_and_configure_account() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
 
+    // create an account without space for the confidential transfer extension
+    let alice_account = Keypair::new();
     token
-        .confidential_transfer_deposit(
-            &alice_meta.token_account,
+        .create_auxiliary_token_account(&alice_account, &alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &alice_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &alice_account.to_bytes()).unwrap();
+
+    // the configure helper reallocates the account before configuring it
+    token
+        .confidential_transfer_configure_token_account(
+            &alice_account,
             &alice.pubkey(),
-            65537,
-            decimals,
+            None,
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
             &[&alice],
         )
         .await
         .unwrap();
 
-    let state = token
-        .get_account_info(&alice_meta.token_account)
-        .await
-        .unwrap();
-    assert_eq!(state.base.amount, 0);
+    let state = token.get_account_info(&alice_account).await.unwrap();
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
-    assert_eq!(extension.pending_balance_credit_counter, 1.into());
-    assert_eq!(extension.expected_pending_balance_credit_counter, 0.into());
-    assert_eq!(extension.actual_pending_balance_credit_counter, 0.into());
+    assert_eq!(extension.elgamal_pubkey, (*elgamal_keypair.pubkey()).into());
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 1,
-                pending_balance_hi: 1,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
+    // no reallocation is needed once the account is configured
+    assert!(token
+        .confidential_transfer_reallocate_for_account(&alice_account, &alice.pubkey(), &[&alice])
+        .await
+        .unwrap()
+        .is_none());
 
-    // deposit zero amount
+    // an account can also be reallocated ahead of time
+    let bob_account = Keypair::new();
     token
-        .confidential_transfer_deposit(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            0,
-            decimals,
-            &[&alice],
-        )
+        .create_auxiliary_token_account(&bob_account, &alice.pubkey())
+        .await
+        .unwrap();
+    let bob_account = bob_account.pubkey();
+
+    assert!(token
+        .confidential_transfer_reallocate_for_account(&bob_account, &alice.pubkey(), &[&alice])
+        .await
+        .unwrap()
+        .is_some());
+
+    let account = token.get_account(bob_account).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        ExtensionType::try_calculate_account_len::<Account>(&[
+            ExtensionType::ConfidentialTransferAccount
+        ])
+        .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_enable_disable_confidential_credits() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
         .await
         .unwrap();
 
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
     token
-        .confidential_transfer_apply_pending_balance(
+        .confidential_transfer_disable_confidential_credits(
             &alice_meta.token_account,
             &alice.pubkey(),
-            None,
-            alice_meta.elgamal_keypair.secret(),
-            &alice_meta.aes_key,
             &[&alice],
         )
         .await
         .unwrap();
-
-    // try to deposit over maximum allowed value
-    let illegal_amount = MAXIMUM_DEPOSIT_TRANSFER_AMOUNT.checked_add(1).unwrap();
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(!bool::from(&extension.allow_confidential_credits));
 
     token
         .mint_to(
             &alice_meta.token_account,
             &mint_authority.pubkey(),
-            illegal_amount,
+            10,
             &[&mint_authority],
         )
         .await
@@ -927,7 +1020,7 @@ async fn confidential_transfer_deposit() {
         .confidential_transfer_deposit(
             &alice_meta.token_account,
             &alice.pubkey(),
-            illegal_amount,
+            10,
             decimals,
             &[&alice],
         )
@@ -939,74 +1032,45 @@ async fn confidential_transfer_deposit() {
         TokenClientError::Client(Box::new(TransportError::TransactionError(
             TransactionError::InstructionError(
                 0,
-                InstructionError::Custom(TokenError::MaximumDepositAmountExceeded as u32),
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferDepositsAndTransfersDisabled as u32
+                )
             )
         )))
     );
 
-    // deposit maximum allowed value
     token
-        .confidential_transfer_deposit(
+        .confidential_transfer_enable_confidential_credits(
             &alice_meta.token_account,
             &alice.pubkey(),
-            MAXIMUM_DEPOSIT_TRANSFER_AMOUNT,
-            decimals,
             &[&alice],
         )
         .await
         .unwrap();
-
-    // maximum pending balance credits exceeded
-    token
-        .confidential_transfer_deposit(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            0,
-            decimals,
-            &[&alice],
-        )
+    let state = token
+        .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(bool::from(&extension.allow_confidential_credits));
 
-    let err = token
+    token
         .confidential_transfer_deposit(
             &alice_meta.token_account,
             &alice.pubkey(),
-            1,
+            10,
             decimals,
             &[&alice],
         )
         .await
-        .unwrap_err();
-
-    assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(
-                0,
-                InstructionError::Custom(
-                    TokenError::MaximumPendingBalanceCreditCounterExceeded as u32
-                ),
-            )
-        )))
-    );
-
-    let state = token
-        .get_account_info(&alice_meta.token_account)
-        .await
-        .unwrap();
-    assert_eq!(state.base.amount, 1);
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
-    assert_eq!(extension.pending_balance_credit_counter, 2.into());
-    assert_eq!(extension.expected_pending_balance_credit_counter, 2.into());
-    assert_eq!(extension.actual_pending_balance_credit_counter, 2.into());
 }
 
 #[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_withdraw() {
+async fn confidential_transfer_enable_confidential_credits_until_slot() {
     let authority = Keypair::new();
     let auto_approve_new_accounts = true;
     let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
@@ -1030,128 +1094,117 @@ async fn confidential_transfer_withdraw() {
         mint_authority,
         decimals,
         ..
-    } = context.token_context.unwrap();
-    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
-        &token,
-        &alice,
-        &mint_authority,
-        42,
-        decimals,
-    )
-    .await;
+    } = context.token_context.take().unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
 
+    let until_slot = 1_000;
+    token
+        .confidential_transfer_enable_confidential_credits_until_slot(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            until_slot,
+            &[&alice],
+        )
+        .await
+        .unwrap();
     let state = token
         .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
-    assert_eq!(state.base.amount, 0);
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 42,
-                decryptable_available_balance: 42,
-            },
-        )
-        .await;
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(bool::from(&extension.allow_confidential_credits));
+    let extension = state
+        .get_extension::<ConfidentialTransferCreditsUntilSlot>()
+        .unwrap();
+    assert_eq!(extension.slot, until_slot.into());
 
-    // withdraw zero amount
     token
-        .confidential_transfer_withdraw(
+        .mint_to(
             &alice_meta.token_account,
-            &alice.pubkey(),
-            None,
-            0,
-            decimals,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            &[&alice],
+            &mint_authority.pubkey(),
+            20,
+            &[&mint_authority],
         )
         .await
         .unwrap();
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 42,
-                decryptable_available_balance: 42,
-            },
-        )
-        .await;
-
-    // withdraw entire balance
+    // deposits are accepted before the slot
     token
-        .confidential_transfer_withdraw(
+        .confidential_transfer_deposit(
             &alice_meta.token_account,
             &alice.pubkey(),
-            None,
-            42,
+            10,
             decimals,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
             &[&alice],
         )
         .await
         .unwrap();
 
-    let state = token
-        .get_account_info(&alice_meta.token_account)
+    context
+        .context
+        .lock()
         .await
+        .warp_to_slot(until_slot)
         .unwrap();
-    assert_eq!(state.base.amount, 42);
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
 
-    // attempt to withdraw without enough funds
+    // deposits are rejected from the slot onward
     let err = token
-        .confidential_transfer_withdraw(
+        .confidential_transfer_deposit(
             &alice_meta.token_account,
             &alice.pubkey(),
-            None,
-            1,
+            10,
             decimals,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
             &[&alice],
         )
         .await
         .unwrap_err();
 
-    assert_eq!(err, TokenClientError::ProofGeneration);
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferCreditsDisabledAsOfSlot as u32
+                )
+            )
+        )))
+    );
 
+    // enabling confidential credits clears the scheduled slot
     token
-        .confidential_transfer_empty_account(
+        .confidential_transfer_enable_confidential_credits(
             &alice_meta.token_account,
             &alice.pubkey(),
-            None,
-            None,
-            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferCreditsUntilSlot>()
+        .unwrap();
+    assert_eq!(extension.slot, 0.into());
+
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            decimals,
             &[&alice],
         )
         .await
         .unwrap();
 }
 
-#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_transfer() {
+async fn confidential_transfer_enable_disable_non_confidential_credits() {
     let authority = Keypair::new();
     let auto_approve_new_accounts = true;
     let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
@@ -1174,246 +1227,141 @@ async fn confidential_transfer_transfer() {
         alice,
         bob,
         mint_authority,
-        decimals,
         ..
     } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
 
-    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
-        &token,
-        &alice,
-        &mint_authority,
-        42,
-        decimals,
-    )
-    .await;
-
-    let bob_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
-        &token, &bob, 2,
-    )
-    .await;
-
-    // Self-transfer of 0 tokens
     token
-        .confidential_transfer_transfer(
-            &alice_meta.token_account,
+        .mint_to(
             &alice_meta.token_account,
-            &alice.pubkey(),
-            None,
-            0,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            alice_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
-            &[&alice],
+            &mint_authority.pubkey(),
+            10,
+            &[&mint_authority],
         )
         .await
         .unwrap();
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 42,
-                decryptable_available_balance: 42,
-            },
+    token
+        .confidential_transfer_disable_non_confidential_credits(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            &[&bob],
         )
-        .await;
+        .await
+        .unwrap();
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(!bool::from(&extension.allow_non_confidential_credits));
 
-    // Self-transfer of N tokens
-    token
-        .confidential_transfer_transfer(
-            &alice_meta.token_account,
+    let err = token
+        .transfer(
             &alice_meta.token_account,
+            &bob_meta.token_account,
             &alice.pubkey(),
-            None,
-            42,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            alice_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
+            10,
             &[&alice],
         )
         .await
-        .unwrap();
+        .unwrap_err();
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 42,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NonConfidentialTransfersDisabled as u32)
+            )
+        )))
+    );
 
     token
-        .confidential_transfer_apply_pending_balance(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            None,
-            alice_meta.elgamal_keypair.secret(),
-            &alice_meta.aes_key,
-            &[&alice],
+        .confidential_transfer_enable_non_confidential_credits(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            &[&bob],
         )
         .await
         .unwrap();
-
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 42,
-                decryptable_available_balance: 42,
-            },
-        )
-        .await;
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(bool::from(&extension.allow_non_confidential_credits));
 
     token
-        .confidential_transfer_transfer(
+        .transfer(
             &alice_meta.token_account,
             &bob_meta.token_account,
             &alice.pubkey(),
-            None,
-            42,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
+            10,
             &[&alice],
         )
         .await
         .unwrap();
+}
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
+#[tokio::test]
+async fn confidential_transfer_empty_account() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 42,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
+    let mut context = TestContext::new().await;
 
-    token
-        .confidential_transfer_transfer(
-            &bob_meta.token_account,
-            &bob_meta.token_account,
-            &bob.pubkey(),
-            None,
-            0,
-            None,
-            &bob_meta.elgamal_keypair,
-            &bob_meta.aes_key,
-            bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
-            &[&bob],
-        )
+    // newly created confidential transfer account should hold no balance and therefore,
+    // immediately closable
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
         .await
         .unwrap();
 
-    let err = token
-        .confidential_transfer_transfer(
-            &bob_meta.token_account,
-            &bob_meta.token_account,
-            &bob.pubkey(),
-            None,
-            0,
-            None,
-            &bob_meta.elgamal_keypair,
-            &bob_meta.aes_key,
-            bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
-            &[&bob],
-        )
-        .await
-        .unwrap_err();
-
-    assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(
-                0,
-                InstructionError::Custom(
-                    TokenError::MaximumPendingBalanceCreditCounterExceeded as u32
-                ),
-            )
-        )))
-    );
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
 
     token
-        .confidential_transfer_apply_pending_balance(
-            &bob_meta.token_account,
-            &bob.pubkey(),
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
             None,
-            bob_meta.elgamal_keypair.secret(),
-            &bob_meta.aes_key,
-            &[&bob],
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
         )
         .await
         .unwrap();
-
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 42,
-                decryptable_available_balance: 42,
-            },
-        )
-        .await;
 }
 
-#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn ct_transfer_with_fee() {
-    let ConfidentialTransferMintWithKeypairs {
-        ct_mint,
-        ct_mint_transfer_auditor_elgamal_keypair,
-        ct_mint_withdraw_withheld_authority_elgamal_keypair,
-        ..
-    } = ConfidentialTransferMintWithKeypairs::new();
+async fn confidential_transfer_why_not_closable() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
     context
         .init_token_with_mint(vec![
-            ExtensionInitializationParams::TransferFeeConfig {
-                transfer_fee_config_authority: Some(Pubkey::new_unique()),
-                withdraw_withheld_authority: Some(Pubkey::new_unique()),
-                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
-                maximum_fee: TEST_MAXIMUM_FEE,
-            },
             ExtensionInitializationParams::ConfidentialTransferMint {
-                authority: ct_mint.authority.into(),
-                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
-                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
-                withdraw_withheld_authority_elgamal_pubkey: ct_mint
-                    .withdraw_withheld_authority_elgamal_pubkey
-                    .into(),
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
         ])
         .await
@@ -1422,156 +1370,235 @@ async fn ct_transfer_with_fee() {
     let TokenContext {
         token,
         alice,
-        bob,
         mint_authority,
         decimals,
         ..
     } = context.token_context.unwrap();
 
-    let epoch_info = test_epoch_info();
-
-    let alice_meta =
-        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
-            .await;
-    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    assert_eq!(
+        token
+            .confidential_transfer_why_not_closable(
+                &alice_meta.token_account,
+                &alice_meta.elgamal_keypair
+            )
+            .await
+            .unwrap(),
+        None,
+    );
 
-    let state = token
-        .get_account_info(&alice_meta.token_account)
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
         .await
         .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
         .unwrap();
+    assert_eq!(
+        token
+            .confidential_transfer_why_not_closable(
+                &alice_meta.token_account,
+                &alice_meta.elgamal_keypair
+            )
+            .await
+            .unwrap(),
+        Some(ClosableBlocker::PendingBalanceLo { amount: Some(42) }),
+    );
 
-    // Self-transfer of 0 tokens
     token
-        .confidential_transfer_transfer(
-            &alice_meta.token_account,
+        .confidential_transfer_apply_pending_balance(
             &alice_meta.token_account,
-            &alice,
+            &alice.pubkey(),
             None,
-            0, // amount
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &alice_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
         )
         .await
         .unwrap();
+    assert_eq!(
+        token
+            .confidential_transfer_why_not_closable(
+                &alice_meta.token_account,
+                &alice_meta.elgamal_keypair
+            )
+            .await
+            .unwrap(),
+        Some(ClosableBlocker::AvailableBalance { amount: Some(42) }),
+    );
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 100,
-                decryptable_available_balance: 100,
-            },
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
         )
-        .await;
-
-    let state = token
-        .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
+    assert_eq!(
+        token
+            .confidential_transfer_why_not_closable(
+                &alice_meta.token_account,
+                &alice_meta.elgamal_keypair
+            )
+            .await
+            .unwrap(),
+        Some(ClosableBlocker::AvailableBalanceNotEmptied),
+    );
 
-    // Self-transfers does not incur a fee
     token
-        .confidential_transfer_transfer(
-            &alice_meta.token_account,
+        .confidential_transfer_empty_account(
             &alice_meta.token_account,
-            &alice,
+            &alice.pubkey(),
             None,
-            100, // amount
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &alice_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
         )
         .await
         .unwrap();
+    assert_eq!(
+        token
+            .confidential_transfer_why_not_closable(
+                &alice_meta.token_account,
+                &alice_meta.elgamal_keypair
+            )
+            .await
+            .unwrap(),
+        None,
+    );
+}
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 100,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_can_empty() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
-        )
-        .await;
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
 
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
     token
-        .confidential_transfer_apply_pending_balance(&alice_meta.token_account, &alice, 0, 100, 2)
+        .confidential_transfer_can_empty(&alice_meta.token_account)
         .await
         .unwrap();
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 100,
-                decryptable_available_balance: 100,
-            },
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
         )
-        .await;
-
-    let state = token
-        .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
 
     token
-        .confidential_transfer_transfer_with_fee(
+        .confidential_transfer_deposit(
             &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
             None,
-            100,
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &bob_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
-            &epoch_info,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
         )
         .await
         .unwrap();
-
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
         )
-        .await;
+        .await
+        .unwrap();
 
-    // Alice account cannot be closed since there are withheld fees from self-transfer
+    // the available balance encrypts zero, but the pending balance is not cleared
     token
-        .confidential_transfer_empty_account(&alice_meta.token_account, &alice)
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
         .await
         .unwrap();
 
     let err = token
-        .confidential_transfer_empty_account(&bob_meta.token_account, &bob)
+        .confidential_transfer_can_empty(&alice_meta.token_account)
         .await
         .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Program(TokenError::ConfidentialTransferAccountHasBalance.into())
+    );
 
+    let err = token
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
     assert_eq!(
         err,
         TokenClientError::Client(Box::new(TransportError::TransactionError(
@@ -1582,64 +1609,64 @@ async fn ct_transfer_with_fee() {
         )))
     );
 
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 97,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
         )
-        .await;
-
+        .await
+        .unwrap();
     token
-        .confidential_transfer_apply_pending_balance(&bob_meta.token_account, &bob, 0, 97, 1)
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
         .await
         .unwrap();
 
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 97,
-                decryptable_available_balance: 97,
-            },
+    token
+        .confidential_transfer_can_empty(&alice_meta.token_account)
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
         )
-        .await;
+        .await
+        .unwrap();
 }
 
-#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn ct_withdraw_withheld_tokens_from_mint() {
-    let ConfidentialTransferMintWithKeypairs {
-        ct_mint,
-        ct_mint_transfer_auditor_elgamal_keypair,
-        ct_mint_withdraw_withheld_authority_elgamal_keypair,
-        ..
-    } = ConfidentialTransferMintWithKeypairs::new();
-
-    let ct_mint_withdraw_withheld_authority = Keypair::new();
+async fn confidential_transfer_deposit() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
     context
         .init_token_with_mint(vec![
-            ExtensionInitializationParams::TransferFeeConfig {
-                transfer_fee_config_authority: Some(Pubkey::new_unique()),
-                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
-                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
-                maximum_fee: TEST_MAXIMUM_FEE,
-            },
             ExtensionInitializationParams::ConfidentialTransferMint {
-                authority: ct_mint.authority.into(),
-                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
-                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
-                withdraw_withheld_authority_elgamal_pubkey: ct_mint
-                    .withdraw_withheld_authority_elgamal_pubkey
-                    .into(),
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
         ])
         .await
@@ -1648,159 +1675,217 @@ async fn ct_withdraw_withheld_tokens_from_mint() {
     let TokenContext {
         token,
         alice,
-        bob,
         mint_authority,
         decimals,
         ..
     } = context.token_context.unwrap();
-
-    let epoch_info = test_epoch_info();
-
-    let alice_meta =
-        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
-            .await;
-    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &alice, 2,
+    )
+    .await;
 
     token
-        .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
-            &ct_mint_withdraw_withheld_authority,
+        .mint_to(
             &alice_meta.token_account,
-            &alice_meta.elgamal_keypair.public,
-            0_u64,
-            &ct_mint.withheld_amount.try_into().unwrap(),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &mint_authority.pubkey(),
+            65537,
+            &[&mint_authority],
         )
         .await
         .unwrap();
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 100,
-                decryptable_available_balance: 100,
-            },
-        )
-        .await;
-
-    check_withheld_amount_in_mint(
-        &token,
-        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
-        0,
-    )
-    .await;
-
     let state = token
         .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
+    assert_eq!(state.base.amount, 65537);
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 0.into());
+    assert_eq!(extension.expected_pending_balance_credit_counter, 0.into());
+    assert_eq!(extension.actual_pending_balance_credit_counter, 0.into());
+    assert_eq!(
+        extension.pending_balance_lo,
+        pod::ElGamalCiphertext::zeroed()
+    );
+    assert_eq!(
+        extension.pending_balance_hi,
+        pod::ElGamalCiphertext::zeroed()
+    );
+    assert_eq!(
+        extension.available_balance,
+        pod::ElGamalCiphertext::zeroed()
+    );
 
-    // Test fee is 2.5% so the withheld fees should be 3
     token
-        .confidential_transfer_transfer_with_fee(
+        .confidential_transfer_deposit(
             &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice,
-            None,
-            100,
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &bob_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
-            &epoch_info,
+            &alice.pubkey(),
+            65537,
+            decimals,
+            &[&alice],
         )
         .await
         .unwrap();
 
     let state = token
-        .get_account_info(&bob_meta.token_account)
+        .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
+    assert_eq!(state.base.amount, 0);
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 1.into());
+    assert_eq!(extension.expected_pending_balance_credit_counter, 0.into());
+    assert_eq!(extension.actual_pending_balance_credit_counter, 0.into());
 
-    assert_eq!(
-        extension
-            .withheld_amount
-            .decrypt(&ct_mint_withdraw_withheld_authority_elgamal_keypair.secret),
-        Some(3),
-    );
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 1,
+                pending_balance_hi: 1,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
 
+    // deposit zero amount
     token
-        .confidential_transfer_harvest_withheld_tokens_to_mint(&[&bob_meta.token_account])
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            0,
+            decimals,
+            &[&alice],
+        )
         .await
         .unwrap();
 
-    check_withheld_amount_in_mint(
-        &token,
-        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
-        3,
-    )
-    .await;
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
 
-    let state = token.get_mint_info().await.unwrap();
-    let ct_mint = state.get_extension::<ConfidentialTransferMint>().unwrap();
+    // try to deposit over maximum allowed value
+    let illegal_amount = MAXIMUM_DEPOSIT_TRANSFER_AMOUNT.checked_add(1).unwrap();
 
     token
-        .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
-            &ct_mint_withdraw_withheld_authority,
+        .mint_to(
             &alice_meta.token_account,
-            &alice_meta.elgamal_keypair.public,
-            3_u64,
-            &ct_mint.withheld_amount.try_into().unwrap(),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &mint_authority.pubkey(),
+            illegal_amount,
+            &[&mint_authority],
         )
         .await
         .unwrap();
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 3,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            illegal_amount,
+            decimals,
+            &[&alice],
         )
-        .await;
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::MaximumDepositAmountExceeded as u32),
+            )
+        )))
+    );
+
+    // deposit maximum allowed value
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            MAXIMUM_DEPOSIT_TRANSFER_AMOUNT,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // maximum pending balance credits exceeded
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            0,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            1,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::MaximumPendingBalanceCreditCounterExceeded as u32
+                ),
+            )
+        )))
+    );
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 1);
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 2.into());
+    assert_eq!(extension.expected_pending_balance_credit_counter, 2.into());
+    assert_eq!(extension.actual_pending_balance_credit_counter, 2.into());
 }
 
-#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn ct_withdraw_withheld_tokens_from_accounts() {
-    let ConfidentialTransferMintWithKeypairs {
-        ct_mint,
-        ct_mint_transfer_auditor_elgamal_keypair,
-        ct_mint_withdraw_withheld_authority_elgamal_keypair,
-        ..
-    } = ConfidentialTransferMintWithKeypairs::new();
-
-    let ct_mint_withdraw_withheld_authority = Keypair::new();
+async fn confidential_transfer_update_max_pending_balance_credit_counter() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
     context
         .init_token_with_mint(vec![
-            ExtensionInitializationParams::TransferFeeConfig {
-                transfer_fee_config_authority: Some(Pubkey::new_unique()),
-                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
-                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
-                maximum_fee: TEST_MAXIMUM_FEE,
-            },
             ExtensionInitializationParams::ConfidentialTransferMint {
-                authority: ct_mint.authority.into(),
-                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
-                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
-                withdraw_withheld_authority_elgamal_pubkey: ct_mint
-                    .withdraw_withheld_authority_elgamal_pubkey
-                    .into(),
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
         ])
         .await
@@ -1814,100 +1899,144 @@ async fn ct_withdraw_withheld_tokens_from_accounts() {
         decimals,
         ..
     } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &alice, 1,
+    )
+    .await;
 
-    let epoch_info = test_epoch_info();
-
-    let alice_meta =
-        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
-            .await;
-    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
-
-    let state = token
-        .get_account_info(&alice_meta.token_account)
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            2,
+            &[&mint_authority],
+        )
         .await
         .unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
 
-    // Test fee is 2.5% so the withheld fees should be 3
     token
-        .confidential_transfer_transfer_with_fee(
+        .confidential_transfer_deposit(
             &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice,
-            None,
-            100,
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &bob_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
-            &epoch_info,
+            &alice.pubkey(),
+            1,
+            decimals,
+            &[&alice],
         )
         .await
         .unwrap();
-
-    let state = token
-        .get_account_info(&bob_meta.token_account)
+    assert_eq!(
+        token
+            .confidential_transfer_remaining_pending_balance_credits(&alice_meta.token_account)
+            .await
+            .unwrap(),
+        0
+    );
+    assert!(!token
+        .confidential_transfer_destination_can_receive(&alice_meta.token_account)
+        .await
+        .unwrap());
+
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            1,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::MaximumPendingBalanceCreditCounterExceeded as u32
+                ),
+            )
+        )))
+    );
+
+    // only the account owner can update the maximum pending balance credit counter
+    let err = token
+        .confidential_transfer_update_max_pending_credit_counter(
+            &alice_meta.token_account,
+            &bob.pubkey(),
+            2,
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::OwnerMismatch as u32),
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_update_max_pending_credit_counter(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            2,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
-
+    assert_eq!(extension.maximum_pending_balance_credit_counter, 2.into());
     assert_eq!(
-        extension
-            .withheld_amount
-            .decrypt(&ct_mint_withdraw_withheld_authority_elgamal_keypair.secret),
-        Some(3),
+        token
+            .confidential_transfer_remaining_pending_balance_credits(&alice_meta.token_account)
+            .await
+            .unwrap(),
+        1
     );
+    assert!(token
+        .confidential_transfer_destination_can_receive(&alice_meta.token_account)
+        .await
+        .unwrap());
 
     token
-        .confidential_transfer_withdraw_withheld_tokens_from_accounts_with_key(
-            &ct_mint_withdraw_withheld_authority,
+        .confidential_transfer_deposit(
             &alice_meta.token_account,
-            &alice_meta.elgamal_keypair.public,
-            3_u64,
-            &extension.withheld_amount.try_into().unwrap(),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
-            &[&bob_meta.token_account],
+            &alice.pubkey(),
+            1,
+            decimals,
+            &[&alice],
         )
         .await
         .unwrap();
 
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 97,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
-
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 3,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
-        )
-        .await;
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 2.into());
 }
 
 #[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_transfer_memo() {
+async fn confidential_transfer_deposit_with_auto_apply() {
     let authority = Keypair::new();
     let auto_approve_new_accounts = true;
-    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
-    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let auditor_elgamal_pubkey = None;
 
     let mut context = TestContext::new().await;
     context
@@ -1915,7 +2044,7 @@ async fn confidential_transfer_transfer_memo() {
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+                auditor_elgamal_pubkey,
             },
         ])
         .await
@@ -1924,122 +2053,120 @@ async fn confidential_transfer_transfer_memo() {
     let TokenContext {
         token,
         alice,
-        bob,
         mint_authority,
         decimals,
         ..
     } = context.token_context.unwrap();
-
-    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
-        &token,
-        &alice,
-        &mint_authority,
-        42,
-        decimals,
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &alice, 2,
     )
     .await;
 
-    let bob_meta =
-        ConfidentialTokenAccountMeta::new_with_required_memo_transfers(&token, &bob).await;
-
-    // transfer without memo
-    let err = token
-        .confidential_transfer_transfer(
+    token
+        .mint_to(
             &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice.pubkey(),
-            None,
-            42,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
-            &[&alice],
+            &mint_authority.pubkey(),
+            5,
+            &[&mint_authority],
         )
         .await
-        .unwrap_err();
+        .unwrap();
 
-    assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(
-                0,
-                InstructionError::Custom(TokenError::NoMemo as u32)
+    // five deposits into an account that can only be credited twice between applies
+    for _ in 0..5 {
+        token
+            .confidential_transfer_deposit_with_auto_apply(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                1,
+                decimals,
+                alice_meta.elgamal_keypair.secret(),
+                &alice_meta.aes_key,
+                &[&alice],
             )
-        )))
-    );
+            .await
+            .unwrap();
+    }
 
-    // transfer with memo
-    token
-        .with_memo("🦖", vec![alice.pubkey()])
-        .confidential_transfer_transfer(
-            &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice.pubkey(),
-            None,
-            42,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
-            &[&alice],
-        )
+    let state = token
+        .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
+    assert_eq!(state.base.amount, 0);
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 1.into());
 
     alice_meta
         .check_balances(
             &token,
             ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
+                pending_balance_lo: 1,
                 pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
+                available_balance: 4,
+                decryptable_available_balance: 4,
             },
         )
         .await;
+}
 
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 42,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_deposit_native() {
+    let mut context = TestContext::new().await;
+    context.init_token_with_native_mint().await.unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&alice_account, &alice.pubkey())
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_account.pubkey(),
+            &alice.pubkey(),
+            0,
+            decimals,
+            &[&alice],
         )
-        .await;
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NativeNotSupported as u32)
+            )
+        )))
+    );
 }
 
-#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn ct_transfer_with_fee_memo() {
-    let ConfidentialTransferMintWithKeypairs {
-        ct_mint,
-        ct_mint_transfer_auditor_elgamal_keypair,
-        ct_mint_withdraw_withheld_authority_elgamal_keypair,
-        ..
-    } = ConfidentialTransferMintWithKeypairs::new();
+async fn confidential_transfer_deposit_all() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
     context
         .init_token_with_mint(vec![
-            ExtensionInitializationParams::TransferFeeConfig {
-                transfer_fee_config_authority: Some(Pubkey::new_unique()),
-                withdraw_withheld_authority: Some(Pubkey::new_unique()),
-                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
-                maximum_fee: TEST_MAXIMUM_FEE,
-            },
             ExtensionInitializationParams::ConfidentialTransferMint {
-                authority: ct_mint.authority.into(),
-                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
-                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
-                withdraw_withheld_authority_elgamal_pubkey: ct_mint
-                    .withdraw_withheld_authority_elgamal_pubkey
-                    .into(),
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
         ])
         .await
@@ -2048,102 +2175,236 @@ async fn ct_transfer_with_fee_memo() {
     let TokenContext {
         token,
         alice,
-        bob,
         mint_authority,
         decimals,
         ..
     } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &alice, 1,
+    )
+    .await;
 
-    let epoch_info = test_epoch_info();
+    // the balance does not fit in a single deposit, and the account only allows a single pending
+    // balance credit, so the pending balance must be applied in between
+    let amount = MAXIMUM_DEPOSIT_TRANSFER_AMOUNT + 42;
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
 
-    let alice_meta =
-        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
-            .await;
-    let bob_meta =
-        ConfidentialTokenAccountMeta::new_with_required_memo_transfers(&token, &bob).await;
+    let outputs = token
+        .confidential_transfer_deposit_all(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            decimals,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
 
     let state = token
         .get_account_info(&alice_meta.token_account)
         .await
         .unwrap();
+    assert_eq!(state.base.amount, 0);
     let extension = state
         .get_extension::<ConfidentialTransferAccount>()
         .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 1.into());
+    assert_eq!(
+        extension
+            .pending_balance_lo
+            .decrypt(alice_meta.elgamal_keypair.secret()),
+        Some(42)
+    );
+    assert_eq!(
+        extension
+            .pending_balance_hi
+            .decrypt(alice_meta.elgamal_keypair.secret()),
+        Some(0)
+    );
+    assert_eq!(
+        DecryptableBalance::try_from(extension.decryptable_available_balance)
+            .unwrap()
+            .decrypt(&alice_meta.aes_key),
+        Some(MAXIMUM_DEPOSIT_TRANSFER_AMOUNT)
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_get_balances() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let balances = token
+        .confidential_transfer_get_balances(
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        balances,
+        ConfidentialTokenAccountBalances {
+            pending_balance_lo: 0,
+            pending_balance_hi: 0,
+            available_balance: 42,
+            decryptable_available_balance: 42,
+        }
+    );
 
     let err = token
-        .confidential_transfer_transfer_with_fee(
+        .confidential_transfer_get_balances(
             &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice,
-            None,
-            100,
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &bob_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
-            &epoch_info,
+            &ElGamalKeypair::new_rand(),
+            &alice_meta.aes_key,
         )
         .await
         .unwrap_err();
+    assert_eq!(err, TokenClientError::ElGamalBalanceDecryption);
 
-    assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(
-                0,
-                InstructionError::Custom(TokenError::NoMemo as u32)
-            )
-        )))
-    );
+    let err = token
+        .confidential_transfer_get_balances(
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &AeKey::new_rand(),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::AeBalanceDecryption);
 
-    token
-        .with_memo("🦖", vec![alice.pubkey()])
-        .confidential_transfer_transfer_with_fee(
+    let available_balance = token
+        .confidential_transfer_get_available_balance_bounded(
             &alice_meta.token_account,
-            &bob_meta.token_account,
-            &alice,
-            None,
-            100,
-            100,
-            &extension.available_balance.try_into().unwrap(),
-            &bob_meta.elgamal_keypair.public,
-            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
-            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
-            &epoch_info,
+            alice_meta.elgamal_keypair.secret(),
+            42,
         )
         .await
         .unwrap();
+    assert_eq!(available_balance, Some(42));
 
-    alice_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 0,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
-            },
+    let available_balance = token
+        .confidential_transfer_get_available_balance_bounded(
+            &alice_meta.token_account,
+            alice_meta.elgamal_keypair.secret(),
+            41,
         )
-        .await;
+        .await
+        .unwrap();
+    assert_eq!(available_balance, None);
+}
 
-    bob_meta
-        .check_balances(
-            &token,
-            ConfidentialTokenAccountBalances {
-                pending_balance_lo: 97,
-                pending_balance_hi: 0,
-                available_balance: 0,
-                decryptable_available_balance: 0,
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_get_balances_batch() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let expected_balances = ConfidentialTokenAccountBalances {
+        pending_balance_lo: 0,
+        pending_balance_hi: 0,
+        available_balance: 42,
+        decryptable_available_balance: 42,
+    };
+
+    // accounts that cannot be fetched or decrypted do not abort the rest of the batch
+    let balances = token
+        .confidential_transfer_get_balances_batch(
+            &[
+                alice_meta.token_account,
+                Pubkey::new_unique(),
+                *token.get_address(),
+                alice_meta.token_account,
+            ],
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
         )
-        .await;
+        .await
+        .unwrap();
+    assert_eq!(balances.len(), 4);
+    assert_eq!(balances[0], Ok(expected_balances));
+    assert_eq!(balances[1], Err(TokenClientError::AccountNotFound));
+    assert!(balances[2].is_err());
+    assert_eq!(balances[3], Ok(expected_balances));
 }
 
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_configure_token_account_with_proof_context() {
+async fn confidential_transfer_apply_pending_balance_cycles() {
     let authority = Keypair::new();
-    let auto_approve_new_accounts = false;
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
     context
@@ -2151,166 +2412,277 @@ async fn confidential_transfer_configure_token_account_with_proof_context() {
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: None,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
         ])
         .await
         .unwrap();
 
     let TokenContext {
-        token, alice, bob, ..
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
     } = context.token_context.unwrap();
 
-    let token_account_keypair = Keypair::new();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
+    // each cycle credits the pending balance three times, and the last cycle also sets the high
+    // bits of the pending balance
+    let deposit_amounts = [43, 7, 65_536 + 1];
     token
-        .create_auxiliary_token_account_with_extension_space(
-            &token_account_keypair,
-            &alice.pubkey(),
-            vec![ExtensionType::ConfidentialTransferAccount],
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            deposit_amounts.iter().sum(),
+            &[&mint_authority],
         )
         .await
         .unwrap();
-    let token_account = token_account_keypair.pubkey();
-
-    let elgamal_keypair =
-        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
-    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
-
-    let context_state_account = Keypair::new();
 
-    // create context state
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<PubkeyValidityProofContext>>();
+    let mut expected_available_balance = 0;
+    for amount in deposit_amounts {
+        for _ in 0..2 {
+            token
+                .confidential_transfer_deposit(
+                    &alice_meta.token_account,
+                    &alice.pubkey(),
+                    amount / 2,
+                    decimals,
+                    &[&alice],
+                )
+                .await
+                .unwrap();
+        }
+        token
+            .confidential_transfer_deposit(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                amount % 2,
+                decimals,
+                &[&alice],
+            )
+            .await
+            .unwrap();
 
-        let instruction_type = ProofInstruction::VerifyPubkeyValidity;
+        token
+            .confidential_transfer_apply_pending_balance(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                None,
+                alice_meta.elgamal_keypair.secret(),
+                &alice_meta.aes_key,
+                &[&alice],
+            )
+            .await
+            .unwrap();
+        expected_available_balance += amount;
 
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
+        alice_meta
+            .check_balances(
+                &token,
+                ConfidentialTokenAccountBalances {
+                    pending_balance_lo: 0,
+                    pending_balance_hi: 0,
+                    available_balance: expected_available_balance,
+                    decryptable_available_balance: expected_available_balance,
+                },
+            )
+            .await;
+    }
+}
 
-        let proof_data =
-            confidential_transfer::instruction::PubkeyValidityData::new(&elgamal_keypair).unwrap();
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_apply_pending_balance_with_limit() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
 
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
 
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            20,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    for _ in 0..2 {
+        token
+            .confidential_transfer_deposit(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                10,
+                decimals,
+                &[&alice],
+            )
+            .await
+            .unwrap();
     }
 
-    token
-        .confidential_transfer_configure_token_account(
-            &token_account,
+    // two pending credits of at most 10 on top of 42 may exceed 61
+    let err = token
+        .confidential_transfer_apply_pending_balance_with_limit(
+            &alice_meta.token_account,
             &alice.pubkey(),
-            Some(&context_state_account.pubkey()),
-            None,
-            &elgamal_keypair,
-            &aes_key,
+            10,
+            61,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
             &[&alice],
         )
         .await
-        .unwrap();
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::MaximumAvailableBalanceExceeded);
 
-    let elgamal_pubkey = (*elgamal_keypair.pubkey()).into();
+    // overflowing bound is rejected
+    let err = token
+        .confidential_transfer_apply_pending_balance_with_limit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            u64::MAX,
+            u64::MAX,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::MaximumAvailableBalanceExceeded);
 
-    let state = token.get_account_info(&token_account).await.unwrap();
-    let extension = state
-        .get_extension::<ConfidentialTransferAccount>()
-        .unwrap();
-    assert!(!bool::from(&extension.approved));
-    assert!(bool::from(&extension.allow_confidential_credits));
-    assert_eq!(extension.elgamal_pubkey, elgamal_pubkey);
-    assert_eq!(
-        aes_key
-            .decrypt(&(extension.decryptable_available_balance.try_into().unwrap()))
-            .unwrap(),
-        0
-    );
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 20,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
 
-    // attempt to create an account with a wrong proof type context state
-    let token_account_keypair = Keypair::new();
     token
-        .create_auxiliary_token_account_with_extension_space(
-            &token_account_keypair,
-            &bob.pubkey(),
-            vec![ExtensionType::ConfidentialTransferAccount],
+        .confidential_transfer_apply_pending_balance_with_limit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            62,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
         )
         .await
         .unwrap();
-    let token_account = token_account_keypair.pubkey();
 
-    let elgamal_keypair = ElGamalKeypair::new_from_signer(&bob, &token_account.to_bytes()).unwrap();
-    let aes_key = AeKey::new_from_signer(&bob, &token_account.to_bytes()).unwrap();
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 62,
+                decryptable_available_balance: 62,
+            },
+        )
+        .await;
+}
 
-    let context_state_account = Keypair::new();
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_apply_pending_balance_with_invalid_counter() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<ZeroBalanceProofContext>>();
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
 
-        let instruction_type = ProofInstruction::VerifyZeroBalance;
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
 
-        let ciphertext = elgamal_keypair.pubkey().encrypt(0_u64);
-        let proof_data = confidential_transfer::instruction::ZeroBalanceProofData::new(
-            &elgamal_keypair,
-            &ciphertext,
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
         )
+        .await
         .unwrap();
 
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
-
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
-
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
-    }
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
 
+    // the expected counter cannot exceed the single credit made so far
     let err = token
-        .confidential_transfer_configure_token_account(
-            &token_account,
-            &bob.pubkey(),
-            Some(&context_state_account.pubkey()),
-            None,
-            &elgamal_keypair,
-            &aes_key,
-            &[&bob],
+        .process_ixs(
+            &[confidential_transfer::instruction::apply_pending_balance(
+                &spl_token_2022::id(),
+                &alice_meta.token_account,
+                2,
+                alice_meta.aes_key.encrypt(42),
+                &alice.pubkey(),
+                &[],
+            )
+            .unwrap()],
+            &[&alice],
         )
         .await
         .unwrap_err();
@@ -2318,15 +2690,59 @@ async fn confidential_transfer_configure_token_account_with_proof_context() {
     assert_eq!(
         err,
         TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(0, InstructionError::InvalidArgument,)
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::InvalidPendingBalanceCreditCounter as u32),
+            )
         )))
     );
+
+    token
+        .process_ixs(
+            &[confidential_transfer::instruction::apply_pending_balance(
+                &spl_token_2022::id(),
+                &alice_meta.token_account,
+                1,
+                alice_meta.aes_key.encrypt(42),
+                &alice.pubkey(),
+                &[],
+            )
+            .unwrap()],
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.expected_pending_balance_credit_counter, 1.into());
+    assert_eq!(extension.actual_pending_balance_credit_counter, 1.into());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
 }
 
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_empty_account_with_proof_context() {
+async fn confidential_transfer_accounts_needing_apply() {
     let authority = Keypair::new();
-    let auto_approve_new_accounts = false;
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
     let mut context = TestContext::new().await;
     context
@@ -2334,138 +2750,87 @@ async fn confidential_transfer_empty_account_with_proof_context() {
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: None,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
             },
         ])
         .await
         .unwrap();
 
     let TokenContext {
-        token, alice, bob, ..
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
     } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
 
-    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
-    let context_state_account = Keypair::new();
-
-    // create context state
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<ZeroBalanceProofContext>>();
-
-        let instruction_type = ProofInstruction::VerifyZeroBalance;
-
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
-
-        let proof_data = confidential_transfer::instruction::ZeroBalanceProofData::new(
-            &alice_meta.elgamal_keypair,
-            &ElGamalCiphertext::default(),
+    token
+        .mint_to(
+            &bob_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
         )
+        .await
         .unwrap();
-
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
-
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
-
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
-    }
-
     token
-        .confidential_transfer_empty_account(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            Some(&context_state_account.pubkey()),
-            None,
-            &alice_meta.elgamal_keypair,
-            &[&alice],
+        .confidential_transfer_deposit(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            42,
+            decimals,
+            &[&bob],
         )
         .await
         .unwrap();
 
-    // attempt to create an account with a wrong proof type context state
-    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
-    let context_state_account = Keypair::new();
-
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<PubkeyValidityProofContext>>();
-
-        let instruction_type = ProofInstruction::VerifyPubkeyValidity;
-
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
-
-        let proof_data =
-            confidential_transfer::instruction::PubkeyValidityData::new(&bob_meta.elgamal_keypair)
-                .unwrap();
-
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
-
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
-
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
-    }
+    let accounts = [
+        alice_meta.token_account,
+        bob_meta.token_account,
+        *token.get_address(),
+        Pubkey::new_unique(),
+    ];
+    let accounts_needing_apply = token
+        .confidential_accounts_needing_apply(&accounts)
+        .await
+        .unwrap();
+    assert_eq!(accounts_needing_apply, vec![bob_meta.token_account]);
 
-    let err = token
-        .confidential_transfer_empty_account(
+    token
+        .confidential_transfer_apply_pending_balance(
             &bob_meta.token_account,
             &bob.pubkey(),
-            Some(&context_state_account.pubkey()),
             None,
-            &bob_meta.elgamal_keypair,
+            bob_meta.elgamal_keypair.secret(),
+            &bob_meta.aes_key,
             &[&bob],
         )
         .await
-        .unwrap_err();
+        .unwrap();
 
-    assert_eq!(
-        err,
-        TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(0, InstructionError::InvalidArgument,)
-        )))
-    );
+    let accounts_needing_apply = token
+        .confidential_accounts_needing_apply(&accounts)
+        .await
+        .unwrap();
+    assert!(accounts_needing_apply.is_empty());
 }
 
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_withdraw_with_proof_context() {
+async fn confidential_transfer_estimate_compute_units() {
     let authority = Keypair::new();
     let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
 
     let mut context = TestContext::new().await;
     context
@@ -2473,7 +2838,7 @@ async fn confidential_transfer_withdraw_with_proof_context() {
             ExtensionInitializationParams::ConfidentialTransferMint {
                 authority: Some(authority.pubkey()),
                 auto_approve_new_accounts,
-                auditor_elgamal_pubkey: None,
+                auditor_elgamal_pubkey,
             },
         ])
         .await
@@ -2482,143 +2847,313 @@ async fn confidential_transfer_withdraw_with_proof_context() {
     let TokenContext {
         token,
         alice,
-        bob,
         mint_authority,
         decimals,
         ..
     } = context.token_context.unwrap();
 
-    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
-        &token,
-        &alice,
-        &mint_authority,
-        42,
-        decimals,
-    )
-    .await;
-
-    let context_state_account = Keypair::new();
-
-    // create context state
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<WithdrawProofContext>>();
-
-        let instruction_type = ProofInstruction::VerifyWithdraw;
-
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
-
-        let state = token
-            .get_account_info(&alice_meta.token_account)
-            .await
-            .unwrap();
-        let extension = state
-            .get_extension::<ConfidentialTransferAccount>()
-            .unwrap();
-        let current_ciphertext = extension.available_balance.try_into().unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
 
-        let proof_data = confidential_transfer::instruction::WithdrawData::new(
-            0,
-            &alice_meta.elgamal_keypair,
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
             42,
-            &current_ciphertext,
+            &[&mint_authority],
         )
+        .await
         .unwrap();
 
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
+    // the estimates cover the operations that do not fit in the default compute budget
+    assert!(token.confidential_transfer_estimate_compute_units(ConfidentialOp::Transfer) > 200_000);
+    assert!(
+        token.confidential_transfer_estimate_compute_units(ConfidentialOp::TransferWithFee)
+            > token.confidential_transfer_estimate_compute_units(ConfidentialOp::Transfer)
+    );
 
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
+    // a deposit succeeds with a compute unit limit set to the estimate
+    token
+        .process_ixs(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(
+                    token.confidential_transfer_estimate_compute_units(ConfidentialOp::Deposit),
+                ),
+                confidential_transfer::instruction::deposit(
+                    &spl_token_2022::id(),
+                    &alice_meta.token_account,
+                    token.get_address(),
+                    42,
+                    decimals,
+                    &alice.pubkey(),
+                    &[],
+                )
+                .unwrap(),
+            ],
+            &[&alice],
+        )
+        .await
+        .unwrap();
 
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
-    }
+    // applying the pending balance succeeds with a compute unit limit set to the estimate
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .apply_pending_balance_account_info();
+    let expected_pending_balance_credit_counter = account_info.pending_balance_credit_counter();
+    let new_decryptable_available_balance = account_info
+        .new_decryptable_available_balance(alice_meta.elgamal_keypair.secret(), &alice_meta.aes_key)
+        .unwrap();
 
     token
-        .confidential_transfer_withdraw(
-            &alice_meta.token_account,
-            &alice.pubkey(),
-            Some(&context_state_account.pubkey()),
-            0,
-            decimals,
-            None,
-            &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
+        .process_ixs(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(
+                    token.confidential_transfer_estimate_compute_units(
+                        ConfidentialOp::ApplyPendingBalance,
+                    ),
+                ),
+                confidential_transfer::instruction::apply_pending_balance(
+                    &spl_token_2022::id(),
+                    &alice_meta.token_account,
+                    expected_pending_balance_credit_counter,
+                    new_decryptable_available_balance,
+                    &alice.pubkey(),
+                    &[],
+                )
+                .unwrap(),
+            ],
             &[&alice],
         )
         .await
         .unwrap();
 
-    // attempt to create an account with a wrong proof type context state
-    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
-    let context_state_account = Keypair::new();
-
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<PubkeyValidityProofContext>>();
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
 
-        let instruction_type = ProofInstruction::VerifyPubkeyValidity;
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_mint() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialMintSupply {
+                supply_elgamal_pubkey: auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
 
-        let proof_data =
-            confidential_transfer::instruction::PubkeyValidityData::new(&bob_meta.elgamal_keypair)
-                .unwrap();
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let context_state_authority = Keypair::new();
 
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
+    token
+        .confidential_transfer_mint(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            65537,
+            decimals,
+            alice_meta.elgamal_keypair.pubkey(),
+            auditor_elgamal_keypair.pubkey(),
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
 
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
+    // the mint amount is added to the confidential supply instead of the supply
+    let state = token.get_mint_info().await.unwrap();
+    assert_eq!(state.base.supply, 0);
+    let extension = state.get_extension::<ConfidentialMintSupply>().unwrap();
+    assert!(verify_confidential_supply(
+        auditor_elgamal_keypair.secret(),
+        &extension.confidential_supply,
+        65537,
+    )
+    .unwrap());
 
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
-    }
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 0);
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 1.into());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 1,
+                pending_balance_hi: 1,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_apply_pending_balance_with_fetch(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.expected_pending_balance_credit_counter, 1.into());
+    assert_eq!(extension.actual_pending_balance_credit_counter, 1.into());
 
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 65537,
+                decryptable_available_balance: 65537,
+            },
+        )
+        .await;
+
+    // only the mint authority can mint
     let err = token
-        .confidential_transfer_withdraw(
-            &bob_meta.token_account,
-            &bob.pubkey(),
-            Some(&context_state_account.pubkey()),
-            0,
+        .confidential_transfer_mint(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            1,
             decimals,
-            None,
-            &bob_meta.elgamal_keypair,
-            &bob_meta.aes_key,
-            &[&bob],
+            alice_meta.elgamal_keypair.pubkey(),
+            auditor_elgamal_keypair.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::OwnerMismatch as u32),
+            )
+        )))
+    );
+
+    // the mint amount must be encrypted under the supply ElGamal public key
+    let err = token
+        .confidential_transfer_mint(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            1,
+            decimals,
+            alice_meta.elgamal_keypair.pubkey(),
+            ElGamalKeypair::new_rand().pubkey(),
+            &[&mint_authority],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferElGamalPubkeyMismatch as u32
+                ),
+            )
+        )))
+    );
+
+    // the mint amount must not exceed the maximum deposit and transfer amount
+    let err = token
+        .confidential_transfer_mint(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            MAXIMUM_DEPOSIT_TRANSFER_AMOUNT + 1,
+            decimals,
+            alice_meta.elgamal_keypair.pubkey(),
+            auditor_elgamal_keypair.pubkey(),
+            &[&mint_authority],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Program(TokenError::MaximumDepositAmountExceeded.into())
+    );
+
+    // tokens of a mint with a confidential supply cannot be deposited
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            1,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            1,
+            decimals,
+            &[&alice],
         )
         .await
         .unwrap_err();
@@ -2626,13 +3161,19 @@ async fn confidential_transfer_withdraw_with_proof_context() {
     assert_eq!(
         err,
         TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(0, InstructionError::InvalidArgument,)
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialSupplyConversionNotSupported as u32
+                ),
+            )
         )))
     );
 }
 
+#[cfg(feature = "zk-ops")]
 #[tokio::test]
-async fn confidential_transfer_transfer_with_proof_context() {
+async fn confidential_transfer_mint_without_confidential_supply() {
     let authority = Keypair::new();
     let auto_approve_new_accounts = true;
     let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
@@ -2653,12 +3194,67 @@ async fn confidential_transfer_transfer_with_proof_context() {
     let TokenContext {
         token,
         alice,
-        bob,
         mint_authority,
         decimals,
         ..
     } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
+    // a mint without a confidential supply cannot track confidentially minted tokens
+    let err = token
+        .confidential_transfer_mint(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &Keypair::new().pubkey(),
+            1,
+            decimals,
+            alice_meta.elgamal_keypair.pubkey(),
+            auditor_elgamal_keypair.pubkey(),
+            &[&mint_authority],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ExtensionNotFound as u32),
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_withdraw() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
 
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
     let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
         &token,
         &alice,
@@ -2668,159 +3264,9131 @@ async fn confidential_transfer_transfer_with_proof_context() {
     )
     .await;
 
-    let bob_meta =
-        ConfidentialTokenAccountMeta::new_with_tokens(&token, &bob, &mint_authority, 0, decimals)
-            .await;
-
-    let context_state_account = Keypair::new();
-
-    // create context state
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<TransferProofContext>>();
-
-        let instruction_type = ProofInstruction::VerifyTransfer;
-
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 0);
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
 
-        let state = token
-            .get_account_info(&alice_meta.token_account)
-            .await
-            .unwrap();
-        let extension = state
-            .get_extension::<ConfidentialTransferAccount>()
-            .unwrap();
-        let current_available_balance = extension.available_balance.try_into().unwrap();
+    // withdraw zero amount
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            0,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
 
-        let proof_data = confidential_transfer::instruction::TransferData::new(
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    // withdraw entire balance
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
             42,
-            (42, &current_available_balance),
+            decimals,
+            None,
             &alice_meta.elgamal_keypair,
-            (
-                bob_meta.elgamal_keypair.pubkey(),
-                auditor_elgamal_keypair.pubkey(),
-            ),
+            &alice_meta.aes_key,
+            &[&alice],
         )
+        .await
         .unwrap();
 
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 42);
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
 
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
+    // attempt to withdraw without enough funds
+    let err = token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            1,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
 
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
-    }
+    assert_eq!(
+        err,
+        TokenClientError::InsufficientConfidentialFunds {
+            requested: 1,
+            available: 0,
+        }
+    );
+
+    token
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_withdraw_with_prebuilt_proof() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    // generate the proof separately from submitting the withdraw
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .withdraw_account_info();
+    let proof_data = build_withdraw_proof_data(
+        &account_info,
+        42,
+        &alice_meta.elgamal_keypair,
+        &alice_meta.aes_key,
+    )
+    .unwrap();
+
+    let withdraw = token
+        .confidential_transfer_withdraw_with_proof(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            42,
+            decimals,
+            &account_info,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 42);
+
+    // the returned decryptable balance is the one that was stored in the account
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(
+        withdraw.new_decryptable_available_balance,
+        DecryptableBalance::try_from(extension.decryptable_available_balance).unwrap()
+    );
+    assert_eq!(
+        withdraw
+            .new_decryptable_available_balance
+            .decrypt(&alice_meta.aes_key),
+        Some(0)
+    );
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_withdraw_with_invalid_proof_offset() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .withdraw_account_info();
+    let proof_data = build_withdraw_proof_data(
+        &account_info,
+        42,
+        &alice_meta.elgamal_keypair,
+        &alice_meta.aes_key,
+    )
+    .unwrap();
+
+    // the proof instruction directly follows the withdraw, but the offset points past the end of
+    // the transaction
+    let instructions = vec![
+        confidential_transfer::instruction::inner_withdraw(
+            &spl_token_2022::id(),
+            &alice_meta.token_account,
+            token.get_address(),
+            42,
+            decimals,
+            alice_meta.aes_key.encrypt(0).into(),
+            &alice.pubkey(),
+            &[],
+            ProofLocation::InstructionOffset(3.try_into().unwrap(), &proof_data),
+        )
+        .unwrap(),
+        verify_withdraw(None, &proof_data),
+    ];
+
+    let err = token
+        .process_ixs(&instructions, &[&alice])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::InvalidProofInstructionOffset as u32),
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_withdraw_to() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_freezing_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        freeze_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let freeze_authority = freeze_authority.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // withdraw into an account owned by someone else
+    token
+        .confidential_transfer_withdraw_to(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 0);
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 42);
+
+    // withdraw into a frozen account
+    token
+        .freeze(
+            &bob_meta.token_account,
+            &freeze_authority.pubkey(),
+            &[&freeze_authority],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_withdraw_to(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            0,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::AccountFrozen as u32),
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_burn() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let state = token.get_mint_info().await.unwrap();
+    assert_eq!(state.base.supply, 42);
+
+    // burn part of the balance
+    token
+        .confidential_transfer_burn(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    assert_eq!(state.base.supply, 32);
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 0);
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 32,
+                decryptable_available_balance: 32,
+            },
+        )
+        .await;
+
+    // burn with the wrong authority
+    let err = token
+        .confidential_transfer_burn(
+            &alice_meta.token_account,
+            &bob.pubkey(),
+            None,
+            1,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::OwnerMismatch as u32)
+            )
+        )))
+    );
+
+    // attempt to burn without enough funds
+    let err = token
+        .confidential_transfer_burn(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            33,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(err, TokenClientError::ProofGeneration);
+
+    // burn the remaining balance
+    token
+        .confidential_transfer_burn(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            32,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    assert_eq!(state.base.supply, 0);
+
+    token
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_burn_confidential_supply() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialMintSupply {
+                supply_elgamal_pubkey: auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let context_state_authority = Keypair::new();
+
+    token
+        .confidential_transfer_mint(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            20,
+            decimals,
+            alice_meta.elgamal_keypair.pubkey(),
+            auditor_elgamal_keypair.pubkey(),
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_apply_pending_balance_with_fetch(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // tokens that are minted confidentially are burned from the confidential supply
+    token
+        .confidential_transfer_burn(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            15,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    assert_eq!(state.base.supply, 0);
+    let extension = state.get_extension::<ConfidentialMintSupply>().unwrap();
+    assert!(verify_confidential_supply(
+        auditor_elgamal_keypair.secret(),
+        &extension.confidential_supply,
+        5,
+    )
+    .unwrap());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 5,
+                decryptable_available_balance: 5,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_rotate_elgamal_key() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let new_alice_meta = ConfidentialTokenAccountMeta {
+        token_account: alice_meta.token_account,
+        elgamal_keypair: ElGamalKeypair::new_rand(),
+        aes_key: AeKey::new_rand(),
+    };
+
+    token
+        .confidential_transfer_rotate_elgamal_key(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &new_alice_meta.elgamal_keypair,
+            &new_alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(
+        extension.elgamal_pubkey,
+        (*new_alice_meta.elgamal_keypair.pubkey()).into()
+    );
+    new_alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    // the pending balance must be applied before the key can be rotated
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            10,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_rotate_elgamal_key(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &new_alice_meta.elgamal_keypair,
+            &new_alice_meta.aes_key,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferPendingBalanceNotEmpty as u32
+                )
+            )
+        )))
+    );
+
+    // the old keys can no longer be used to generate proofs for the account
+    let err = token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            1,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(err, TokenClientError::ProofGeneration);
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_rotated_destination_key() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let token = token.with_destination_elgamal_pubkey_check(true);
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // alice fetches the destination key before bob rotates it
+    let stale_bob_elgamal_pubkey = *bob_meta.elgamal_keypair.pubkey();
+
+    let new_bob_meta = ConfidentialTokenAccountMeta {
+        token_account: bob_meta.token_account,
+        elgamal_keypair: ElGamalKeypair::new_rand(),
+        aes_key: AeKey::new_rand(),
+    };
+    token
+        .confidential_transfer_rotate_elgamal_key(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            None,
+            None,
+            &bob_meta.elgamal_keypair,
+            &bob_meta.aes_key,
+            &new_bob_meta.elgamal_keypair,
+            &new_bob_meta.aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &stale_bob_elgamal_pubkey,
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::DestinationElGamalPubkeyChanged);
+
+    // without the check, the program rejects the proof for the stale key
+    let token = token.with_destination_elgamal_pubkey_check(false);
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &stale_bob_elgamal_pubkey,
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferElGamalPubkeyMismatch as u32
+                )
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            new_bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    new_bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &bob, 2,
+    )
+    .await;
+
+    // attempt to transfer more than the available balance
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            43,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::InsufficientConfidentialFunds {
+            requested: 43,
+            available: 42,
+        }
+    );
+
+    // Self-transfer of 0 tokens
+    let transfer = token
+        .confidential_transfer_transfer_with_decryptable_balance(
+            &alice_meta.token_account,
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            0,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            alice_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        transfer
+            .new_decryptable_available_balance
+            .decrypt(&alice_meta.aes_key),
+        Some(42)
+    );
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    // Self-transfer of N tokens
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            alice_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // self-transfers are credited back to the available balance
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_transfer(
+            &bob_meta.token_account,
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            None,
+            0,
+            None,
+            &bob_meta.elgamal_keypair,
+            &bob_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    // self-transfers do not consume pending balance credits
+    token
+        .confidential_transfer_transfer(
+            &bob_meta.token_account,
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            None,
+            0,
+            None,
+            &bob_meta.elgamal_keypair,
+            &bob_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_apply_pending_balance(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            None,
+            bob_meta.elgamal_keypair.secret(),
+            &bob_meta.aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_pending_balance_credit_counter_exceeded() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &bob, 1,
+    )
+    .await;
+
+    token
+        .mint_to(
+            &bob_meta.token_account,
+            &mint_authority.pubkey(),
+            2,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(&bob_meta.token_account, &bob.pubkey(), 1, decimals, &[&bob])
+        .await
+        .unwrap();
+
+    // a deposit into a full account fails because of the depositor's own account
+    let err = token
+        .confidential_transfer_deposit(&bob_meta.token_account, &bob.pubkey(), 1, decimals, &[&bob])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::MaximumPendingBalanceCreditCounterExceeded as u32
+                ),
+            )
+        )))
+    );
+
+    // a transfer to a full account fails because of the recipient's account
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::DestinationPendingBalanceCreditCounterExceeded as u32
+                ),
+            )
+        )))
+    );
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_reference() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // bob shares the reference key with alice as part of the invoice
+    let reference_key = AeKey::new_rand();
+    let invoice_id = *b"invoice-00000042";
+    let reference = EncryptedTransferReference::encrypt(&reference_key, &invoice_id);
+
+    token
+        .confidential_transfer_transfer_with_reference(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            42,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &reference,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    // the recipient reads the reference back from the instruction data
+    let current_available_balance = alice_meta.elgamal_keypair.pubkey().encrypt(0_u64);
+    let proof_data = confidential_transfer::instruction::TransferData::new(
+        0,
+        (0, &current_available_balance),
+        &alice_meta.elgamal_keypair,
+        (
+            bob_meta.elgamal_keypair.pubkey(),
+            auditor_elgamal_keypair.pubkey(),
+        ),
+    )
+    .unwrap();
+    let instructions = confidential_transfer::instruction::transfer_with_reference(
+        &spl_token_2022::id(),
+        &alice_meta.token_account,
+        &bob_meta.token_account,
+        token.get_address(),
+        alice_meta.aes_key.encrypt(0),
+        &alice.pubkey(),
+        &[],
+        &reference,
+        ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+    )
+    .unwrap();
+    let decoded_reference =
+        confidential_transfer::instruction::decode_transfer_reference(&instructions[0].data)
+            .unwrap();
+    assert_eq!(decoded_reference.decrypt(&reference_key), Some(invoice_id));
+    assert_eq!(decoded_reference.decrypt(&bob_meta.aes_key), None);
+
+    // a plain transfer carries no reference
+    let instructions = confidential_transfer::instruction::transfer(
+        &spl_token_2022::id(),
+        &alice_meta.token_account,
+        &bob_meta.token_account,
+        token.get_address(),
+        alice_meta.aes_key.encrypt(0),
+        &alice.pubkey(),
+        &[],
+        ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+    )
+    .unwrap();
+    assert_eq!(
+        confidential_transfer::instruction::decode_transfer_reference(&instructions[0].data),
+        None
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_delegated_transfer() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let delegate = Keypair::new();
+    let context_state_authority = Keypair::new();
+
+    token
+        .confidential_transfer_approve_delegate(
+            &alice_meta.token_account,
+            &delegate.pubkey(),
+            &alice.pubkey(),
+            30,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the delegate cannot move the non-confidential balance
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.delegate, Some(delegate.pubkey()).into());
+    assert_eq!(state.base.delegated_amount, 0);
+
+    // only the approved delegate can spend the allowance
+    let err = token
+        .confidential_transfer_delegated_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            10,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferDelegateNotApproved as u32
+                )
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_delegated_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &delegate.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            20,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&delegate],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 22,
+                decryptable_available_balance: 22,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 20,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let delegate_allowance = state
+        .get_extension::<ConfidentialTransferDelegateAllowance>()
+        .unwrap();
+    let decryptable_allowance = delegate_allowance.decryptable_allowance.try_into().unwrap();
+    assert_eq!(alice_meta.aes_key.decrypt(&decryptable_allowance), Some(10));
+
+    // the client refuses to transfer more than the remaining allowance
+    let err = token
+        .confidential_transfer_delegated_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &delegate.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            11,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&delegate],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Program(TokenError::InsufficientFunds.into())
+    );
+
+    // revoking the delegate also revokes the allowance
+    token
+        .revoke(&alice_meta.token_account, &alice.pubkey(), &[&alice])
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_delegated_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &delegate.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            10,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&delegate],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferDelegateNotApproved as u32
+                )
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_repeated_zero_self_transfers() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &alice, 2,
+    )
+    .await;
+
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // more self-transfers than the maximum pending balance credit counter allows credits
+    for _ in 0..4 {
+        token
+            .confidential_transfer_transfer(
+                &alice_meta.token_account,
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                None,
+                0,
+                None,
+                &alice_meta.elgamal_keypair,
+                &alice_meta.aes_key,
+                alice_meta.elgamal_keypair.pubkey(),
+                Some(auditor_elgamal_keypair.pubkey()),
+                &[&alice],
+            )
+            .await
+            .unwrap();
+    }
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 0.into());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_repeated_zero_self_transfers_with_fee() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let withdraw_withheld_authority_elgamal_keypair = ElGamalKeypair::new_rand();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(authority.pubkey()),
+                withdraw_withheld_authority: Some(authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialTransferFeeConfig {
+                authority: Some(authority.pubkey()),
+                withdraw_withheld_authority_elgamal_pubkey:
+                    (*withdraw_withheld_authority_elgamal_keypair.pubkey()).into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_maximum_pending_balance_credit_counter(
+        &token, &alice, 2,
+    )
+    .await;
+
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // self-transfers do not incur a fee, so they take the same path as on a mint without fees
+    for _ in 0..4 {
+        token
+            .confidential_transfer_transfer_with_fee(
+                &alice_meta.token_account,
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                None,
+                0,
+                None,
+                &alice_meta.elgamal_keypair,
+                &alice_meta.aes_key,
+                alice_meta.elgamal_keypair.pubkey(),
+                Some(auditor_elgamal_keypair.pubkey()),
+                withdraw_withheld_authority_elgamal_keypair.pubkey(),
+                TEST_FEE_BASIS_POINTS,
+                TEST_MAXIMUM_FEE,
+                &[&alice],
+            )
+            .await
+            .unwrap();
+    }
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(extension.pending_balance_credit_counter, 0.into());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_checked() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let account_info = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .transfer_account_info();
+    let proof_data = account_info
+        .generate_transfer_proof_data(
+            42,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+        )
+        .unwrap();
+    let new_decryptable_available_balance = account_info
+        .new_decryptable_available_balance(42, &alice_meta.aes_key)
+        .unwrap();
+
+    // a transfer built against stale mint decimals is rejected
+    let err = token
+        .process_ixs(
+            &confidential_transfer::instruction::transfer_checked(
+                &spl_token_2022::id(),
+                &alice_meta.token_account,
+                &bob_meta.token_account,
+                token.get_address(),
+                new_decryptable_available_balance,
+                &alice.pubkey(),
+                &[],
+                decimals + 1,
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )
+            .unwrap(),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::MintDecimalsMismatch as u32)
+            )
+        )))
+    );
+
+    token
+        .process_ixs(
+            &confidential_transfer::instruction::transfer_checked(
+                &spl_token_2022::id(),
+                &alice_meta.token_account,
+                &bob_meta.token_account,
+                token.get_address(),
+                new_decryptable_available_balance,
+                &alice.pubkey(),
+                &[],
+                decimals,
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )
+            .unwrap(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_stale_auditor() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let current_auditor = token.confidential_transfer_current_auditor().await.unwrap();
+    assert_eq!(current_auditor, Some(*auditor_elgamal_keypair.pubkey()));
+
+    // rotate the auditor of the mint
+    let new_auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    token
+        .confidential_transfer_update_mint(
+            &authority.pubkey(),
+            auto_approve_new_accounts,
+            Some((*new_auditor_elgamal_keypair.pubkey()).into()),
+            &[&authority],
+        )
+        .await
+        .unwrap();
+
+    // transfers with the stale auditor key are rejected
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferElGamalPubkeyMismatch as u32
+                ),
+            )
+        )))
+    );
+
+    let current_auditor = token.confidential_transfer_current_auditor().await.unwrap();
+    assert_eq!(current_auditor, Some(*new_auditor_elgamal_keypair.pubkey()));
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            current_auditor.as_ref(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_auditor_decrypt_transfer_amount() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    // the transfer amount spans both the low and high bits
+    let transfer_amount = 65_537;
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        transfer_amount,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .transfer_account_info();
+
+    let proof_data = account_info
+        .generate_transfer_proof_data(
+            transfer_amount,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+        )
+        .unwrap();
+    let new_decryptable_available_balance = account_info
+        .new_decryptable_available_balance(transfer_amount, &alice_meta.aes_key)
+        .unwrap();
+
+    token
+        .process_ixs(
+            &confidential_transfer::instruction::transfer(
+                &spl_token_2022::id(),
+                &alice_meta.token_account,
+                &bob_meta.token_account,
+                token.get_address(),
+                new_decryptable_available_balance,
+                &alice.pubkey(),
+                &[],
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )
+            .unwrap(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the auditor recovers the transfer amount from the proof data of the transaction
+    let auditor_transfer_amount = decrypt_transfer_amount_for_auditor(
+        auditor_elgamal_keypair.secret(),
+        &proof_data.context.ciphertext_lo,
+        &proof_data.context.ciphertext_hi,
+    )
+    .unwrap();
+    assert_eq!(auditor_transfer_amount, transfer_amount);
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 1,
+                pending_balance_hi: 1,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_auditor_decrypt_handle() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    // the transfer amount spans both the low and high bits
+    let transfer_amount = 65_537;
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        transfer_amount,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let transfer = token
+        .confidential_transfer_transfer_with_auditor_decrypt_handle(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            transfer_amount,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the auditor recovers the transfer amount from the returned ciphertexts
+    let auditor_transfer_amount = decrypt_transfer_amount_for_auditor(
+        auditor_elgamal_keypair.secret(),
+        &transfer.transfer_amount_ciphertext_lo,
+        &transfer.transfer_amount_ciphertext_hi,
+    )
+    .unwrap();
+    assert_eq!(auditor_transfer_amount, transfer_amount);
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 1,
+                pending_balance_hi: 1,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_transfer_with_fee() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(Pubkey::new_unique()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Self-transfer of 0 tokens
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &alice_meta.token_account,
+            &alice,
+            None,
+            0, // amount
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &alice_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 100,
+                decryptable_available_balance: 100,
+            },
+        )
+        .await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Self-transfers does not incur a fee
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &alice_meta.token_account,
+            &alice,
+            None,
+            100, // amount
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &alice_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 100,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_apply_pending_balance(&alice_meta.token_account, &alice, 0, 100, 2)
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 100,
+                decryptable_available_balance: 100,
+            },
+        )
+        .await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    // Alice account cannot be closed since there are withheld fees from self-transfer
+    token
+        .confidential_transfer_empty_account(&alice_meta.token_account, &alice)
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_empty_account(&bob_meta.token_account, &bob)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ConfidentialTransferAccountHasBalance as u32)
+            )
+        )))
+    );
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 97,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_apply_pending_balance(&bob_meta.token_account, &bob, 0, 97, 1)
+        .await
+        .unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 97,
+                decryptable_available_balance: 97,
+            },
+        )
+        .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_close_with_fees() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(Pubkey::new_unique()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // the fee of the transfer is withheld in Bob's account
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_apply_pending_balance(&bob_meta.token_account, &bob, 0, 97, 1)
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    token
+        .confidential_transfer_transfer_with_fee(
+            &bob_meta.token_account,
+            &alice_meta.token_account,
+            &bob,
+            None,
+            97,
+            97,
+            &extension.available_balance.try_into().unwrap(),
+            &alice_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    // Bob's balances are empty, but the withheld fee keeps the account from being closed
+    token
+        .confidential_transfer_empty_account(&bob_meta.token_account, &bob)
+        .await
+        .unwrap();
+
+    let err = token
+        .close_account(
+            &bob_meta.token_account,
+            &Pubkey::new_unique(),
+            &bob.pubkey(),
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferFeeAccountHasWithheldFee as u32
+                )
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_close_with_fees(
+            &bob_meta.token_account,
+            &Pubkey::new_unique(),
+            &bob.pubkey(),
+            None,
+            &bob_meta.elgamal_keypair,
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(
+        token.get_account(bob_meta.token_account).await.unwrap_err(),
+        TokenClientError::AccountNotFound
+    );
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_configure_token_account_without_fee_space() {
+    let ConfidentialTransferMintWithKeypairs { ct_mint, .. } =
+        ConfidentialTransferMintWithKeypairs::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(Pubkey::new_unique()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+
+    // the account has room for the confidential transfer extension, but not for the
+    // confidential transfer fee extension that a fee mint requires
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let proof_data =
+        confidential_transfer::instruction::PubkeyValidityData::new(&elgamal_keypair).unwrap();
+
+    let err = token
+        .process_ixs(
+            &confidential_transfer::instruction::configure_account(
+                &spl_token_2022::id(),
+                &token_account,
+                token.get_address(),
+                aes_key.encrypt(0),
+                65536,
+                &alice.pubkey(),
+                &[],
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )
+            .unwrap(),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::InsufficientConfidentialTransferAccountSpace as u32
+                ),
+            )
+        )))
+    );
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_withdraw_withheld_tokens_from_mint() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let ct_mint_withdraw_withheld_authority = Keypair::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    token
+        .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
+            &ct_mint_withdraw_withheld_authority.pubkey(),
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            0_u64,
+            &ct_mint.withheld_amount.try_into().unwrap(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &[&ct_mint_withdraw_withheld_authority],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 100,
+                decryptable_available_balance: 100,
+            },
+        )
+        .await;
+
+    check_withheld_amount_in_mint(
+        &token,
+        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        0,
+    )
+    .await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Test fee is 2.5% so the withheld fees should be 3
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    assert_eq!(
+        extension
+            .withheld_amount
+            .decrypt(&ct_mint_withdraw_withheld_authority_elgamal_keypair.secret),
+        Some(3),
+    );
+
+    token
+        .confidential_transfer_harvest_withheld_tokens_to_mint(&[&bob_meta.token_account])
+        .await
+        .unwrap();
+
+    check_withheld_amount_in_mint(
+        &token,
+        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        3,
+    )
+    .await;
+
+    let state = token.get_mint_info().await.unwrap();
+    let ct_mint = state.get_extension::<ConfidentialTransferMint>().unwrap();
+
+    token
+        .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
+            &ct_mint_withdraw_withheld_authority.pubkey(),
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            3_u64,
+            &ct_mint.withheld_amount.try_into().unwrap(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &[&ct_mint_withdraw_withheld_authority],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 3,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_sweep_fees() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let ct_mint_withdraw_withheld_authority = Keypair::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Test fee is 2.5% so the withheld fees should be 3
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    let (_, harvested, withdraw_output) = token
+        .confidential_transfer_sweep_fees(
+            &[&bob_meta.token_account],
+            &ct_mint_withdraw_withheld_authority.pubkey(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            &[&ct_mint_withdraw_withheld_authority],
+        )
+        .await
+        .unwrap();
+    assert_eq!(harvested, vec![bob_meta.token_account]);
+    assert!(withdraw_output.is_some());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 3,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    // nothing is left to harvest, so sweeping again skips the withdraw
+    let (_, harvested, withdraw_output) = token
+        .confidential_transfer_sweep_fees(
+            &[&bob_meta.token_account],
+            &ct_mint_withdraw_withheld_authority.pubkey(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            &[&ct_mint_withdraw_withheld_authority],
+        )
+        .await
+        .unwrap();
+    assert_eq!(harvested, vec![bob_meta.token_account]);
+    assert!(withdraw_output.is_none());
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_withdraw_withheld_tokens_from_mints() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let ct_mint_withdraw_withheld_authority = Keypair::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Test fee is 2.5% so the withheld fees should be 3
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_harvest_withheld_tokens_to_mint(&[&bob_meta.token_account])
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    let ct_mint = state.get_extension::<ConfidentialTransferMint>().unwrap();
+
+    let proof_data = confidential_transfer::instruction::WithdrawWithheldTokensData::new(
+        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        &alice_meta.elgamal_keypair.public,
+        &ct_mint.withheld_amount.try_into().unwrap(),
+        3,
+    )
+    .unwrap();
+
+    let results = token
+        .confidential_transfer_withdraw_withheld_from_mints(
+            &ct_mint_withdraw_withheld_authority.pubkey(),
+            &[(*token.get_address(), alice_meta.token_account, proof_data)],
+            &[&ct_mint_withdraw_withheld_authority],
+        )
+        .await
+        .unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, vec![*token.get_address()]);
+    assert!(results[0].1.is_ok());
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 3,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    check_withheld_amount_in_mint(
+        &token,
+        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        0,
+    )
+    .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_harvest_withheld_tokens_to_mint_skips_unharvestable_accounts() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let ct_mint_withdraw_withheld_authority = Keypair::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Test fee is 2.5% so the withheld fees should be 3
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    // an account that does not exist cannot be harvested and is skipped
+    let (_, harvested) = token
+        .confidential_transfer_harvest_withheld_tokens_to_mint(&[
+            &bob_meta.token_account,
+            &Pubkey::new_unique(),
+        ])
+        .await
+        .unwrap();
+    assert_eq!(harvested, vec![bob_meta.token_account]);
+
+    check_withheld_amount_in_mint(
+        &token,
+        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        3,
+    )
+    .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_withdraw_withheld_tokens_from_mint_with_multisig() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let multisig = Keypair::new();
+    let multisig_members = [Keypair::new(), Keypair::new(), Keypair::new()];
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(multisig.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let multisig_member_pubkeys = multisig_members
+        .iter()
+        .map(|member| member.pubkey())
+        .collect::<Vec<_>>();
+    token
+        .create_multisig(
+            &multisig,
+            &multisig_member_pubkeys.iter().collect::<Vec<_>>(),
+            2,
+        )
+        .await
+        .unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Test fee is 2.5% so the withheld fees should be 3
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_harvest_withheld_tokens_to_mint(&[&bob_meta.token_account])
+        .await
+        .unwrap();
+
+    let state = token.get_mint_info().await.unwrap();
+    let ct_mint = state.get_extension::<ConfidentialTransferMint>().unwrap();
+
+    // a single signer does not meet the threshold
+    token
+        .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
+            &multisig.pubkey(),
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            3_u64,
+            &ct_mint.withheld_amount.try_into().unwrap(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &[&multisig_members[0]],
+        )
+        .await
+        .unwrap_err();
+
+    token
+        .confidential_transfer_withdraw_withheld_tokens_from_mint_with_key(
+            &multisig.pubkey(),
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            3_u64,
+            &ct_mint.withheld_amount.try_into().unwrap(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &[&multisig_members[0], &multisig_members[2]],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 3,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    check_withheld_amount_in_mint(
+        &token,
+        &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        0,
+    )
+    .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_withdraw_withheld_tokens_from_accounts() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let ct_mint_withdraw_withheld_authority = Keypair::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(ct_mint_withdraw_withheld_authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    // Test fee is 2.5% so the withheld fees should be 3
+    token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    assert_eq!(
+        extension
+            .withheld_amount
+            .decrypt(&ct_mint_withdraw_withheld_authority_elgamal_keypair.secret),
+        Some(3),
+    );
+
+    token
+        .confidential_transfer_withdraw_withheld_tokens_from_accounts_with_key(
+            &ct_mint_withdraw_withheld_authority.pubkey(),
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair.public,
+            3_u64,
+            &extension.withheld_amount.try_into().unwrap(),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair,
+            &[&bob_meta.token_account],
+            &[&ct_mint_withdraw_withheld_authority],
+        )
+        .await
+        .unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 97,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 3,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_memo() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_required_memo_transfers(&token, &bob).await;
+
+    // transfer without memo
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NoMemo as u32)
+            )
+        )))
+    );
+
+    // transfer with memo
+    token
+        .with_memo("🦖", vec![alice.pubkey()])
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(all(feature = "zk-ops", feature = "proof-program"))]
+#[tokio::test]
+async fn ct_transfer_with_fee_memo() {
+    let ConfidentialTransferMintWithKeypairs {
+        ct_mint,
+        ct_mint_transfer_auditor_elgamal_keypair,
+        ct_mint_withdraw_withheld_authority_elgamal_keypair,
+        ..
+    } = ConfidentialTransferMintWithKeypairs::new();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(Pubkey::new_unique()),
+                withdraw_withheld_authority: Some(Pubkey::new_unique()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: ct_mint.authority.into(),
+                auto_approve_new_accounts: ct_mint.auto_approve_new_accounts.try_into().unwrap(),
+                auditor_elgamal_pubkey: ct_mint.auditor_elgamal_pubkey.into(),
+                withdraw_withheld_authority_elgamal_pubkey: ct_mint
+                    .withdraw_withheld_authority_elgamal_pubkey
+                    .into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let epoch_info = test_epoch_info();
+
+    let alice_meta =
+        ConfidentialTokenAccountMeta::with_tokens(&token, &alice, &mint_authority, 100, decimals)
+            .await;
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_required_memo_transfers(&token, &bob).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NoMemo as u32)
+            )
+        )))
+    );
+
+    token
+        .with_memo("🦖", vec![alice.pubkey()])
+        .confidential_transfer_transfer_with_fee(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice,
+            None,
+            100,
+            100,
+            &extension.available_balance.try_into().unwrap(),
+            &bob_meta.elgamal_keypair.public,
+            Some(ct_mint_transfer_auditor_elgamal_keypair.public),
+            &ct_mint_withdraw_withheld_authority_elgamal_keypair.public,
+            &epoch_info,
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 97,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn confidential_transfer_configure_token_account_with_proof_context() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = false;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token, alice, bob, ..
+    } = context.token_context.unwrap();
+
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+
+    let context_state_account = Keypair::new();
+
+    // create context state
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<PubkeyValidityProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyPubkeyValidity;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let proof_data =
+            confidential_transfer::instruction::PubkeyValidityData::new(&elgamal_keypair).unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let elgamal_pubkey = (*elgamal_keypair.pubkey()).into();
+
+    let state = token.get_account_info(&token_account).await.unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(!bool::from(&extension.approved));
+    assert!(bool::from(&extension.allow_confidential_credits));
+    assert_eq!(extension.elgamal_pubkey, elgamal_pubkey);
+    assert_eq!(
+        DecryptableBalance::try_from(extension.decryptable_available_balance)
+            .unwrap()
+            .decrypt(&aes_key)
+            .unwrap(),
+        0
+    );
+
+    // attempt to create an account with a wrong proof type context state
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &bob.pubkey(),
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair = ElGamalKeypair::new_from_signer(&bob, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&bob, &token_account.to_bytes()).unwrap();
+
+    let context_state_account = Keypair::new();
+
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<ZeroBalanceProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyZeroBalance;
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let ciphertext = elgamal_keypair.pubkey().encrypt(0_u64);
+        let proof_data = confidential_transfer::instruction::ZeroBalanceProofData::new(
+            &elgamal_keypair,
+            &ciphertext,
+        )
+        .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    let err = token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &bob.pubkey(),
+            Some(&context_state_account.pubkey()),
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ProofTypeMismatch as u32),
+            )
+        )))
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_empty_account_with_proof_context() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = false;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token, alice, bob, ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let context_state_account = Keypair::new();
+    let context_state_authority = Keypair::new();
+
+    // create context state
+    {
+        let space = size_of::<ProofContextState<ZeroBalanceProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyZeroBalance;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let proof_data = confidential_transfer::instruction::ZeroBalanceProofData::new(
+            &alice_meta.elgamal_keypair,
+            &ElGamalCiphertext::default(),
+        )
+        .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    token
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // close the context state account and reclaim the rent
+    let lamport_destination = Pubkey::new_unique();
+    token
+        .confidential_transfer_close_context_state(
+            &context_state_account.pubkey(),
+            &lamport_destination,
+            &context_state_authority.pubkey(),
+            &[&context_state_authority],
+        )
+        .await
+        .unwrap();
+
+    {
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+        let space = size_of::<ProofContextState<ZeroBalanceProofContext>>();
+        assert!(ctx
+            .banks_client
+            .get_account(context_state_account.pubkey())
+            .await
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            ctx.banks_client
+                .get_balance(lamport_destination)
+                .await
+                .unwrap(),
+            rent.minimum_balance(space)
+        );
+    }
+
+    // attempt to create an account with a wrong proof type context state
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let context_state_account = Keypair::new();
+
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<PubkeyValidityProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyPubkeyValidity;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let proof_data =
+            confidential_transfer::instruction::PubkeyValidityData::new(&bob_meta.elgamal_keypair)
+                .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    let err = token
+        .confidential_transfer_empty_account(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            Some(&context_state_account.pubkey()),
+            None,
+            &bob_meta.elgamal_keypair,
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ProofTypeMismatch as u32),
+            )
+        )))
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_withdraw_with_proof_context() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let context_state_account = Keypair::new();
+
+    // create context state
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<WithdrawProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyWithdraw;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let state = token
+            .get_account_info(&alice_meta.token_account)
+            .await
+            .unwrap();
+        let extension = state
+            .get_extension::<ConfidentialTransferAccount>()
+            .unwrap();
+        let current_ciphertext = extension.available_balance.try_into().unwrap();
+
+        let proof_data = confidential_transfer::instruction::WithdrawData::new(
+            0,
+            &alice_meta.elgamal_keypair,
+            42,
+            &current_ciphertext,
+        )
+        .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            0,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // attempt to create an account with a wrong proof type context state
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let context_state_account = Keypair::new();
+
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<PubkeyValidityProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyPubkeyValidity;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let proof_data =
+            confidential_transfer::instruction::PubkeyValidityData::new(&bob_meta.elgamal_keypair)
+                .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    let err = token
+        .confidential_transfer_withdraw(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            Some(&context_state_account.pubkey()),
+            0,
+            decimals,
+            None,
+            &bob_meta.elgamal_keypair,
+            &bob_meta.aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ProofTypeMismatch as u32),
+            )
+        )))
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_withdraw_with_reused_proof_context() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let context_state_account = Keypair::new();
+    let context_state_authority = Keypair::new();
+
+    // both withdraws verify their proofs into the same context state account
+    for withdrawn in [10, 20] {
+        let account_info = token
+            .get_account_info(&alice_meta.token_account)
+            .await
+            .unwrap()
+            .get_extension::<ConfidentialTransferAccount>()
+            .unwrap()
+            .withdraw_account_info();
+        let proof_data = build_withdraw_proof_data(
+            &account_info,
+            10,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        )
+        .unwrap();
+
+        token
+            .confidential_transfer_reuse_proof_context(
+                &context_state_account,
+                &context_state_authority,
+                ProofInstruction::VerifyWithdraw,
+                &proof_data,
+            )
+            .await
+            .unwrap();
+
+        token
+            .confidential_transfer_withdraw(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                Some(&context_state_account.pubkey()),
+                10,
+                decimals,
+                Some(account_info),
+                &alice_meta.elgamal_keypair,
+                &alice_meta.aes_key,
+                &[&alice],
+            )
+            .await
+            .unwrap();
+
+        let state = token
+            .get_account_info(&alice_meta.token_account)
+            .await
+            .unwrap();
+        assert_eq!(state.base.amount, withdrawn);
+    }
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 22,
+                decryptable_available_balance: 22,
+            },
+        )
+        .await;
+
+    // the account still holds the proof of the last withdraw and can be closed as usual
+    let lamport_destination = Pubkey::new_unique();
+    token
+        .confidential_transfer_close_context_state(
+            &context_state_account.pubkey(),
+            &lamport_destination,
+            &context_state_authority.pubkey(),
+            &[&context_state_authority],
+        )
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn confidential_transfer_transfer_with_proof_context() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_tokens(&token, &bob, &mint_authority, 0, decimals)
+            .await;
+
+    let context_state_account = Keypair::new();
+
+    // create context state
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<TransferProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyTransfer;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let state = token
+            .get_account_info(&alice_meta.token_account)
+            .await
+            .unwrap();
+        let extension = state
+            .get_extension::<ConfidentialTransferAccount>()
+            .unwrap();
+        let current_available_balance = extension.available_balance.try_into().unwrap();
+
+        let proof_data = confidential_transfer::instruction::TransferData::new(
+            42,
+            (42, &current_available_balance),
+            &alice_meta.elgamal_keypair,
+            (
+                bob_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_keypair.pubkey(),
+            ),
+        )
+        .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // attempt to create an account with a wrong proof type context state
+    let context_state_account = Keypair::new();
+
+    {
+        let context_state_authority = Keypair::new();
+        let space = size_of::<ProofContextState<WithdrawProofContext>>();
+
+        let instruction_type = ProofInstruction::VerifyWithdraw;
+
+        let context_state_info = ContextStateInfo {
+            context_state_account: &context_state_account.pubkey(),
+            context_state_authority: &context_state_authority.pubkey(),
+        };
+
+        let state = token
+            .get_account_info(&alice_meta.token_account)
+            .await
+            .unwrap();
+        let extension = state
+            .get_extension::<ConfidentialTransferAccount>()
+            .unwrap();
+        let current_ciphertext = extension.available_balance.try_into().unwrap();
+
+        let proof_data = confidential_transfer::instruction::WithdrawData::new(
+            0,
+            &alice_meta.elgamal_keypair,
+            0,
+            &current_ciphertext,
+        )
+        .unwrap();
+
+        let mut ctx = context.context.lock().await;
+        let rent = ctx.banks_client.get_rent().await.unwrap();
+
+        let instructions = vec![
+            system_instruction::create_account(
+                &ctx.payer.pubkey(),
+                &context_state_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &zk_token_proof_program::id(),
+            ),
+            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
+        ];
+
+        let tx = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&ctx.payer.pubkey()),
+            &[&ctx.payer, &context_state_account],
+            ctx.last_blockhash,
+        );
+        ctx.banks_client.process_transaction(tx).await.unwrap();
+    }
+
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            0,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ProofTypeMismatch as u32),
+            )
+        )))
+    )
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_additional_auditors() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let additional_auditor_elgamal_keypairs =
+        [ElGamalKeypair::new_rand(), ElGamalKeypair::new_rand()];
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialTransferAuditors {
+                auditor_elgamal_pubkeys: additional_auditor_elgamal_keypairs
+                    .iter()
+                    .map(|keypair| (*keypair.pubkey()).into())
+                    .collect(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    // the transfer amount spans both the low and high bits
+    let transfer_amount = 65_537;
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        transfer_amount,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // a transfer without the proofs for the additional auditors is rejected
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            transfer_amount,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferAdditionalProofsRequired as u32
+                )
+            )
+        )))
+    );
+
+    let transfer_proof_account = Keypair::new();
+    let auditor_proof_accounts = [
+        Keypair::new(),
+        Keypair::new(),
+        Keypair::new(),
+        Keypair::new(),
+    ];
+    let context_state_authority = Keypair::new();
+
+    token
+        .confidential_transfer_transfer_with_additional_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &transfer_proof_account,
+            &auditor_proof_accounts
+                .iter()
+                .map(|account| account as &dyn Signer)
+                .collect::<Vec<_>>(),
+            &context_state_authority.pubkey(),
+            transfer_amount,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // each additional auditor recovers the transfer amount from its proof context state accounts
+    for (auditor_elgamal_keypair, auditor_proof_accounts) in additional_auditor_elgamal_keypairs
+        .iter()
+        .zip(auditor_proof_accounts.chunks(2))
+    {
+        let mut auditor_ciphertexts = vec![];
+        for auditor_proof_account in auditor_proof_accounts {
+            let account = token
+                .get_account(auditor_proof_account.pubkey())
+                .await
+                .unwrap();
+            let context_state = pod_from_bytes::<
+                ProofContextState<CiphertextCiphertextEqualityProofContext>,
+            >(&account.data)
+            .unwrap();
+            auditor_ciphertexts.push(context_state.proof_context.destination_ciphertext);
+        }
+
+        let auditor_transfer_amount = decrypt_transfer_amount_for_additional_auditor(
+            auditor_elgamal_keypair.secret(),
+            &auditor_ciphertexts[0],
+            &auditor_ciphertexts[1],
+        )
+        .unwrap();
+        assert_eq!(auditor_transfer_amount, transfer_amount);
+    }
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 1,
+                pending_balance_hi: 1,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_split_proofs() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_tokens(&token, &bob, &mint_authority, 0, decimals)
+            .await;
+
+    let equality_proof_account = Keypair::new();
+    let ciphertext_validity_proof_account = Keypair::new();
+    let range_proof_account = Keypair::new();
+    let source_amount_lo_proof_account = Keypair::new();
+    let source_amount_hi_proof_account = Keypair::new();
+    let context_state_authority = Keypair::new();
+
+    token
+        .confidential_transfer_transfer_with_split_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &equality_proof_account,
+            &ciphertext_validity_proof_account,
+            &range_proof_account,
+            &source_amount_lo_proof_account,
+            &source_amount_hi_proof_account,
+            &context_state_authority.pubkey(),
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_split_validity_proofs() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    // the transfer amount has both low and high bits set
+    let transfer_amount = (1 << 16) + 42;
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        transfer_amount + 1,
+        decimals,
+    )
+    .await;
+
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_tokens(&token, &bob, &mint_authority, 0, decimals)
+            .await;
+
+    let equality_proof_account = Keypair::new();
+    let range_proof_account = Keypair::new();
+    let source_amount_lo_proof_account = Keypair::new();
+    let source_amount_hi_proof_account = Keypair::new();
+    let context_state_authority = Keypair::new();
+
+    token
+        .confidential_transfer_transfer_with_split_validity_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &equality_proof_account,
+            &range_proof_account,
+            &source_amount_lo_proof_account,
+            &source_amount_hi_proof_account,
+            &context_state_authority.pubkey(),
+            transfer_amount,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 1,
+                decryptable_available_balance: 1,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 1,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_split_validity_proofs_and_fee() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let withdraw_withheld_authority_elgamal_keypair = ElGamalKeypair::new_rand();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(authority.pubkey()),
+                withdraw_withheld_authority: Some(authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialTransferFeeConfig {
+                authority: Some(authority.pubkey()),
+                withdraw_withheld_authority_elgamal_pubkey:
+                    (*withdraw_withheld_authority_elgamal_keypair.pubkey()).into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let context_state_authority = Keypair::new();
+
+    // a self-transfer does not incur a fee, so it can be processed with split proofs
+    token
+        .confidential_transfer_transfer_with_split_validity_proofs(
+            &alice_meta.token_account,
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            alice_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    // a transfer to another account incurs a fee, which the split proofs do not certify
+    let err = token
+        .confidential_transfer_transfer_with_split_validity_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &context_state_authority.pubkey(),
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_with_maximum_transfer_amount() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let maximum_transfer_amount = 100;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialTransferMaximumTransferAmount {
+                maximum_transfer_amount,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        maximum_transfer_amount + 1,
+        decimals,
+    )
+    .await;
+
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // a transfer without the proofs for the maximum transfer amount is rejected
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            1,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferAdditionalProofsRequired as u32
+                )
+            )
+        )))
+    );
+
+    // the client refuses to transfer more than the maximum transfer amount
+    let err = token
+        .confidential_transfer_transfer_with_additional_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &[&Keypair::new(), &Keypair::new()],
+            &alice.pubkey(),
+            maximum_transfer_amount + 1,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Program(TokenError::ConfidentialTransferMaximumAmountExceeded.into())
+    );
+
+    // proofs generated against a higher maximum transfer amount are rejected by the program
+    {
+        let account_info = token
+            .get_account_info(&alice_meta.token_account)
+            .await
+            .unwrap()
+            .get_extension::<ConfidentialTransferAccount>()
+            .unwrap()
+            .transfer_account_info();
+
+        let transfer_amount = maximum_transfer_amount + 1;
+        let proof_data = account_info
+            .generate_transfer_proof_data(
+                transfer_amount,
+                &alice_meta.elgamal_keypair,
+                &alice_meta.aes_key,
+                bob_meta.elgamal_keypair.pubkey(),
+                Some(auditor_elgamal_keypair.pubkey()),
+            )
+            .unwrap();
+        let MaximumTransferAmountProofData {
+            equality_proof_data,
+            range_proof_data,
+        } = generate_maximum_transfer_amount_proof_data(
+            &proof_data,
+            transfer_amount,
+            transfer_amount,
+            &alice_meta.elgamal_keypair,
+        )
+        .unwrap();
+
+        let transfer_proof_account = Keypair::new();
+        let equality_proof_account = Keypair::new();
+        let range_proof_account = Keypair::new();
+        let context_state_authority = Keypair::new();
+
+        for (context_state_account, space, verify_proof_instruction) in [
+            (
+                &transfer_proof_account,
+                size_of::<ProofContextState<TransferProofContext>>(),
+                ProofInstruction::VerifyTransfer.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &transfer_proof_account.pubkey(),
+                        context_state_authority: &context_state_authority.pubkey(),
+                    }),
+                    &proof_data,
+                ),
+            ),
+            (
+                &equality_proof_account,
+                size_of::<ProofContextState<CiphertextCommitmentEqualityProofContext>>(),
+                ProofInstruction::VerifyCiphertextCommitmentEquality.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &equality_proof_account.pubkey(),
+                        context_state_authority: &context_state_authority.pubkey(),
+                    }),
+                    &equality_proof_data,
+                ),
+            ),
+            (
+                &range_proof_account,
+                size_of::<ProofContextState<BatchedRangeProofContext>>(),
+                ProofInstruction::VerifyBatchedRangeProofU64.encode_verify_proof(
+                    Some(ContextStateInfo {
+                        context_state_account: &range_proof_account.pubkey(),
+                        context_state_authority: &context_state_authority.pubkey(),
+                    }),
+                    &range_proof_data,
+                ),
+            ),
+        ] {
+            let mut ctx = context.context.lock().await;
+            let rent = ctx.banks_client.get_rent().await.unwrap();
+
+            let instructions = vec![
+                system_instruction::create_account(
+                    &ctx.payer.pubkey(),
+                    &context_state_account.pubkey(),
+                    rent.minimum_balance(space),
+                    space as u64,
+                    &zk_token_proof_program::id(),
+                ),
+                verify_proof_instruction,
+            ];
+
+            let tx = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&ctx.payer.pubkey()),
+                &[&ctx.payer, context_state_account],
+                ctx.last_blockhash,
+            );
+            ctx.banks_client.process_transaction(tx).await.unwrap();
+        }
+
+        let new_decryptable_available_balance = account_info
+            .new_decryptable_available_balance(transfer_amount, &alice_meta.aes_key)
+            .unwrap();
+
+        let err = token
+            .process_ixs(
+                &[
+                    confidential_transfer::instruction::inner_transfer_with_additional_proofs(
+                        &spl_token_2022::id(),
+                        &alice_meta.token_account,
+                        &bob_meta.token_account,
+                        token.get_address(),
+                        new_decryptable_available_balance.into(),
+                        &alice.pubkey(),
+                        &[],
+                        ProofLocation::ContextStateAccount(&transfer_proof_account.pubkey()),
+                        &[(
+                            equality_proof_account.pubkey(),
+                            range_proof_account.pubkey(),
+                        )],
+                    )
+                    .unwrap(),
+                ],
+                &[&alice],
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err,
+            TokenClientError::Client(Box::new(TransportError::TransactionError(
+                TransactionError::InstructionError(
+                    0,
+                    InstructionError::Custom(
+                        TokenError::ConfidentialTransferAdditionalProofMismatch as u32
+                    )
+                )
+            )))
+        );
+    }
+
+    // a transfer of exactly the maximum transfer amount succeeds
+    token
+        .confidential_transfer_transfer_with_additional_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &[&Keypair::new(), &Keypair::new()],
+            &alice.pubkey(),
+            maximum_transfer_amount,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the maximum transfer amount can be lowered by the confidential transfer mint authority
+    token
+        .confidential_transfer_update_maximum_transfer_amount(&authority.pubkey(), 1, &[&authority])
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_transfer_with_additional_proofs(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &[&Keypair::new(), &Keypair::new()],
+            &alice.pubkey(),
+            1,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: maximum_transfer_amount + 1,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_pull_transfer() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // alice generates the transfer proof and authorizes bob to pull the transfer
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .transfer_account_info();
+    let proof_data = build_transfer_proof_data(
+        &account_info,
+        42,
+        &alice_meta.elgamal_keypair,
+        &alice_meta.aes_key,
+        bob_meta.elgamal_keypair.pubkey(),
+        Some(auditor_elgamal_keypair.pubkey()),
+    )
+    .unwrap();
+
+    token
+        .confidential_transfer_approve_pull_transfer(
+            &alice_meta.token_account,
+            &bob.pubkey(),
+            &alice.pubkey(),
+            &proof_data,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // a puller that was not authorized cannot pull the transfer
+    let mallory = Keypair::new();
+    let err = token
+        .confidential_transfer_pull_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &mallory.pubkey(),
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            &[&mallory],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ConfidentialTransferPullNotAuthorized as u32)
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_pull_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    // the pull leaves the decryptable available balance for alice to update
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+    token
+        .confidential_transfer_repair_decryptable_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    // the authorization is cleared after a single pull
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let pull_authorization = state
+        .get_extension::<ConfidentialTransferPullAuthorization>()
+        .unwrap();
+    assert_eq!(Option::<Pubkey>::from(pull_authorization.puller), None);
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_pull_transfer_stale_decryptable_balance() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        50,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .transfer_account_info();
+    let proof_data = build_transfer_proof_data(
+        &account_info,
+        42,
+        &alice_meta.elgamal_keypair,
+        &alice_meta.aes_key,
+        bob_meta.elgamal_keypair.pubkey(),
+        None,
+    )
+    .unwrap();
+
+    token
+        .confidential_transfer_approve_pull_transfer(
+            &alice_meta.token_account,
+            &bob.pubkey(),
+            &alice.pubkey(),
+            &proof_data,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_pull_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    // alice cannot transfer out of the account until she updates the decryptable balance
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            8,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferDecryptableBalanceStale as u32
+                )
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_repair_decryptable_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            8,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 50,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_pull_transfer_with_additional_auditors() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialTransferAuditors {
+                auditor_elgamal_pubkeys: vec![(*ElGamalKeypair::new_rand().pubkey()).into()],
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .transfer_account_info();
+    let proof_data = build_transfer_proof_data(
+        &account_info,
+        42,
+        &alice_meta.elgamal_keypair,
+        &alice_meta.aes_key,
+        bob_meta.elgamal_keypair.pubkey(),
+        Some(auditor_elgamal_keypair.pubkey()),
+    )
+    .unwrap();
+
+    token
+        .confidential_transfer_approve_pull_transfer(
+            &alice_meta.token_account,
+            &bob.pubkey(),
+            &alice.pubkey(),
+            &proof_data,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // a pull transfer cannot carry the proofs for the additional auditors of the mint
+    let err = token
+        .confidential_transfer_pull_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferAdditionalProofsRequired as u32
+                )
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_apply_pending_balance_frozen() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_freezing_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        freeze_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let freeze_authority = freeze_authority.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .freeze(
+            &alice_meta.token_account,
+            &freeze_authority.pubkey(),
+            &[&freeze_authority],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::AccountFrozen as u32),
+            )
+        )))
+    );
+
+    // the pending balance is left untouched
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_preflight_memo() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_required_memo_transfers(&token, &bob).await;
+
+    let account_info = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .transfer_account_info();
+    let proof_data = build_transfer_proof_data(
+        &account_info,
+        42,
+        &alice_meta.elgamal_keypair,
+        &alice_meta.aes_key,
+        bob_meta.elgamal_keypair.pubkey(),
+        Some(auditor_elgamal_keypair.pubkey()),
+    )
+    .unwrap();
+    let new_decryptable_available_balance = account_info
+        .new_decryptable_available_balance(42, &alice_meta.aes_key)
+        .unwrap();
+
+    let instructions = confidential_transfer::instruction::transfer(
+        &spl_token_2022::id(),
+        &alice_meta.token_account,
+        &bob_meta.token_account,
+        token.get_address(),
+        new_decryptable_available_balance,
+        &alice.pubkey(),
+        &[],
+        ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+    )
+    .unwrap();
+
+    // the transfer is caught before submission without a memo
+    let err = token
+        .confidential_transfer_preflight_memo(&instructions)
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::MissingMemo(bob_meta.token_account));
+
+    // a memo set on the token is taken into account
+    token
+        .with_memo("🦖", vec![alice.pubkey()])
+        .confidential_transfer_preflight_memo(&instructions)
+        .await
+        .unwrap();
+
+    token.process_ixs(&instructions, &[&alice]).await.unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_deposit_and_apply() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            100,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    // a regular deposit is held in the pending balance
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            58,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_deposit_and_apply(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            None,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert_eq!(state.base.amount, 0);
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(u64::from(extension.pending_balance_credit_counter), 1);
+
+    // the deposit goes straight to the available balance, leaving the pending balance untouched
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 58,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_associated_confidential_account() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token, alice, bob, ..
+    } = context.token_context.take().unwrap();
+
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token: other_token, ..
+    } = context.token_context.take().unwrap();
+
+    // the address only depends on the owner, the mint, and the token program
+    let address = token.get_associated_confidential_account_address(&alice.pubkey());
+    assert_eq!(
+        address,
+        token.get_associated_confidential_account_address(&alice.pubkey())
+    );
+    assert_eq!(
+        address,
+        Pubkey::create_with_seed(
+            &alice.pubkey(),
+            &token.get_address().to_string()[..32],
+            &spl_token_2022::id(),
+        )
+        .unwrap()
+    );
+    assert_ne!(
+        address,
+        token.get_associated_confidential_account_address(&bob.pubkey())
+    );
+    assert_ne!(
+        address,
+        other_token.get_associated_confidential_account_address(&alice.pubkey())
+    );
+    assert_ne!(address, token.get_associated_token_address(&alice.pubkey()));
+
+    let elgamal_keypair = ElGamalKeypair::new_from_signer(&alice, &address.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &address.to_bytes()).unwrap();
+    token
+        .create_associated_confidential_account(
+            &alice.pubkey(),
+            None,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_account_info(&address).await.unwrap();
+    assert_eq!(state.base.owner, alice.pubkey());
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert!(bool::from(&extension.approved));
+    assert_eq!(extension.elgamal_pubkey, (*elgamal_keypair.pubkey()).into());
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_mint_mismatch() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token: other_token, ..
+    } = context.token_context.take().unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&other_token, &bob).await;
+
+    // the destination belongs to the other mint
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::AccountInvalidMint);
+
+    // the source belongs to this mint, but not to the mint of `other_token`
+    let err = other_token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::AccountInvalidMint);
+
+    let err = other_token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::AccountInvalidMint);
+
+    // nothing was sent
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_atomic_swap() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token: counterparty_token,
+        mint_authority: counterparty_mint_authority,
+        ..
+    } = context.token_context.take().unwrap();
+
+    // alice sells tokens of the first mint to bob for tokens of the second mint
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let alice_counterparty_meta =
+        ConfidentialTokenAccountMeta::new(&counterparty_token, &alice).await;
+    let bob_counterparty_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &counterparty_token,
+        &bob,
+        &counterparty_mint_authority,
+        17,
+        decimals,
+    )
+    .await;
+
+    // the swap fails as a whole if the second leg fails
+    counterparty_token
+        .confidential_transfer_disable_confidential_credits(
+            &alice_counterparty_meta.token_account,
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_atomic_swap(
+            &counterparty_token,
+            ConfidentialSwapLeg {
+                source_account: &alice_meta.token_account,
+                destination_account: &bob_meta.token_account,
+                source_authority: &alice.pubkey(),
+                transfer_amount: 42,
+                source_elgamal_keypair: &alice_meta.elgamal_keypair,
+                source_aes_key: &alice_meta.aes_key,
+                destination_elgamal_pubkey: bob_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_pubkey: None,
+                proof_account: &Keypair::new(),
+            },
+            ConfidentialSwapLeg {
+                source_account: &bob_counterparty_meta.token_account,
+                destination_account: &alice_counterparty_meta.token_account,
+                source_authority: &bob.pubkey(),
+                transfer_amount: 17,
+                source_elgamal_keypair: &bob_counterparty_meta.elgamal_keypair,
+                source_aes_key: &bob_counterparty_meta.aes_key,
+                destination_elgamal_pubkey: alice_counterparty_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_pubkey: None,
+                proof_account: &Keypair::new(),
+            },
+            &alice.pubkey(),
+            &[&alice, &bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferDepositsAndTransfersDisabled as u32
+                )
+            )
+        )))
+    );
+
+    // the first leg is rolled back
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    counterparty_token
+        .confidential_transfer_enable_confidential_credits(
+            &alice_counterparty_meta.token_account,
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_atomic_swap(
+            &counterparty_token,
+            ConfidentialSwapLeg {
+                source_account: &alice_meta.token_account,
+                destination_account: &bob_meta.token_account,
+                source_authority: &alice.pubkey(),
+                transfer_amount: 42,
+                source_elgamal_keypair: &alice_meta.elgamal_keypair,
+                source_aes_key: &alice_meta.aes_key,
+                destination_elgamal_pubkey: bob_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_pubkey: None,
+                proof_account: &Keypair::new(),
+            },
+            ConfidentialSwapLeg {
+                source_account: &bob_counterparty_meta.token_account,
+                destination_account: &alice_counterparty_meta.token_account,
+                source_authority: &bob.pubkey(),
+                transfer_amount: 17,
+                source_elgamal_keypair: &bob_counterparty_meta.elgamal_keypair,
+                source_aes_key: &bob_counterparty_meta.aes_key,
+                destination_elgamal_pubkey: alice_counterparty_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_pubkey: None,
+                proof_account: &Keypair::new(),
+            },
+            &alice.pubkey(),
+            &[&alice, &bob],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_counterparty_meta
+        .check_balances(
+            &counterparty_token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    alice_counterparty_meta
+        .check_balances(
+            &counterparty_token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 17,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_atomic_swap_with_additional_auditors() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+            ExtensionInitializationParams::ConfidentialTransferAuditors {
+                auditor_elgamal_pubkeys: vec![(*ElGamalKeypair::new_rand().pubkey()).into()],
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+    let TokenContext {
+        token: counterparty_token,
+        mint_authority: counterparty_mint_authority,
+        ..
+    } = context.token_context.take().unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let alice_counterparty_meta =
+        ConfidentialTokenAccountMeta::new(&counterparty_token, &alice).await;
+    let bob_counterparty_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &counterparty_token,
+        &bob,
+        &counterparty_mint_authority,
+        17,
+        decimals,
+    )
+    .await;
+
+    // a swap leg cannot carry the proofs for the additional auditors of its mint
+    let err = token
+        .confidential_atomic_swap(
+            &counterparty_token,
+            ConfidentialSwapLeg {
+                source_account: &alice_meta.token_account,
+                destination_account: &bob_meta.token_account,
+                source_authority: &alice.pubkey(),
+                transfer_amount: 42,
+                source_elgamal_keypair: &alice_meta.elgamal_keypair,
+                source_aes_key: &alice_meta.aes_key,
+                destination_elgamal_pubkey: bob_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_pubkey: None,
+                proof_account: &Keypair::new(),
+            },
+            ConfidentialSwapLeg {
+                source_account: &bob_counterparty_meta.token_account,
+                destination_account: &alice_counterparty_meta.token_account,
+                source_authority: &bob.pubkey(),
+                transfer_amount: 17,
+                source_elgamal_keypair: &bob_counterparty_meta.elgamal_keypair,
+                source_aes_key: &bob_counterparty_meta.aes_key,
+                destination_elgamal_pubkey: alice_counterparty_meta.elgamal_keypair.pubkey(),
+                auditor_elgamal_pubkey: None,
+                proof_account: &Keypair::new(),
+            },
+            &alice.pubkey(),
+            &[&alice, &bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::ConfidentialTransferAdditionalProofsRequired as u32
+                )
+            )
+        )))
+    );
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_retry_on_balance_mismatch() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+    let token = token.with_retry_on_balance_mismatch(true);
+
+    // credit alice's pending balance so that applying it changes her available balance
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            20,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the available balance changes after the account information is fetched
+    let extension = *token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the transfer is retried with the current available balance
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            Some(extension.transfer_account_info()),
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+
+    // the withdrawal fails with a stale available balance once the retry is disabled
+    let token = token.with_retry_on_balance_mismatch(false);
+    let extension = *token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap()
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            alice_meta.elgamal_keypair.secret(),
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let err = token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            decimals,
+            Some(extension.withdraw_account_info()),
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ConfidentialTransferBalanceMismatch as u32)
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_account_summary() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let summary = token
+        .confidential_transfer_account_summary(
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        AccountSummary {
+            available: 42,
+            pending: 0,
+            needs_apply: false,
+            credits_enabled: true,
+            non_confidential_credits_enabled: true,
+        }
+    );
+
+    // a deposit larger than the low bits of the pending balance must be applied
+    let deposit_amount = (1 << 16) + 7;
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            deposit_amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            deposit_amount,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_disable_confidential_credits(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_disable_non_confidential_credits(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let summary = token
+        .confidential_transfer_account_summary(
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        summary,
+        AccountSummary {
+            available: 42,
+            pending: deposit_amount,
+            needs_apply: true,
+            credits_enabled: false,
+            non_confidential_credits_enabled: false,
+        }
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_compute_budget() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            10,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    // a static compute unit limit is prepended to confidential transfer transactions
+    let token = token.with_compute_unit_limit(1_000);
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            10,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(1, InstructionError::ComputationalBudgetExceeded)
+        )))
+    );
+
+    // a user-supplied compute budget instruction is respected
+    token
+        .process_ixs(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(
+                    token.confidential_transfer_estimate_compute_units(ConfidentialOp::Deposit),
+                ),
+                confidential_transfer::instruction::deposit(
+                    &spl_token_2022::id(),
+                    &alice_meta.token_account,
+                    token.get_address(),
+                    10,
+                    decimals,
+                    &alice.pubkey(),
+                    &[],
+                )
+                .unwrap(),
+            ],
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // the automatic compute budget covers the operations in the transaction
+    let token = token.with_auto_compute_budget();
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_empty_account_full() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_tokens(&token, &bob, &mint_authority, 42, decimals)
+            .await;
+
+    // a transfer of 0 tokens leaves a residual pending balance ciphertext that encrypts 0
+    token
+        .confidential_transfer_transfer(
+            &bob_meta.token_account,
+            &alice_meta.token_account,
+            &bob.pubkey(),
+            None,
+            0,
+            None,
+            &bob_meta.elgamal_keypair,
+            &bob_meta.aes_key,
+            alice_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    // emptying only the available balance does not make the account closable
+    let err = token
+        .confidential_transfer_empty_account(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::ConfidentialTransferAccountHasBalance as u32)
+            )
+        )))
+    );
+
+    token
+        .confidential_transfer_empty_account_full(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            &alice_meta.elgamal_keypair,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(u64::from(extension.pending_balance_credit_counter), 0);
+
+    token
+        .close_account(
+            &alice_meta.token_account,
+            &Pubkey::new_unique(),
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_available_balance_changed() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let cached_available_balance = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap()
+        .available_balance;
+
+    assert!(!token
+        .confidential_transfer_available_balance_changed(
+            &alice_meta.token_account,
+            &cached_available_balance,
+        )
+        .await
+        .unwrap());
+
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    assert!(token
+        .confidential_transfer_available_balance_changed(
+            &alice_meta.token_account,
+            &cached_available_balance,
+        )
+        .await
+        .unwrap());
+}
+
+#[tokio::test]
+async fn confidential_transfer_configure_token_account_with_required_memo() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+
+    // the account is created without extension space and reallocated on configuration
+    let alice_meta =
+        ConfidentialTokenAccountMeta::new_with_required_memo_transfers(&token, &alice).await;
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    assert!(bool::from(
+        state
+            .get_extension::<MemoTransfer>()
+            .unwrap()
+            .require_incoming_transfer_memos
+    ));
+    assert!(state.get_extension::<ConfidentialTransferAccount>().is_ok());
+
+    let account = token.get_account(alice_meta.token_account).await.unwrap();
+    assert_eq!(
+        account.data.len(),
+        ExtensionType::try_calculate_account_len::<Account>(&[
+            ExtensionType::ConfidentialTransferAccount,
+            ExtensionType::MemoTransfer,
+        ])
+        .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_current_epoch_fee() {
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![ExtensionInitializationParams::TransferFeeConfig {
+            transfer_fee_config_authority: Some(Pubkey::new_unique()),
+            withdraw_withheld_authority: Some(Pubkey::new_unique()),
+            transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+            maximum_fee: TEST_MAXIMUM_FEE,
+        }])
+        .await
+        .unwrap();
+
+    let TokenContext { token, .. } = context.token_context.unwrap();
+
+    let transfer_fee = token
+        .confidential_transfer_current_epoch_fee()
+        .await
+        .unwrap();
+    assert_eq!(
+        u16::from(transfer_fee.transfer_fee_basis_points),
+        TEST_FEE_BASIS_POINTS
+    );
+    assert_eq!(u64::from(transfer_fee.maximum_fee), TEST_MAXIMUM_FEE);
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_send_without_fee() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    token
+        .confidential_transfer_send(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            42,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 42,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_send_with_fee() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let auditor_elgamal_pubkey = (*auditor_elgamal_keypair.pubkey()).into();
+    let withdraw_withheld_authority_elgamal_keypair = ElGamalKeypair::new_rand();
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(authority.pubkey()),
+                withdraw_withheld_authority: Some(authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+            },
+            ExtensionInitializationParams::ConfidentialTransferFeeConfig {
+                authority: Some(authority.pubkey()),
+                withdraw_withheld_authority_elgamal_pubkey:
+                    (*withdraw_withheld_authority_elgamal_keypair.pubkey()).into(),
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        100,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // a self-transfer does not incur a fee and goes through the transfer without fee path
+    token
+        .confidential_transfer_send(
+            &alice_meta.token_account,
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            0,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            alice_meta.elgamal_keypair.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_send(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            100,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    // 2.5% of 100 rounded up is withheld in the destination account
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 97,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_transfer_batch() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        100,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // the batch exceeds the available balance, so nothing is sent
+    let err = token
+        .confidential_transfer_transfer_batch(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &[
+                (
+                    bob_meta.token_account,
+                    60,
+                    *bob_meta.elgamal_keypair.pubkey(),
+                ),
+                (
+                    bob_meta.token_account,
+                    60,
+                    *bob_meta.elgamal_keypair.pubkey(),
+                ),
+            ],
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::InsufficientConfidentialFunds {
+            requested: 120,
+            available: 100,
+        }
+    );
+
+    // each proof is generated against the balance left by the transfer before it, and the
+    // self-transfer in the middle leaves the available balance unchanged
+    let outputs = token
+        .confidential_transfer_transfer_batch(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &[
+                (
+                    bob_meta.token_account,
+                    10,
+                    *bob_meta.elgamal_keypair.pubkey(),
+                ),
+                (
+                    alice_meta.token_account,
+                    5,
+                    *alice_meta.elgamal_keypair.pubkey(),
+                ),
+                (
+                    bob_meta.token_account,
+                    20,
+                    *bob_meta.elgamal_keypair.pubkey(),
+                ),
+            ],
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    assert_eq!(outputs.len(), 3);
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 70,
+                decryptable_available_balance: 70,
+            },
+        )
+        .await;
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 30,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_repair_decryptable_balance() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    // corrupt the decryptable available balance, which leaves the available balance untouched
+    token
+        .process_ixs(
+            &[
+                confidential_transfer::instruction::update_decryptable_balance(
+                    &spl_token_2022::id(),
+                    &alice_meta.token_account,
+                    &alice.pubkey(),
+                    &[],
+                    alice_meta.aes_key.encrypt(7).into(),
+                )
+                .unwrap(),
+            ],
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 7,
+            },
+        )
+        .await;
+
+    token
+        .confidential_transfer_repair_decryptable_balance(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_rotate_aes_key() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let mut alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    // the old key must decrypt the current decryptable available balance
+    let new_aes_key = AeKey::new_rand();
+    let err = token
+        .confidential_transfer_rotate_aes_key(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &alice_meta.elgamal_keypair,
+            &new_aes_key,
+            &new_aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::AeBalanceDecryption);
+
+    token
+        .confidential_transfer_rotate_aes_key(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &new_aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    let decryptable_available_balance =
+        DecryptableBalance::try_from(extension.decryptable_available_balance).unwrap();
+    assert_eq!(
+        decryptable_available_balance.decrypt(&alice_meta.aes_key),
+        None
+    );
+    assert_eq!(
+        decryptable_available_balance.decrypt(&new_aes_key),
+        Some(42)
+    );
+
+    // the account keeps working with the new key
+    alice_meta.aes_key = new_aes_key;
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            42,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_non_transferable_mint() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::NonTransferable,
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    // confidential deposits are rejected with the same error as transfers
+    let err = token
+        .confidential_transfer_deposit(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NonTransferable as u32)
+            )
+        )))
+    );
+
+    let err = token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            0,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::NonTransferable as u32)
+            )
+        )))
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_prove_reserves() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta =
+        ConfidentialTokenAccountMeta::new_with_tokens(&token, &bob, &mint_authority, 8, decimals)
+            .await;
+
+    let prover_accounts = [
+        (
+            alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        ),
+        (
+            bob_meta.token_account,
+            &bob_meta.elgamal_keypair,
+            &bob_meta.aes_key,
+        ),
+    ];
+    let verifier_accounts = [alice_meta.token_account, bob_meta.token_account];
+
+    // total of 50 is above the threshold
+    let attestation = token
+        .confidential_transfer_prove_reserves(&prover_accounts, 45)
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_verify_reserves(&verifier_accounts, &attestation)
+        .await
+        .unwrap();
+
+    // total of 50 is exactly the threshold
+    let attestation = token
+        .confidential_transfer_prove_reserves(&prover_accounts, 50)
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_verify_reserves(&verifier_accounts, &attestation)
+        .await
+        .unwrap();
+
+    // total of 50 is below the threshold
+    let err = token
+        .confidential_transfer_prove_reserves(&prover_accounts, 51)
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::NotEnoughFunds);
+
+    // raising the threshold of a valid attestation invalidates it
+    let mut attestation = token
+        .confidential_transfer_prove_reserves(&prover_accounts, 45)
+        .await
+        .unwrap();
+    attestation.threshold = 51;
+    let err = token
+        .confidential_transfer_verify_reserves(&verifier_accounts, &attestation)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Program(TokenError::ConfidentialTransferBalanceMismatch.into())
+    );
+
+    // the attestation only holds for the balances it was created against
+    let attestation = token
+        .confidential_transfer_prove_reserves(&prover_accounts, 45)
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    let err = token
+        .confidential_transfer_verify_reserves(&verifier_accounts, &attestation)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Program(TokenError::ConfidentialTransferBalanceMismatch.into())
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_await_credit() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // no credit arrives
+    let err = token
+        .confidential_transfer_await_credit(&bob_meta.token_account, 0, Duration::from_millis(0))
+        .await
+        .unwrap_err();
+    match err {
+        TokenClientError::Client(err) => assert!(err.to_string().contains("No credit")),
+        _ => panic!("unexpected error: {:?}", err),
+    }
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let counter = token
+        .confidential_transfer_await_credit(&bob_meta.token_account, 0, Duration::from_secs(5))
+        .await
+        .unwrap();
+    assert_eq!(counter, 1);
+
+    // a credit that was already observed does not count again
+    let err = token
+        .confidential_transfer_await_credit(
+            &bob_meta.token_account,
+            counter,
+            Duration::from_millis(0),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, TokenClientError::Client(_)));
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_proof_context_with_separate_authority() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+
+    // the payer of the token client relays the proof, but alice controls the context state
+    let context_state_account = Keypair::new();
+    token
+        .confidential_transfer_create_configure_account_proof_context(
+            &context_state_account,
+            &alice.pubkey(),
+            &elgamal_keypair,
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // only the designated authority can close the context state
+    let lamport_destination = Pubkey::new_unique();
+    token
+        .confidential_transfer_close_context_state(
+            &context_state_account.pubkey(),
+            &lamport_destination,
+            &bob.pubkey(),
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    token
+        .confidential_transfer_close_context_state(
+            &context_state_account.pubkey(),
+            &lamport_destination,
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    {
+        let mut ctx = context.context.lock().await;
+        assert!(ctx
+            .banks_client
+            .get_account(context_state_account.pubkey())
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    // the same applies to the proof of a withdrawal
+    token
+        .mint_to(
+            &token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(&token_account, &alice.pubkey(), 42, decimals, &[&alice])
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &token_account,
+            &alice.pubkey(),
+            None,
+            elgamal_keypair.secret(),
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let context_state_account = Keypair::new();
+    token
+        .confidential_transfer_create_withdraw_proof_context(
+            &token_account,
+            &context_state_account,
+            &alice.pubkey(),
+            10,
+            None,
+            &elgamal_keypair,
+            &aes_key,
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_withdraw(
+            &token_account,
+            &alice.pubkey(),
+            Some(&context_state_account.pubkey()),
+            10,
+            decimals,
+            None,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_close_context_state(
+            &context_state_account.pubkey(),
+            &lamport_destination,
+            &bob.pubkey(),
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    token
+        .confidential_transfer_close_context_state(
+            &context_state_account.pubkey(),
+            &lamport_destination,
+            &alice.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_account_info(&token_account).await.unwrap();
+    assert_eq!(state.base.amount, 10);
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_get_balances_decryption_cache() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+
+    let token = token.with_decryption_cache(16);
+    assert_eq!(token.decryption_cache_len(), 0);
+
+    for _ in 0..2 {
+        let balances = token
+            .confidential_transfer_get_balances(
+                &alice_meta.token_account,
+                &alice_meta.elgamal_keypair,
+                &alice_meta.aes_key,
+            )
+            .await
+            .unwrap();
+        assert_eq!(balances.available_balance, 42);
+        assert_eq!(balances.pending_balance_lo, 0);
+        assert_eq!(balances.pending_balance_hi, 0);
+    }
+
+    // the applied pending balance ciphertexts are both zeroed and share an entry, and reading the
+    // balances again does not add any
+    assert_eq!(token.decryption_cache_len(), 2);
+
+    // a new ciphertext adds a cache entry
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            2,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    let balances = token
+        .confidential_transfer_get_balances(
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        )
+        .await
+        .unwrap();
+    assert_eq!(balances.available_balance, 40);
+    assert_eq!(token.decryption_cache_len(), 3);
+}
+
+#[cfg(feature = "zk-ops")]
+#[derive(Default)]
+struct RecordingMetricsSink {
+    events: Mutex<Vec<(&'static str, ConfidentialPhase, bool)>>,
+}
+
+#[cfg(feature = "zk-ops")]
+impl ConfidentialMetricsSink for RecordingMetricsSink {
+    fn after(
+        &self,
+        operation: &'static str,
+        phase: ConfidentialPhase,
+        _elapsed: Duration,
+        success: bool,
+    ) {
+        self.events
+            .lock()
+            .unwrap()
+            .push((operation, phase, success));
+    }
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_metrics_sink() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let sink = Arc::new(RecordingMetricsSink::default());
+    let token = token.with_metrics_sink(sink.clone());
+
+    token
+        .confidential_transfer_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let balances = token
+        .confidential_transfer_get_balances(
+            &alice_meta.token_account,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+        )
+        .await
+        .unwrap();
+    assert_eq!(balances.available_balance, 22);
+
+    // a failed submission is reported as unsuccessful
+    token
+        .confidential_transfer_withdraw(
+            &alice_meta.token_account,
+            &alice.pubkey(),
+            None,
+            10,
+            decimals,
+            None,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        *sink.events.lock().unwrap(),
+        vec![
+            ("transfer", ConfidentialPhase::ProofGeneration, true),
+            ("transfer", ConfidentialPhase::Submission, true),
+            ("withdraw", ConfidentialPhase::ProofGeneration, true),
+            ("withdraw", ConfidentialPhase::Submission, true),
+            ("get_balances", ConfidentialPhase::Decryption, true),
+            ("withdraw", ConfidentialPhase::ProofGeneration, true),
+            ("withdraw", ConfidentialPhase::Submission, false),
+        ]
+    );
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_configure_token_account_with_invalid_pubkey() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    // the zero secret key maps to the identity public key
+    let elgamal_keypair = ElGamalKeypair::from_bytes(&[0; 64]).unwrap();
+    assert!(!elgamal_keypair.pubkey().is_valid_for_proof());
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+
+    let err = token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err, TokenClientError::InvalidElGamalPubkey);
+
+    // nothing was sent, so the account is still unconfigured
+    let state = token.get_account_info(&token_account).await.unwrap();
+    assert!(state
+        .get_extension::<ConfidentialTransferAccount>()
+        .is_err());
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    assert!(elgamal_keypair.pubkey().is_valid_for_proof());
+    token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_configure_token_account_without_space() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+
+    // the account is created without room for the confidential transfer extension
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let proof_data =
+        confidential_transfer::instruction::PubkeyValidityData::new(&elgamal_keypair).unwrap();
+
+    let err = token
+        .process_ixs(
+            &confidential_transfer::instruction::configure_account(
+                &spl_token_2022::id(),
+                &token_account,
+                token.get_address(),
+                aes_key.encrypt(0),
+                65536,
+                &alice.pubkey(),
+                &[],
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )
+            .unwrap(),
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(
+                    TokenError::InsufficientConfidentialTransferAccountSpace as u32
+                ),
+            )
+        )))
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_configure_token_account_without_confidential_fee_config() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    // the mint charges transfer fees, but is not extended for confidential transfer fees
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: Some(authority.pubkey()),
+                withdraw_withheld_authority: Some(authority.pubkey()),
+                transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+                maximum_fee: TEST_MAXIMUM_FEE,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext { token, alice, .. } = context.token_context.unwrap();
+
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![
+                ExtensionType::ConfidentialTransferAccount,
+                ExtensionType::ConfidentialTransferFeeAmount,
+            ],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+
+    let err = token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenError::InvalidExtensionCombination as u32),
+            )
+        )))
+    );
+
+    let state = token.get_account_info(&token_account).await.unwrap();
+    assert!(state
+        .get_extension::<ConfidentialTransferAccount>()
+        .is_err());
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_simulate_transfer() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
+
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new_with_tokens(
+        &token,
+        &alice,
+        &mint_authority,
+        42,
+        decimals,
+    )
+    .await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    let simulation = token
+        .simulate_confidential_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            10,
+            &alice_meta.elgamal_keypair,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            Some(&bob_meta.elgamal_keypair),
+            None,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    assert_eq!(simulation.simulation.result, Some(Ok(())));
+    assert_eq!(simulation.source_available_balance, 32);
+    assert_eq!(simulation.destination_pending_balance, Some(10));
+
+    // nothing was submitted
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 42,
+                decryptable_available_balance: 42,
+            },
+        )
+        .await;
 
+    // the prediction matches the outcome of the transfer
     token
         .confidential_transfer_transfer(
             &alice_meta.token_account,
             &bob_meta.token_account,
             &alice.pubkey(),
-            Some(&context_state_account.pubkey()),
-            42,
+            None,
+            10,
             None,
             &alice_meta.elgamal_keypair,
             &alice_meta.aes_key,
             bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
+            None,
             &[&alice],
         )
         .await
         .unwrap();
 
-    // attempt to create an account with a wrong proof type context state
-    let context_state_account = Keypair::new();
-
-    {
-        let context_state_authority = Keypair::new();
-        let space = size_of::<ProofContextState<WithdrawProofContext>>();
-
-        let instruction_type = ProofInstruction::VerifyWithdraw;
-
-        let context_state_info = ContextStateInfo {
-            context_state_account: &context_state_account.pubkey(),
-            context_state_authority: &context_state_authority.pubkey(),
-        };
-
-        let state = token
-            .get_account_info(&alice_meta.token_account)
-            .await
-            .unwrap();
-        let extension = state
-            .get_extension::<ConfidentialTransferAccount>()
-            .unwrap();
-        let current_ciphertext = extension.available_balance.try_into().unwrap();
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 32,
+                decryptable_available_balance: 32,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 10,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
 
-        let proof_data = confidential_transfer::instruction::WithdrawData::new(
-            0,
+    // the destination balance is not predicted without its keypair
+    let simulation = token
+        .simulate_confidential_transfer(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            10,
             &alice_meta.elgamal_keypair,
-            0,
-            &current_ciphertext,
+            &alice_meta.aes_key,
+            bob_meta.elgamal_keypair.pubkey(),
+            None,
+            None,
+            &[&alice],
         )
+        .await
         .unwrap();
+    assert_eq!(simulation.source_available_balance, 22);
+    assert_eq!(simulation.destination_pending_balance, None);
+}
 
-        let mut ctx = context.context.lock().await;
-        let rent = ctx.banks_client.get_rent().await.unwrap();
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_forward_pending_balance() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = None;
 
-        let instructions = vec![
-            system_instruction::create_account(
-                &ctx.payer.pubkey(),
-                &context_state_account.pubkey(),
-                rent.minimum_balance(space),
-                space as u64,
-                &zk_token_proof_program::id(),
-            ),
-            instruction_type.encode_verify_proof(Some(context_state_info), &proof_data),
-        ];
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey,
+            },
+        ])
+        .await
+        .unwrap();
 
-        let tx = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&ctx.payer.pubkey()),
-            &[&ctx.payer, &context_state_account],
-            ctx.last_blockhash,
-        );
-        ctx.banks_client.process_transaction(tx).await.unwrap();
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+    let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+    // two credits, the first of which fills both the low and the high bits
+    token
+        .mint_to(
+            &alice_meta.token_account,
+            &mint_authority.pubkey(),
+            70_005,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    for amount in [70_000, 5] {
+        token
+            .confidential_transfer_deposit(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                amount,
+                decimals,
+                &[&alice],
+            )
+            .await
+            .unwrap();
     }
 
+    // the pending balance cannot be forwarded to the same account
     let err = token
-        .confidential_transfer_transfer(
+        .confidential_transfer_forward_pending(
+            &alice_meta.token_account,
             &alice_meta.token_account,
-            &bob_meta.token_account,
             &alice.pubkey(),
-            Some(&context_state_account.pubkey()),
-            0,
+            &Keypair::new(),
+            &Keypair::new(),
+            &alice.pubkey(),
             None,
             &alice_meta.elgamal_keypair,
-            &alice_meta.aes_key,
-            bob_meta.elgamal_keypair.pubkey(),
-            Some(auditor_elgamal_keypair.pubkey()),
+            alice_meta.elgamal_keypair.pubkey(),
             &[&alice],
         )
         .await
         .unwrap_err();
-
     assert_eq!(
         err,
         TokenClientError::Client(Box::new(TransportError::TransactionError(
-            TransactionError::InstructionError(0, InstructionError::InvalidArgument,)
+            TransactionError::InstructionError(0, InstructionError::InvalidArgument)
         )))
-    )
+    );
+
+    let forwarded_amount = token
+        .confidential_transfer_forward_pending(
+            &alice_meta.token_account,
+            &bob_meta.token_account,
+            &alice.pubkey(),
+            &Keypair::new(),
+            &Keypair::new(),
+            &alice.pubkey(),
+            None,
+            &alice_meta.elgamal_keypair,
+            bob_meta.elgamal_keypair.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    assert_eq!(forwarded_amount, 70_005);
+
+    alice_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 70_005 & 0xffff,
+                pending_balance_hi: 70_005 >> 16,
+                available_balance: 0,
+                decryptable_available_balance: 0,
+            },
+        )
+        .await;
+
+    // the forwarded credits count against the destination credit counter
+    let state = token
+        .get_account_info(&alice_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(u64::from(extension.pending_balance_credit_counter), 0);
+
+    let state = token
+        .get_account_info(&bob_meta.token_account)
+        .await
+        .unwrap();
+    let extension = state
+        .get_extension::<ConfidentialTransferAccount>()
+        .unwrap();
+    assert_eq!(u64::from(extension.pending_balance_credit_counter), 2);
+
+    token
+        .confidential_transfer_apply_pending_balance(
+            &bob_meta.token_account,
+            &bob.pubkey(),
+            None,
+            bob_meta.elgamal_keypair.secret(),
+            &bob_meta.aes_key,
+            &[&bob],
+        )
+        .await
+        .unwrap();
+
+    bob_meta
+        .check_balances(
+            &token,
+            ConfidentialTokenAccountBalances {
+                pending_balance_lo: 0,
+                pending_balance_hi: 0,
+                available_balance: 70_005,
+                decryptable_available_balance: 70_005,
+            },
+        )
+        .await;
+}
+
+#[cfg(feature = "zk-ops")]
+#[tokio::test]
+async fn confidential_transfer_forward_pending_balance_audited_or_capped() {
+    let authority = Keypair::new();
+    let auto_approve_new_accounts = true;
+    let auditor_elgamal_pubkey = (*ElGamalKeypair::new_rand().pubkey()).into();
+
+    for extension_init_params in [
+        vec![ExtensionInitializationParams::ConfidentialTransferMint {
+            authority: Some(authority.pubkey()),
+            auto_approve_new_accounts,
+            auditor_elgamal_pubkey: Some(auditor_elgamal_pubkey),
+        }],
+        vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+            ExtensionInitializationParams::ConfidentialTransferAuditors {
+                auditor_elgamal_pubkeys: vec![auditor_elgamal_pubkey],
+            },
+        ],
+        vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            },
+            ExtensionInitializationParams::ConfidentialTransferMaximumTransferAmount {
+                maximum_transfer_amount: 100,
+            },
+        ],
+    ] {
+        let mut context = TestContext::new().await;
+        context
+            .init_token_with_mint(extension_init_params)
+            .await
+            .unwrap();
+
+        let TokenContext {
+            token,
+            alice,
+            bob,
+            mint_authority,
+            decimals,
+            ..
+        } = context.token_context.unwrap();
+
+        let alice_meta = ConfidentialTokenAccountMeta::new(&token, &alice).await;
+        let bob_meta = ConfidentialTokenAccountMeta::new(&token, &bob).await;
+
+        token
+            .mint_to(
+                &alice_meta.token_account,
+                &mint_authority.pubkey(),
+                42,
+                &[&mint_authority],
+            )
+            .await
+            .unwrap();
+        token
+            .confidential_transfer_deposit(
+                &alice_meta.token_account,
+                &alice.pubkey(),
+                42,
+                decimals,
+                &[&alice],
+            )
+            .await
+            .unwrap();
+
+        // the forwarded amount would bypass the auditors and the maximum transfer amount
+        let err = token
+            .confidential_transfer_forward_pending(
+                &alice_meta.token_account,
+                &bob_meta.token_account,
+                &alice.pubkey(),
+                &Keypair::new(),
+                &Keypair::new(),
+                &alice.pubkey(),
+                None,
+                &alice_meta.elgamal_keypair,
+                bob_meta.elgamal_keypair.pubkey(),
+                &[&alice],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err,
+            TokenClientError::Client(Box::new(TransportError::TransactionError(
+                TransactionError::InstructionError(
+                    0,
+                    InstructionError::Custom(
+                        TokenError::ConfidentialTransferForwardNotSupported as u32
+                    )
+                )
+            )))
+        );
+
+        alice_meta
+            .check_balances(
+                &token,
+                ConfidentialTokenAccountBalances {
+                    pending_balance_lo: 42,
+                    pending_balance_hi: 0,
+                    available_balance: 0,
+                    decryptable_available_balance: 0,
+                },
+            )
+            .await;
+    }
 }
@@ -0,0 +1,374 @@
+#![cfg(feature = "test-sbf")]
+
+//! Compute unit benchmarks for the confidential transfer instructions
+//!
+//! Each confidential operation is submitted with the BPF build of the program and the compute
+//! units consumed by the whole transaction, including the proof instruction, are checked against
+//! the estimates of the token client. A regression in the ciphertext arithmetic or in proof
+//! decoding shows up as a failure here before it makes the client estimates too small. To accept
+//! an intentional increase, raise the corresponding `CONFIDENTIAL_*_COMPUTE_UNITS` constant of the
+//! client together with its documentation.
+//!
+//! The consumed compute units are printed, so `cargo test-sbf -- --nocapture` reports them for
+//! comparison across releases.
+
+mod program_test;
+use {
+    program_test::{keypair_clone, TestContext, TokenContext},
+    solana_program_test::{tokio, BanksClient, ProgramTest},
+    solana_sdk::{
+        pubkey::Pubkey, signature::Signer, signer::keypair::Keypair, transaction::Transaction,
+    },
+    spl_token_2022::{
+        extension::ExtensionType,
+        solana_zk_token_sdk::encryption::{auth_encryption::AeKey, elgamal::ElGamalKeypair},
+    },
+    spl_token_client::{
+        client::{
+            ProgramBanksClient, ProgramBanksClientProcessTransaction, ProgramClient,
+            ProgramClientResult, SendTransaction, SendTransactionBanksClient, SimulateTransaction,
+            SimulateTransactionBanksClient,
+        },
+        token::{
+            ExtensionInitializationParams, Token, CONFIDENTIAL_APPLY_PENDING_BALANCE_COMPUTE_UNITS,
+            CONFIDENTIAL_DEPOSIT_COMPUTE_UNITS, CONFIDENTIAL_TRANSFER_COMPUTE_UNITS,
+            CONFIDENTIAL_TRANSFER_WITH_FEE_COMPUTE_UNITS, CONFIDENTIAL_WITHDRAW_COMPUTE_UNITS,
+        },
+    },
+    std::{future::Future, pin::Pin, sync::Arc},
+};
+
+const TEST_MAXIMUM_FEE: u64 = 100;
+const TEST_FEE_BASIS_POINTS: u16 = 250;
+
+/// Compute unit limit of the benchmarked transactions, so that an operation that exceeds its
+/// estimate is reported with the units it consumed instead of failing the transaction
+const BENCHMARK_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Send transactions with `BanksClient::process_transaction_with_metadata` and return the compute
+/// units consumed by the transaction
+#[derive(Debug, Clone, Copy, Default)]
+struct ProgramBanksClientComputeUnits;
+
+impl SendTransaction for ProgramBanksClientComputeUnits {
+    type Output = u64;
+}
+
+impl SendTransactionBanksClient for ProgramBanksClientComputeUnits {
+    fn send<'a>(
+        &self,
+        client: &'a mut BanksClient,
+        transaction: Transaction,
+    ) -> Pin<Box<dyn Future<Output = ProgramClientResult<Self::Output>> + Send + 'a>> {
+        Box::pin(async move {
+            let result = client
+                .process_transaction_with_metadata(transaction)
+                .await?;
+            result.result?;
+            let metadata = result.metadata.ok_or("missing transaction metadata")?;
+            Ok(metadata.compute_units_consumed)
+        })
+    }
+}
+
+impl SimulateTransaction for ProgramBanksClientComputeUnits {
+    type SimulationOutput = ();
+}
+
+impl SimulateTransactionBanksClient for ProgramBanksClientComputeUnits {
+    fn simulate<'a>(
+        &self,
+        client: &'a mut BanksClient,
+        transaction: Transaction,
+    ) -> Pin<Box<dyn Future<Output = ProgramClientResult<Self::SimulationOutput>> + Send + 'a>>
+    {
+        Box::pin(async move {
+            client.simulate_transaction(transaction).await?;
+            Ok(())
+        })
+    }
+}
+
+async fn setup(extension_init_params: Vec<ExtensionInitializationParams>) -> TestContext {
+    // the compute units are only representative for the BPF build of the program
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program("spl_token_2022", spl_token_2022::id(), None);
+
+    let context = program_test.start_with_context().await;
+    let mut context = TestContext {
+        context: Arc::new(tokio::sync::Mutex::new(context)),
+        token_context: None,
+    };
+    context
+        .init_token_with_mint(extension_init_params)
+        .await
+        .unwrap();
+    context
+}
+
+/// A token client for the mint of `token` that returns the compute units of every transaction
+async fn metered_token(
+    context: &TestContext,
+    token: &Token<ProgramBanksClientProcessTransaction>,
+    decimals: u8,
+) -> Token<ProgramBanksClientComputeUnits> {
+    let payer = keypair_clone(&context.context.lock().await.payer);
+    let client: Arc<dyn ProgramClient<ProgramBanksClientComputeUnits>> =
+        Arc::new(ProgramBanksClient::new_from_context(
+            Arc::clone(&context.context),
+            ProgramBanksClientComputeUnits,
+        ));
+
+    Token::new(
+        client,
+        &spl_token_2022::id(),
+        token.get_address(),
+        Some(decimals),
+        Arc::new(payer),
+    )
+    .with_compute_unit_limit(BENCHMARK_COMPUTE_UNIT_LIMIT)
+}
+
+struct ConfidentialTokenAccount {
+    token_account: Pubkey,
+    elgamal_keypair: ElGamalKeypair,
+    aes_key: AeKey,
+}
+
+impl ConfidentialTokenAccount {
+    async fn new(token: &Token<ProgramBanksClientProcessTransaction>, owner: &Keypair) -> Self {
+        let token_account_keypair = Keypair::new();
+        token
+            .create_auxiliary_token_account_with_extension_space(
+                &token_account_keypair,
+                &owner.pubkey(),
+                vec![ExtensionType::ConfidentialTransferAccount],
+            )
+            .await
+            .unwrap();
+        let token_account = token_account_keypair.pubkey();
+
+        let elgamal_keypair =
+            ElGamalKeypair::new_from_signer(owner, &token_account.to_bytes()).unwrap();
+        let aes_key = AeKey::new_from_signer(owner, &token_account.to_bytes()).unwrap();
+
+        token
+            .confidential_transfer_configure_token_account(
+                &token_account,
+                &owner.pubkey(),
+                None,
+                None,
+                false,
+                &elgamal_keypair,
+                &aes_key,
+                &[owner],
+            )
+            .await
+            .unwrap();
+
+        Self {
+            token_account,
+            elgamal_keypair,
+            aes_key,
+        }
+    }
+}
+
+fn check_compute_units(operation: &str, consumed: u64, threshold: u32) {
+    println!("{operation}: {consumed} compute units (threshold {threshold})");
+    assert!(
+        consumed <= u64::from(threshold),
+        "{operation} consumed {consumed} compute units, above the threshold of {threshold}",
+    );
+}
+
+#[tokio::test]
+async fn confidential_transfer_compute_units() {
+    let authority = Keypair::new();
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+
+    let mut context = setup(vec![
+        ExtensionInitializationParams::ConfidentialTransferMint {
+            authority: Some(authority.pubkey()),
+            auto_approve_new_accounts: true,
+            auditor_elgamal_pubkey: Some((*auditor_elgamal_keypair.pubkey()).into()),
+        },
+    ])
+    .await;
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+    let metered_token = metered_token(&context, &token, decimals).await;
+
+    let alice_account = ConfidentialTokenAccount::new(&token, &alice).await;
+    let bob_account = ConfidentialTokenAccount::new(&token, &bob).await;
+
+    token
+        .mint_to(
+            &alice_account.token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    let consumed = metered_token
+        .confidential_transfer_deposit(
+            &alice_account.token_account,
+            &alice.pubkey(),
+            42,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    check_compute_units("deposit", consumed, CONFIDENTIAL_DEPOSIT_COMPUTE_UNITS);
+
+    let consumed = metered_token
+        .confidential_transfer_apply_pending_balance(
+            &alice_account.token_account,
+            &alice.pubkey(),
+            None,
+            alice_account.elgamal_keypair.secret(),
+            &alice_account.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    check_compute_units(
+        "apply pending balance",
+        consumed,
+        CONFIDENTIAL_APPLY_PENDING_BALANCE_COMPUTE_UNITS,
+    );
+
+    let consumed = metered_token
+        .confidential_transfer_withdraw(
+            &alice_account.token_account,
+            &alice.pubkey(),
+            None,
+            2,
+            decimals,
+            None,
+            &alice_account.elgamal_keypair,
+            &alice_account.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    check_compute_units("withdraw", consumed, CONFIDENTIAL_WITHDRAW_COMPUTE_UNITS);
+
+    let consumed = metered_token
+        .confidential_transfer_transfer(
+            &alice_account.token_account,
+            &bob_account.token_account,
+            &alice.pubkey(),
+            None,
+            40,
+            None,
+            &alice_account.elgamal_keypair,
+            &alice_account.aes_key,
+            bob_account.elgamal_keypair.pubkey(),
+            Some(auditor_elgamal_keypair.pubkey()),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    check_compute_units("transfer", consumed, CONFIDENTIAL_TRANSFER_COMPUTE_UNITS);
+}
+
+#[tokio::test]
+async fn confidential_transfer_with_fee_compute_units() {
+    let authority = Keypair::new();
+    let auditor_elgamal_keypair = ElGamalKeypair::new_rand();
+    let withdraw_withheld_authority_elgamal_keypair = ElGamalKeypair::new_rand();
+
+    let mut context = setup(vec![
+        ExtensionInitializationParams::TransferFeeConfig {
+            transfer_fee_config_authority: Some(authority.pubkey()),
+            withdraw_withheld_authority: Some(authority.pubkey()),
+            transfer_fee_basis_points: TEST_FEE_BASIS_POINTS,
+            maximum_fee: TEST_MAXIMUM_FEE,
+        },
+        ExtensionInitializationParams::ConfidentialTransferMint {
+            authority: Some(authority.pubkey()),
+            auto_approve_new_accounts: true,
+            auditor_elgamal_pubkey: Some((*auditor_elgamal_keypair.pubkey()).into()),
+        },
+        ExtensionInitializationParams::ConfidentialTransferFeeConfig {
+            authority: Some(authority.pubkey()),
+            withdraw_withheld_authority_elgamal_pubkey:
+                (*withdraw_withheld_authority_elgamal_keypair.pubkey()).into(),
+        },
+    ])
+    .await;
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+    let metered_token = metered_token(&context, &token, decimals).await;
+
+    let alice_account = ConfidentialTokenAccount::new(&token, &alice).await;
+    let bob_account = ConfidentialTokenAccount::new(&token, &bob).await;
+
+    token
+        .mint_to(
+            &alice_account.token_account,
+            &mint_authority.pubkey(),
+            100,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_deposit(
+            &alice_account.token_account,
+            &alice.pubkey(),
+            100,
+            decimals,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    token
+        .confidential_transfer_apply_pending_balance(
+            &alice_account.token_account,
+            &alice.pubkey(),
+            None,
+            alice_account.elgamal_keypair.secret(),
+            &alice_account.aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let consumed = metered_token
+        .confidential_transfer_send(
+            &alice_account.token_account,
+            &bob_account.token_account,
+            &alice.pubkey(),
+            100,
+            None,
+            &alice_account.elgamal_keypair,
+            &alice_account.aes_key,
+            bob_account.elgamal_keypair.pubkey(),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    check_compute_units(
+        "transfer with fee",
+        consumed,
+        CONFIDENTIAL_TRANSFER_WITH_FEE_COMPUTE_UNITS,
+    );
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{TestContext, TokenContext},
+    solana_program_test::{
+        processor,
+        tokio::{self, sync::Mutex},
+        ProgramTest,
+    },
+    solana_sdk::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
+        program::invoke_signed,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+    },
+    spl_token_2022::{
+        extension::{confidential_transfer, ExtensionType},
+        instruction::AuthorityType,
+        processor::Processor,
+        solana_zk_token_sdk::encryption::{auth_encryption::AeKey, elgamal::ElGamalKeypair},
+    },
+    spl_token_client::token::{ConfidentialTokenAccountBalances, ExtensionInitializationParams},
+    std::{convert::TryInto, sync::Arc},
+};
+
+const AUTHORITY_SEED: &[u8] = b"authority";
+
+// deposits into the pending balance of a confidential token account that is owned by the
+// program derived address of this program
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let token_program_info = next_account_info(account_info_iter)?;
+
+    let (authority, bump_seed) = Pubkey::find_program_address(&[AUTHORITY_SEED], program_id);
+    if *authority_info.key != authority {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let amount = input
+        .get(..8)
+        .and_then(|amount| amount.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)?;
+    let decimals = *input.get(8).ok_or(ProgramError::InvalidInstructionData)?;
+
+    let (instruction, account_infos) = confidential_transfer::instruction::deposit_cpi(
+        token_program_info.key,
+        token_account_info.clone(),
+        mint_info.clone(),
+        authority_info.clone(),
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &instruction,
+        &account_infos,
+        &[&[AUTHORITY_SEED, &[bump_seed]]],
+    )
+}
+
+#[tokio::test]
+async fn confidential_transfer_deposit_cpi() {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(Processor::process),
+    );
+    let program_id = Pubkey::new_unique();
+    program_test.add_program(
+        "confidential_deposit",
+        program_id,
+        processor!(process_instruction),
+    );
+
+    let context = program_test.start_with_context().await;
+    let context = Arc::new(Mutex::new(context));
+    let mut context = TestContext {
+        context,
+        token_context: None,
+    };
+
+    let authority = Keypair::new();
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(authority.pubkey()),
+                auto_approve_new_accounts: true,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.take().unwrap();
+
+    // the account is configured by alice and then handed over to the program derived address
+    let token_account_keypair = Keypair::new();
+    token
+        .create_auxiliary_token_account_with_extension_space(
+            &token_account_keypair,
+            &alice.pubkey(),
+            vec![ExtensionType::ConfidentialTransferAccount],
+        )
+        .await
+        .unwrap();
+    let token_account = token_account_keypair.pubkey();
+
+    let elgamal_keypair =
+        ElGamalKeypair::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    let aes_key = AeKey::new_from_signer(&alice, &token_account.to_bytes()).unwrap();
+    token
+        .confidential_transfer_configure_token_account(
+            &token_account,
+            &alice.pubkey(),
+            None,
+            None,
+            false,
+            &elgamal_keypair,
+            &aes_key,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let (program_authority, _) = Pubkey::find_program_address(&[AUTHORITY_SEED], &program_id);
+    token
+        .set_authority(
+            &token_account,
+            &alice.pubkey(),
+            Some(&program_authority),
+            AuthorityType::AccountOwner,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    token
+        .mint_to(
+            &token_account,
+            &mint_authority.pubkey(),
+            42,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    let mut data = 42_u64.to_le_bytes().to_vec();
+    data.push(decimals);
+    token
+        .process_ixs(
+            &[Instruction {
+                program_id,
+                accounts: vec![
+                    AccountMeta::new(token_account, false),
+                    AccountMeta::new_readonly(*token.get_address(), false),
+                    AccountMeta::new_readonly(program_authority, false),
+                    AccountMeta::new_readonly(spl_token_2022::id(), false),
+                ],
+                data,
+            }],
+            &[] as &[&dyn Signer; 0],
+        )
+        .await
+        .unwrap();
+
+    let state = token.get_account_info(&token_account).await.unwrap();
+    assert_eq!(state.base.amount, 0);
+    assert_eq!(state.base.owner, program_authority);
+    assert_eq!(
+        token
+            .confidential_transfer_get_balances(&token_account, &elgamal_keypair, &aes_key)
+            .await
+            .unwrap(),
+        ConfidentialTokenAccountBalances {
+            pending_balance_lo: 42,
+            pending_balance_hi: 0,
+            available_balance: 0,
+            decryptable_available_balance: 0,
+        },
+    );
+}
@@ -0,0 +1,321 @@
+#![cfg(all(feature = "test-sbf", feature = "zk-ops"))]
+#![cfg(twoxtx)]
+
+mod program_test;
+use {
+    program_test::{TestContext, TokenContext},
+    proptest::prelude::*,
+    solana_program_test::tokio,
+    solana_sdk::{pubkey::Pubkey, signature::Signer, signer::keypair::Keypair},
+    spl_token_2022::{
+        extension::{confidential_transfer::PENDING_BALANCE_LO_BIT_LENGTH, ExtensionType},
+        solana_zk_token_sdk::encryption::{auth_encryption::*, elgamal::*},
+    },
+    spl_token_client::{
+        client::ProgramBanksClientProcessTransaction,
+        token::{ExtensionInitializationParams, Token},
+    },
+};
+
+/// Number of confidential token accounts that the operations are sequenced across
+const NUM_ACCOUNTS: usize = 3;
+
+/// Non-confidential balance that each account starts with
+const INITIAL_AMOUNT: u64 = 1_000;
+
+/// Operation on the confidential token accounts, which are referred to by index
+#[derive(Clone, Debug)]
+enum Operation {
+    Deposit {
+        account: usize,
+        amount: u64,
+    },
+    Withdraw {
+        account: usize,
+        amount: u64,
+    },
+    Transfer {
+        source: usize,
+        destination: usize,
+        amount: u64,
+    },
+    ApplyPendingBalance {
+        account: usize,
+    },
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        (0..NUM_ACCOUNTS, 1..=INITIAL_AMOUNT)
+            .prop_map(|(account, amount)| Operation::Deposit { account, amount }),
+        (0..NUM_ACCOUNTS, 1..=INITIAL_AMOUNT)
+            .prop_map(|(account, amount)| Operation::Withdraw { account, amount }),
+        (0..NUM_ACCOUNTS, 0..NUM_ACCOUNTS, 0..=INITIAL_AMOUNT).prop_map(
+            |(source, destination, amount)| Operation::Transfer {
+                source,
+                destination,
+                amount,
+            }
+        ),
+        (0..NUM_ACCOUNTS).prop_map(|account| Operation::ApplyPendingBalance { account }),
+    ]
+}
+
+/// Expected balances of a confidential token account
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct ModelBalances {
+    non_confidential: u64,
+    pending: u64,
+    available: u64,
+}
+
+struct ConfidentialAccount {
+    owner: Keypair,
+    token_account: Pubkey,
+    elgamal_keypair: ElGamalKeypair,
+    aes_key: AeKey,
+}
+
+impl ConfidentialAccount {
+    async fn new(
+        token: &Token<ProgramBanksClientProcessTransaction>,
+        owner: Keypair,
+        mint_authority: &Keypair,
+    ) -> Self {
+        let token_account_keypair = Keypair::new();
+        token
+            .create_auxiliary_token_account_with_extension_space(
+                &token_account_keypair,
+                &owner.pubkey(),
+                vec![ExtensionType::ConfidentialTransferAccount],
+            )
+            .await
+            .unwrap();
+        let token_account = token_account_keypair.pubkey();
+
+        let elgamal_keypair =
+            ElGamalKeypair::new_from_signer(&owner, &token_account.to_bytes()).unwrap();
+        let aes_key = AeKey::new_from_signer(&owner, &token_account.to_bytes()).unwrap();
+
+        token
+            .confidential_transfer_configure_token_account(
+                &token_account,
+                &owner.pubkey(),
+                None,
+                None,
+                false,
+                &elgamal_keypair,
+                &aes_key,
+                &[&owner],
+            )
+            .await
+            .unwrap();
+
+        token
+            .mint_to(
+                &token_account,
+                &mint_authority.pubkey(),
+                INITIAL_AMOUNT,
+                &[mint_authority],
+            )
+            .await
+            .unwrap();
+
+        Self {
+            owner,
+            token_account,
+            elgamal_keypair,
+            aes_key,
+        }
+    }
+
+    async fn balances(&self, token: &Token<ProgramBanksClientProcessTransaction>) -> ModelBalances {
+        let non_confidential = token
+            .get_account_info(&self.token_account)
+            .await
+            .unwrap()
+            .base
+            .amount;
+        let balances = token
+            .confidential_transfer_get_balances(
+                &self.token_account,
+                &self.elgamal_keypair,
+                &self.aes_key,
+            )
+            .await
+            .unwrap();
+
+        // the owner's view of the available balance must agree with the ElGamal ciphertext
+        assert_eq!(
+            balances.available_balance,
+            balances.decryptable_available_balance
+        );
+
+        ModelBalances {
+            non_confidential,
+            pending: (balances.pending_balance_hi << PENDING_BALANCE_LO_BIT_LENGTH)
+                + balances.pending_balance_lo,
+            available: balances.available_balance,
+        }
+    }
+}
+
+/// Apply `operations` to fresh confidential token accounts, skipping operations that the model
+/// predicts to fail, and check the decrypted balances against the model after each step
+///
+/// Tokens only move between the non-confidential, pending, and available balances of the accounts,
+/// so the sum of the decrypted confidential balances must always equal the amount deposited minus
+/// the amount withdrawn.
+async fn check_operations(operations: Vec<Operation>) {
+    let mut context = TestContext::new().await;
+    context
+        .init_token_with_mint(vec![
+            ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: Some(Keypair::new().pubkey()),
+                auto_approve_new_accounts: true,
+                auditor_elgamal_pubkey: None,
+            },
+        ])
+        .await
+        .unwrap();
+
+    let TokenContext {
+        token,
+        alice,
+        bob,
+        mint_authority,
+        decimals,
+        ..
+    } = context.token_context.unwrap();
+
+    let mut accounts = vec![];
+    for owner in [alice, bob, Keypair::new()] {
+        accounts.push(ConfidentialAccount::new(&token, owner, &mint_authority).await);
+    }
+    let mut model = vec![
+        ModelBalances {
+            non_confidential: INITIAL_AMOUNT,
+            ..ModelBalances::default()
+        };
+        NUM_ACCOUNTS
+    ];
+    let mut deposited = 0;
+    let mut withdrawn = 0;
+
+    for operation in operations {
+        match operation {
+            Operation::Deposit { account, amount } => {
+                if amount > model[account].non_confidential {
+                    continue;
+                }
+                let meta = &accounts[account];
+                token
+                    .confidential_transfer_deposit(
+                        &meta.token_account,
+                        &meta.owner.pubkey(),
+                        amount,
+                        decimals,
+                        &[&meta.owner],
+                    )
+                    .await
+                    .unwrap();
+                model[account].non_confidential -= amount;
+                model[account].pending += amount;
+                deposited += amount;
+            }
+            Operation::Withdraw { account, amount } => {
+                if amount > model[account].available {
+                    continue;
+                }
+                let meta = &accounts[account];
+                token
+                    .confidential_transfer_withdraw(
+                        &meta.token_account,
+                        &meta.owner.pubkey(),
+                        None,
+                        amount,
+                        decimals,
+                        None,
+                        &meta.elgamal_keypair,
+                        &meta.aes_key,
+                        &[&meta.owner],
+                    )
+                    .await
+                    .unwrap();
+                model[account].available -= amount;
+                model[account].non_confidential += amount;
+                withdrawn += amount;
+            }
+            Operation::Transfer {
+                source,
+                destination,
+                amount,
+            } => {
+                if amount > model[source].available {
+                    continue;
+                }
+                let source_meta = &accounts[source];
+                let destination_meta = &accounts[destination];
+                token
+                    .confidential_transfer_transfer(
+                        &source_meta.token_account,
+                        &destination_meta.token_account,
+                        &source_meta.owner.pubkey(),
+                        None,
+                        amount,
+                        None,
+                        &source_meta.elgamal_keypair,
+                        &source_meta.aes_key,
+                        destination_meta.elgamal_keypair.pubkey(),
+                        None,
+                        &[&source_meta.owner],
+                    )
+                    .await
+                    .unwrap();
+                // a self-transfer is credited back to the available balance
+                if source != destination {
+                    model[source].available -= amount;
+                    model[destination].pending += amount;
+                }
+            }
+            Operation::ApplyPendingBalance { account } => {
+                let meta = &accounts[account];
+                token
+                    .confidential_transfer_apply_pending_balance(
+                        &meta.token_account,
+                        &meta.owner.pubkey(),
+                        None,
+                        meta.elgamal_keypair.secret(),
+                        &meta.aes_key,
+                        &[&meta.owner],
+                    )
+                    .await
+                    .unwrap();
+                model[account].available += model[account].pending;
+                model[account].pending = 0;
+            }
+        }
+
+        let mut confidential_total = 0;
+        for (meta, expected) in accounts.iter().zip(model.iter()) {
+            let balances = meta.balances(&token).await;
+            assert_eq!(balances, *expected);
+            confidential_total += balances.pending + balances.available;
+        }
+        assert_eq!(confidential_total, deposited - withdrawn);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(8))]
+    #[test]
+    fn confidential_balances_are_conserved(
+        operations in prop::collection::vec(operation(), 1..12),
+    ) {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(check_operations(operations));
+    }
+}
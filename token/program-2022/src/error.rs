@@ -215,6 +215,73 @@ pub enum TokenError {
     /// An invalid proof instruction offset was provided
     #[error("An invalid proof instruction offset was provided ")]
     InvalidProofInstructionOffset,
+    /// Tokens of a mint with a confidential supply cannot be deposited or withdrawn
+    #[error("Tokens of a mint with a confidential supply cannot be deposited or withdrawn")]
+    ConfidentialSupplyConversionNotSupported,
+    /// The range proof of a confidential mint does not match the mint amount ciphertexts
+    #[error("The range proof of a confidential mint does not match the mint amount ciphertexts")]
+    ConfidentialMintRangeProofMismatch,
+    /// The pending balance of a confidential account must be applied first
+    #[error("The pending balance of a confidential account must be applied first")]
+    ConfidentialTransferPendingBalanceNotEmpty,
+    /// Confidential transfers of the mint require additional zero-knowledge proofs
+    #[error("Confidential transfers of the mint require additional zero-knowledge proofs")]
+    ConfidentialTransferAdditionalProofsRequired,
+
+    // 60
+    /// An additional zero-knowledge proof does not match the confidential transfer
+    #[error("An additional zero-knowledge proof does not match the confidential transfer")]
+    ConfidentialTransferAdditionalProofMismatch,
+    /// The split proof contexts of a confidential transfer are inconsistent
+    #[error("The split proof contexts of a confidential transfer are inconsistent")]
+    ConfidentialTransferSplitProofMismatch,
+    /// A proof context state account holds a proof of an unexpected type
+    #[error("A proof context state account holds a proof of an unexpected type")]
+    ProofTypeMismatch,
+    /// The authority is not the approved confidential delegate of the source account
+    #[error("The authority is not the approved confidential delegate of the source account")]
+    ConfidentialTransferDelegateNotApproved,
+    /// The delegate allowance proofs do not match the confidential transfer
+    #[error("The delegate allowance proofs do not match the confidential transfer")]
+    ConfidentialTransferDelegateAllowanceMismatch,
+
+    // 65
+    /// Crediting the pending balance again could make it infeasible to decrypt
+    #[error("The pending balance of a confidential account must be applied before it can be credited again")]
+    ConfidentialTransferPendingBalanceOverflow,
+    /// The expected pending balance credit counter exceeds the current pending balance credit
+    /// counter
+    #[error("The expected pending balance credit counter exceeds the current pending balance credit counter")]
+    InvalidPendingBalanceCreditCounter,
+    /// The pull transfer is not authorized by the source account
+    #[error("The pull transfer is not authorized by the source account")]
+    ConfidentialTransferPullNotAuthorized,
+    /// The transfer amount exceeds the maximum transfer amount of the mint
+    #[error("The transfer amount exceeds the maximum transfer amount of the mint")]
+    ConfidentialTransferMaximumAmountExceeded,
+    /// Account stopped accepting deposits or transfers as of a scheduled slot
+    #[error("Account stopped accepting deposits or transfers as of a scheduled slot")]
+    ConfidentialTransferCreditsDisabledAsOfSlot,
+
+    // 70
+    /// The decryptable available balance must be updated after a pull transfer
+    #[error("The decryptable available balance must be updated after a pull transfer")]
+    ConfidentialTransferDecryptableBalanceStale,
+    /// Instruction data was encoded with a version that the program does not support
+    #[error("Instruction data was encoded with a version that the program does not support")]
+    UnsupportedInstructionDataVersion,
+    /// The account does not have enough space for the confidential transfer extensions
+    #[error(
+        "Insufficient account space for the confidential transfer extensions; reallocate first"
+    )]
+    InsufficientConfidentialTransferAccountSpace,
+    /// Pending balances of audited or capped mints cannot be forwarded
+    #[error("Pending balances of audited or capped mints cannot be forwarded")]
+    ConfidentialTransferForwardNotSupported,
+    /// The destination account of a transfer cannot receive another transfer until it applies
+    /// its pending balance
+    #[error("The destination account of a transfer cannot receive another transfer until it applies its pending balance")]
+    DestinationPendingBalanceCreditCounterExceeded,
 }
 impl From<TokenError> for ProgramError {
     fn from(e: TokenError) -> Self {
@@ -375,6 +442,67 @@ impl PrintProgramError for TokenError {
             TokenError::InvalidProofInstructionOffset => {
                 msg!("An invalid proof instruction offset was provided")
             }
+            TokenError::ConfidentialSupplyConversionNotSupported => {
+                msg!("Tokens of a mint with a confidential supply cannot be deposited or withdrawn")
+            }
+            TokenError::ConfidentialMintRangeProofMismatch => {
+                msg!("The range proof of a confidential mint does not match the mint amount ciphertexts")
+            }
+            TokenError::ConfidentialTransferPendingBalanceNotEmpty => {
+                msg!("The pending balance of a confidential account must be applied first")
+            }
+            TokenError::ConfidentialTransferAdditionalProofsRequired => {
+                msg!("Confidential transfers of the mint require additional zero-knowledge proofs")
+            }
+            TokenError::ConfidentialTransferAdditionalProofMismatch => {
+                msg!("An additional zero-knowledge proof does not match the confidential transfer")
+            }
+            TokenError::ConfidentialTransferSplitProofMismatch => {
+                msg!("The split proof contexts of a confidential transfer are inconsistent")
+            }
+            TokenError::ProofTypeMismatch => {
+                msg!("A proof context state account holds a proof of an unexpected type")
+            }
+            TokenError::ConfidentialTransferDelegateNotApproved => {
+                msg!(
+                    "The authority is not the approved confidential delegate of the source account"
+                )
+            }
+            TokenError::ConfidentialTransferDelegateAllowanceMismatch => {
+                msg!("The delegate allowance proofs do not match the confidential transfer")
+            }
+            TokenError::ConfidentialTransferPendingBalanceOverflow => {
+                msg!("The pending balance of a confidential account must be applied before it can be credited again")
+            }
+            TokenError::InvalidPendingBalanceCreditCounter => {
+                msg!("The expected pending balance credit counter exceeds the current pending balance credit counter")
+            }
+            TokenError::ConfidentialTransferPullNotAuthorized => {
+                msg!("The pull transfer is not authorized by the source account")
+            }
+            TokenError::ConfidentialTransferMaximumAmountExceeded => {
+                msg!("The transfer amount exceeds the maximum transfer amount of the mint")
+            }
+            TokenError::ConfidentialTransferCreditsDisabledAsOfSlot => {
+                msg!("Account stopped accepting deposits or transfers as of a scheduled slot")
+            }
+            TokenError::ConfidentialTransferDecryptableBalanceStale => {
+                msg!("The decryptable available balance must be updated after a pull transfer")
+            }
+            TokenError::UnsupportedInstructionDataVersion => {
+                msg!(
+                    "Instruction data was encoded with a version that the program does not support"
+                )
+            }
+            TokenError::ConfidentialTransferForwardNotSupported => {
+                msg!("Pending balances of audited or capped mints cannot be forwarded")
+            }
+            TokenError::InsufficientConfidentialTransferAccountSpace => {
+                msg!("Insufficient account space for the confidential transfer extensions; reallocate first")
+            }
+            TokenError::DestinationPendingBalanceCreditCounterExceeded => {
+                msg!("The destination account of a transfer cannot receive another transfer until it applies its pending balance")
+            }
         }
     }
 }
@@ -2,7 +2,10 @@ use {
     crate::{
         error::TokenError,
         extension::confidential_transfer::{
-            DecryptableBalance, EncryptedBalance, PENDING_BALANCE_LO_BIT_LENGTH,
+            transfer_amount_destination_ciphertext, transfer_amount_source_ciphertext,
+            DecryptableBalance, EncryptedBalance, DELEGATE_ALLOWANCE_RANGE_PROOF_BIT_LENGTH,
+            MAXIMUM_DEPOSIT_TRANSFER_AMOUNT, MAXIMUM_TRANSFER_AMOUNT_RANGE_PROOF_BIT_LENGTH,
+            MINT_AMOUNT_HI_BIT_LENGTH, MINT_AMOUNT_LO_BIT_LENGTH, PENDING_BALANCE_LO_BIT_LENGTH,
         },
         pod::*,
     },
@@ -10,13 +13,31 @@ use {
     solana_zk_token_sdk::{
         encryption::{
             auth_encryption::{AeCiphertext, AeKey},
-            elgamal::{ElGamalKeypair, ElGamalPubkey, ElGamalSecretKey},
+            elgamal::{
+                DecryptHandle, ElGamalCiphertext, ElGamalKeypair, ElGamalPubkey, ElGamalSecretKey,
+            },
+            grouped_elgamal::{GroupedElGamal, GroupedElGamalCiphertext},
+            pedersen::{Pedersen, PedersenOpening},
         },
         instruction::{
+            batched_range_proof::{
+                batched_range_proof_u128::BatchedRangeProofU128Data,
+                batched_range_proof_u64::BatchedRangeProofU64Data,
+            },
+            ciphertext_ciphertext_equality::CiphertextCiphertextEqualityProofData,
+            ciphertext_commitment_equality::CiphertextCommitmentEqualityProofData,
+            grouped_ciphertext_validity::{
+                BatchedGroupedCiphertext2HandlesValidityProofData,
+                GroupedCiphertext2HandlesValidityProofData,
+            },
             transfer::{FeeParameters, TransferData, TransferWithFeeData},
             withdraw::WithdrawData,
             zero_balance::ZeroBalanceProofData,
         },
+        zk_token_elgamal::{
+            ops,
+            pod::{ElGamalCiphertext as PodElGamalCiphertext, TransferAmountCiphertext},
+        },
     },
 };
 
@@ -43,6 +64,39 @@ impl EmptyAccountAccountInfo {
     }
 }
 
+/// Confidential transfer extension information needed to construct an `EmptyAccountFull`
+/// instruction.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct EmptyAccountFullAccountInfo {
+    /// The available balance
+    pub(crate) available_balance: EncryptedBalance,
+    /// The low 16 bits of the pending balance (encrypted by `elgamal_pubkey`)
+    pub(crate) pending_balance_lo: EncryptedBalance,
+    /// The high 48 bits of the pending balance (encrypted by `elgamal_pubkey`)
+    pub(crate) pending_balance_hi: EncryptedBalance,
+}
+impl EmptyAccountFullAccountInfo {
+    /// Create an empty account proof data for the sum of the available and pending balances.
+    pub fn generate_proof_data(
+        &self,
+        elgamal_keypair: &ElGamalKeypair,
+    ) -> Result<ZeroBalanceProofData, TokenError> {
+        // compute the total balance ciphertext in the same way as the program
+        let total_balance: ElGamalCiphertext = ops::add_with_lo_hi(
+            &self.available_balance,
+            &self.pending_balance_lo,
+            &self.pending_balance_hi,
+        )
+        .ok_or(TokenError::AccountDecryption)?
+        .try_into()
+        .map_err(|_| TokenError::AccountDecryption)?;
+
+        ZeroBalanceProofData::new(elgamal_keypair, &total_balance)
+            .map_err(|_| TokenError::ProofGeneration)
+    }
+}
+
 /// Confidential Transfer extension information needed to construct an `ApplyPendingBalance`
 /// instruction.
 #[repr(C)]
@@ -228,6 +282,43 @@ impl TransferAccountInfo {
         .map_err(|_| TokenError::ProofGeneration)
     }
 
+    /// Return the account information of the source account after a `Transfer` instruction with
+    /// the given proof data is processed.
+    ///
+    /// The new available balance ciphertext is computed in the same way as the program, so that
+    /// the proof for a following transfer can be generated before this transfer is processed. For
+    /// a self-transfer, the transfer amount is added back to the available balance.
+    pub fn after_transfer(
+        &self,
+        transfer_amount: u64,
+        proof_data: &TransferData,
+        aes_key: &AeKey,
+        is_self_transfer: bool,
+    ) -> Result<Self, TokenError> {
+        let new_source_available_balance = proof_data.context.new_source_ciphertext;
+
+        if is_self_transfer {
+            let available_balance = ops::add_with_lo_hi(
+                &new_source_available_balance,
+                &transfer_amount_destination_ciphertext(&proof_data.context.ciphertext_lo),
+                &transfer_amount_destination_ciphertext(&proof_data.context.ciphertext_hi),
+            )
+            .ok_or(TokenError::AccountDecryption)?;
+
+            return Ok(Self {
+                available_balance,
+                decryptable_available_balance: self.decryptable_available_balance,
+            });
+        }
+
+        Ok(Self {
+            available_balance: new_source_available_balance,
+            decryptable_available_balance: self
+                .new_decryptable_available_balance(transfer_amount, aes_key)?
+                .into(),
+        })
+    }
+
     /// Create a transfer with fee proof data
     #[allow(clippy::too_many_arguments)]
     pub fn generate_transfer_with_fee_proof_data(
@@ -270,6 +361,238 @@ impl TransferAccountInfo {
         .map_err(|_| TokenError::ProofGeneration)
     }
 
+    /// Create the split proof data for a `TransferWithSplitProofs` instruction.
+    pub fn generate_split_transfer_proof_data(
+        &self,
+        transfer_amount: u64,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+    ) -> Result<TransferSplitProofData, TokenError> {
+        let default_auditor_pubkey = ElGamalPubkey::default();
+        let auditor_elgamal_pubkey = auditor_elgamal_pubkey.unwrap_or(&default_auditor_pubkey);
+
+        let split_transfer_amount = self.split_transfer_amount(
+            transfer_amount,
+            elgamal_keypair,
+            aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+        )?;
+
+        let ciphertext_validity_proof_data =
+            BatchedGroupedCiphertext2HandlesValidityProofData::new(
+                destination_elgamal_pubkey,
+                auditor_elgamal_pubkey,
+                &split_transfer_amount.grouped_ciphertext_lo,
+                &split_transfer_amount.grouped_ciphertext_hi,
+                split_transfer_amount.transfer_amount_lo,
+                split_transfer_amount.transfer_amount_hi,
+                &split_transfer_amount.opening_lo,
+                &split_transfer_amount.opening_hi,
+            )
+            .map_err(|_| TokenError::ProofGeneration)?;
+
+        Ok(TransferSplitProofData {
+            equality_proof_data: split_transfer_amount.equality_proof_data,
+            ciphertext_validity_proof_data,
+            range_proof_data: split_transfer_amount.range_proof_data,
+            source_amount_equality_proof_data_lo: split_transfer_amount
+                .source_amount_equality_proof_data_lo,
+            source_amount_equality_proof_data_hi: split_transfer_amount
+                .source_amount_equality_proof_data_hi,
+        })
+    }
+
+    /// Create the split proof data for a `TransferWithSplitValidityProofs` instruction.
+    pub fn generate_split_validity_transfer_proof_data(
+        &self,
+        transfer_amount: u64,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: Option<&ElGamalPubkey>,
+    ) -> Result<TransferSplitValidityProofData, TokenError> {
+        let default_auditor_pubkey = ElGamalPubkey::default();
+        let auditor_elgamal_pubkey = auditor_elgamal_pubkey.unwrap_or(&default_auditor_pubkey);
+
+        let split_transfer_amount = self.split_transfer_amount(
+            transfer_amount,
+            elgamal_keypair,
+            aes_key,
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+        )?;
+
+        let ciphertext_validity_proof_data_lo = GroupedCiphertext2HandlesValidityProofData::new(
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+            &split_transfer_amount.grouped_ciphertext_lo,
+            split_transfer_amount.transfer_amount_lo,
+            &split_transfer_amount.opening_lo,
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+        let ciphertext_validity_proof_data_hi = GroupedCiphertext2HandlesValidityProofData::new(
+            destination_elgamal_pubkey,
+            auditor_elgamal_pubkey,
+            &split_transfer_amount.grouped_ciphertext_hi,
+            split_transfer_amount.transfer_amount_hi,
+            &split_transfer_amount.opening_hi,
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+
+        Ok(TransferSplitValidityProofData {
+            equality_proof_data: split_transfer_amount.equality_proof_data,
+            ciphertext_validity_proof_data_lo,
+            ciphertext_validity_proof_data_hi,
+            range_proof_data: split_transfer_amount.range_proof_data,
+            source_amount_equality_proof_data_lo: split_transfer_amount
+                .source_amount_equality_proof_data_lo,
+            source_amount_equality_proof_data_hi: split_transfer_amount
+                .source_amount_equality_proof_data_hi,
+        })
+    }
+
+    /// Split a transfer amount into its low and high bits, encrypt them under the source,
+    /// destination, and auditor public keys, and create the equality and range proof data that are
+    /// shared by the split proof transfer instructions.
+    fn split_transfer_amount(
+        &self,
+        transfer_amount: u64,
+        elgamal_keypair: &ElGamalKeypair,
+        aes_key: &AeKey,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+        auditor_elgamal_pubkey: &ElGamalPubkey,
+    ) -> Result<SplitTransferAmount, TokenError> {
+        const REMAINING_BALANCE_BIT_LENGTH: usize = 64;
+        const TRANSFER_AMOUNT_LO_BIT_LENGTH: usize = 16;
+        const TRANSFER_AMOUNT_HI_BIT_LENGTH: usize = 32;
+        const PADDING_BIT_LENGTH: usize = 16;
+
+        if transfer_amount > MAXIMUM_DEPOSIT_TRANSFER_AMOUNT {
+            return Err(TokenError::ProofGeneration);
+        }
+
+        let current_decrypted_available_balance = self.decrypted_available_balance(aes_key)?;
+        let new_decrypted_available_balance = current_decrypted_available_balance
+            .checked_sub(transfer_amount)
+            .ok_or(TokenError::InsufficientFunds)?;
+
+        // encrypt the low and high bits of the transfer amount under the destination and auditor
+        // public keys, and compute the corresponding source decryption handles
+        let transfer_amount_lo = transfer_amount & (u16::MAX as u64);
+        let transfer_amount_hi = transfer_amount >> PENDING_BALANCE_LO_BIT_LENGTH;
+
+        let opening_lo = PedersenOpening::new_rand();
+        let opening_hi = PedersenOpening::new_rand();
+
+        let grouped_ciphertext_lo = GroupedElGamal::encrypt_with(
+            [destination_elgamal_pubkey, auditor_elgamal_pubkey],
+            transfer_amount_lo,
+            &opening_lo,
+        );
+        let grouped_ciphertext_hi = GroupedElGamal::encrypt_with(
+            [destination_elgamal_pubkey, auditor_elgamal_pubkey],
+            transfer_amount_hi,
+            &opening_hi,
+        );
+
+        let source_decrypt_handle_lo = DecryptHandle::new(elgamal_keypair.pubkey(), &opening_lo);
+        let source_decrypt_handle_hi = DecryptHandle::new(elgamal_keypair.pubkey(), &opening_hi);
+
+        // compute the new source available balance ciphertext in the same way as the program
+        let source_ciphertext_lo = ElGamalCiphertext {
+            commitment: grouped_ciphertext_lo.commitment,
+            handle: source_decrypt_handle_lo,
+        };
+        let source_ciphertext_hi = ElGamalCiphertext {
+            commitment: grouped_ciphertext_hi.commitment,
+            handle: source_decrypt_handle_hi,
+        };
+        let new_available_balance_ciphertext: ElGamalCiphertext = ops::subtract_with_lo_hi(
+            &self.available_balance,
+            &source_ciphertext_lo.into(),
+            &source_ciphertext_hi.into(),
+        )
+        .ok_or(TokenError::AccountDecryption)?
+        .try_into()
+        .map_err(|_| TokenError::AccountDecryption)?;
+
+        // certify that the source ciphertexts encrypt the same amounts as the grouped ciphertexts
+        let source_amount_equality_proof_data_lo = CiphertextCommitmentEqualityProofData::new(
+            elgamal_keypair,
+            &source_ciphertext_lo,
+            &grouped_ciphertext_lo.commitment,
+            &opening_lo,
+            transfer_amount_lo,
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+        let source_amount_equality_proof_data_hi = CiphertextCommitmentEqualityProofData::new(
+            elgamal_keypair,
+            &source_ciphertext_hi,
+            &grouped_ciphertext_hi.commitment,
+            &opening_hi,
+            transfer_amount_hi,
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+
+        let new_source_opening = PedersenOpening::new_rand();
+        let new_source_commitment =
+            Pedersen::with(new_decrypted_available_balance, &new_source_opening);
+
+        let equality_proof_data = CiphertextCommitmentEqualityProofData::new(
+            elgamal_keypair,
+            &new_available_balance_ciphertext,
+            &new_source_commitment,
+            &new_source_opening,
+            new_decrypted_available_balance,
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+
+        let (padding_commitment, padding_opening) = Pedersen::new(0_u64);
+        let range_proof_data = BatchedRangeProofU128Data::new(
+            vec![
+                &new_source_commitment,
+                &grouped_ciphertext_lo.commitment,
+                &grouped_ciphertext_hi.commitment,
+                &padding_commitment,
+            ],
+            vec![
+                new_decrypted_available_balance,
+                transfer_amount_lo,
+                transfer_amount_hi,
+                0,
+            ],
+            vec![
+                REMAINING_BALANCE_BIT_LENGTH,
+                TRANSFER_AMOUNT_LO_BIT_LENGTH,
+                TRANSFER_AMOUNT_HI_BIT_LENGTH,
+                PADDING_BIT_LENGTH,
+            ],
+            vec![
+                &new_source_opening,
+                &opening_lo,
+                &opening_hi,
+                &padding_opening,
+            ],
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+
+        Ok(SplitTransferAmount {
+            transfer_amount_lo,
+            transfer_amount_hi,
+            grouped_ciphertext_lo,
+            grouped_ciphertext_hi,
+            opening_lo,
+            opening_hi,
+            equality_proof_data,
+            range_proof_data,
+            source_amount_equality_proof_data_lo,
+            source_amount_equality_proof_data_hi,
+        })
+    }
+
     /// Update the decryptable available balance.
     pub fn new_decryptable_available_balance(
         &self,
@@ -285,8 +608,594 @@ impl TransferAccountInfo {
     }
 }
 
+/// The proof data needed to construct a `TransferWithSplitProofs` instruction.
+pub struct TransferSplitProofData {
+    /// Proof that the new source available balance ciphertext and commitment are equal
+    pub equality_proof_data: CiphertextCommitmentEqualityProofData,
+    /// Proof that the transfer amount ciphertexts are valid
+    pub ciphertext_validity_proof_data: BatchedGroupedCiphertext2HandlesValidityProofData,
+    /// Proof that the new source available balance and the transfer amount are in range
+    pub range_proof_data: BatchedRangeProofU128Data,
+    /// Proof that the low bits of the transfer amount under the source public key are equal to the
+    /// corresponding transfer amount commitment
+    pub source_amount_equality_proof_data_lo: CiphertextCommitmentEqualityProofData,
+    /// Proof that the high bits of the transfer amount under the source public key are equal to
+    /// the corresponding transfer amount commitment
+    pub source_amount_equality_proof_data_hi: CiphertextCommitmentEqualityProofData,
+}
+
+/// The proof data needed to construct a `TransferWithSplitValidityProofs` instruction.
+pub struct TransferSplitValidityProofData {
+    /// Proof that the new source available balance ciphertext and commitment are equal
+    pub equality_proof_data: CiphertextCommitmentEqualityProofData,
+    /// Proof that the ciphertext of the low bits of the transfer amount is valid
+    pub ciphertext_validity_proof_data_lo: GroupedCiphertext2HandlesValidityProofData,
+    /// Proof that the ciphertext of the high bits of the transfer amount is valid
+    pub ciphertext_validity_proof_data_hi: GroupedCiphertext2HandlesValidityProofData,
+    /// Proof that the new source available balance and the transfer amount are in range
+    pub range_proof_data: BatchedRangeProofU128Data,
+    /// Proof that the low bits of the transfer amount under the source public key are equal to the
+    /// corresponding transfer amount commitment
+    pub source_amount_equality_proof_data_lo: CiphertextCommitmentEqualityProofData,
+    /// Proof that the high bits of the transfer amount under the source public key are equal to
+    /// the corresponding transfer amount commitment
+    pub source_amount_equality_proof_data_hi: CiphertextCommitmentEqualityProofData,
+}
+
+/// The low and high bits of a transfer amount encrypted under the destination and auditor public
+/// keys, together with the proof data that is shared by the split proof transfer instructions.
+struct SplitTransferAmount {
+    transfer_amount_lo: u64,
+    transfer_amount_hi: u64,
+    grouped_ciphertext_lo: GroupedElGamalCiphertext<2>,
+    grouped_ciphertext_hi: GroupedElGamalCiphertext<2>,
+    opening_lo: PedersenOpening,
+    opening_hi: PedersenOpening,
+    equality_proof_data: CiphertextCommitmentEqualityProofData,
+    range_proof_data: BatchedRangeProofU128Data,
+    source_amount_equality_proof_data_lo: CiphertextCommitmentEqualityProofData,
+    source_amount_equality_proof_data_hi: CiphertextCommitmentEqualityProofData,
+}
+
+/// Confidential Transfer extension information needed to construct a `RotateElGamalKey`
+/// instruction.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct RotateElGamalKeyAccountInfo {
+    /// The available balance (encrypted by `encrypiton_pubkey`)
+    pub available_balance: EncryptedBalance,
+    /// The decryptable available balance
+    pub decryptable_available_balance: DecryptableBalance,
+}
+impl RotateElGamalKeyAccountInfo {
+    fn decrypted_available_balance(&self, aes_key: &AeKey) -> Result<u64, TokenError> {
+        let decryptable_available_balance = self
+            .decryptable_available_balance
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+        aes_key
+            .decrypt(&decryptable_available_balance)
+            .ok_or(TokenError::AccountDecryption)
+    }
+
+    /// Create a ciphertext-ciphertext equality proof data that certifies that the available
+    /// balance re-encrypted under the new ElGamal public key is equal to the current available
+    /// balance.
+    pub fn generate_proof_data(
+        &self,
+        current_elgamal_keypair: &ElGamalKeypair,
+        current_aes_key: &AeKey,
+        new_elgamal_pubkey: &ElGamalPubkey,
+    ) -> Result<CiphertextCiphertextEqualityProofData, TokenError> {
+        let current_available_balance = self
+            .available_balance
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+        let current_decrypted_available_balance =
+            self.decrypted_available_balance(current_aes_key)?;
+
+        let new_opening = PedersenOpening::new_rand();
+        let new_available_balance =
+            new_elgamal_pubkey.encrypt_with(current_decrypted_available_balance, &new_opening);
+
+        CiphertextCiphertextEqualityProofData::new(
+            current_elgamal_keypair,
+            new_elgamal_pubkey,
+            &current_available_balance,
+            &new_available_balance,
+            &new_opening,
+            current_decrypted_available_balance,
+        )
+        .map_err(|_| TokenError::ProofGeneration)
+    }
+
+    /// Re-encrypt the decryptable available balance under the new authenticated encryption key.
+    pub fn new_decryptable_available_balance(
+        &self,
+        current_aes_key: &AeKey,
+        new_aes_key: &AeKey,
+    ) -> Result<AeCiphertext, TokenError> {
+        let current_decrypted_available_balance =
+            self.decrypted_available_balance(current_aes_key)?;
+
+        Ok(new_aes_key.encrypt(current_decrypted_available_balance))
+    }
+}
+
+/// Confidential Transfer extension information needed to construct a `ForwardPendingBalance`
+/// instruction.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ForwardPendingBalanceAccountInfo {
+    /// The low 16 bits of the pending balance (encrypted by `elgamal_pubkey`)
+    pub pending_balance_lo: EncryptedBalance,
+    /// The high 48 bits of the pending balance (encrypted by `elgamal_pubkey`)
+    pub pending_balance_hi: EncryptedBalance,
+}
+impl ForwardPendingBalanceAccountInfo {
+    /// Create the ciphertext-ciphertext equality proof data that certifies that the low and high
+    /// bits of the pending balance re-encrypted under the destination ElGamal public key are equal
+    /// to the current low and high bits.
+    pub fn generate_proof_data(
+        &self,
+        source_elgamal_keypair: &ElGamalKeypair,
+        destination_elgamal_pubkey: &ElGamalPubkey,
+    ) -> Result<ForwardPendingBalanceProofData, TokenError> {
+        let generate_equality_proof_data =
+            |pending_balance: &EncryptedBalance| -> Result<_, TokenError> {
+                let pending_balance: ElGamalCiphertext = (*pending_balance)
+                    .try_into()
+                    .map_err(|_| TokenError::AccountDecryption)?;
+                let amount = source_elgamal_keypair
+                    .secret()
+                    .decrypt_u32(&pending_balance)
+                    .ok_or(TokenError::AccountDecryption)?;
+
+                let destination_opening = PedersenOpening::new_rand();
+                let destination_ciphertext =
+                    destination_elgamal_pubkey.encrypt_with(amount, &destination_opening);
+
+                let proof_data = CiphertextCiphertextEqualityProofData::new(
+                    source_elgamal_keypair,
+                    destination_elgamal_pubkey,
+                    &pending_balance,
+                    &destination_ciphertext,
+                    &destination_opening,
+                    amount,
+                )
+                .map_err(|_| TokenError::ProofGeneration)?;
+                Ok((proof_data, amount))
+            };
+
+        let (equality_proof_data_lo, amount_lo) =
+            generate_equality_proof_data(&self.pending_balance_lo)?;
+        let (equality_proof_data_hi, amount_hi) =
+            generate_equality_proof_data(&self.pending_balance_hi)?;
+
+        let forwarded_amount = amount_hi
+            .checked_shl(PENDING_BALANCE_LO_BIT_LENGTH)
+            .and_then(|amount_hi| amount_hi.checked_add(amount_lo))
+            .ok_or(TokenError::Overflow)?;
+
+        Ok(ForwardPendingBalanceProofData {
+            equality_proof_data_lo,
+            equality_proof_data_hi,
+            forwarded_amount,
+        })
+    }
+}
+
+/// The proof data required for a `ForwardPendingBalance` instruction
+pub struct ForwardPendingBalanceProofData {
+    /// Proof that the re-encrypted low bits of the pending balance are equal to the current ones
+    pub equality_proof_data_lo: CiphertextCiphertextEqualityProofData,
+    /// Proof that the re-encrypted high bits of the pending balance are equal to the current ones
+    pub equality_proof_data_hi: CiphertextCiphertextEqualityProofData,
+    /// The decrypted pending balance that is forwarded
+    pub forwarded_amount: u64,
+}
+
+/// Check that a decryptable balance is an encryption of zero under an authenticated encryption
+/// key.
+///
+/// The program stores the decryptable zero balance of a `ConfigureAccount` instruction as is, since
+/// authenticated encryption ciphertexts are randomized and cannot be checked on-chain. A client
+/// that did not encrypt the balance itself should verify it before submitting the instruction.
+pub fn verify_decryptable_zero_balance(
+    aes_key: &AeKey,
+    decryptable_zero_balance: &DecryptableBalance,
+) -> Result<(), TokenError> {
+    let decryptable_zero_balance: AeCiphertext = (*decryptable_zero_balance)
+        .try_into()
+        .map_err(|_| TokenError::AccountDecryption)?;
+    let decrypted_balance = aes_key
+        .decrypt(&decryptable_zero_balance)
+        .ok_or(TokenError::AccountDecryption)?;
+
+    if decrypted_balance != 0 {
+        return Err(TokenError::ConfidentialTransferBalanceMismatch);
+    }
+    Ok(())
+}
+
+/// Decrypt the amount of a confidential transfer with the auditor ElGamal secret key.
+///
+/// The low and high bits of the transfer amount are encrypted as transfer amount ciphertexts,
+/// which can be found in the context of the `VerifyTransfer` proof that accompanies a `Transfer`
+/// instruction.
+pub fn decrypt_transfer_amount_for_auditor(
+    auditor_elgamal_secret_key: &ElGamalSecretKey,
+    transfer_amount_ciphertext_lo: &TransferAmountCiphertext,
+    transfer_amount_ciphertext_hi: &TransferAmountCiphertext,
+) -> Result<u64, TokenError> {
+    let decrypt = |transfer_amount_ciphertext: &TransferAmountCiphertext| {
+        let auditor_ciphertext: ElGamalCiphertext =
+            transfer_amount_auditor_ciphertext(transfer_amount_ciphertext)
+                .try_into()
+                .map_err(|_| TokenError::AccountDecryption)?;
+        auditor_elgamal_secret_key
+            .decrypt_u32(&auditor_ciphertext)
+            .ok_or(TokenError::AccountDecryption)
+    };
+
+    let transfer_amount_lo = decrypt(transfer_amount_ciphertext_lo)?;
+    let transfer_amount_hi = decrypt(transfer_amount_ciphertext_hi)?;
+
+    combine_balances(transfer_amount_lo, transfer_amount_hi).ok_or(TokenError::AccountDecryption)
+}
+
+/// Decrypt an ElGamal ciphertext that encrypts an amount of at most `maximum_amount`.
+///
+/// The discrete log is solved over consecutive 32-bit ranges of amounts, each of which takes a
+/// bounded amount of time, and the search stops once the ranges exceed `maximum_amount`. Returns
+/// `None` if the ciphertext does not encrypt an amount in `0..=maximum_amount` under the secret
+/// key.
+pub fn decrypt_u32_with_max(
+    ciphertext: &ElGamalCiphertext,
+    elgamal_secret_key: &ElGamalSecretKey,
+    maximum_amount: u64,
+) -> Option<u64> {
+    const RANGE_LENGTH: u64 = 1 << 32;
+
+    let mut range_start = 0_u64;
+    while range_start <= maximum_amount {
+        let shifted_ciphertext = ciphertext.subtract_amount(range_start);
+        if let Some(offset) = elgamal_secret_key.decrypt_u32(&shifted_ciphertext) {
+            let amount = range_start.checked_add(offset)?;
+            return (amount <= maximum_amount).then_some(amount);
+        }
+        range_start = range_start.checked_add(RANGE_LENGTH)?;
+    }
+    None
+}
+
+/// Extract the transfer amount ciphertext encrypted under the auditor ElGamal public key.
+///
+/// A transfer amount ciphertext consists of the following 32-byte components that are serialized
+/// in order:
+///   1. The `commitment` component that encodes the transfer amount.
+///   2. The `decryption handle` component with respect to the source public key.
+///   3. The `decryption handle` component with respect to the destination public key.
+///   4. The `decryption handle` component with respect to the auditor public key.
+///
+/// An ElGamal ciphertext for the auditor consists of the `commitment` component and the
+/// `decryption handle` component with respect to the auditor public key.
+fn transfer_amount_auditor_ciphertext(
+    transfer_amount_ciphertext: &TransferAmountCiphertext,
+) -> PodElGamalCiphertext {
+    let transfer_amount_ciphertext_bytes = bytemuck::bytes_of(transfer_amount_ciphertext);
+
+    let mut auditor_ciphertext_bytes = [0u8; 64];
+    auditor_ciphertext_bytes[..32].copy_from_slice(&transfer_amount_ciphertext_bytes[..32]);
+    auditor_ciphertext_bytes[32..].copy_from_slice(&transfer_amount_ciphertext_bytes[96..128]);
+
+    PodElGamalCiphertext(auditor_ciphertext_bytes)
+}
+
 fn combine_balances(balance_lo: u64, balance_hi: u64) -> Option<u64> {
     balance_hi
         .checked_shl(PENDING_BALANCE_LO_BIT_LENGTH)?
         .checked_add(balance_lo)
 }
+
+/// Create the proof data needed for a `MintConfidential` instruction.
+///
+/// The low and high bits of the mint amount are encrypted as grouped ciphertexts under the
+/// destination and supply ElGamal public keys. The range proof certifies the bit lengths of the
+/// commitments of the grouped ciphertexts, along with a commitment to zero that pads the bit
+/// lengths to the 64 bits of a `VerifyBatchedRangeProofU64` proof.
+pub fn generate_mint_confidential_proof_data(
+    mint_amount: u64,
+    destination_elgamal_pubkey: &ElGamalPubkey,
+    supply_elgamal_pubkey: &ElGamalPubkey,
+) -> Result<MintConfidentialProofData, TokenError> {
+    if mint_amount > MAXIMUM_DEPOSIT_TRANSFER_AMOUNT {
+        return Err(TokenError::MaximumDepositAmountExceeded);
+    }
+    let mint_amount_lo = mint_amount & (u16::MAX as u64);
+    let mint_amount_hi = mint_amount >> MINT_AMOUNT_LO_BIT_LENGTH;
+
+    let opening_lo = PedersenOpening::new_rand();
+    let opening_hi = PedersenOpening::new_rand();
+    let grouped_ciphertext_lo = GroupedElGamal::encrypt_with(
+        [destination_elgamal_pubkey, supply_elgamal_pubkey],
+        mint_amount_lo,
+        &opening_lo,
+    );
+    let grouped_ciphertext_hi = GroupedElGamal::encrypt_with(
+        [destination_elgamal_pubkey, supply_elgamal_pubkey],
+        mint_amount_hi,
+        &opening_hi,
+    );
+
+    let ciphertext_validity_proof_data = BatchedGroupedCiphertext2HandlesValidityProofData::new(
+        destination_elgamal_pubkey,
+        supply_elgamal_pubkey,
+        &grouped_ciphertext_lo,
+        &grouped_ciphertext_hi,
+        mint_amount_lo,
+        mint_amount_hi,
+        &opening_lo,
+        &opening_hi,
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    let (padding_commitment, padding_opening) = Pedersen::new(0_u64);
+    let range_proof_data = BatchedRangeProofU64Data::new(
+        vec![
+            &grouped_ciphertext_lo.commitment,
+            &grouped_ciphertext_hi.commitment,
+            &padding_commitment,
+        ],
+        vec![mint_amount_lo, mint_amount_hi, 0],
+        vec![
+            MINT_AMOUNT_LO_BIT_LENGTH as usize,
+            MINT_AMOUNT_HI_BIT_LENGTH as usize,
+            (64 - MINT_AMOUNT_LO_BIT_LENGTH - MINT_AMOUNT_HI_BIT_LENGTH) as usize,
+        ],
+        vec![&opening_lo, &opening_hi, &padding_opening],
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    Ok(MintConfidentialProofData {
+        ciphertext_validity_proof_data,
+        range_proof_data,
+    })
+}
+
+/// The proof data required for a `MintConfidential` instruction
+pub struct MintConfidentialProofData {
+    /// Proof that the grouped ciphertexts of the low and high bits of the mint amount are valid
+    pub ciphertext_validity_proof_data: BatchedGroupedCiphertext2HandlesValidityProofData,
+    /// Proof that the low and high bits of the mint amount are in range
+    pub range_proof_data: BatchedRangeProofU64Data,
+}
+
+/// Check that the confidential supply of a mint encrypts the expected supply.
+///
+/// The confidential supply is generally too large to be decrypted. Instead, the expected supply is
+/// subtracted from it, and the result must decrypt to zero under the supply ElGamal secret key.
+pub fn verify_confidential_supply(
+    supply_elgamal_secret_key: &ElGamalSecretKey,
+    confidential_supply: &EncryptedBalance,
+    expected_supply: u64,
+) -> Result<bool, TokenError> {
+    let remaining_supply: ElGamalCiphertext =
+        ops::subtract_from(confidential_supply, expected_supply)
+            .ok_or(TokenError::AccountDecryption)?
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+
+    Ok(supply_elgamal_secret_key.decrypt_u32(&remaining_supply) == Some(0))
+}
+
+/// Create the ciphertext-ciphertext equality proof data that certifies that the low and high bits
+/// of the transfer amount of a transfer proof, re-encrypted under the ElGamal public key of an
+/// additional auditor, are equal to the transfer amount ciphertexts under the source ElGamal public
+/// key.
+pub fn generate_auditor_proof_data(
+    transfer_proof_data: &TransferData,
+    transfer_amount: u64,
+    source_elgamal_keypair: &ElGamalKeypair,
+    auditor_elgamal_pubkey: &ElGamalPubkey,
+) -> Result<AuditorProofData, TokenError> {
+    let generate_equality_proof_data = |transfer_amount_ciphertext: &TransferAmountCiphertext,
+                                        amount: u64|
+     -> Result<_, TokenError> {
+        let source_ciphertext: ElGamalCiphertext =
+            transfer_amount_source_ciphertext(transfer_amount_ciphertext)
+                .try_into()
+                .map_err(|_| TokenError::AccountDecryption)?;
+
+        let auditor_opening = PedersenOpening::new_rand();
+        let auditor_ciphertext = auditor_elgamal_pubkey.encrypt_with(amount, &auditor_opening);
+
+        CiphertextCiphertextEqualityProofData::new(
+            source_elgamal_keypair,
+            auditor_elgamal_pubkey,
+            &source_ciphertext,
+            &auditor_ciphertext,
+            &auditor_opening,
+            amount,
+        )
+        .map_err(|_| TokenError::ProofGeneration)
+    };
+
+    let transfer_amount_lo = transfer_amount & (u16::MAX as u64);
+    let transfer_amount_hi = transfer_amount >> PENDING_BALANCE_LO_BIT_LENGTH;
+
+    Ok(AuditorProofData {
+        equality_proof_data_lo: generate_equality_proof_data(
+            &transfer_proof_data.context.ciphertext_lo,
+            transfer_amount_lo,
+        )?,
+        equality_proof_data_hi: generate_equality_proof_data(
+            &transfer_proof_data.context.ciphertext_hi,
+            transfer_amount_hi,
+        )?,
+    })
+}
+
+/// The proof data required for an additional auditor of a `TransferWithAdditionalProofs`
+/// instruction
+pub struct AuditorProofData {
+    /// Proof that the low bits of the transfer amount are re-encrypted under the auditor ElGamal
+    /// public key
+    pub equality_proof_data_lo: CiphertextCiphertextEqualityProofData,
+    /// Proof that the high bits of the transfer amount are re-encrypted under the auditor ElGamal
+    /// public key
+    pub equality_proof_data_hi: CiphertextCiphertextEqualityProofData,
+}
+
+/// Create the proof data that certifies that the transfer amount of a delegated transfer does not
+/// exceed the remaining allowance of the delegate.
+///
+/// The remaining allowance minus the transfer amount is encrypted under the source ElGamal public
+/// key by subtracting the transfer amount ciphertexts of the transfer proof from the allowance
+/// ciphertext, exactly as the program does. The equality proof certifies that this ciphertext
+/// encrypts the same amount as a fresh commitment, and the range proof certifies that the committed
+/// amount is a 64-bit amount.
+pub fn generate_delegate_allowance_proof_data(
+    transfer_proof_data: &TransferData,
+    transfer_amount: u64,
+    allowance_ciphertext: &EncryptedBalance,
+    allowance: u64,
+    source_elgamal_keypair: &ElGamalKeypair,
+) -> Result<DelegateAllowanceProofData, TokenError> {
+    let new_allowance = allowance
+        .checked_sub(transfer_amount)
+        .ok_or(TokenError::InsufficientFunds)?;
+
+    let new_allowance_ciphertext: ElGamalCiphertext = ops::subtract_with_lo_hi(
+        allowance_ciphertext,
+        &transfer_amount_source_ciphertext(&transfer_proof_data.context.ciphertext_lo),
+        &transfer_amount_source_ciphertext(&transfer_proof_data.context.ciphertext_hi),
+    )
+    .ok_or(TokenError::ProofGeneration)?
+    .try_into()
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    let (new_allowance_commitment, new_allowance_opening) = Pedersen::new(new_allowance);
+
+    let equality_proof_data = CiphertextCommitmentEqualityProofData::new(
+        source_elgamal_keypair,
+        &new_allowance_ciphertext,
+        &new_allowance_commitment,
+        &new_allowance_opening,
+        new_allowance,
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    let range_proof_data = BatchedRangeProofU64Data::new(
+        vec![&new_allowance_commitment],
+        vec![new_allowance],
+        vec![DELEGATE_ALLOWANCE_RANGE_PROOF_BIT_LENGTH as usize],
+        vec![&new_allowance_opening],
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    Ok(DelegateAllowanceProofData {
+        equality_proof_data,
+        range_proof_data,
+    })
+}
+
+/// The proof data required for the allowance of a `DelegatedTransfer` instruction
+pub struct DelegateAllowanceProofData {
+    /// Proof that the remaining allowance minus the transfer amount, encrypted under the source
+    /// ElGamal public key, is equal to the committed amount of the range proof
+    pub equality_proof_data: CiphertextCommitmentEqualityProofData,
+    /// Proof that the remaining allowance minus the transfer amount is a 64-bit amount
+    pub range_proof_data: BatchedRangeProofU64Data,
+}
+
+/// Create the proof data that certifies that the transfer amount of a transfer proof does not
+/// exceed the maximum transfer amount of the mint.
+///
+/// The maximum transfer amount minus the transfer amount is encrypted under the source ElGamal
+/// public key by subtracting the transfer amount ciphertexts of the transfer proof from the maximum
+/// transfer amount, exactly as the program does. The equality proof certifies that this ciphertext
+/// encrypts the same amount as a fresh commitment, and the range proof certifies that the committed
+/// amount is a 64-bit amount.
+pub fn generate_maximum_transfer_amount_proof_data(
+    transfer_proof_data: &TransferData,
+    transfer_amount: u64,
+    maximum_transfer_amount: u64,
+    source_elgamal_keypair: &ElGamalKeypair,
+) -> Result<MaximumTransferAmountProofData, TokenError> {
+    let remaining_amount = maximum_transfer_amount
+        .checked_sub(transfer_amount)
+        .ok_or(TokenError::ConfidentialTransferMaximumAmountExceeded)?;
+
+    let maximum_transfer_amount_ciphertext =
+        ops::add_to(&PodElGamalCiphertext::zeroed(), maximum_transfer_amount)
+            .ok_or(TokenError::ProofGeneration)?;
+    let remaining_amount_ciphertext: ElGamalCiphertext = ops::subtract_with_lo_hi(
+        &maximum_transfer_amount_ciphertext,
+        &transfer_amount_source_ciphertext(&transfer_proof_data.context.ciphertext_lo),
+        &transfer_amount_source_ciphertext(&transfer_proof_data.context.ciphertext_hi),
+    )
+    .ok_or(TokenError::ProofGeneration)?
+    .try_into()
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    let (remaining_amount_commitment, remaining_amount_opening) = Pedersen::new(remaining_amount);
+
+    let equality_proof_data = CiphertextCommitmentEqualityProofData::new(
+        source_elgamal_keypair,
+        &remaining_amount_ciphertext,
+        &remaining_amount_commitment,
+        &remaining_amount_opening,
+        remaining_amount,
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    let range_proof_data = BatchedRangeProofU64Data::new(
+        vec![&remaining_amount_commitment],
+        vec![remaining_amount],
+        vec![MAXIMUM_TRANSFER_AMOUNT_RANGE_PROOF_BIT_LENGTH as usize],
+        vec![&remaining_amount_opening],
+    )
+    .map_err(|_| TokenError::ProofGeneration)?;
+
+    Ok(MaximumTransferAmountProofData {
+        equality_proof_data,
+        range_proof_data,
+    })
+}
+
+/// The proof data required for the maximum transfer amount of a `TransferWithAdditionalProofs`
+/// instruction
+pub struct MaximumTransferAmountProofData {
+    /// Proof that the maximum transfer amount minus the transfer amount, encrypted under the source
+    /// ElGamal public key, is equal to the committed amount of the range proof
+    pub equality_proof_data: CiphertextCommitmentEqualityProofData,
+    /// Proof that the maximum transfer amount minus the transfer amount is a 64-bit amount
+    pub range_proof_data: BatchedRangeProofU64Data,
+}
+
+/// Decrypt the amount of a confidential transfer with the ElGamal secret key of an additional
+/// auditor.
+///
+/// The low and high bits of the transfer amount are the destination ciphertexts of the
+/// `VerifyCiphertextCiphertextEquality` proofs for the auditor that accompany a
+/// `TransferWithAdditionalProofs` instruction.
+pub fn decrypt_transfer_amount_for_additional_auditor(
+    auditor_elgamal_secret_key: &ElGamalSecretKey,
+    auditor_ciphertext_lo: &PodElGamalCiphertext,
+    auditor_ciphertext_hi: &PodElGamalCiphertext,
+) -> Result<u64, TokenError> {
+    let decrypt = |auditor_ciphertext: &PodElGamalCiphertext| {
+        let auditor_ciphertext: ElGamalCiphertext = (*auditor_ciphertext)
+            .try_into()
+            .map_err(|_| TokenError::AccountDecryption)?;
+        auditor_elgamal_secret_key
+            .decrypt_u32(&auditor_ciphertext)
+            .ok_or(TokenError::AccountDecryption)
+    };
+
+    let transfer_amount_lo = decrypt(auditor_ciphertext_lo)?;
+    let transfer_amount_hi = decrypt(auditor_ciphertext_hi)?;
+
+    combine_balances(transfer_amount_lo, transfer_amount_hi).ok_or(TokenError::AccountDecryption)
+}
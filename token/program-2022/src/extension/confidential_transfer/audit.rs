@@ -0,0 +1,116 @@
+use {
+    crate::{
+        extension::confidential_transfer::{
+            ConfidentialTransferAccount, DecryptableBalance, EncryptedBalance,
+        },
+        pod::{pod_from_bytes, PodBool, PodU64},
+    },
+    bytemuck::{Pod, Zeroable},
+    solana_program::program_error::ProgramError,
+    solana_zk_token_sdk::zk_token_elgamal::pod::ElGamalPubkey,
+};
+
+/// Version of the audit record layout produced by `ConfidentialTransferAccount::export_audit_record`
+pub const AUDIT_RECORD_VERSION: u8 = 1;
+
+/// Versioned snapshot of the confidential state of a token account for archival
+///
+/// The layout of this record is fixed for a given `version`, independently of the layout of the
+/// `ConfidentialTransferAccount` extension, so records remain parseable if the extension changes.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferAuditRecord {
+    /// Layout version of the record, currently `AUDIT_RECORD_VERSION`
+    pub version: u8,
+    /// Whether the account was approved for confidential transfers
+    pub approved: PodBool,
+    /// The ElGamal public key of the account
+    pub elgamal_pubkey: ElGamalPubkey,
+    /// The low 16 bits of the pending balance
+    pub pending_balance_lo: EncryptedBalance,
+    /// The high 48 bits of the pending balance
+    pub pending_balance_hi: EncryptedBalance,
+    /// The available balance
+    pub available_balance: EncryptedBalance,
+    /// The decryptable available balance
+    pub decryptable_available_balance: DecryptableBalance,
+    /// The number of credits to the pending balance since it was last applied
+    pub pending_balance_credit_counter: PodU64,
+    /// The maximum number of credits to the pending balance before it must be applied
+    pub maximum_pending_balance_credit_counter: PodU64,
+    /// The expected pending balance credit counter of the last `ApplyPendingBalance`
+    pub expected_pending_balance_credit_counter: PodU64,
+    /// The actual pending balance credit counter when the last `ApplyPendingBalance` executed
+    pub actual_pending_balance_credit_counter: PodU64,
+}
+
+impl ConfidentialTransferAuditRecord {
+    /// Serialize the record
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bytemuck::bytes_of(self).to_vec()
+    }
+
+    /// Parse a record serialized with `to_bytes`, failing if it is not of a known version
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ProgramError> {
+        match bytes.first() {
+            Some(&AUDIT_RECORD_VERSION) => pod_from_bytes::<Self>(bytes).map(|record| *record),
+            _ => Err(ProgramError::InvalidAccountData),
+        }
+    }
+}
+
+impl ConfidentialTransferAccount {
+    /// Export the confidential state of the account as a versioned audit record
+    pub fn export_audit_record(&self) -> ConfidentialTransferAuditRecord {
+        ConfidentialTransferAuditRecord {
+            version: AUDIT_RECORD_VERSION,
+            approved: self.approved,
+            elgamal_pubkey: self.elgamal_pubkey,
+            pending_balance_lo: self.pending_balance_lo,
+            pending_balance_hi: self.pending_balance_hi,
+            available_balance: self.available_balance,
+            decryptable_available_balance: self.decryptable_available_balance,
+            pending_balance_credit_counter: self.pending_balance_credit_counter,
+            maximum_pending_balance_credit_counter: self.maximum_pending_balance_credit_counter,
+            expected_pending_balance_credit_counter: self.expected_pending_balance_credit_counter,
+            actual_pending_balance_credit_counter: self.actual_pending_balance_credit_counter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_record_round_trip() {
+        let account = ConfidentialTransferAccount {
+            approved: true.into(),
+            pending_balance_credit_counter: 3.into(),
+            maximum_pending_balance_credit_counter: 65536.into(),
+            expected_pending_balance_credit_counter: 2.into(),
+            actual_pending_balance_credit_counter: 2.into(),
+            ..ConfidentialTransferAccount::default()
+        };
+
+        let record = account.export_audit_record();
+        assert_eq!(record.version, AUDIT_RECORD_VERSION);
+
+        let bytes = record.to_bytes();
+        assert_eq!(
+            ConfidentialTransferAuditRecord::from_bytes(&bytes).unwrap(),
+            record
+        );
+
+        let mut unknown_version = bytes.clone();
+        unknown_version[0] = AUDIT_RECORD_VERSION + 1;
+        assert_eq!(
+            ConfidentialTransferAuditRecord::from_bytes(&unknown_version),
+            Err(ProgramError::InvalidAccountData)
+        );
+        assert_eq!(
+            ConfidentialTransferAuditRecord::from_bytes(&bytes[..bytes.len() - 1]),
+            Err(ProgramError::InvalidArgument)
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Extraction of ElGamal ciphertexts from grouped transfer and fee ciphertexts
+//!
+//! The functions in this module only rearrange bytes and depend on nothing but `core`, so that
+//! off-chain verifiers and embedded or HSM environments can vendor the module and extract
+//! ciphertexts exactly as the program does, without `std` or `solana_program`. The processor
+//! applies them to the pod types of the zero-knowledge proof contexts.
+//!
+//! A grouped ciphertext consists of 32-byte components that are serialized in order: the
+//! Pedersen `commitment` that encodes the amount, followed by one `decryption handle` per public
+//! key. An ElGamal ciphertext for one of the public keys consists of the `commitment` followed by
+//! the `decryption handle` with respect to that public key.
+
+/// Byte length of a Pedersen commitment or a decryption handle
+pub const CIPHERTEXT_COMPONENT_LEN: usize = 32;
+
+/// Byte length of an ElGamal ciphertext
+pub const ELGAMAL_CIPHERTEXT_LEN: usize = 2 * CIPHERTEXT_COMPONENT_LEN;
+
+/// Byte length of a transfer amount ciphertext, which has decryption handles for the source,
+/// destination, and auditor public keys in that order
+pub const TRANSFER_AMOUNT_CIPHERTEXT_LEN: usize = 4 * CIPHERTEXT_COMPONENT_LEN;
+
+/// Byte length of a fee ciphertext, which has decryption handles for the destination and withdraw
+/// withheld authority public keys in that order
+pub const FEE_CIPHERTEXT_LEN: usize = 3 * CIPHERTEXT_COMPONENT_LEN;
+
+/// Combine the commitment of a grouped ciphertext with its decryption handle at `handle_index`
+fn extract_ciphertext(
+    grouped_ciphertext: &[u8],
+    handle_index: usize,
+) -> [u8; ELGAMAL_CIPHERTEXT_LEN] {
+    let handle_start = (handle_index + 1) * CIPHERTEXT_COMPONENT_LEN;
+
+    let mut ciphertext = [0u8; ELGAMAL_CIPHERTEXT_LEN];
+    ciphertext[..CIPHERTEXT_COMPONENT_LEN]
+        .copy_from_slice(&grouped_ciphertext[..CIPHERTEXT_COMPONENT_LEN]);
+    ciphertext[CIPHERTEXT_COMPONENT_LEN..].copy_from_slice(
+        &grouped_ciphertext[handle_start..handle_start + CIPHERTEXT_COMPONENT_LEN],
+    );
+    ciphertext
+}
+
+/// Extract the transfer amount ciphertext encrypted under the source ElGamal public key
+pub fn transfer_amount_source_ciphertext(
+    transfer_amount_ciphertext: &[u8; TRANSFER_AMOUNT_CIPHERTEXT_LEN],
+) -> [u8; ELGAMAL_CIPHERTEXT_LEN] {
+    extract_ciphertext(transfer_amount_ciphertext, 0)
+}
+
+/// Extract the transfer amount ciphertext encrypted under the destination ElGamal public key
+pub fn transfer_amount_destination_ciphertext(
+    transfer_amount_ciphertext: &[u8; TRANSFER_AMOUNT_CIPHERTEXT_LEN],
+) -> [u8; ELGAMAL_CIPHERTEXT_LEN] {
+    extract_ciphertext(transfer_amount_ciphertext, 1)
+}
+
+/// Extract the fee ciphertext encrypted under the destination ElGamal public key
+pub fn fee_amount_destination_ciphertext(
+    fee_ciphertext: &[u8; FEE_CIPHERTEXT_LEN],
+) -> [u8; ELGAMAL_CIPHERTEXT_LEN] {
+    extract_ciphertext(fee_ciphertext, 0)
+}
+
+/// Extract the fee ciphertext encrypted under the withdraw withheld authority ElGamal public key
+pub fn fee_amount_withdraw_withheld_authority_ciphertext(
+    fee_ciphertext: &[u8; FEE_CIPHERTEXT_LEN],
+) -> [u8; ELGAMAL_CIPHERTEXT_LEN] {
+    extract_ciphertext(fee_ciphertext, 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A grouped ciphertext whose `i`-th component is filled with the byte `i + 1`
+    fn grouped_ciphertext<const N: usize>() -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, component) in bytes.chunks_mut(CIPHERTEXT_COMPONENT_LEN).enumerate() {
+            component.fill(i as u8 + 1);
+        }
+        bytes
+    }
+
+    fn elgamal_ciphertext(commitment: u8, handle: u8) -> [u8; ELGAMAL_CIPHERTEXT_LEN] {
+        let mut bytes = [commitment; ELGAMAL_CIPHERTEXT_LEN];
+        bytes[CIPHERTEXT_COMPONENT_LEN..].fill(handle);
+        bytes
+    }
+
+    #[test]
+    fn test_transfer_amount_ciphertext_offsets() {
+        let transfer_amount_ciphertext = grouped_ciphertext::<TRANSFER_AMOUNT_CIPHERTEXT_LEN>();
+
+        assert_eq!(
+            transfer_amount_source_ciphertext(&transfer_amount_ciphertext),
+            elgamal_ciphertext(1, 2)
+        );
+        assert_eq!(
+            transfer_amount_destination_ciphertext(&transfer_amount_ciphertext),
+            elgamal_ciphertext(1, 3)
+        );
+    }
+
+    #[test]
+    fn test_fee_ciphertext_offsets() {
+        let fee_ciphertext = grouped_ciphertext::<FEE_CIPHERTEXT_LEN>();
+
+        assert_eq!(
+            fee_amount_destination_ciphertext(&fee_ciphertext),
+            elgamal_ciphertext(1, 2)
+        );
+        assert_eq!(
+            fee_amount_withdraw_withheld_authority_ciphertext(&fee_ciphertext),
+            elgamal_ciphertext(1, 3)
+        );
+    }
+}
@@ -7,12 +7,16 @@ use {
     crate::{
         check_program_account,
         extension::confidential_transfer::*,
-        instruction::{encode_instruction, TokenInstruction},
+        instruction::{
+            decode_instruction_type, decode_versioned_instruction_data, encode_instruction,
+            encode_versioned_instruction, TokenInstruction,
+        },
         proof::ProofLocation,
     },
     bytemuck::{Pod, Zeroable},
     num_enum::{IntoPrimitive, TryFromPrimitive},
     solana_program::{
+        account_info::AccountInfo,
         instruction::{AccountMeta, Instruction},
         program_error::ProgramError,
         pubkey::Pubkey,
@@ -20,6 +24,27 @@ use {
     },
 };
 
+/// Layout version of the instruction data of the versioned confidential transfer instructions
+///
+/// The data of every instruction that follows `DisableNonConfidentialCredits` in
+/// `ConfidentialTransferInstruction` is prefixed with this version. These are the instructions
+/// that were added on top of the original extension, whose data layouts are still expected to
+/// change as the extension gains features such as additional auditors or a maximum transfer
+/// amount. The program rejects any other version with
+/// `TokenError::UnsupportedInstructionDataVersion` instead of misparsing data that was encoded
+/// for a different layout. New instructions are versioned as well.
+///
+/// The data of the original instructions, from `InitializeMint` through
+/// `DisableNonConfidentialCredits`, is unversioned and keeps its original layout, since existing
+/// clients encode these instructions and a version prefix would break them. A new field for one of
+/// these instructions requires a new, versioned instruction instead.
+///
+/// Migration: clients that encode the versioned instructions without the builders in this module
+/// must insert the version byte between the `ConfidentialTransferInstruction` type and the
+/// instruction data. Instructions encoded without it fail to decode rather than being interpreted
+/// with a shifted layout.
+pub const CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION: u8 = 1;
+
 /// Confidential Transfer extension instructions
 #[derive(Clone, Copy, Debug, TryFromPrimitive, IntoPrimitive)]
 #[repr(u8)]
@@ -211,6 +236,15 @@ pub enum ConfidentialTransferInstruction {
     ///
     /// Fails if the associated mint is extended as `NonTransferable`.
     ///
+    /// If the source and destination accounts are the same, the transfer amount is credited
+    /// directly back to the available balance instead of the pending balance. A self-transfer
+    /// therefore leaves the balances of the account unchanged and does not count towards the
+    /// maximum pending balance credit counter, and the new decryptable available balance in the
+    /// instruction data must encrypt the unchanged available balance.
+    ///
+    /// The transfer must be authorized by the owner of the source account. A delegate approved
+    /// with `ApproveConfidentialDelegate` transfers with `DelegatedTransfer` instead.
+    ///
     ///   * Single owner/delegate
     ///   1. `[writable]` The source SPL Token account.
     ///   2. `[writable]` The destination SPL Token account.
@@ -245,6 +279,10 @@ pub enum ConfidentialTransferInstruction {
     /// with `ConfidentialTransferAccount::available_balance`. If they differ then there is more
     /// pending balance to be applied.
     ///
+    /// Fails with `InvalidPendingBalanceCreditCounter` if the expected pending balance credit
+    /// counter exceeds the current `ConfidentialTransferAccount::pending_balance_credit_counter`,
+    /// and with `AccountFrozen` if the account is frozen.
+    ///
     /// Account expected by this instruction:
     ///
     ///   * Single owner/delegate
@@ -343,183 +381,2687 @@ pub enum ConfidentialTransferInstruction {
     ///   None
     ///
     DisableNonConfidentialCredits,
-}
-
-/// Data expected by `ConfidentialTransferInstruction::InitializeMint`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct InitializeMintData {
-    /// Authority to modify the `ConfidentialTransferMint` configuration and to approve new
-    /// accounts.
-    pub authority: OptionalNonZeroPubkey,
-    /// Determines if newly configured accounts must be approved by the `authority` before they may
-    /// be used by the user.
-    pub auto_approve_new_accounts: PodBool,
-    /// New authority to decode any transfer amount in a confidential transfer.
-    pub auditor_elgamal_pubkey: OptionalNonZeroElGamalPubkey,
-}
 
-/// Data expected by `ConfidentialTransferInstruction::UpdateMint`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct UpdateMintData {
-    /// Determines if newly configured accounts must be approved by the `authority` before they may
-    /// be used by the user.
-    pub auto_approve_new_accounts: PodBool,
-    /// New authority to decode any transfer amount in a confidential transfer.
-    pub auditor_elgamal_pubkey: OptionalNonZeroElGamalPubkey,
-}
+    /// Mint tokens directly into the pending balance of a confidential token account.
+    ///
+    /// The mint amount is never revealed on-chain. It is encrypted as a pair of lo and hi grouped
+    /// ciphertexts under the destination ElGamal public key and the supply ElGamal public key of
+    /// the `ConfidentialMintSupply` extension. The ciphertexts under the destination ElGamal
+    /// public key are credited to the destination pending balance, and the ciphertexts under the
+    /// supply ElGamal public key are added to the confidential supply of the mint. The supply of
+    /// the mint is not updated.
+    ///
+    /// Fails if the destination account is frozen.
+    /// Fails if the associated mint is extended as `NonTransferable`.
+    /// Fails if the mint is not extended with `ConfidentialMintSupply`.
+    /// Fails if the mint has an auditor ElGamal public key that is not the supply ElGamal public
+    /// key.
+    ///
+    /// In order for this instruction to be successfully processed, the
+    /// `VerifyBatchedGroupedCiphertext2HandlesValidity` proof that the grouped ciphertexts are
+    /// valid and the `VerifyBatchedRangeProofU64` proof that the lo and hi bits of the mint amount
+    /// are `MINT_AMOUNT_LO_BIT_LENGTH` and `MINT_AMOUNT_HI_BIT_LENGTH` bit amounts must be
+    /// pre-verified into context state accounts.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The destination SPL Token account.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[]` Context state account for `VerifyBatchedGroupedCiphertext2HandlesValidity`.
+    ///   3. `[]` Context state account for `VerifyBatchedRangeProofU64`.
+    ///   4. `[signer]` The mint's minting authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The destination SPL Token account.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[]` Context state account for `VerifyBatchedGroupedCiphertext2HandlesValidity`.
+    ///   3. `[]` Context state account for `VerifyBatchedRangeProofU64`.
+    ///   4. `[]` The mint's multisig minting authority.
+    ///   5.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `MintConfidentialInstructionData`
+    ///
+    MintConfidential,
 
-/// Data expected by `ConfidentialTransferInstruction::ConfigureAccount`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct ConfigureAccountInstructionData {
-    /// The decryptable balance (always 0) once the configure account succeeds
-    pub decryptable_zero_balance: DecryptableBalance,
-    /// The maximum number of despots and transfers that an account can receiver before the
-    /// `ApplyPendingBalance` is executed
-    pub maximum_pending_balance_credit_counter: PodU64,
-    /// Relative location of the `ProofInstruction::ZeroBalanceProof` instruction to the
-    /// `ConfigureAccount` instruction in the transaction. If the offset is `0`, then use a context
-    /// state account for the proof.
-    pub proof_instruction_offset: i8,
-}
+    /// Initializes the confidential supply of a mint.
+    ///
+    /// The `ConfidentialTransferInstruction::InitializeConfidentialMintSupply` instruction
+    /// requires no signers and MUST be included within the same Transaction as
+    /// `TokenInstruction::InitializeMint`. Otherwise another party can initialize the
+    /// configuration.
+    ///
+    /// The instruction fails if the `TokenInstruction::InitializeMint` instruction has already
+    /// executed for the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `InitializeConfidentialMintSupplyData`
+    ///
+    InitializeConfidentialMintSupply,
 
-/// Data expected by `ConfidentialTransferInstruction::EmptyAccount`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct EmptyAccountInstructionData {
-    /// Relative location of the `ProofInstruction::VerifyCloseAccount` instruction to the
-    /// `EmptyAccount` instruction in the transaction. If the offset is `0`, then use a context
-    /// state account for the proof.
-    pub proof_instruction_offset: i8,
-}
+    /// Burn SPL Tokens from the available balance of a confidential token account.
+    ///
+    /// The burn amount is deducted from the available balance ciphertext and from the supply of
+    /// the mint. If the mint is extended with `ConfidentialMintSupply`, the burn amount is
+    /// deducted from the confidential supply instead. Unlike `Withdraw`, the non-confidential
+    /// `amount` of the token account is not credited.
+    ///
+    /// Fails if the source account is frozen.
+    /// Fails if the associated mint is extended as `NonTransferable`.
+    ///
+    /// In order for this instruction to be successfully processed, it must be accompanied by the
+    /// `VerifyWithdraw` instruction of the `zk_token_proof` program in the same transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[]` Instructions sysvar if `WithdrawProof` is included in the same transaction or
+    ///      context state account if `WithdrawProof` is pre-verified into a context state account.
+    ///   3. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[writable]` The token mint.
+    ///   2. `[]` Instructions sysvar if `WithdrawProof` is included in the same transaction or
+    ///      context state account if `WithdrawProof` is pre-verified into a context state account.
+    ///   3. `[]` The multisig source account owner.
+    ///   4.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `BurnConfidentialInstructionData`
+    ///
+    BurnConfidential,
 
-/// Data expected by `ConfidentialTransferInstruction::Deposit`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct DepositInstructionData {
-    /// The amount of tokens to deposit
-    pub amount: PodU64,
-    /// Expected number of base 10 digits to the right of the decimal place
-    pub decimals: u8,
-}
+    /// Rotate the ElGamal public key associated with a confidential token account.
+    ///
+    /// The available balance of the account is replaced by a re-encryption of the same amount
+    /// under the new ElGamal public key. The pending balance of the account must be empty, so
+    /// `ApplyPendingBalance` must be executed before this instruction whenever the account has
+    /// pending credits.
+    ///
+    /// Fails if the token account is frozen.
+    ///
+    /// In order for this instruction to be successfully processed, it must be accompanied by the
+    /// `VerifyCiphertextCiphertextEquality` instruction of the `zk_token_proof` program in the
+    /// same transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` Instructions sysvar if `CiphertextCiphertextEquality` is included in the same
+    ///      transaction or context state account if `CiphertextCiphertextEquality` is
+    ///      pre-verified into a context state account.
+    ///   2. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` Instructions sysvar if `CiphertextCiphertextEquality` is included in the same
+    ///      transaction or context state account if `CiphertextCiphertextEquality` is
+    ///      pre-verified into a context state account.
+    ///   2. `[]` The multisig source account owner.
+    ///   3.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `RotateElGamalKeyInstructionData`
+    ///
+    RotateElGamalKey,
 
-/// Data expected by `ConfidentialTransferInstruction::Withdraw`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct WithdrawInstructionData {
-    /// The amount of tokens to withdraw
-    pub amount: PodU64,
-    /// Expected number of base 10 digits to the right of the decimal place
-    pub decimals: u8,
-    /// The new decryptable balance if the withdrawal succeeds
-    pub new_decryptable_available_balance: DecryptableBalance,
-    /// Relative location of the `ProofInstruction::VerifyWithdraw` instruction to the `Withdraw`
-    /// instruction in the transaction. If the offset is `0`, then use a context state account for
-    /// the proof.
-    pub proof_instruction_offset: i8,
-}
+    /// Initializes the additional auditors of a confidential transfer mint.
+    ///
+    /// The `ConfidentialTransferInstruction::InitializeAuditors` instruction requires no signers
+    /// and MUST be included within the same Transaction as `TokenInstruction::InitializeMint`.
+    /// Otherwise another party can initialize the configuration.
+    ///
+    /// The instruction fails if the `TokenInstruction::InitializeMint` instruction has already
+    /// executed for the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `AuditorsInstructionData`
+    ///
+    InitializeAuditors,
 
-/// Data expected by `ConfidentialTransferInstruction::Transfer`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct TransferInstructionData {
-    /// The new source decryptable balance if the transfer succeeds
-    pub new_source_decryptable_available_balance: DecryptableBalance,
-    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
-    /// `Transfer` instruction in the transaction. If the offset is `0`, then use a context state
-    /// account for the proof.
-    pub proof_instruction_offset: i8,
-}
+    /// Updates the additional auditors of a confidential transfer mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[signer]` Confidential transfer mint authority.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `AuditorsInstructionData`
+    ///
+    UpdateAuditors,
 
-/// Data expected by `ConfidentialTransferInstruction::ApplyPendingBalance`
-#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
-#[repr(C)]
-pub struct ApplyPendingBalanceData {
-    /// The expected number of pending balance credits since the last successful
-    /// `ApplyPendingBalance` instruction
-    pub expected_pending_balance_credit_counter: PodU64,
-    /// The new decryptable balance if the pending balance is applied successfully
-    pub new_decryptable_available_balance: DecryptableBalance,
-}
+    /// Transfer tokens confidentially with the additional proofs required by the mint.
+    ///
+    /// This instruction behaves exactly like `Transfer`, and additionally verifies a
+    /// `VerifyCiphertextCiphertextEquality` proof for each of the low and high bits of the
+    /// transfer amount per additional auditor of the `ConfidentialTransferAuditors` extension of
+    /// the mint. Each proof re-encrypts the transfer amount ciphertext under the source ElGamal
+    /// public key to the ElGamal public key of the auditor, which decrypts the destination
+    /// ciphertext of the proof context.
+    ///
+    /// If the mint has a `ConfidentialTransferMaximumTransferAmount` extension, the auditor proofs
+    /// are followed by a `VerifyCiphertextCommitmentEquality` proof for the maximum transfer
+    /// amount minus the transfer amount, encrypted under the source ElGamal public key, and a
+    /// `VerifyBatchedRangeProofU64` proof for the commitment of the equality proof.
+    ///
+    /// The additional proofs must be pre-verified into context state accounts, which are expected
+    /// in the order of the auditors, the low bits proof before the high bits proof, followed by
+    /// the equality and range proofs of the maximum transfer amount.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` or `TransferWithFeeProof` is included in
+    ///      the same transaction or context state account if the proof is pre-verified into a
+    ///      context state account.
+    ///   4..4+2N. `[]` The context state accounts of the additional proofs, a pair for each
+    ///      additional auditor and for the maximum transfer amount.
+    ///   4+2N. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar or context state account for the transfer proof.
+    ///   4..4+2N. `[]` The context state accounts of the additional proofs.
+    ///   4+2N. `[]` The multisig source account owner.
+    ///   5+2N.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `TransferWithAdditionalProofsInstructionData`
+    ///
+    TransferWithAdditionalProofs,
 
-/// Create a `InitializeMint` instruction
-#[cfg(not(target_os = "solana"))]
-pub fn initialize_mint(
-    token_program_id: &Pubkey,
-    mint: &Pubkey,
-    authority: Option<Pubkey>,
-    auto_approve_new_accounts: bool,
-    auditor_elgamal_pubkey: Option<ElGamalPubkey>,
-) -> Result<Instruction, ProgramError> {
-    check_program_account(token_program_id)?;
-    let accounts = vec![AccountMeta::new(*mint, false)];
+    /// Transfer tokens confidentially using split proofs.
+    ///
+    /// This instruction is equivalent to `Transfer`, but instead of a single `VerifyTransfer`
+    /// proof, it consumes three smaller proofs that must each be pre-verified into a context state
+    /// account:
+    ///   * a `VerifyCiphertextCommitmentEquality` proof for the new source available balance,
+    ///   * a `VerifyBatchedGroupedCiphertext2HandlesValidity` proof for the transfer amount
+    ///     ciphertexts, and
+    ///   * a `VerifyBatchedRangeProofU128` proof for the new source available balance and the
+    ///     transfer amount, and
+    ///   * two `VerifyCiphertextCommitmentEquality` proofs for the low and high bits of the
+    ///     transfer amount encrypted under the source ElGamal public key. Each proof must be for the
+    ///     commitment of the corresponding grouped transfer amount ciphertext.
+    ///
+    /// Fails if the associated mint is extended as `NonTransferable`.
+    /// Fails if the associated mint is extended for transfer fees and the transfer is not a
+    /// self-transfer.
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Context state account for `VerifyCiphertextCommitmentEquality`.
+    ///   4. `[]` Context state account for `VerifyBatchedGroupedCiphertext2HandlesValidity`.
+    ///   5. `[]` Context state account for `VerifyBatchedRangeProofU128`.
+    ///   6. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the low bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   7. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the high bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   8. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Context state account for `VerifyCiphertextCommitmentEquality`.
+    ///   4. `[]` Context state account for `VerifyBatchedGroupedCiphertext2HandlesValidity`.
+    ///   5. `[]` Context state account for `VerifyBatchedRangeProofU128`.
+    ///   6. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the low bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   7. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the high bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   8. `[]` The multisig source account owner.
+    ///   9.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `TransferWithSplitProofsInstructionData`
+    ///
+    TransferWithSplitProofs,
 
-    Ok(encode_instruction(
-        token_program_id,
-        accounts,
-        TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::InitializeMint,
-        &InitializeMintData {
-            authority: authority.try_into()?,
-            auto_approve_new_accounts: auto_approve_new_accounts.into(),
-            auditor_elgamal_pubkey: auditor_elgamal_pubkey.try_into()?,
-        },
-    ))
-}
+    /// Withdraw SPL Tokens from the available balance of a confidential token account into the
+    /// non-confidential balance of a different token account.
+    ///
+    /// This instruction is equivalent to `Withdraw`, except that the withdrawn amount is credited
+    /// to the destination account instead of the source account. The destination account does not
+    /// need to be configured for confidential transfers.
+    ///
+    /// Fails if the source or destination accounts are frozen.
+    /// Fails if the source and destination accounts are not associated with the same mint.
+    /// Fails if the associated mint is extended as `NonTransferable`.
+    ///
+    /// In order for this instruction to be successfully processed, it must be accompanied by the
+    /// `VerifyWithdraw` instruction of the `zk_token_proof` program in the same transaction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `WithdrawProof` is included in the same transaction or
+    ///      context state account if `WithdrawProof` is pre-verified into a context state account.
+    ///   4. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `WithdrawProof` is included in the same transaction or
+    ///      context state account if `WithdrawProof` is pre-verified into a context state account.
+    ///   4. `[]` The multisig source account owner.
+    ///   5.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `WithdrawInstructionData`
+    ///
+    WithdrawTo,
 
-/// Create a `UpdateMint` instruction
-#[cfg(not(target_os = "solana"))]
-pub fn update_mint(
-    token_program_id: &Pubkey,
-    mint: &Pubkey,
-    authority: &Pubkey,
-    multisig_signers: &[&Pubkey],
+    /// Configure a confidential extension account to accept incoming confidential transfers until
+    /// a given slot.
+    ///
+    /// Incoming confidential transfers and deposits are accepted before `slot` and rejected from
+    /// `slot` onward, which lets account owners schedule deposit windows in advance. A `slot` of
+    /// `0` accepts incoming confidential transfers indefinitely. The `EnableConfidentialCredits`
+    /// and `DisableConfidentialCredits` instructions clear any scheduled slot.
+    ///
+    /// The slot is stored in the `ConfidentialTransferCreditsUntilSlot` account extension, which
+    /// is initialized on the first nonzero `slot`. The account must have enough space for the
+    /// extension, for example by a prior `Reallocate` instruction.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The multisig account owner.
+    ///   2.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `EnableConfidentialCreditsUntilSlotData`
+    ///
+    EnableConfidentialCreditsUntilSlot,
+
+    /// Update the maximum number of `Deposit` and `Transfer` instructions that can credit the
+    /// pending balance of a confidential extension account before the `ApplyPendingBalance`
+    /// instruction is executed.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The multisig account owner.
+    ///   2.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `UpdateMaxPendingBalanceCreditCounterData`
+    ///
+    UpdateMaxPendingBalanceCreditCounter,
+
+    /// Transfer tokens confidentially using split proofs with separate ciphertext validity proofs
+    /// for the low and high bits of the transfer amount.
+    ///
+    /// This instruction is equivalent to `TransferWithSplitProofs`, but instead of a single
+    /// `VerifyBatchedGroupedCiphertext2HandlesValidity` proof, it consumes two
+    /// `VerifyGroupedCiphertext2HandlesValidity` proofs, one for each of the low and high transfer
+    /// amount ciphertexts. Each ciphertext validity proof can either be included in the same
+    /// transaction, referenced by its relative instruction offset, or pre-verified into a context
+    /// state account. The `VerifyCiphertextCommitmentEquality` and `VerifyBatchedRangeProofU128`
+    /// proofs, as well as the two `VerifyCiphertextCommitmentEquality` proofs for the transfer
+    /// amount under the source ElGamal public key, must be pre-verified into context state
+    /// accounts.
+    ///
+    /// Fails if the associated mint is extended as `NonTransferable`.
+    /// Fails if the associated mint is extended for transfer fees and the transfer is not a
+    /// self-transfer.
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Context state account for `VerifyCiphertextCommitmentEquality`.
+    ///   4. `[]` Instructions sysvar if the low `GroupedCiphertext2HandlesValidityProof` is
+    ///      included in the same transaction or context state account if it is pre-verified into a
+    ///      context state account.
+    ///   5. `[]` Instructions sysvar if the high `GroupedCiphertext2HandlesValidityProof` is
+    ///      included in the same transaction or context state account if it is pre-verified into a
+    ///      context state account.
+    ///   6. `[]` Context state account for `VerifyBatchedRangeProofU128`.
+    ///   7. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the low bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   8. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the high bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   9. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Context state account for `VerifyCiphertextCommitmentEquality`.
+    ///   4. `[]` Instructions sysvar if the low `GroupedCiphertext2HandlesValidityProof` is
+    ///      included in the same transaction or context state account if it is pre-verified into a
+    ///      context state account.
+    ///   5. `[]` Instructions sysvar if the high `GroupedCiphertext2HandlesValidityProof` is
+    ///      included in the same transaction or context state account if it is pre-verified into a
+    ///      context state account.
+    ///   6. `[]` Context state account for `VerifyBatchedRangeProofU128`.
+    ///   7. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the low bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   8. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the high bits
+    ///      of the transfer amount under the source ElGamal public key.
+    ///   9. `[]` The multisig source account owner.
+    ///   10.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `TransferWithSplitValidityProofsInstructionData`
+    ///
+    TransferWithSplitValidityProofs,
+
+    /// Approves a delegate to transfer up to a given amount out of the confidential balance of a
+    /// token account with `DelegatedTransfer`.
+    ///
+    /// The account delegate is set to the approved delegate as with `TokenInstruction::Approve`,
+    /// but the delegated amount is cleared, so the delegate can only move the confidential
+    /// balance. The approved amount is public, and it is stored as the remaining allowance of the
+    /// delegate in the `ConfidentialTransferDelegateAllowance` extension, encrypted under the
+    /// ElGamal public key of the account with zero randomness. The allowance is also stored in
+    /// the decryptable form provided in the instruction data, which must encrypt the approved
+    /// amount under the AES key of the account. Any previous allowance is replaced, and
+    /// `TokenInstruction::Revoke` revokes the allowance along with the delegate.
+    ///
+    /// The account must have been reallocated for the `ConfidentialTransferDelegateAllowance`
+    /// extension.
+    ///
+    /// Fails if the account is frozen.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The delegate.
+    ///   2. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The delegate.
+    ///   2. `[]` The multisig account owner.
+    ///   3.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `ApproveConfidentialDelegateInstructionData`
+    ///
+    ApproveConfidentialDelegate,
+
+    /// Transfer tokens confidentially on behalf of the owner of the source account, up to the
+    /// remaining allowance approved with `ApproveConfidentialDelegate`.
+    ///
+    /// The instruction is signed by the approved delegate instead of the source account owner.
+    /// It must be accompanied by the `VerifyTransfer` instruction of the `zk_token_proof` program
+    /// as in `Transfer`. In addition, the delegate pre-verifies two proofs into context state
+    /// accounts that certify that the transfer amount does not exceed the remaining allowance:
+    ///
+    ///   * a `VerifyCiphertextCommitmentEquality` proof that the remaining allowance minus the
+    ///     transfer amount, as computed homomorphically under the source ElGamal public key, is
+    ///     equal to a committed amount, and
+    ///   * a `VerifyBatchedRangeProofU64` proof whose first commitment is this commitment, with a
+    ///     bit length of `DELEGATE_ALLOWANCE_RANGE_PROOF_BIT_LENGTH`.
+    ///
+    /// The allowance ciphertext is then replaced by the remaining allowance minus the transfer
+    /// amount, and the decryptable allowance by the one in the instruction data.
+    ///
+    /// Fails with `ConfidentialTransferDelegateNotApproved` if the signer is not both the account
+    /// delegate and the delegate of the allowance, and with
+    /// `ConfidentialTransferDelegateAllowanceMismatch` if the allowance proofs do not match the
+    /// transfer. Fails with `ConfidentialTransferDecryptableBalanceStale` after a `PullTransfer`
+    /// until the owner updates the decryptable available balance. Fails if the associated mint is
+    /// extended as `NonTransferable` or for transfer fees.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` is included in the same transaction or
+    ///      context state account if the proof is pre-verified into a context state account.
+    ///   4. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the remaining
+    ///      allowance.
+    ///   5. `[]` Context state account for `VerifyBatchedRangeProofU64` for the remaining
+    ///      allowance.
+    ///   6. `[signer]` The single source account delegate.
+    ///
+    ///   * Multisignature delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` is included in the same transaction or
+    ///      context state account if the proof is pre-verified into a context state account.
+    ///   4. `[]` Context state account for `VerifyCiphertextCommitmentEquality` for the remaining
+    ///      allowance.
+    ///   5. `[]` Context state account for `VerifyBatchedRangeProofU64` for the remaining
+    ///      allowance.
+    ///   6. `[]` The multisig source account delegate.
+    ///   7.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `DelegatedTransferInstructionData`
+    ///
+    DelegatedTransfer,
+
+    /// Transfer tokens confidentially, asserting the decimals of the mint.
+    ///
+    /// This instruction behaves exactly like `Transfer`, and additionally fails with
+    /// `MintDecimalsMismatch` if `decimals` does not match the decimals of the mint. This catches
+    /// transfers that a client built against a stale view of the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` or `TransferWithFeeProof` is included in
+    ///      the same transaction or context state account if the proof is pre-verified into a
+    ///      context state account.
+    ///   4. `[signer]` The single source account owner or delegate.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` or `TransferWithFeeProof` is included in
+    ///      the same transaction or context state account if the proof is pre-verified into a
+    ///      context state account.
+    ///   4. `[]` The multisig source account owner.
+    ///   5.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `TransferCheckedInstructionData`
+    ///
+    TransferChecked,
+
+    /// Updates the confidential transfer mint configuration for a mint, leaving the auditor
+    /// ElGamal public key unchanged unless explicitly requested.
+    ///
+    /// Unlike `UpdateMint`, which always overwrites the auditor ElGamal public key, this
+    /// instruction only writes `auditor_elgamal_pubkey` if `update_auditor_elgamal_pubkey` is
+    /// set. A zero `auditor_elgamal_pubkey` then clears the auditor.
+    ///
+    /// Use `TokenInstruction::SetAuthority` to update the confidential transfer mint authority.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[signer]` Confidential transfer mint authority.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `UpdateMintPartialData`
+    ///
+    UpdateMintPartial,
+
+    /// Authorizes another account to pull a single confidential transfer from a token account.
+    ///
+    /// Since the puller cannot generate a transfer proof for the source account, the owner of the
+    /// source account generates the `VerifyTransfer` proof data for the pull transfer in advance
+    /// and authorizes the transfer amount ciphertexts under the source ElGamal public key that are
+    /// contained in the proof. A subsequent `PullTransfer` instruction signed by the puller must
+    /// present a proof with exactly these ciphertexts.
+    ///
+    /// The source account must have been reallocated for the
+    /// `ConfidentialTransferPullAuthorization` extension. Any previous authorization is replaced.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[]` The account authorized to pull the transfer.
+    ///   2. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[]` The account authorized to pull the transfer.
+    ///   2. `[]` The multisig source account owner.
+    ///   3.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `ApprovePullTransferInstructionData`
+    ///
+    ApprovePullTransfer,
+
+    /// Transfer tokens confidentially from an account that authorized the transfer with
+    /// `ApprovePullTransfer`.
+    ///
+    /// The instruction is signed by the authorized puller instead of the source account owner. It
+    /// must be accompanied by the `VerifyTransfer` instruction of the `zk_token_proof` program
+    /// whose source transfer amount ciphertexts match the authorized ciphertexts, and the
+    /// authorization is cleared once the transfer succeeds.
+    ///
+    /// The decryptable available balance of the source account is left unchanged, since only the
+    /// owner can encrypt it, so the owner updates it with `UpdateDecryptableBalance` after the
+    /// transfer. Until then, transfers, withdrawals, and burns from the source account fail with
+    /// `ConfidentialTransferDecryptableBalanceStale`.
+    ///
+    /// Fails if the associated mint is extended as `NonTransferable` or for transfer fees.
+    ///
+    ///   * Single puller
+    ///   1. `[writable]` The source SPL Token account.
+    ///   2. `[writable]` The destination SPL Token account.
+    ///   3. `[]` The token mint.
+    ///   4. `[]` Instructions sysvar if `TransferProof` is included in the same transaction or
+    ///      context state account if the proof is pre-verified into a context state account.
+    ///   5. `[signer]` The single authorized puller.
+    ///
+    ///   * Multisignature puller
+    ///   1. `[writable]` The source SPL Token account.
+    ///   2. `[writable]` The destination SPL Token account.
+    ///   3. `[]` The token mint.
+    ///   4. `[]` Instructions sysvar if `TransferProof` is included in the same transaction or
+    ///      context state account if the proof is pre-verified into a context state account.
+    ///   5. `[]` The multisig authorized puller.
+    ///   6.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `PullTransferInstructionData`
+    ///
+    PullTransfer,
+
+    /// Initializes the maximum transfer amount of a confidential transfer mint.
+    ///
+    /// The `ConfidentialTransferInstruction::InitializeMaximumTransferAmount` instruction requires
+    /// no signers and MUST be included within the same Transaction as
+    /// `TokenInstruction::InitializeMint`. Otherwise another party can initialize the
+    /// configuration.
+    ///
+    /// The instruction fails if the `TokenInstruction::InitializeMint` instruction has already
+    /// executed for the mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `MaximumTransferAmountInstructionData`
+    ///
+    InitializeMaximumTransferAmount,
+
+    /// Updates the maximum transfer amount of a confidential transfer mint.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The SPL Token mint.
+    ///   1. `[signer]` Confidential transfer mint authority.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `MaximumTransferAmountInstructionData`
+    ///
+    UpdateMaximumTransferAmount,
+
+    /// Deposit SPL Tokens directly into the available balance of a confidential token account.
+    ///
+    /// The deposit amount is public, so it can be added to the available balance without a
+    /// zero-knowledge proof, saving the `ApplyPendingBalance` instruction that would otherwise be
+    /// needed to make a `Deposit` usable. The client provides the new decryptable available balance
+    /// that includes the deposit. The pending balance, including any credits that it already
+    /// holds, is left untouched and the deposit does not count towards the maximum pending balance
+    /// credit counter.
+    ///
+    /// Fails if the account is frozen.
+    /// Fails if the associated mint is extended as `NonTransferable`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[signer]` The single account owner or delegate.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The token mint.
+    ///   2. `[]` The multisig account owner or delegate.
+    ///   3.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `DepositAndApplyInstructionData`
+    ///
+    DepositAndApply,
+
+    /// Atomically swap confidential balances of two mints between two token account owners.
+    ///
+    /// The instruction executes two confidential transfers, or legs: the first leg transfers
+    /// tokens of the first mint from the first owner to the second owner, and the second leg
+    /// transfers tokens of the second mint from the second owner to the first owner. Each leg is
+    /// processed exactly as a `Transfer` instruction, and the instruction fails if either leg
+    /// fails, so neither transfer takes effect without the other.
+    ///
+    /// Since the proofs for both legs do not fit in a single transaction, each leg must be
+    /// accompanied by a transfer proof (or transfer with fee proof for mints extended for transfer
+    /// fees) that is pre-verified into a context state account.
+    ///
+    /// Fails if the destination of a leg is not owned by the owner of the source account of the
+    /// other leg.
+    /// Fails if either associated mint is extended as `NonTransferable`.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The first source SPL Token account.
+    ///   1. `[writable]` The first destination SPL Token account.
+    ///   2. `[]` The first token mint.
+    ///   3. `[]` Context state account for the proof of the first transfer.
+    ///   4. `[signer]` The single owner of the first source account.
+    ///   5. `[writable]` The second source SPL Token account.
+    ///   6. `[writable]` The second destination SPL Token account.
+    ///   7. `[]` The second token mint.
+    ///   8. `[]` Context state account for the proof of the second transfer.
+    ///   9. `[signer]` The single owner of the second source account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `AtomicSwapInstructionData`
+    ///
+    AtomicSwap,
+
+    /// Empty the available and pending balances in a confidential token account.
+    ///
+    /// Like `ConfidentialTransferInstruction::EmptyAccount`, but the pending balance is added to
+    /// the available balance in the same way as `ConfidentialTransferInstruction::ApplyPendingBalance`
+    /// and the zero-balance proof certifies that the combined balance is 0. This allows an account
+    /// whose pending balance ciphertexts encrypt 0, e.g. after a transfer of 0 tokens, to be
+    /// emptied for closing without a separate `ApplyPendingBalance` instruction. The pending
+    /// balance credit counter is reset.
+    ///
+    /// In order for this instruction to be successfully processed, it must be accompanied by the
+    /// `VerifyZeroBalance` instruction of the `zk_token_proof` program in the same transaction or
+    /// the proof must be pre-verified into a context state account.
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` Instructions sysvar if `ZeroBalanceProof` is included in the same transaction or
+    ///      context state account if `ZeroBalanceProof` is pre-verified into a context state
+    ///      account.
+    ///   2. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` Instructions sysvar if `ZeroBalanceProof` is included in the same transaction or
+    ///      context state account if `ZeroBalanceProof` is pre-verified into a context state
+    ///      account.
+    ///   2. `[]` The multisig account owner.
+    ///   3.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `EmptyAccountInstructionData`
+    ///
+    EmptyAccountFull,
+
+    /// Update the decryptable available balance of a confidential extension account.
+    ///
+    /// The decryptable available balance is only a hint that lets the account owner decrypt the
+    /// available balance efficiently, so the owner may overwrite it, e.g. when it no longer
+    /// encrypts the value of the available balance after an interrupted client flow or a
+    /// `PullTransfer`. The available balance itself is not modified.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[signer]` The single account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The SPL Token account.
+    ///   1. `[]` The multisig account owner.
+    ///   2.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `UpdateDecryptableBalanceInstructionData`
+    ///
+    UpdateDecryptableBalance,
+
+    /// Forward the entire pending balance of a confidential token account to the pending balance
+    /// of another account, without first applying it to the available balance.
+    ///
+    /// The low and high bits of the pending balance are re-encrypted under the destination
+    /// ElGamal public key, and a `VerifyCiphertextCiphertextEquality` proof for each certifies
+    /// that the re-encryption holds the same amount. The pending balance cannot be forwarded in
+    /// part: the source pending balance and its credit counter are reset, and the destination
+    /// credit counter is increased by the source credit counter, since the forwarded low bits may
+    /// hold that many credits.
+    ///
+    /// Fails if the source and destination accounts are the same.
+    /// Fails if either account is frozen.
+    /// Fails if the associated mint is extended as `NonTransferable` or for transfer fees.
+    /// Fails with `ConfidentialTransferForwardNotSupported` if the mint has an auditor, additional
+    /// auditors, or a maximum transfer amount, since the forwarded amounts are not encrypted under
+    /// the auditor ElGamal public keys nor certified to be within the maximum transfer amount.
+    ///
+    /// In order for this instruction to be successfully processed, each of the two proofs must
+    /// either be included in the same transaction or pre-verified into a context state account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if the `CiphertextCiphertextEquality` proof for the low bits
+    ///      is included in the same transaction or context state account if it is pre-verified
+    ///      into a context state account.
+    ///   4. `[]` Instructions sysvar if the `CiphertextCiphertextEquality` proof for the high bits
+    ///      is included in the same transaction or context state account if it is pre-verified
+    ///      into a context state account.
+    ///   5. `[signer]` The single source account owner.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar or context state account for the low bits proof.
+    ///   4. `[]` Instructions sysvar or context state account for the high bits proof.
+    ///   5. `[]` The multisig source account owner.
+    ///   6.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `ForwardPendingBalanceInstructionData`
+    ///
+    ForwardPendingBalance,
+
+    /// Transfer tokens confidentially with an encrypted transfer reference.
+    ///
+    /// This instruction behaves exactly like `Transfer`, and additionally logs the
+    /// `EncryptedTransferReference` of the instruction data as a
+    /// `ConfidentialTransferReferenceEvent`, so that the recipient can reconcile the payment. The
+    /// reference is not part of the zero-knowledge proof of the transfer and is not verified by
+    /// the program.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` or `TransferWithFeeProof` is included in
+    ///      the same transaction or context state account if the proof is pre-verified into a
+    ///      context state account.
+    ///   4. `[signer]` The single source account owner or delegate.
+    ///
+    ///   * Multisignature owner/delegate
+    ///   0. `[writable]` The source SPL Token account.
+    ///   1. `[writable]` The destination SPL Token account.
+    ///   2. `[]` The token mint.
+    ///   3. `[]` Instructions sysvar if `TransferProof` or `TransferWithFeeProof` is included in
+    ///      the same transaction or context state account if the proof is pre-verified into a
+    ///      context state account.
+    ///   4. `[]` The multisig source account owner.
+    ///   5.. `[signer]` Required M signer accounts for the SPL Token Multisig account.
+    ///
+    /// Data expected by this instruction:
+    ///   `CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION` followed by
+    ///   `TransferWithReferenceInstructionData`
+    ///
+    TransferWithReference,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::InitializeMint`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct InitializeMintData {
+    /// Authority to modify the `ConfidentialTransferMint` configuration and to approve new
+    /// accounts.
+    pub authority: OptionalNonZeroPubkey,
+    /// Determines if newly configured accounts must be approved by the `authority` before they may
+    /// be used by the user.
+    pub auto_approve_new_accounts: PodBool,
+    /// New authority to decode any transfer amount in a confidential transfer.
+    pub auditor_elgamal_pubkey: OptionalNonZeroElGamalPubkey,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::UpdateMint`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct UpdateMintData {
+    /// Determines if newly configured accounts must be approved by the `authority` before they may
+    /// be used by the user.
+    pub auto_approve_new_accounts: PodBool,
+    /// New authority to decode any transfer amount in a confidential transfer.
+    pub auditor_elgamal_pubkey: OptionalNonZeroElGamalPubkey,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::UpdateMintPartial`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct UpdateMintPartialData {
+    /// Determines if newly configured accounts must be approved by the `authority` before they may
+    /// be used by the user.
+    pub auto_approve_new_accounts: PodBool,
+    /// Whether to overwrite the auditor ElGamal public key of the mint with
+    /// `auditor_elgamal_pubkey`
+    pub update_auditor_elgamal_pubkey: PodBool,
+    /// New authority to decode any transfer amount in a confidential transfer. Ignored unless
+    /// `update_auditor_elgamal_pubkey` is set.
+    pub auditor_elgamal_pubkey: OptionalNonZeroElGamalPubkey,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::ConfigureAccount`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ConfigureAccountInstructionData {
+    /// The decryptable balance (always 0) once the configure account succeeds
+    ///
+    /// Authenticated encryption ciphertexts are randomized and are not certified by a proof, so
+    /// the program does not check that this ciphertext actually encrypts zero. Clients can check it
+    /// with `account_info::verify_decryptable_zero_balance`.
+    pub decryptable_zero_balance: DecryptableBalance,
+    /// The maximum number of despots and transfers that an account can receiver before the
+    /// `ApplyPendingBalance` is executed
+    pub maximum_pending_balance_credit_counter: PodU64,
+    /// Relative location of the `ProofInstruction::ZeroBalanceProof` instruction to the
+    /// `ConfigureAccount` instruction in the transaction. If the offset is `0`, then use a context
+    /// state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::EmptyAccount` and
+/// `ConfidentialTransferInstruction::EmptyAccountFull`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct EmptyAccountInstructionData {
+    /// Relative location of the `ProofInstruction::VerifyCloseAccount` instruction to the
+    /// `EmptyAccount` instruction in the transaction. If the offset is `0`, then use a context
+    /// state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::Deposit`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct DepositInstructionData {
+    /// The amount of tokens to deposit
+    pub amount: PodU64,
+    /// Expected number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::DepositAndApply`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct DepositAndApplyInstructionData {
+    /// The amount of tokens to deposit
+    pub amount: PodU64,
+    /// Expected number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+    /// The new decryptable balance if the deposit succeeds
+    pub new_decryptable_available_balance: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::Withdraw`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct WithdrawInstructionData {
+    /// The amount of tokens to withdraw
+    pub amount: PodU64,
+    /// Expected number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+    /// The new decryptable balance if the withdrawal succeeds
+    pub new_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyWithdraw` instruction to the `Withdraw`
+    /// instruction in the transaction. If the offset is `0`, then use a context state account for
+    /// the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::Transfer`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TransferInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
+    /// `Transfer` instruction in the transaction. If the offset is `0`, then use a context state
+    /// account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::TransferWithAdditionalProofs`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TransferWithAdditionalProofsInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
+    /// `TransferWithAdditionalProofs` instruction in the transaction. If the offset is `0`, then
+    /// use a context state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::TransferChecked`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TransferCheckedInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
+    /// `TransferChecked` instruction in the transaction. If the offset is `0`, then use a context
+    /// state account for the proof.
+    pub proof_instruction_offset: i8,
+    /// Expected number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::TransferWithReference`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TransferWithReferenceInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
+    /// `TransferWithReference` instruction in the transaction. If the offset is `0`, then use a
+    /// context state account for the proof.
+    pub proof_instruction_offset: i8,
+    /// The encrypted transfer reference
+    pub reference: EncryptedTransferReference,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::ApplyPendingBalance`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ApplyPendingBalanceData {
+    /// The expected number of pending balance credits since the last successful
+    /// `ApplyPendingBalance` instruction
+    pub expected_pending_balance_credit_counter: PodU64,
+    /// The new decryptable balance if the pending balance is applied successfully
+    pub new_decryptable_available_balance: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::MintConfidential`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct MintConfidentialInstructionData {
+    /// Expected number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::InitializeConfidentialMintSupply`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct InitializeConfidentialMintSupplyData {
+    /// The ElGamal public key that the confidential supply is encrypted under
+    pub supply_elgamal_pubkey: ElGamalPubkey,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::BurnConfidential`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct BurnConfidentialInstructionData {
+    /// The amount of tokens to burn
+    pub amount: PodU64,
+    /// Expected number of base 10 digits to the right of the decimal place
+    pub decimals: u8,
+    /// The new decryptable balance if the burn succeeds
+    pub new_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyWithdraw` instruction to the
+    /// `BurnConfidential` instruction in the transaction. If the offset is `0`, then use a
+    /// context state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::RotateElGamalKey`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct RotateElGamalKeyInstructionData {
+    /// The new ElGamal public key for the account
+    pub new_elgamal_pubkey: ElGamalPubkey,
+    /// The available balance re-encrypted under the new authenticated encryption key
+    pub new_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyCiphertextCiphertextEquality`
+    /// instruction to the `RotateElGamalKey` instruction in the transaction. If the offset is
+    /// `0`, then use a context state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::InitializeAuditors` and
+/// `ConfidentialTransferInstruction::UpdateAuditors`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct AuditorsInstructionData {
+    /// ElGamal public keys of the additional auditors. Unused entries are `None`.
+    pub auditor_elgamal_pubkeys: [OptionalNonZeroElGamalPubkey; MAXIMUM_ADDITIONAL_AUDITORS],
+}
+impl AuditorsInstructionData {
+    fn new(auditor_elgamal_pubkeys: &[ElGamalPubkey]) -> Result<Self, ProgramError> {
+        if auditor_elgamal_pubkeys.len() > MAXIMUM_ADDITIONAL_AUDITORS {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let mut data = Self::zeroed();
+        for (auditor_elgamal_pubkey_out, auditor_elgamal_pubkey) in data
+            .auditor_elgamal_pubkeys
+            .iter_mut()
+            .zip(auditor_elgamal_pubkeys)
+        {
+            *auditor_elgamal_pubkey_out = Some(*auditor_elgamal_pubkey).try_into()?;
+        }
+        Ok(data)
+    }
+}
+
+/// Data expected by `ConfidentialTransferInstruction::InitializeMaximumTransferAmount` and
+/// `ConfidentialTransferInstruction::UpdateMaximumTransferAmount`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct MaximumTransferAmountInstructionData {
+    /// The maximum amount of a single confidential transfer
+    pub maximum_transfer_amount: PodU64,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::TransferWithSplitProofs`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TransferWithSplitProofsInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::EnableConfidentialCreditsUntilSlot`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct EnableConfidentialCreditsUntilSlotData {
+    /// The slot from which incoming confidential transfers are rejected, or `0` for no limit
+    pub slot: PodU64,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::UpdateMaxPendingBalanceCreditCounter`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct UpdateMaxPendingBalanceCreditCounterData {
+    /// The new maximum number of `Deposit` and `Transfer` instructions that can credit the pending
+    /// balance before the `ApplyPendingBalance` instruction is executed
+    pub maximum_pending_balance_credit_counter: PodU64,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::TransferWithSplitValidityProofs`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct TransferWithSplitValidityProofsInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyGroupedCiphertext2HandlesValidity`
+    /// instruction for the low bits of the transfer amount to the
+    /// `TransferWithSplitValidityProofs` instruction in the transaction. If the offset is `0`,
+    /// then use a context state account for the proof.
+    pub ciphertext_validity_proof_lo_instruction_offset: i8,
+    /// Relative location of the `ProofInstruction::VerifyGroupedCiphertext2HandlesValidity`
+    /// instruction for the high bits of the transfer amount to the
+    /// `TransferWithSplitValidityProofs` instruction in the transaction. If the offset is `0`,
+    /// then use a context state account for the proof.
+    pub ciphertext_validity_proof_hi_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::ApproveConfidentialDelegate`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ApproveConfidentialDelegateInstructionData {
+    /// The amount that the delegate is allowed to transfer out of the confidential balance
+    pub amount: PodU64,
+    /// The approved amount encrypted under the AES key of the account
+    pub decryptable_allowance: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::DelegatedTransfer`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct DelegatedTransferInstructionData {
+    /// The new source decryptable balance if the transfer succeeds
+    pub new_source_decryptable_available_balance: DecryptableBalance,
+    /// The new decryptable remaining allowance of the delegate if the transfer succeeds
+    pub new_decryptable_allowance: DecryptableBalance,
+    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
+    /// `DelegatedTransfer` instruction in the transaction. If the offset is `0`, then use a
+    /// context state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::ApprovePullTransfer`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ApprovePullTransferInstructionData {
+    /// The low 16 bits of the authorized transfer amount encrypted under the source ElGamal
+    /// public key
+    pub amount_lo: EncryptedBalance,
+    /// The high 32 bits of the authorized transfer amount encrypted under the source ElGamal
+    /// public key
+    pub amount_hi: EncryptedBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::PullTransfer`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct PullTransferInstructionData {
+    /// Relative location of the `ProofInstruction::VerifyTransfer` instruction to the
+    /// `PullTransfer` instruction in the transaction. If the offset is `0`, then use a context
+    /// state account for the proof.
+    pub proof_instruction_offset: i8,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::AtomicSwap`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct AtomicSwapInstructionData {
+    /// The new source decryptable balance of the first leg if the swap succeeds
+    pub first_new_source_decryptable_available_balance: DecryptableBalance,
+    /// The new source decryptable balance of the second leg if the swap succeeds
+    pub second_new_source_decryptable_available_balance: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::UpdateDecryptableBalance`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct UpdateDecryptableBalanceInstructionData {
+    /// The new decryptable balance of the available balance
+    pub new_decryptable_available_balance: DecryptableBalance,
+}
+
+/// Data expected by `ConfidentialTransferInstruction::ForwardPendingBalance`
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct ForwardPendingBalanceInstructionData {
+    /// Relative location of the `ProofInstruction::VerifyCiphertextCiphertextEquality`
+    /// instruction for the low bits of the pending balance to the `ForwardPendingBalance`
+    /// instruction in the transaction. If the offset is `0`, then use a context state account for
+    /// the proof.
+    pub equality_proof_lo_instruction_offset: i8,
+    /// Relative location of the `ProofInstruction::VerifyCiphertextCiphertextEquality`
+    /// instruction for the high bits of the pending balance to the `ForwardPendingBalance`
+    /// instruction in the transaction. If the offset is `0`, then use a context state account for
+    /// the proof.
+    pub equality_proof_hi_instruction_offset: i8,
+}
+
+/// Create a `InitializeMint` instruction
+#[cfg(not(target_os = "solana"))]
+pub fn initialize_mint(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: Option<Pubkey>,
+    auto_approve_new_accounts: bool,
+    auditor_elgamal_pubkey: Option<ElGamalPubkey>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::InitializeMint,
+        &InitializeMintData {
+            authority: authority.try_into()?,
+            auto_approve_new_accounts: auto_approve_new_accounts.into(),
+            auditor_elgamal_pubkey: auditor_elgamal_pubkey.try_into()?,
+        },
+    ))
+}
+
+/// Create a `UpdateMint` instruction
+#[cfg(not(target_os = "solana"))]
+pub fn update_mint(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
     auto_approve_new_accounts: bool,
     auditor_elgamal_pubkey: Option<ElGamalPubkey>,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
-        AccountMeta::new(*mint, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::UpdateMint,
+        &UpdateMintData {
+            auto_approve_new_accounts: auto_approve_new_accounts.into(),
+            auditor_elgamal_pubkey: auditor_elgamal_pubkey.try_into()?,
+        },
+    ))
+}
+
+/// Create a `UpdateMintPartial` instruction
+///
+/// The auditor ElGamal public key of the mint is left unchanged if `auditor_elgamal_pubkey` is
+/// `None`, cleared if it is `Some(None)`, and set if it is `Some(Some(_))`.
+pub fn update_mint_partial(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    auto_approve_new_accounts: bool,
+    auditor_elgamal_pubkey: Option<Option<ElGamalPubkey>>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::UpdateMintPartial,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &UpdateMintPartialData {
+            auto_approve_new_accounts: auto_approve_new_accounts.into(),
+            update_auditor_elgamal_pubkey: auditor_elgamal_pubkey.is_some().into(),
+            auditor_elgamal_pubkey: auditor_elgamal_pubkey.flatten().try_into()?,
+        },
+    ))
+}
+
+/// Create a `ConfigureAccount` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn inner_configure_account(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    decryptable_zero_balance: AeCiphertext,
+    maximum_pending_balance_credit_counter: u64,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<PubkeyValidityData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::ConfigureAccount,
+        &ConfigureAccountInstructionData {
+            decryptable_zero_balance: decryptable_zero_balance.into(),
+            maximum_pending_balance_credit_counter: maximum_pending_balance_credit_counter.into(),
+            proof_instruction_offset,
+        },
+    ))
+}
+
+/// Create a `ConfigureAccount` instruction
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn configure_account(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    decryptable_zero_balance: AeCiphertext,
+    maximum_pending_balance_credit_counter: u64,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<PubkeyValidityData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_configure_account(
+        token_program_id,
+        token_account,
+        mint,
+        decryptable_zero_balance,
+        maximum_pending_balance_credit_counter,
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `ConfigureAccount`
+        // instruction. This means that the proof instruction offset must be always be 1. To
+        // use an arbitrary proof instruction offset, use the `inner_configure_account`
+        // constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_pubkey_validity(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create an `ApproveAccount` instruction
+pub fn approve_account(
+    token_program_id: &Pubkey,
+    account_to_approve: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*account_to_approve, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::ApproveAccount,
+        &(),
+    ))
+}
+
+/// Create an inner `EmptyAccount` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+pub fn inner_empty_account(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<ZeroBalanceProofData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![AccountMeta::new(*token_account, false)];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::EmptyAccount,
+        &EmptyAccountInstructionData {
+            proof_instruction_offset,
+        },
+    ))
+}
+
+/// Create a `EmptyAccount` instruction
+pub fn empty_account(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<ZeroBalanceProofData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_empty_account(
+        token_program_id,
+        token_account,
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `EmptyAccount`
+        // instruction. This means that the proof instruction offset must be always be 1. To use an
+        // arbitrary proof instruction offset, use the `inner_empty_account` constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_zero_balance(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create an inner `EmptyAccountFull` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+pub fn inner_empty_account_full(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<ZeroBalanceProofData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![AccountMeta::new(*token_account, false)];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::EmptyAccountFull,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &EmptyAccountInstructionData {
+            proof_instruction_offset,
+        },
+    ))
+}
+
+/// Create a `EmptyAccountFull` instruction
+pub fn empty_account_full(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<ZeroBalanceProofData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_empty_account_full(
+        token_program_id,
+        token_account,
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `EmptyAccountFull`
+        // instruction. This means that the proof instruction offset must be always be 1. To use an
+        // arbitrary proof instruction offset, use the `inner_empty_account_full` constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_zero_balance(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create a `Deposit` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn deposit(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::Deposit,
+        &DepositInstructionData {
+            amount: amount.into(),
+            decimals,
+        },
+    ))
+}
+
+/// Create a `Deposit` instruction and the account infos to pass to a cross-program
+/// `invoke_signed`
+///
+/// The accounts are ordered as the processor reads them: the token account, the mint, the
+/// authority, and then any multisig signers. The authority may be a program derived address, in
+/// which case the calling program signs for it with its seeds.
+pub fn deposit_cpi<'a>(
+    token_program_id: &Pubkey,
+    token_account_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    multisig_signer_infos: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+) -> Result<(Instruction, Vec<AccountInfo<'a>>), ProgramError> {
+    let multisig_signers = multisig_signer_infos
+        .iter()
+        .map(|info| info.key)
+        .collect::<Vec<_>>();
+    let instruction = deposit(
+        token_program_id,
+        token_account_info.key,
+        mint_info.key,
+        amount,
+        decimals,
+        authority_info.key,
+        &multisig_signers,
+    )?;
+
+    let mut account_infos = vec![token_account_info, mint_info, authority_info];
+    account_infos.extend_from_slice(multisig_signer_infos);
+    Ok((instruction, account_infos))
+}
+
+/// Create a `DepositAndApply` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn deposit_and_apply(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::DepositAndApply,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &DepositAndApplyInstructionData {
+            amount: amount.into(),
+            decimals,
+            new_decryptable_available_balance,
+        },
+    ))
+}
+
+/// Create a inner `Withdraw` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_withdraw(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<WithdrawData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::Withdraw,
+        &WithdrawInstructionData {
+            amount: amount.into(),
+            decimals,
+            new_decryptable_available_balance,
+            proof_instruction_offset,
+        },
+    ))
+}
+
+/// Create a `Withdraw` instruction
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn withdraw(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<WithdrawData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_withdraw(
+        token_program_id,
+        token_account,
+        mint,
+        amount,
+        decimals,
+        new_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `Withdraw` instruction.
+        // This means that the proof instruction offset must be always be 1. To use an arbitrary
+        // proof instruction offset, use the `inner_withdraw` constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_withdraw(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create a `Withdraw` instruction and the account infos to pass to a cross-program
+/// `invoke_signed`
+///
+/// The accounts are ordered as the processor reads them: the token account, the mint, the proof
+/// account, the authority, and then any multisig signers. The proof account is the instructions
+/// sysvar if the `VerifyWithdraw` instruction is included in the transaction at
+/// `proof_instruction_offset` relative to the top-level instruction, or a context state account if
+/// `proof_instruction_offset` is 0.
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_cpi<'a>(
+    token_program_id: &Pubkey,
+    token_account_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    proof_account_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    multisig_signer_infos: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+    proof_instruction_offset: i8,
+) -> Result<(Instruction, Vec<AccountInfo<'a>>), ProgramError> {
+    check_program_account(token_program_id)?;
+    if proof_instruction_offset != 0 && *proof_account_info.key != sysvar::instructions::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let mut accounts = vec![
+        AccountMeta::new(*token_account_info.key, false),
+        AccountMeta::new_readonly(*mint_info.key, false),
+        AccountMeta::new_readonly(*proof_account_info.key, false),
+        AccountMeta::new_readonly(*authority_info.key, multisig_signer_infos.is_empty()),
+    ];
+    for multisig_signer_info in multisig_signer_infos.iter() {
+        accounts.push(AccountMeta::new_readonly(*multisig_signer_info.key, true));
+    }
+
+    let instruction = encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::Withdraw,
+        &WithdrawInstructionData {
+            amount: amount.into(),
+            decimals,
+            new_decryptable_available_balance,
+            proof_instruction_offset,
+        },
+    );
+
+    let mut account_infos = vec![
+        token_account_info,
+        mint_info,
+        proof_account_info,
+        authority_info,
+    ];
+    account_infos.extend_from_slice(multisig_signer_infos);
+    Ok((instruction, account_infos))
+}
+
+/// Create a inner `Transfer` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_transfer(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<TransferData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::Transfer,
+        &TransferInstructionData {
+            new_source_decryptable_available_balance,
+            proof_instruction_offset,
+        },
+    ))
+}
+
+/// Create a `Transfer` instruction with regular (no-fee) proof
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn transfer(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<TransferData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_transfer(
+        token_program_id,
+        source_token_account,
+        destination_token_account,
+        mint,
+        new_source_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `ConfigureAccount`
+        // instruction. This means that the proof instruction offset must be always be 1. To
+        // use an arbitrary proof instruction offset, use the `inner_configure_account`
+        // constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_transfer(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create a inner `TransferChecked` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_transfer_checked(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    decimals: u8,
+    proof_data_location: ProofLocation<TransferData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::TransferChecked,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &TransferCheckedInstructionData {
+            new_source_decryptable_available_balance,
+            proof_instruction_offset,
+            decimals,
+        },
+    ))
+}
+
+/// Create a `TransferChecked` instruction with regular (no-fee) proof
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn transfer_checked(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    decimals: u8,
+    proof_data_location: ProofLocation<TransferData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_transfer_checked(
+        token_program_id,
+        source_token_account,
+        destination_token_account,
+        mint,
+        new_source_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        decimals,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `TransferChecked`
+        // instruction. This means that the proof instruction offset must be always be 1. To use
+        // an arbitrary proof instruction offset, use the `inner_transfer_checked` constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_transfer(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create a inner `TransferWithReference` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_transfer_with_reference(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    reference: &EncryptedTransferReference,
+    proof_data_location: ProofLocation<TransferData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::TransferWithReference,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &TransferWithReferenceInstructionData {
+            new_source_decryptable_available_balance,
+            proof_instruction_offset,
+            reference: *reference,
+        },
+    ))
+}
+
+/// Create a `TransferWithReference` instruction with regular (no-fee) proof
+///
+/// The reference is not part of the zero-knowledge proof of the transfer. See
+/// [EncryptedTransferReference].
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn transfer_with_reference(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    reference: &EncryptedTransferReference,
+    proof_data_location: ProofLocation<TransferData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_transfer_with_reference(
+        token_program_id,
+        source_token_account,
+        destination_token_account,
+        mint,
+        new_source_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        reference,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `TransferWithReference`
+        // instruction. This means that the proof instruction offset must be always be 1. To use
+        // an arbitrary proof instruction offset, use the `inner_transfer_with_reference`
+        // constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_transfer(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Decode the encrypted transfer reference of a `TransferWithReference` instruction, or return
+/// `None` if the instruction is not a confidential `TransferWithReference`
+pub fn decode_transfer_reference(instruction_data: &[u8]) -> Option<EncryptedTransferReference> {
+    if !matches!(
+        TokenInstruction::unpack(instruction_data),
+        Ok(TokenInstruction::ConfidentialTransferExtension)
+    ) {
+        return None;
+    }
+    let input = &instruction_data[1..];
+    match decode_instruction_type(input) {
+        Ok(ConfidentialTransferInstruction::TransferWithReference) => {
+            decode_versioned_instruction_data::<TransferWithReferenceInstructionData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )
+            .ok()
+            .map(|data| data.reference)
+        }
+        _ => None,
+    }
+}
+
+/// Create a inner `TransferWithFee` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_transfer_with_fee(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<TransferWithFeeData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::Transfer,
+        &TransferInstructionData {
+            new_source_decryptable_available_balance,
+            proof_instruction_offset,
+        },
+    ))
+}
+
+/// Create a `Transfer` instruction with fee proof
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn transfer_with_fee(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<TransferWithFeeData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_transfer_with_fee(
+        token_program_id,
+        source_token_account,
+        destination_token_account,
+        mint,
+        new_source_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `TransferWithFee`
+        // instruction. This means that the proof instruction offset must be always be 1. To
+        // use an arbitrary proof instruction offset, use the `inner_transfer_with_fee`
+        // constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_transfer_with_fee(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create a inner `ApplyPendingBalance` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+pub fn inner_apply_pending_balance(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    expected_pending_balance_credit_counter: u64,
+    new_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::ApplyPendingBalance,
+        &ApplyPendingBalanceData {
+            expected_pending_balance_credit_counter: expected_pending_balance_credit_counter.into(),
+            new_decryptable_available_balance,
+        },
+    ))
+}
+
+/// Create a `ApplyPendingBalance` instruction
+#[cfg(not(target_os = "solana"))]
+pub fn apply_pending_balance(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    pending_balance_instructions: u64,
+    new_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    inner_apply_pending_balance(
+        token_program_id,
+        token_account,
+        pending_balance_instructions,
+        new_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+    ) // calls check_program_account
+}
+
+/// Create an `ApplyPendingBalance` instruction and the account infos to pass to a cross-program
+/// `invoke_signed`
+///
+/// The accounts are ordered as the processor reads them: the token account, the authority, and
+/// then any multisig signers.
+pub fn apply_pending_balance_cpi<'a>(
+    token_program_id: &Pubkey,
+    token_account_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    multisig_signer_infos: &[AccountInfo<'a>],
+    expected_pending_balance_credit_counter: u64,
+    new_decryptable_available_balance: DecryptableBalance,
+) -> Result<(Instruction, Vec<AccountInfo<'a>>), ProgramError> {
+    let multisig_signers = multisig_signer_infos
+        .iter()
+        .map(|info| info.key)
+        .collect::<Vec<_>>();
+    let instruction = inner_apply_pending_balance(
+        token_program_id,
+        token_account_info.key,
+        expected_pending_balance_credit_counter,
+        new_decryptable_available_balance,
+        authority_info.key,
+        &multisig_signers,
+    )?;
+
+    let mut account_infos = vec![token_account_info, authority_info];
+    account_infos.extend_from_slice(multisig_signer_infos);
+    Ok((instruction, account_infos))
+}
+
+fn enable_or_disable_balance_credits(
+    instruction: ConfidentialTransferInstruction,
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
         AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
     ];
+
     for multisig_signer in multisig_signers.iter() {
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
+
     Ok(encode_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::UpdateMint,
-        &UpdateMintData {
-            auto_approve_new_accounts: auto_approve_new_accounts.into(),
-            auditor_elgamal_pubkey: auditor_elgamal_pubkey.try_into()?,
+        instruction,
+        &(),
+    ))
+}
+
+/// Create a `EnableConfidentialCredits` instruction
+pub fn enable_confidential_credits(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    enable_or_disable_balance_credits(
+        ConfidentialTransferInstruction::EnableConfidentialCredits,
+        token_program_id,
+        token_account,
+        authority,
+        multisig_signers,
+    )
+}
+
+/// Create a `DisableConfidentialCredits` instruction
+pub fn disable_confidential_credits(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    enable_or_disable_balance_credits(
+        ConfidentialTransferInstruction::DisableConfidentialCredits,
+        token_program_id,
+        token_account,
+        authority,
+        multisig_signers,
+    )
+}
+
+/// Create a `EnableConfidentialCreditsUntilSlot` instruction
+pub fn enable_confidential_credits_until_slot(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    slot: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::EnableConfidentialCreditsUntilSlot,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &EnableConfidentialCreditsUntilSlotData { slot: slot.into() },
+    ))
+}
+
+/// Create a `UpdateMaxPendingBalanceCreditCounter` instruction
+pub fn update_max_pending_balance_credit_counter(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    maximum_pending_balance_credit_counter: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::UpdateMaxPendingBalanceCreditCounter,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &UpdateMaxPendingBalanceCreditCounterData {
+            maximum_pending_balance_credit_counter: maximum_pending_balance_credit_counter.into(),
+        },
+    ))
+}
+
+/// Create a `EnableNonConfidentialCredits` instruction
+pub fn enable_non_confidential_credits(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    enable_or_disable_balance_credits(
+        ConfidentialTransferInstruction::EnableNonConfidentialCredits,
+        token_program_id,
+        token_account,
+        authority,
+        multisig_signers,
+    )
+}
+
+/// Create a `DisableNonConfidentialCredits` instruction
+pub fn disable_non_confidential_credits(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    enable_or_disable_balance_credits(
+        ConfidentialTransferInstruction::DisableNonConfidentialCredits,
+        token_program_id,
+        token_account,
+        authority,
+        multisig_signers,
+    )
+}
+
+/// Create a `MintConfidential` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn mint_confidential(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    decimals: u8,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    ciphertext_validity_proof_context_state_account: &Pubkey,
+    range_proof_context_state_account: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*ciphertext_validity_proof_context_state_account, false),
+        AccountMeta::new_readonly(*range_proof_context_state_account, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::MintConfidential,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &MintConfidentialInstructionData { decimals },
+    ))
+}
+
+/// Create an `InitializeConfidentialMintSupply` instruction
+pub fn initialize_confidential_mint_supply(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    supply_elgamal_pubkey: ElGamalPubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::InitializeConfidentialMintSupply,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &InitializeConfidentialMintSupplyData {
+            supply_elgamal_pubkey,
+        },
+    ))
+}
+
+/// Create an inner `BurnConfidential` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_burn_confidential(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<WithdrawData>,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new(*mint, false),
+    ];
+
+    let proof_instruction_offset = match proof_data_location {
+        ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+            accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+            proof_instruction_offset.into()
+        }
+        ProofLocation::ContextStateAccount(context_state_account) => {
+            accounts.push(AccountMeta::new_readonly(*context_state_account, false));
+            0
+        }
+    };
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::BurnConfidential,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &BurnConfidentialInstructionData {
+            amount: amount.into(),
+            decimals,
+            new_decryptable_available_balance,
+            proof_instruction_offset,
         },
     ))
 }
 
-/// Create a `ConfigureAccount` instruction
+/// Create a `BurnConfidential` instruction
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn burn_confidential(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    mint: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    new_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    proof_data_location: ProofLocation<WithdrawData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_burn_confidential(
+        token_program_id,
+        token_account,
+        mint,
+        amount,
+        decimals,
+        new_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        proof_data_location,
+    )?];
+
+    if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+        proof_data_location
+    {
+        // This constructor appends the proof instruction right after the `BurnConfidential`
+        // instruction. This means that the proof instruction offset must be always be 1. To use
+        // an arbitrary proof instruction offset, use the `inner_burn_confidential` constructor.
+        let proof_instruction_offset: i8 = proof_instruction_offset.into();
+        if proof_instruction_offset != 1 {
+            return Err(TokenError::InvalidProofInstructionOffset.into());
+        }
+        instructions.push(verify_withdraw(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create an inner `RotateElGamalKey` instruction
 ///
 /// This instruction is suitable for use with a cross-program `invoke`
-#[allow(clippy::too_many_arguments)]
-#[cfg(not(target_os = "solana"))]
-pub fn inner_configure_account(
+pub fn inner_rotate_elgamal_key(
     token_program_id: &Pubkey,
     token_account: &Pubkey,
-    mint: &Pubkey,
-    decryptable_zero_balance: AeCiphertext,
-    maximum_pending_balance_credit_counter: u64,
+    new_elgamal_pubkey: ElGamalPubkey,
+    new_decryptable_available_balance: DecryptableBalance,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
-    proof_data_location: ProofLocation<PubkeyValidityData>,
+    proof_data_location: ProofLocation<CiphertextCiphertextEqualityProofData>,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
-
-    let mut accounts = vec![
-        AccountMeta::new(*token_account, false),
-        AccountMeta::new_readonly(*mint, false),
-    ];
+    let mut accounts = vec![AccountMeta::new(*token_account, false)];
 
     let proof_instruction_offset = match proof_data_location {
         ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
@@ -541,38 +3083,36 @@ pub fn inner_configure_account(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::ConfigureAccount,
-        &ConfigureAccountInstructionData {
-            decryptable_zero_balance: decryptable_zero_balance.into(),
-            maximum_pending_balance_credit_counter: maximum_pending_balance_credit_counter.into(),
+        ConfidentialTransferInstruction::RotateElGamalKey,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &RotateElGamalKeyInstructionData {
+            new_elgamal_pubkey,
+            new_decryptable_available_balance,
             proof_instruction_offset,
         },
     ))
 }
 
-/// Create a `ConfigureAccount` instruction
-#[allow(clippy::too_many_arguments)]
+/// Create a `RotateElGamalKey` instruction
 #[cfg(not(target_os = "solana"))]
-pub fn configure_account(
+pub fn rotate_elgamal_key(
     token_program_id: &Pubkey,
     token_account: &Pubkey,
-    mint: &Pubkey,
-    decryptable_zero_balance: AeCiphertext,
-    maximum_pending_balance_credit_counter: u64,
+    new_elgamal_pubkey: ElGamalPubkey,
+    new_decryptable_available_balance: AeCiphertext,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
-    proof_data_location: ProofLocation<PubkeyValidityData>,
+    proof_data_location: ProofLocation<CiphertextCiphertextEqualityProofData>,
 ) -> Result<Vec<Instruction>, ProgramError> {
-    let mut instructions = vec![inner_configure_account(
+    let mut instructions = vec![inner_rotate_elgamal_key(
         token_program_id,
         token_account,
-        mint,
-        decryptable_zero_balance,
-        maximum_pending_balance_credit_counter,
+        new_elgamal_pubkey,
+        new_decryptable_available_balance.into(),
         authority,
         multisig_signers,
         proof_data_location,
@@ -581,58 +3121,97 @@ pub fn configure_account(
     if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
         proof_data_location
     {
-        // This constructor appends the proof instruction right after the `ConfigureAccount`
-        // instruction. This means that the proof instruction offset must be always be 1. To
-        // use an arbitrary proof instruction offset, use the `inner_configure_account`
-        // constructor.
+        // This constructor appends the proof instruction right after the `RotateElGamalKey`
+        // instruction. This means that the proof instruction offset must be always be 1. To use
+        // an arbitrary proof instruction offset, use the `inner_rotate_elgamal_key` constructor.
         let proof_instruction_offset: i8 = proof_instruction_offset.into();
         if proof_instruction_offset != 1 {
             return Err(TokenError::InvalidProofInstructionOffset.into());
         }
-        instructions.push(verify_pubkey_validity(None, proof_data));
+        instructions.push(
+            ProofInstruction::VerifyCiphertextCiphertextEquality
+                .encode_verify_proof(None, proof_data),
+        );
     };
 
     Ok(instructions)
 }
 
-/// Create an `ApproveAccount` instruction
-pub fn approve_account(
+/// Create an `InitializeAuditors` instruction
+///
+/// Fails if more than `MAXIMUM_ADDITIONAL_AUDITORS` auditors are given.
+pub fn initialize_auditors(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    auditor_elgamal_pubkeys: &[ElGamalPubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::InitializeAuditors,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &AuditorsInstructionData::new(auditor_elgamal_pubkeys)?,
+    ))
+}
+
+/// Create an `UpdateAuditors` instruction
+///
+/// Fails if more than `MAXIMUM_ADDITIONAL_AUDITORS` auditors are given.
+pub fn update_auditors(
     token_program_id: &Pubkey,
-    account_to_approve: &Pubkey,
     mint: &Pubkey,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
+    auditor_elgamal_pubkeys: &[ElGamalPubkey],
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
-        AccountMeta::new(*account_to_approve, false),
-        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new(*mint, false),
         AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
     ];
     for multisig_signer in multisig_signers.iter() {
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
-    Ok(encode_instruction(
+
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::ApproveAccount,
-        &(),
+        ConfidentialTransferInstruction::UpdateAuditors,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &AuditorsInstructionData::new(auditor_elgamal_pubkeys)?,
     ))
 }
 
-/// Create an inner `EmptyAccount` instruction
+/// Create an inner `TransferWithAdditionalProofs` instruction
+///
+/// `additional_proof_context_state_accounts` holds the context state accounts of the low and high
+/// bits proofs of each additional auditor of the mint, in the order of the auditors, followed by
+/// the equality and range proofs of the maximum transfer amount if the mint has one.
 ///
 /// This instruction is suitable for use with a cross-program `invoke`
-pub fn inner_empty_account(
+#[allow(clippy::too_many_arguments)]
+pub fn inner_transfer_with_additional_proofs(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: DecryptableBalance,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
-    proof_data_location: ProofLocation<ZeroBalanceProofData>,
+    proof_data_location: ProofLocation<TransferData>,
+    additional_proof_context_state_accounts: &[(Pubkey, Pubkey)],
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
-    let mut accounts = vec![AccountMeta::new(*token_account, false)];
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+    ];
 
     let proof_instruction_offset = match proof_data_location {
         ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
@@ -645,6 +3224,19 @@ pub fn inner_empty_account(
         }
     };
 
+    for (first_context_state_account, second_context_state_account) in
+        additional_proof_context_state_accounts
+    {
+        accounts.push(AccountMeta::new_readonly(
+            *first_context_state_account,
+            false,
+        ));
+        accounts.push(AccountMeta::new_readonly(
+            *second_context_state_account,
+            false,
+        ));
+    }
+
     accounts.push(AccountMeta::new_readonly(
         *authority,
         multisig_signers.is_empty(),
@@ -654,64 +3246,140 @@ pub fn inner_empty_account(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::EmptyAccount,
-        &EmptyAccountInstructionData {
+        ConfidentialTransferInstruction::TransferWithAdditionalProofs,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &TransferWithAdditionalProofsInstructionData {
+            new_source_decryptable_available_balance,
             proof_instruction_offset,
         },
     ))
 }
 
-/// Create a `EmptyAccount` instruction
-pub fn empty_account(
+/// Create a `TransferWithAdditionalProofs` instruction with regular (no-fee) proof
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn transfer_with_additional_proofs(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: AeCiphertext,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
-    proof_data_location: ProofLocation<ZeroBalanceProofData>,
+    proof_data_location: ProofLocation<TransferData>,
+    additional_proof_context_state_accounts: &[(Pubkey, Pubkey)],
 ) -> Result<Vec<Instruction>, ProgramError> {
-    let mut instructions = vec![inner_empty_account(
+    let mut instructions = vec![inner_transfer_with_additional_proofs(
         token_program_id,
-        token_account,
+        source_token_account,
+        destination_token_account,
+        mint,
+        new_source_decryptable_available_balance.into(),
         authority,
         multisig_signers,
         proof_data_location,
+        additional_proof_context_state_accounts,
     )?];
 
     if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
         proof_data_location
     {
-        // This constructor appends the proof instruction right after the `EmptyAccount`
-        // instruction. This means that the proof instruction offset must be always be 1. To use an
-        // arbitrary proof instruction offset, use the `inner_empty_account` constructor.
+        // This constructor appends the proof instruction right after the
+        // `TransferWithAdditionalProofs` instruction. This means that the proof instruction offset
+        // must be always be 1. To use an arbitrary proof instruction offset, use the
+        // `inner_transfer_with_additional_proofs` constructor.
         let proof_instruction_offset: i8 = proof_instruction_offset.into();
         if proof_instruction_offset != 1 {
             return Err(TokenError::InvalidProofInstructionOffset.into());
         }
-        instructions.push(verify_zero_balance(None, proof_data));
+        instructions.push(verify_transfer(None, proof_data));
     };
 
     Ok(instructions)
 }
 
-/// Create a `Deposit` instruction
+/// Create an `InitializeMaximumTransferAmount` instruction
+pub fn initialize_maximum_transfer_amount(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    maximum_transfer_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::InitializeMaximumTransferAmount,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &MaximumTransferAmountInstructionData {
+            maximum_transfer_amount: maximum_transfer_amount.into(),
+        },
+    ))
+}
+
+/// Create an `UpdateMaximumTransferAmount` instruction
+pub fn update_maximum_transfer_amount(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    maximum_transfer_amount: u64,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::UpdateMaximumTransferAmount,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &MaximumTransferAmountInstructionData {
+            maximum_transfer_amount: maximum_transfer_amount.into(),
+        },
+    ))
+}
+
+/// Create a `TransferWithSplitProofs` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
 #[allow(clippy::too_many_arguments)]
-pub fn deposit(
+pub fn transfer_with_split_proofs(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
     mint: &Pubkey,
-    amount: u64,
-    decimals: u8,
+    new_source_decryptable_available_balance: DecryptableBalance,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
+    equality_proof_context_state_account: &Pubkey,
+    ciphertext_validity_proof_context_state_account: &Pubkey,
+    range_proof_context_state_account: &Pubkey,
+    source_amount_lo_proof_context_state_account: &Pubkey,
+    source_amount_hi_proof_context_state_account: &Pubkey,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
-        AccountMeta::new(*token_account, false),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
         AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*equality_proof_context_state_account, false),
+        AccountMeta::new_readonly(*ciphertext_validity_proof_context_state_account, false),
+        AccountMeta::new_readonly(*range_proof_context_state_account, false),
+        AccountMeta::new_readonly(*source_amount_lo_proof_context_state_account, false),
+        AccountMeta::new_readonly(*source_amount_hi_proof_context_state_account, false),
         AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
     ];
 
@@ -719,25 +3387,26 @@ pub fn deposit(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::Deposit,
-        &DepositInstructionData {
-            amount: amount.into(),
-            decimals,
+        ConfidentialTransferInstruction::TransferWithSplitProofs,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &TransferWithSplitProofsInstructionData {
+            new_source_decryptable_available_balance,
         },
     ))
 }
 
-/// Create a inner `Withdraw` instruction
+/// Create a inner `WithdrawTo` instruction
 ///
 /// This instruction is suitable for use with a cross-program `invoke`
 #[allow(clippy::too_many_arguments)]
-pub fn inner_withdraw(
+pub fn inner_withdraw_to(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
     mint: &Pubkey,
     amount: u64,
     decimals: u8,
@@ -748,7 +3417,8 @@ pub fn inner_withdraw(
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
-        AccountMeta::new(*token_account, false),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
         AccountMeta::new_readonly(*mint, false),
     ];
 
@@ -772,11 +3442,12 @@ pub fn inner_withdraw(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::Withdraw,
+        ConfidentialTransferInstruction::WithdrawTo,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
         &WithdrawInstructionData {
             amount: amount.into(),
             decimals,
@@ -786,12 +3457,13 @@ pub fn inner_withdraw(
     ))
 }
 
-/// Create a `Withdraw` instruction
+/// Create a `WithdrawTo` instruction
 #[allow(clippy::too_many_arguments)]
 #[cfg(not(target_os = "solana"))]
-pub fn withdraw(
+pub fn withdraw_to(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
     mint: &Pubkey,
     amount: u64,
     decimals: u8,
@@ -800,9 +3472,10 @@ pub fn withdraw(
     multisig_signers: &[&Pubkey],
     proof_data_location: ProofLocation<WithdrawData>,
 ) -> Result<Vec<Instruction>, ProgramError> {
-    let mut instructions = vec![inner_withdraw(
+    let mut instructions = vec![inner_withdraw_to(
         token_program_id,
-        token_account,
+        source_token_account,
+        destination_token_account,
         mint,
         amount,
         decimals,
@@ -815,30 +3488,221 @@ pub fn withdraw(
     if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
         proof_data_location
     {
-        // This constructor appends the proof instruction right after the `Withdraw` instruction.
-        // This means that the proof instruction offset must be always be 1. To use an arbitrary
-        // proof instruction offset, use the `inner_withdraw` constructor.
+        // This constructor appends the proof instruction right after the `WithdrawTo`
+        // instruction. This means that the proof instruction offset must be always be 1. To use an
+        // arbitrary proof instruction offset, use the `inner_withdraw_to` constructor.
         let proof_instruction_offset: i8 = proof_instruction_offset.into();
         if proof_instruction_offset != 1 {
             return Err(TokenError::InvalidProofInstructionOffset.into());
         }
-        instructions.push(verify_withdraw(None, proof_data));
-    };
+        instructions.push(verify_withdraw(None, proof_data));
+    };
+
+    Ok(instructions)
+}
+
+/// Create a inner `TransferWithSplitValidityProofs` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_transfer_with_split_validity_proofs(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: DecryptableBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    equality_proof_context_state_account: &Pubkey,
+    ciphertext_validity_proof_lo_data_location: ProofLocation<
+        GroupedCiphertext2HandlesValidityProofData,
+    >,
+    ciphertext_validity_proof_hi_data_location: ProofLocation<
+        GroupedCiphertext2HandlesValidityProofData,
+    >,
+    range_proof_context_state_account: &Pubkey,
+    source_amount_lo_proof_context_state_account: &Pubkey,
+    source_amount_hi_proof_context_state_account: &Pubkey,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
+        AccountMeta::new_readonly(*equality_proof_context_state_account, false),
+    ];
+
+    let mut ciphertext_validity_proof_instruction_offsets = [0; 2];
+    for (proof_data_location, proof_instruction_offset_out) in [
+        ciphertext_validity_proof_lo_data_location,
+        ciphertext_validity_proof_hi_data_location,
+    ]
+    .iter()
+    .zip(ciphertext_validity_proof_instruction_offsets.iter_mut())
+    {
+        *proof_instruction_offset_out = match proof_data_location {
+            ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+                accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+                (*proof_instruction_offset).into()
+            }
+            ProofLocation::ContextStateAccount(context_state_account) => {
+                accounts.push(AccountMeta::new_readonly(**context_state_account, false));
+                0
+            }
+        };
+    }
+
+    accounts.push(AccountMeta::new_readonly(
+        *range_proof_context_state_account,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *source_amount_lo_proof_context_state_account,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *source_amount_hi_proof_context_state_account,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::TransferWithSplitValidityProofs,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &TransferWithSplitValidityProofsInstructionData {
+            new_source_decryptable_available_balance,
+            ciphertext_validity_proof_lo_instruction_offset:
+                ciphertext_validity_proof_instruction_offsets[0],
+            ciphertext_validity_proof_hi_instruction_offset:
+                ciphertext_validity_proof_instruction_offsets[1],
+        },
+    ))
+}
+
+/// Create a `TransferWithSplitValidityProofs` instruction
+#[allow(clippy::too_many_arguments)]
+#[cfg(not(target_os = "solana"))]
+pub fn transfer_with_split_validity_proofs(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
+    new_source_decryptable_available_balance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    equality_proof_context_state_account: &Pubkey,
+    ciphertext_validity_proof_lo_data_location: ProofLocation<
+        GroupedCiphertext2HandlesValidityProofData,
+    >,
+    ciphertext_validity_proof_hi_data_location: ProofLocation<
+        GroupedCiphertext2HandlesValidityProofData,
+    >,
+    range_proof_context_state_account: &Pubkey,
+    source_amount_lo_proof_context_state_account: &Pubkey,
+    source_amount_hi_proof_context_state_account: &Pubkey,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_transfer_with_split_validity_proofs(
+        token_program_id,
+        source_token_account,
+        destination_token_account,
+        mint,
+        new_source_decryptable_available_balance.into(),
+        authority,
+        multisig_signers,
+        equality_proof_context_state_account,
+        ciphertext_validity_proof_lo_data_location,
+        ciphertext_validity_proof_hi_data_location,
+        range_proof_context_state_account,
+        source_amount_lo_proof_context_state_account,
+        source_amount_hi_proof_context_state_account,
+    )?];
+
+    // This constructor appends the proof instructions that are included in the transaction right
+    // after the `TransferWithSplitValidityProofs` instruction in order, so the proof instruction
+    // offsets must be consecutive starting from 1. To use arbitrary proof instruction offsets,
+    // use the `inner_transfer_with_split_validity_proofs` constructor.
+    let mut expected_proof_instruction_offset: i8 = 1;
+    for proof_data_location in [
+        ciphertext_validity_proof_lo_data_location,
+        ciphertext_validity_proof_hi_data_location,
+    ] {
+        if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+            proof_data_location
+        {
+            let proof_instruction_offset: i8 = proof_instruction_offset.into();
+            if proof_instruction_offset != expected_proof_instruction_offset {
+                return Err(TokenError::InvalidProofInstructionOffset.into());
+            }
+            instructions.push(
+                ProofInstruction::VerifyGroupedCiphertext2HandlesValidity
+                    .encode_verify_proof(None, proof_data),
+            );
+            expected_proof_instruction_offset += 1;
+        }
+    }
 
     Ok(instructions)
 }
 
-/// Create a inner `Transfer` instruction
+/// Create an `ApproveConfidentialDelegate` instruction
+#[cfg(not(target_os = "solana"))]
+pub fn approve_confidential_delegate(
+    token_program_id: &Pubkey,
+    token_account: &Pubkey,
+    delegate: &Pubkey,
+    amount: u64,
+    decryptable_allowance: AeCiphertext,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*token_account, false),
+        AccountMeta::new_readonly(*delegate, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::ApproveConfidentialDelegate,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &ApproveConfidentialDelegateInstructionData {
+            amount: amount.into(),
+            decryptable_allowance: decryptable_allowance.into(),
+        },
+    ))
+}
+
+/// Create a inner `DelegatedTransfer` instruction
 ///
 /// This instruction is suitable for use with a cross-program `invoke`
 #[allow(clippy::too_many_arguments)]
-pub fn inner_transfer(
+pub fn inner_delegated_transfer(
     token_program_id: &Pubkey,
     source_token_account: &Pubkey,
     destination_token_account: &Pubkey,
     mint: &Pubkey,
     new_source_decryptable_available_balance: DecryptableBalance,
-    authority: &Pubkey,
+    new_decryptable_allowance: DecryptableBalance,
+    equality_proof_context_state_account: &Pubkey,
+    range_proof_context_state_account: &Pubkey,
+    delegate: &Pubkey,
     multisig_signers: &[&Pubkey],
     proof_data_location: ProofLocation<TransferData>,
 ) -> Result<Instruction, ProgramError> {
@@ -861,7 +3725,15 @@ pub fn inner_transfer(
     };
 
     accounts.push(AccountMeta::new_readonly(
-        *authority,
+        *equality_proof_context_state_account,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *range_proof_context_state_account,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *delegate,
         multisig_signers.is_empty(),
     ));
 
@@ -869,38 +3741,46 @@ pub fn inner_transfer(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::Transfer,
-        &TransferInstructionData {
+        ConfidentialTransferInstruction::DelegatedTransfer,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &DelegatedTransferInstructionData {
             new_source_decryptable_available_balance,
+            new_decryptable_allowance,
             proof_instruction_offset,
         },
     ))
 }
 
-/// Create a `Transfer` instruction with regular (no-fee) proof
+/// Create a `DelegatedTransfer` instruction with regular (no-fee) proof
 #[allow(clippy::too_many_arguments)]
 #[cfg(not(target_os = "solana"))]
-pub fn transfer(
+pub fn delegated_transfer(
     token_program_id: &Pubkey,
     source_token_account: &Pubkey,
     destination_token_account: &Pubkey,
     mint: &Pubkey,
     new_source_decryptable_available_balance: AeCiphertext,
-    authority: &Pubkey,
+    new_decryptable_allowance: AeCiphertext,
+    equality_proof_context_state_account: &Pubkey,
+    range_proof_context_state_account: &Pubkey,
+    delegate: &Pubkey,
     multisig_signers: &[&Pubkey],
     proof_data_location: ProofLocation<TransferData>,
 ) -> Result<Vec<Instruction>, ProgramError> {
-    let mut instructions = vec![inner_transfer(
+    let mut instructions = vec![inner_delegated_transfer(
         token_program_id,
         source_token_account,
         destination_token_account,
         mint,
         new_source_decryptable_available_balance.into(),
-        authority,
+        new_decryptable_allowance.into(),
+        equality_proof_context_state_account,
+        range_proof_context_state_account,
+        delegate,
         multisig_signers,
         proof_data_location,
     )?];
@@ -908,10 +3788,9 @@ pub fn transfer(
     if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
         proof_data_location
     {
-        // This constructor appends the proof instruction right after the `ConfigureAccount`
-        // instruction. This means that the proof instruction offset must be always be 1. To
-        // use an arbitrary proof instruction offset, use the `inner_configure_account`
-        // constructor.
+        // This constructor appends the proof instruction right after the `DelegatedTransfer`
+        // instruction. This means that the proof instruction offset must be always be 1. To use an
+        // arbitrary proof instruction offset, use the `inner_delegated_transfer` constructor.
         let proof_instruction_offset: i8 = proof_instruction_offset.into();
         if proof_instruction_offset != 1 {
             return Err(TokenError::InvalidProofInstructionOffset.into());
@@ -922,19 +3801,74 @@ pub fn transfer(
     Ok(instructions)
 }
 
-/// Create a inner `TransferWithFee` instruction
+/// Create an `ApprovePullTransfer` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn inner_approve_pull_transfer(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    puller: &Pubkey,
+    amount_lo: EncryptedBalance,
+    amount_hi: EncryptedBalance,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new_readonly(*puller, false),
+        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+    ];
+
+    for multisig_signer in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
+    }
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::ApprovePullTransfer,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &ApprovePullTransferInstructionData {
+            amount_lo,
+            amount_hi,
+        },
+    ))
+}
+
+/// Create an `ApprovePullTransfer` instruction that authorizes the transfer amount of a
+/// `VerifyTransfer` proof
+#[allow(clippy::too_many_arguments)]
+pub fn approve_pull_transfer(
+    token_program_id: &Pubkey,
+    source_token_account: &Pubkey,
+    puller: &Pubkey,
+    proof_data: &TransferData,
+    authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+) -> Result<Instruction, ProgramError> {
+    inner_approve_pull_transfer(
+        token_program_id,
+        source_token_account,
+        puller,
+        transfer_amount_source_ciphertext(&proof_data.context.ciphertext_lo),
+        transfer_amount_source_ciphertext(&proof_data.context.ciphertext_hi),
+        authority,
+        multisig_signers,
+    )
+}
+
+/// Create a inner `PullTransfer` instruction
 ///
 /// This instruction is suitable for use with a cross-program `invoke`
-#[allow(clippy::too_many_arguments)]
-pub fn inner_transfer_with_fee(
+pub fn inner_pull_transfer(
     token_program_id: &Pubkey,
     source_token_account: &Pubkey,
     destination_token_account: &Pubkey,
     mint: &Pubkey,
-    new_source_decryptable_available_balance: DecryptableBalance,
-    authority: &Pubkey,
+    puller: &Pubkey,
     multisig_signers: &[&Pubkey],
-    proof_data_location: ProofLocation<TransferWithFeeData>,
+    proof_data_location: ProofLocation<TransferData>,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
@@ -955,7 +3889,7 @@ pub fn inner_transfer_with_fee(
     };
 
     accounts.push(AccountMeta::new_readonly(
-        *authority,
+        *puller,
         multisig_signers.is_empty(),
     ));
 
@@ -963,38 +3897,35 @@ pub fn inner_transfer_with_fee(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::Transfer,
-        &TransferInstructionData {
-            new_source_decryptable_available_balance,
+        ConfidentialTransferInstruction::PullTransfer,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &PullTransferInstructionData {
             proof_instruction_offset,
         },
     ))
 }
 
-/// Create a `Transfer` instruction with fee proof
-#[allow(clippy::too_many_arguments)]
+/// Create a `PullTransfer` instruction
 #[cfg(not(target_os = "solana"))]
-pub fn transfer_with_fee(
+pub fn pull_transfer(
     token_program_id: &Pubkey,
     source_token_account: &Pubkey,
     destination_token_account: &Pubkey,
     mint: &Pubkey,
-    new_source_decryptable_available_balance: AeCiphertext,
-    authority: &Pubkey,
+    puller: &Pubkey,
     multisig_signers: &[&Pubkey],
-    proof_data_location: ProofLocation<TransferWithFeeData>,
+    proof_data_location: ProofLocation<TransferData>,
 ) -> Result<Vec<Instruction>, ProgramError> {
-    let mut instructions = vec![inner_transfer_with_fee(
+    let mut instructions = vec![inner_pull_transfer(
         token_program_id,
         source_token_account,
         destination_token_account,
         mint,
-        new_source_decryptable_available_balance.into(),
-        authority,
+        puller,
         multisig_signers,
         proof_data_location,
     )?];
@@ -1002,30 +3933,72 @@ pub fn transfer_with_fee(
     if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
         proof_data_location
     {
-        // This constructor appends the proof instruction right after the `TransferWithFee`
-        // instruction. This means that the proof instruction offset must be always be 1. To
-        // use an arbitrary proof instruction offset, use the `inner_transfer_with_fee`
-        // constructor.
+        // This constructor appends the proof instruction right after the `PullTransfer`
+        // instruction. This means that the proof instruction offset must be always be 1. To use an
+        // arbitrary proof instruction offset, use the `inner_pull_transfer` constructor.
         let proof_instruction_offset: i8 = proof_instruction_offset.into();
         if proof_instruction_offset != 1 {
             return Err(TokenError::InvalidProofInstructionOffset.into());
         }
-        instructions.push(verify_transfer_with_fee(None, proof_data));
+        instructions.push(verify_transfer(None, proof_data));
     };
 
     Ok(instructions)
 }
 
-/// Create a inner `ApplyPendingBalance` instruction
+/// Create an `AtomicSwap` instruction
 ///
 /// This instruction is suitable for use with a cross-program `invoke`
-pub fn inner_apply_pending_balance(
+#[allow(clippy::too_many_arguments)]
+pub fn atomic_swap(
+    token_program_id: &Pubkey,
+    first_source_token_account: &Pubkey,
+    first_destination_token_account: &Pubkey,
+    first_mint: &Pubkey,
+    first_context_state_account: &Pubkey,
+    first_authority: &Pubkey,
+    first_new_source_decryptable_available_balance: DecryptableBalance,
+    second_source_token_account: &Pubkey,
+    second_destination_token_account: &Pubkey,
+    second_mint: &Pubkey,
+    second_context_state_account: &Pubkey,
+    second_authority: &Pubkey,
+    second_new_source_decryptable_available_balance: DecryptableBalance,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![
+        AccountMeta::new(*first_source_token_account, false),
+        AccountMeta::new(*first_destination_token_account, false),
+        AccountMeta::new_readonly(*first_mint, false),
+        AccountMeta::new_readonly(*first_context_state_account, false),
+        AccountMeta::new_readonly(*first_authority, true),
+        AccountMeta::new(*second_source_token_account, false),
+        AccountMeta::new(*second_destination_token_account, false),
+        AccountMeta::new_readonly(*second_mint, false),
+        AccountMeta::new_readonly(*second_context_state_account, false),
+        AccountMeta::new_readonly(*second_authority, true),
+    ];
+
+    Ok(encode_versioned_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::ConfidentialTransferExtension,
+        ConfidentialTransferInstruction::AtomicSwap,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &AtomicSwapInstructionData {
+            first_new_source_decryptable_available_balance,
+            second_new_source_decryptable_available_balance,
+        },
+    ))
+}
+
+/// Create a `UpdateDecryptableBalance` instruction
+pub fn update_decryptable_balance(
     token_program_id: &Pubkey,
     token_account: &Pubkey,
-    expected_pending_balance_credit_counter: u64,
-    new_decryptable_available_balance: DecryptableBalance,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
+    new_decryptable_available_balance: DecryptableBalance,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
@@ -1037,124 +4010,127 @@ pub fn inner_apply_pending_balance(
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        ConfidentialTransferInstruction::ApplyPendingBalance,
-        &ApplyPendingBalanceData {
-            expected_pending_balance_credit_counter: expected_pending_balance_credit_counter.into(),
+        ConfidentialTransferInstruction::UpdateDecryptableBalance,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &UpdateDecryptableBalanceInstructionData {
             new_decryptable_available_balance,
         },
     ))
 }
 
-/// Create a `ApplyPendingBalance` instruction
-#[cfg(not(target_os = "solana"))]
-pub fn apply_pending_balance(
-    token_program_id: &Pubkey,
-    token_account: &Pubkey,
-    pending_balance_instructions: u64,
-    new_decryptable_available_balance: AeCiphertext,
-    authority: &Pubkey,
-    multisig_signers: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    inner_apply_pending_balance(
-        token_program_id,
-        token_account,
-        pending_balance_instructions,
-        new_decryptable_available_balance.into(),
-        authority,
-        multisig_signers,
-    ) // calls check_program_account
-}
-
-fn enable_or_disable_balance_credits(
-    instruction: ConfidentialTransferInstruction,
+/// Create an inner `ForwardPendingBalance` instruction
+///
+/// This instruction is suitable for use with a cross-program `invoke`
+#[allow(clippy::too_many_arguments)]
+pub fn inner_forward_pending_balance(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
+    equality_proof_lo_data_location: ProofLocation<CiphertextCiphertextEqualityProofData>,
+    equality_proof_hi_data_location: ProofLocation<CiphertextCiphertextEqualityProofData>,
 ) -> Result<Instruction, ProgramError> {
     check_program_account(token_program_id)?;
     let mut accounts = vec![
-        AccountMeta::new(*token_account, false),
-        AccountMeta::new_readonly(*authority, multisig_signers.is_empty()),
+        AccountMeta::new(*source_token_account, false),
+        AccountMeta::new(*destination_token_account, false),
+        AccountMeta::new_readonly(*mint, false),
     ];
 
+    let mut equality_proof_instruction_offsets = [0; 2];
+    for (proof_data_location, proof_instruction_offset_out) in [
+        equality_proof_lo_data_location,
+        equality_proof_hi_data_location,
+    ]
+    .iter()
+    .zip(equality_proof_instruction_offsets.iter_mut())
+    {
+        *proof_instruction_offset_out = match proof_data_location {
+            ProofLocation::InstructionOffset(proof_instruction_offset, _) => {
+                accounts.push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+                (*proof_instruction_offset).into()
+            }
+            ProofLocation::ContextStateAccount(context_state_account) => {
+                accounts.push(AccountMeta::new_readonly(**context_state_account, false));
+                0
+            }
+        };
+    }
+
+    accounts.push(AccountMeta::new_readonly(
+        *authority,
+        multisig_signers.is_empty(),
+    ));
+
     for multisig_signer in multisig_signers.iter() {
         accounts.push(AccountMeta::new_readonly(**multisig_signer, true));
     }
 
-    Ok(encode_instruction(
+    Ok(encode_versioned_instruction(
         token_program_id,
         accounts,
         TokenInstruction::ConfidentialTransferExtension,
-        instruction,
-        &(),
+        ConfidentialTransferInstruction::ForwardPendingBalance,
+        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        &ForwardPendingBalanceInstructionData {
+            equality_proof_lo_instruction_offset: equality_proof_instruction_offsets[0],
+            equality_proof_hi_instruction_offset: equality_proof_instruction_offsets[1],
+        },
     ))
 }
 
-/// Create a `EnableConfidentialCredits` instruction
-pub fn enable_confidential_credits(
-    token_program_id: &Pubkey,
-    token_account: &Pubkey,
-    authority: &Pubkey,
-    multisig_signers: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    enable_or_disable_balance_credits(
-        ConfidentialTransferInstruction::EnableConfidentialCredits,
-        token_program_id,
-        token_account,
-        authority,
-        multisig_signers,
-    )
-}
-
-/// Create a `DisableConfidentialCredits` instruction
-pub fn disable_confidential_credits(
+/// Create a `ForwardPendingBalance` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn forward_pending_balance(
     token_program_id: &Pubkey,
-    token_account: &Pubkey,
+    source_token_account: &Pubkey,
+    destination_token_account: &Pubkey,
+    mint: &Pubkey,
     authority: &Pubkey,
     multisig_signers: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    enable_or_disable_balance_credits(
-        ConfidentialTransferInstruction::DisableConfidentialCredits,
+    equality_proof_lo_data_location: ProofLocation<CiphertextCiphertextEqualityProofData>,
+    equality_proof_hi_data_location: ProofLocation<CiphertextCiphertextEqualityProofData>,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let mut instructions = vec![inner_forward_pending_balance(
         token_program_id,
-        token_account,
+        source_token_account,
+        destination_token_account,
+        mint,
         authority,
         multisig_signers,
-    )
-}
+        equality_proof_lo_data_location,
+        equality_proof_hi_data_location,
+    )?];
 
-/// Create a `EnableNonConfidentialCredits` instruction
-pub fn enable_non_confidential_credits(
-    token_program_id: &Pubkey,
-    token_account: &Pubkey,
-    authority: &Pubkey,
-    multisig_signers: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    enable_or_disable_balance_credits(
-        ConfidentialTransferInstruction::EnableNonConfidentialCredits,
-        token_program_id,
-        token_account,
-        authority,
-        multisig_signers,
-    )
-}
+    // This constructor appends the proof instructions that are included in the transaction right
+    // after the `ForwardPendingBalance` instruction in order, so the proof instruction offsets
+    // must be consecutive starting from 1. To use arbitrary proof instruction offsets, use the
+    // `inner_forward_pending_balance` constructor.
+    let mut expected_proof_instruction_offset: i8 = 1;
+    for proof_data_location in [
+        equality_proof_lo_data_location,
+        equality_proof_hi_data_location,
+    ] {
+        if let ProofLocation::InstructionOffset(proof_instruction_offset, proof_data) =
+            proof_data_location
+        {
+            let proof_instruction_offset: i8 = proof_instruction_offset.into();
+            if proof_instruction_offset != expected_proof_instruction_offset {
+                return Err(TokenError::InvalidProofInstructionOffset.into());
+            }
+            instructions.push(
+                ProofInstruction::VerifyCiphertextCiphertextEquality
+                    .encode_verify_proof(None, proof_data),
+            );
+            expected_proof_instruction_offset += 1;
+        }
+    }
 
-/// Create a `DisableNonConfidentialCredits` instruction
-pub fn disable_non_confidential_credits(
-    token_program_id: &Pubkey,
-    token_account: &Pubkey,
-    authority: &Pubkey,
-    multisig_signers: &[&Pubkey],
-) -> Result<Instruction, ProgramError> {
-    enable_or_disable_balance_credits(
-        ConfidentialTransferInstruction::DisableNonConfidentialCredits,
-        token_program_id,
-        token_account,
-        authority,
-        multisig_signers,
-    )
+    Ok(instructions)
 }
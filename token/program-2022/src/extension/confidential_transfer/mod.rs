@@ -1,5 +1,12 @@
+#[cfg(feature = "serde-traits")]
+use serde::{Deserialize, Serialize};
 #[cfg(not(target_os = "solana"))]
-use crate::extension::confidential_transfer::account_info::*;
+use {
+    crate::extension::confidential_transfer::account_info::*,
+    solana_zk_token_sdk::encryption::auth_encryption::{
+        AeCiphertext as DecodedAeCiphertext, AeKey,
+    },
+};
 use {
     crate::{
         error::TokenError,
@@ -7,8 +14,10 @@ use {
         pod::*,
     },
     bytemuck::{Pod, Zeroable},
-    solana_program::entrypoint::ProgramResult,
-    solana_zk_token_sdk::zk_token_elgamal::pod::{AeCiphertext, ElGamalCiphertext, ElGamalPubkey},
+    solana_program::{entrypoint::ProgramResult, pubkey::Pubkey},
+    solana_zk_token_sdk::zk_token_elgamal::pod::{
+        AeCiphertext, ElGamalCiphertext, ElGamalPubkey, TransferAmountCiphertext,
+    },
 };
 
 /// Maximum bit length of any deposit or transfer amount
@@ -19,6 +28,37 @@ pub const MAXIMUM_DEPOSIT_TRANSFER_AMOUNT: u64 = (u16::MAX as u64) + (1 << 16) *
 /// Bit length of the low bits of pending balance plaintext
 pub const PENDING_BALANCE_LO_BIT_LENGTH: u32 = 16;
 
+/// Maximum number of pending balance credits that can be decrypted without an apply
+///
+/// Each deposit or transfer adds at most 2^`PENDING_BALANCE_LO_BIT_LENGTH` to the low bits of the
+/// pending balance, which the account owner decrypts as a 32-bit value. After this many credits,
+/// the low bits could exceed 32 bits, so the pending balance must be applied before the account
+/// can be credited again, regardless of its `maximum_pending_balance_credit_counter`.
+pub const MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER: u64 =
+    1 << (32 - PENDING_BALANCE_LO_BIT_LENGTH);
+
+/// Bit length of the low bits of a deposit amount
+///
+/// The low bits of a deposit amount are added to the low bits of the pending balance, so the split
+/// must match `PENDING_BALANCE_LO_BIT_LENGTH`.
+pub const DEPOSIT_AMOUNT_LO_BIT_LENGTH: u32 = PENDING_BALANCE_LO_BIT_LENGTH;
+
+/// Bit length of the high bits of a deposit amount
+///
+/// The high bits of a deposit amount are added to the high bits of the pending balance, which must
+/// remain small enough to be decrypted by the account owner.
+pub const DEPOSIT_AMOUNT_HI_BIT_LENGTH: u32 = 32;
+
+/// Maximum deposit amount
+///
+/// Any deposit amount must be less than 2^(`DEPOSIT_AMOUNT_LO_BIT_LENGTH` +
+/// `DEPOSIT_AMOUNT_HI_BIT_LENGTH`)
+pub const MAXIMUM_DEPOSIT_AMOUNT: u64 =
+    (1 << (DEPOSIT_AMOUNT_LO_BIT_LENGTH + DEPOSIT_AMOUNT_HI_BIT_LENGTH)) - 1;
+
+/// Confidential Transfer Extension ciphertext extraction helpers that only depend on `core`
+pub mod ciphertext_extraction;
+
 /// Confidential Transfer Extension instructions
 pub mod instruction;
 
@@ -29,11 +69,80 @@ pub mod processor;
 #[cfg(not(target_os = "solana"))]
 pub mod account_info;
 
+/// Confidential Transfer Extension audit records for archival
+#[cfg(not(target_os = "solana"))]
+pub mod audit;
+
+/// Confidential Transfer Extension proof-of-reserves attestations
+#[cfg(not(target_os = "solana"))]
+pub mod reserves;
+
 /// ElGamal ciphertext containing an account balance
 pub type EncryptedBalance = ElGamalCiphertext;
 /// Authenticated encryption containing an account balance
 pub type DecryptableBalance = AeCiphertext;
 
+/// Byte-wise comparison of ElGamal ciphertexts
+///
+/// Two ciphertexts with equal bytes encrypt the same value under the same public key, so a cached
+/// ciphertext can be compared against the on-chain one without decryption. Different bytes do not
+/// imply different values, since a ciphertext is re-randomized on every update.
+pub trait CiphertextBytesEq {
+    /// Compare the ciphertext bytes in constant time
+    fn ct_eq_bytes(&self, other: &Self) -> bool;
+}
+
+impl CiphertextBytesEq for ElGamalCiphertext {
+    fn ct_eq_bytes(&self, other: &Self) -> bool {
+        let difference = self
+            .0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        difference == 0
+    }
+}
+
+/// Extract the transfer amount ciphertext encrypted under the source ElGamal public key.
+///
+/// A transfer amount ciphertext consists of the following 32-byte components that are serialized
+/// in order:
+///   1. The `commitment` component that encodes the transfer amount.
+///   2. The `decryption handle` component with respect to the source public key.
+///   3. The `decryption handle` component with respect to the destination public key.
+///   4. The `decryption handle` component with respect to the auditor public key.
+///
+/// An ElGamal ciphertext for the source consists of the `commitment` component and the `decryption
+/// handle` component with respect to the source.
+pub(crate) fn transfer_amount_source_ciphertext(
+    transfer_amount_ciphertext: &TransferAmountCiphertext,
+) -> ElGamalCiphertext {
+    ElGamalCiphertext(ciphertext_extraction::transfer_amount_source_ciphertext(
+        bytemuck::cast_ref(transfer_amount_ciphertext),
+    ))
+}
+
+/// Extract the transfer amount ciphertext encrypted under the destination ElGamal public key.
+///
+/// A transfer amount ciphertext consists of the following 32-byte components that are serialized
+/// in order:
+///   1. The `commitment` component that encodes the transfer amount.
+///   2. The `decryption handle` component with respect to the source public key.
+///   3. The `decryption handle` component with respect to the destination public key.
+///   4. The `decryption handle` component with respect to the auditor public key.
+///
+/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
+/// `decryption handle` component with respect to the destination public key.
+pub(crate) fn transfer_amount_destination_ciphertext(
+    transfer_amount_ciphertext: &TransferAmountCiphertext,
+) -> ElGamalCiphertext {
+    ElGamalCiphertext(
+        ciphertext_extraction::transfer_amount_destination_ciphertext(bytemuck::cast_ref(
+            transfer_amount_ciphertext,
+        )),
+    )
+}
+
 /// Confidential transfer mint configuration
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
@@ -53,6 +162,8 @@ pub struct ConfidentialTransferMint {
     pub auto_approve_new_accounts: PodBool,
 
     /// Authority to decode any transfer amount in a confidential transafer.
+    ///
+    /// More auditors can be added with the `ConfidentialTransferAuditors` extension.
     pub auditor_elgamal_pubkey: OptionalNonZeroElGamalPubkey,
 }
 
@@ -60,6 +171,94 @@ impl Extension for ConfidentialTransferMint {
     const TYPE: ExtensionType = ExtensionType::ConfidentialTransferMint;
 }
 
+/// Bit length of the low bits of a confidential mint amount
+pub const MINT_AMOUNT_LO_BIT_LENGTH: u8 = 16;
+
+/// Bit length of the high bits of a confidential mint amount
+pub const MINT_AMOUNT_HI_BIT_LENGTH: u8 = 32;
+
+/// Confidential supply of a mint
+///
+/// `MintConfidential` never reveals the mint amount, so it cannot update the supply of the mint.
+/// Instead, the mint amount is encrypted under the destination ElGamal public key and the supply
+/// ElGamal public key, and the ciphertext under the supply ElGamal public key is added to the
+/// confidential supply. `BurnConfidential` subtracts the burn amount from the confidential supply.
+///
+/// The supply of the mint only accounts for tokens that are minted with `MintTo`. Since tokens
+/// cannot move between the two supplies, confidential deposits and withdrawals are rejected for a
+/// mint with a confidential supply.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialMintSupply {
+    /// The ElGamal public key that the confidential supply is encrypted under. If the mint has an
+    /// auditor, it must be the auditor ElGamal public key for `MintConfidential` to succeed.
+    pub supply_elgamal_pubkey: ElGamalPubkey,
+
+    /// The supply of confidentially minted tokens, encrypted under `supply_elgamal_pubkey`
+    pub confidential_supply: EncryptedBalance,
+}
+
+impl Extension for ConfidentialMintSupply {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialMintSupply;
+}
+
+/// Maximum number of additional auditors of a confidential mint
+pub const MAXIMUM_ADDITIONAL_AUDITORS: usize = 4;
+
+/// Confidential transfer mint configuration of additional auditors
+///
+/// The transfer proofs only hold a decryption handle for the auditor of the
+/// `ConfidentialTransferMint`. Every confidential transfer of a mint with additional auditors must
+/// therefore use `TransferWithAdditionalProofs`, which re-encrypts the low and high bits of the
+/// transfer amount under the ElGamal public key of each additional auditor and certifies the
+/// re-encryptions with `VerifyCiphertextCiphertextEquality` proofs.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferAuditors {
+    /// ElGamal public keys of the additional auditors. Unused entries are `None`.
+    pub auditor_elgamal_pubkeys: [OptionalNonZeroElGamalPubkey; MAXIMUM_ADDITIONAL_AUDITORS],
+}
+
+impl Extension for ConfidentialTransferAuditors {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialTransferAuditors;
+}
+
+impl ConfidentialTransferAuditors {
+    /// The ElGamal public keys of the additional auditors in order, skipping unused entries
+    pub fn auditor_elgamal_pubkeys(&self) -> impl Iterator<Item = ElGamalPubkey> + '_ {
+        self.auditor_elgamal_pubkeys
+            .iter()
+            .filter_map(|auditor_elgamal_pubkey| {
+                Option::<ElGamalPubkey>::from(*auditor_elgamal_pubkey)
+            })
+    }
+}
+
+/// Bit length of the range proof that certifies that a confidential transfer does not exceed the
+/// maximum transfer amount of the mint
+pub const MAXIMUM_TRANSFER_AMOUNT_RANGE_PROOF_BIT_LENGTH: u8 = 64;
+
+/// Confidential transfer mint configuration of the maximum amount of a single transfer
+///
+/// The transfer amount is encrypted, so the program cannot compare it with the maximum transfer
+/// amount directly. Every confidential transfer of a mint with a maximum transfer amount must
+/// therefore use `TransferWithAdditionalProofs`. The program subtracts the transfer amount
+/// ciphertexts under the source ElGamal public key from the maximum transfer amount, and the
+/// transfer comes with a `VerifyCiphertextCommitmentEquality` proof that commits to the remaining
+/// amount and a `VerifyBatchedRangeProofU64` proof that the committed amount is a 64-bit amount.
+/// If the transfer amount exceeded the maximum, the remaining amount would wrap around the group
+/// order and no such range proof could be generated.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferMaximumTransferAmount {
+    /// The maximum amount of a single confidential transfer
+    pub maximum_transfer_amount: PodU64,
+}
+
+impl Extension for ConfidentialTransferMaximumTransferAmount {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialTransferMaximumTransferAmount;
+}
+
 /// Confidential account state
 #[repr(C)]
 #[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
@@ -143,40 +342,88 @@ impl ConfidentialTransferAccount {
     }
 
     /// Checks if a `ConfidentialTransferAccount` is configured to send funds.
+    ///
+    /// Fails with `ConfidentialTransferAccountNotApproved` if the account is not approved by the
+    /// confidential transfer mint authority.
     pub fn valid_as_source(&self) -> ProgramResult {
         self.approved()
     }
 
+    /// Checks if a confidential extension accepts credits, regardless of its pending balance.
+    ///
+    /// Credits are accepted if the account is approved by the confidential transfer mint authority
+    /// and not disabled by the account owner. Each condition fails with a distinct error:
+    /// `ConfidentialTransferAccountNotApproved` and
+    /// `ConfidentialTransferDepositsAndTransfersDisabled` respectively. A slot from which credits
+    /// are disabled is checked separately with `ConfidentialTransferCreditsUntilSlot`.
+    pub fn credits_allowed(&self) -> ProgramResult {
+        self.approved()?;
+
+        if !bool::from(self.allow_confidential_credits) {
+            return Err(TokenError::ConfidentialTransferDepositsAndTransfersDisabled.into());
+        }
+
+        Ok(())
+    }
+
     /// Checks if a confidential extension is configured to receive funds.
     ///
     /// A destination account can receive funds if the following conditions are satisfied:
     ///   1. The account is approved by the confidential transfer mint authority
     ///   2. The account is not disabled by the account owner
-    ///   3. The number of credits into the account has reached the maximum credit counter
+    ///   3. The number of credits into the account has not reached the maximum credit counter
+    ///
+    /// The first two conditions fail as in `credits_allowed`, and the last fails with
+    /// `MaximumPendingBalanceCreditCounterExceeded`, or with
+    /// `ConfidentialTransferPendingBalanceOverflow` if the pending balance could no longer be
+    /// decrypted.
     pub fn valid_as_destination(&self) -> ProgramResult {
-        self.approved()?;
+        self.valid_as_destination_of_credits(1)
+    }
 
-        if !bool::from(self.allow_confidential_credits) {
-            return Err(TokenError::ConfidentialTransferDepositsAndTransfersDisabled.into());
-        }
+    /// Checks if a confidential extension is configured to receive `credits` credits at once, as
+    /// when a pending balance that accumulated that many credits is forwarded into it.
+    ///
+    /// Fails as `valid_as_destination` if the account could not receive that many separate
+    /// credits.
+    pub fn valid_as_destination_of_credits(&self, credits: u64) -> ProgramResult {
+        self.credits_allowed()?;
 
         let new_destination_pending_balance_credit_counter =
             u64::from(self.pending_balance_credit_counter)
-                .checked_add(1)
+                .checked_add(credits)
                 .ok_or(TokenError::Overflow)?;
         if new_destination_pending_balance_credit_counter
             > u64::from(self.maximum_pending_balance_credit_counter)
         {
             return Err(TokenError::MaximumPendingBalanceCreditCounterExceeded.into());
         }
+        if new_destination_pending_balance_credit_counter
+            > MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER
+        {
+            return Err(TokenError::ConfidentialTransferPendingBalanceOverflow.into());
+        }
 
         Ok(())
     }
 
+    /// Returns the number of deposits and transfers the account can still receive before its
+    /// pending balance must be applied.
+    pub fn remaining_pending_balance_credits(&self) -> u64 {
+        u64::from(self.maximum_pending_balance_credit_counter)
+            .min(MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER)
+            .saturating_sub(u64::from(self.pending_balance_credit_counter))
+    }
+
     /// Increments a confidential extension pending balance credit counter.
     pub fn increment_pending_balance_credit_counter(&mut self) -> ProgramResult {
+        self.add_pending_balance_credits(1)
+    }
+
+    /// Increases a confidential extension pending balance credit counter by `credits`.
+    pub fn add_pending_balance_credits(&mut self, credits: u64) -> ProgramResult {
         self.pending_balance_credit_counter = (u64::from(self.pending_balance_credit_counter)
-            .checked_add(1)
+            .checked_add(credits)
             .ok_or(TokenError::Overflow)?)
         .into();
         Ok(())
@@ -190,6 +437,20 @@ impl ConfidentialTransferAccount {
         EmptyAccountAccountInfo { available_balance }
     }
 
+    /// Return the account information needed to construct an `EmptyAccountFull` instruction.
+    #[cfg(not(target_os = "solana"))]
+    pub fn empty_account_full_account_info(&self) -> EmptyAccountFullAccountInfo {
+        let available_balance = self.available_balance;
+        let pending_balance_lo = self.pending_balance_lo;
+        let pending_balance_hi = self.pending_balance_hi;
+
+        EmptyAccountFullAccountInfo {
+            available_balance,
+            pending_balance_lo,
+            pending_balance_hi,
+        }
+    }
+
     /// Return the account information needed to construct an `ApplyPendingBalance` instruction.
     #[cfg(not(target_os = "solana"))]
     pub fn apply_pending_balance_account_info(&self) -> ApplyPendingBalanceAccountInfo {
@@ -229,4 +490,438 @@ impl ConfidentialTransferAccount {
             decryptable_available_balance,
         }
     }
+
+    /// Return the account information needed to construct a `ForwardPendingBalance` instruction.
+    #[cfg(not(target_os = "solana"))]
+    pub fn forward_pending_balance_account_info(&self) -> ForwardPendingBalanceAccountInfo {
+        let pending_balance_lo = self.pending_balance_lo;
+        let pending_balance_hi = self.pending_balance_hi;
+
+        ForwardPendingBalanceAccountInfo {
+            pending_balance_lo,
+            pending_balance_hi,
+        }
+    }
+
+    /// Return the account information needed to construct a `RotateElGamalKey` instruction.
+    #[cfg(not(target_os = "solana"))]
+    pub fn rotate_elgamal_key_account_info(&self) -> RotateElGamalKeyAccountInfo {
+        let available_balance = self.available_balance;
+        let decryptable_available_balance = self.decryptable_available_balance;
+
+        RotateElGamalKeyAccountInfo {
+            available_balance,
+            decryptable_available_balance,
+        }
+    }
+}
+
+/// Plaintext fields of a `ConfidentialTransferAccount`
+///
+/// Omits the encrypted balances, so that account metadata can be logged or serialized without
+/// exposing ciphertexts. The ElGamal public key is encoded as a base58 string.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-traits", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde-traits", serde(rename_all = "camelCase"))]
+pub struct ConfidentialTransferAccountView {
+    /// `true` if this account has been approved for use
+    pub approved: bool,
+    /// The public key associated with ElGamal encryption, in base58
+    pub elgamal_pubkey: String,
+    /// If `false`, the extended account rejects any incoming confidential transfers
+    pub allow_confidential_credits: bool,
+    /// If `false`, the base account rejects any incoming transfers
+    pub allow_non_confidential_credits: bool,
+    /// The total number of `Deposit` and `Transfer` instructions that have credited
+    /// `pending_balance`
+    pub pending_balance_credit_counter: u64,
+    /// The maximum number of `Deposit` and `Transfer` instructions that can credit
+    /// `pending_balance` before the `ApplyPendingBalance` instruction is executed
+    pub maximum_pending_balance_credit_counter: u64,
+    /// The `expected_pending_balance_credit_counter` value that was included in the last
+    /// `ApplyPendingBalance` instruction
+    pub expected_pending_balance_credit_counter: u64,
+    /// The actual `pending_balance_credit_counter` when the last `ApplyPendingBalance` instruction
+    /// was executed
+    pub actual_pending_balance_credit_counter: u64,
+}
+
+impl From<&ConfidentialTransferAccount> for ConfidentialTransferAccountView {
+    fn from(account: &ConfidentialTransferAccount) -> Self {
+        Self {
+            approved: bool::from(&account.approved),
+            // an ElGamal public key has the same length as a `Pubkey`, so reuse its base58 encoding
+            elgamal_pubkey: Pubkey::new_from_array(account.elgamal_pubkey.0).to_string(),
+            allow_confidential_credits: bool::from(&account.allow_confidential_credits),
+            allow_non_confidential_credits: bool::from(&account.allow_non_confidential_credits),
+            pending_balance_credit_counter: account.pending_balance_credit_counter.into(),
+            maximum_pending_balance_credit_counter: account
+                .maximum_pending_balance_credit_counter
+                .into(),
+            expected_pending_balance_credit_counter: account
+                .expected_pending_balance_credit_counter
+                .into(),
+            actual_pending_balance_credit_counter: account
+                .actual_pending_balance_credit_counter
+                .into(),
+        }
+    }
+}
+
+/// Confidential transfer credit window state
+///
+/// Schedules the slot from which the extended account rejects incoming confidential transfers and
+/// deposits, even if `allow_confidential_credits` is `true` in its `ConfidentialTransferAccount`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferCreditsUntilSlot {
+    /// If nonzero, the slot from which the extended account rejects incoming confidential credits
+    pub slot: PodU64,
+}
+
+impl Extension for ConfidentialTransferCreditsUntilSlot {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialTransferCreditsUntilSlot;
+}
+
+impl ConfidentialTransferCreditsUntilSlot {
+    /// Checks if the extended account accepts confidential credits at `slot`.
+    ///
+    /// Fails with `ConfidentialTransferCreditsDisabledAsOfSlot` from the scheduled slot onward.
+    pub fn credits_allowed(&self, slot: u64) -> ProgramResult {
+        let until_slot = u64::from(self.slot);
+        if until_slot != 0 && slot >= until_slot {
+            return Err(TokenError::ConfidentialTransferCreditsDisabledAsOfSlot.into());
+        }
+        Ok(())
+    }
+}
+
+/// Bit length of the range proof that certifies that a delegated confidential transfer does not
+/// exceed the remaining allowance of the delegate
+pub const DELEGATE_ALLOWANCE_RANGE_PROOF_BIT_LENGTH: u8 = 64;
+
+/// Confidential transfer delegate allowance state
+///
+/// Holds the remaining amount that the delegate of the extended account can transfer out of its
+/// confidential balance with `DelegatedTransfer`. The allowance is encrypted, so the amounts of
+/// delegated transfers remain confidential. Each delegated transfer proves that the transfer
+/// amount does not exceed the remaining allowance and subtracts it homomorphically.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferDelegateAllowance {
+    /// The delegate that the allowance was approved for, if any
+    pub delegate: OptionalNonZeroPubkey,
+
+    /// The remaining allowance encrypted under the ElGamal public key of the extended account
+    pub allowance: EncryptedBalance,
+
+    /// The remaining allowance encrypted under the AES key of the extended account
+    pub decryptable_allowance: DecryptableBalance,
+}
+
+impl Extension for ConfidentialTransferDelegateAllowance {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialTransferDelegateAllowance;
+}
+
+/// Confidential transfer pull authorization state
+///
+/// Authorizes a single `PullTransfer` from the extended account, similar to a delegate scoped to
+/// the confidential balance. The authorization is cleared once the pull transfer is processed.
+/// The pull transfer leaves the decryptable available balance of the extended account unchanged,
+/// so the owner updates it with `UpdateDecryptableBalance` after the pull. Until then, transfers,
+/// withdrawals, and burns from the extended account fail, since their new decryptable available
+/// balance would be computed from the stale one.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferPullAuthorization {
+    /// The account that is authorized to pull a confidential transfer, if any
+    pub puller: OptionalNonZeroPubkey,
+
+    /// The low 16 bits of the authorized transfer amount encrypted under the ElGamal public key of
+    /// the extended account
+    pub amount_lo: EncryptedBalance,
+
+    /// The high 32 bits of the authorized transfer amount encrypted under the ElGamal public key of
+    /// the extended account
+    pub amount_hi: EncryptedBalance,
+
+    /// `true` if a pull transfer debited the available balance since the owner last updated the
+    /// decryptable available balance
+    pub decryptable_available_balance_stale: PodBool,
+}
+
+impl Extension for ConfidentialTransferPullAuthorization {
+    const TYPE: ExtensionType = ExtensionType::ConfidentialTransferPullAuthorization;
+}
+
+/// Prefix of the structured log data emitted for a confidential deposit
+pub const DEPOSIT_EVENT_PREFIX: &[u8] = b"spl-token-2022:confidential-deposit";
+
+/// Structured log data emitted by the `Deposit` and `DepositAndApply` instructions
+///
+/// The event is logged with `sol_log_data` as two fields, `DEPOSIT_EVENT_PREFIX` followed by the
+/// bytes of this struct, which appear in the transaction logs as
+/// `Program data: <base64 prefix> <base64 event>`. Since the deposit amount is public, indexers can
+/// decode this event to surface deposits without parsing the instruction data.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialDepositEvent {
+    /// The token account that received the deposit
+    pub token_account: Pubkey,
+
+    /// The mint of the token account
+    pub mint: Pubkey,
+
+    /// The deposited amount
+    pub amount: PodU64,
+
+    /// Whether the amount was credited directly to the available balance instead of the pending
+    /// balance
+    pub applied: PodBool,
+}
+
+impl ConfidentialDepositEvent {
+    /// Decodes an event from the base64-decoded fields of a `Program data:` log, returning `None`
+    /// if the fields do not hold a deposit event
+    pub fn from_log_data(fields: &[&[u8]]) -> Option<Self> {
+        match fields {
+            [prefix, data] if *prefix == DEPOSIT_EVENT_PREFIX => {
+                bytemuck::try_pod_read_unaligned(data).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Byte length of a transfer reference, such as an invoice identifier
+pub const TRANSFER_REFERENCE_LEN: usize = 16;
+
+/// A transfer reference encrypted under an authenticated encryption key
+///
+/// A reference, such as an invoice identifier, is carried by a `TransferWithReference` instruction
+/// so that the recipient can reconcile the payment, separately from any public memo. It is encrypted
+/// under an authenticated encryption key that the recipient shares with its payers, for example as
+/// part of the invoice, since an ElGamal ciphertext under the destination public key only holds
+/// amounts that are small enough to be decrypted. The reference is not part of the zero-knowledge
+/// proof of the transfer: the program does not check that it decrypts, and only logs it as a
+/// [ConfidentialTransferReferenceEvent]. Decryption authenticates that it was encrypted under the
+/// shared key, but not that it was attached by the owner of the source account.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct EncryptedTransferReference {
+    /// The first 8 bytes of the reference, as a little-endian `u64`
+    pub reference_lo: DecryptableBalance,
+    /// The last 8 bytes of the reference, as a little-endian `u64`
+    pub reference_hi: DecryptableBalance,
+}
+
+#[cfg(not(target_os = "solana"))]
+impl EncryptedTransferReference {
+    /// Encrypts a transfer reference under `aes_key`
+    pub fn encrypt(aes_key: &AeKey, reference: &[u8; TRANSFER_REFERENCE_LEN]) -> Self {
+        let (reference_lo, reference_hi) = reference.split_at(TRANSFER_REFERENCE_LEN / 2);
+        let encrypt = |bytes: &[u8]| {
+            let value = u64::from_le_bytes(bytes.try_into().unwrap());
+            aes_key.encrypt(value).into()
+        };
+
+        Self {
+            reference_lo: encrypt(reference_lo),
+            reference_hi: encrypt(reference_hi),
+        }
+    }
+
+    /// Decrypts a transfer reference with `aes_key`, or returns `None` if it was not encrypted
+    /// under that key
+    pub fn decrypt(&self, aes_key: &AeKey) -> Option<[u8; TRANSFER_REFERENCE_LEN]> {
+        let decrypt = |ciphertext: DecryptableBalance| {
+            DecodedAeCiphertext::try_from(ciphertext)
+                .ok()
+                .and_then(|ciphertext| aes_key.decrypt(&ciphertext))
+        };
+
+        let mut reference = [0; TRANSFER_REFERENCE_LEN];
+        reference[..TRANSFER_REFERENCE_LEN / 2]
+            .copy_from_slice(&decrypt(self.reference_lo)?.to_le_bytes());
+        reference[TRANSFER_REFERENCE_LEN / 2..]
+            .copy_from_slice(&decrypt(self.reference_hi)?.to_le_bytes());
+        Some(reference)
+    }
+}
+
+/// Prefix of the structured log data emitted for a confidential transfer with a reference
+pub const TRANSFER_REFERENCE_EVENT_PREFIX: &[u8] =
+    b"spl-token-2022:confidential-transfer-reference";
+
+/// Structured log data emitted by a `TransferWithReference` instruction for its
+/// [EncryptedTransferReference]
+///
+/// The event is logged with `sol_log_data` as two fields, `TRANSFER_REFERENCE_EVENT_PREFIX`
+/// followed by the bytes of this struct, in the same format as a [ConfidentialDepositEvent].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Pod, Zeroable)]
+pub struct ConfidentialTransferReferenceEvent {
+    /// The source token account of the transfer
+    pub source: Pubkey,
+
+    /// The destination token account of the transfer
+    pub destination: Pubkey,
+
+    /// The mint of the token accounts
+    pub mint: Pubkey,
+
+    /// The encrypted transfer reference
+    pub reference: EncryptedTransferReference,
+}
+
+impl ConfidentialTransferReferenceEvent {
+    /// Decodes an event from the base64-decoded fields of a `Program data:` log, returning `None`
+    /// if the fields do not hold a transfer reference event
+    pub fn from_log_data(fields: &[&[u8]]) -> Option<Self> {
+        match fields {
+            [prefix, data] if *prefix == TRANSFER_REFERENCE_EVENT_PREFIX => {
+                bytemuck::try_pod_read_unaligned(data).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validity_check_errors() {
+        let account = ConfidentialTransferAccount {
+            approved: true.into(),
+            allow_confidential_credits: true.into(),
+            maximum_pending_balance_credit_counter: 2.into(),
+            ..ConfidentialTransferAccount::default()
+        };
+        assert_eq!(account.valid_as_source(), Ok(()));
+        assert_eq!(account.valid_as_destination(), Ok(()));
+
+        let not_approved = ConfidentialTransferAccount {
+            approved: false.into(),
+            ..account
+        };
+        assert_eq!(
+            not_approved.valid_as_source(),
+            Err(TokenError::ConfidentialTransferAccountNotApproved.into())
+        );
+        assert_eq!(
+            not_approved.valid_as_destination(),
+            Err(TokenError::ConfidentialTransferAccountNotApproved.into())
+        );
+
+        let credits_disabled = ConfidentialTransferAccount {
+            allow_confidential_credits: false.into(),
+            ..account
+        };
+        assert_eq!(credits_disabled.valid_as_source(), Ok(()));
+        assert_eq!(
+            credits_disabled.valid_as_destination(),
+            Err(TokenError::ConfidentialTransferDepositsAndTransfersDisabled.into())
+        );
+
+        let counter_exceeded = ConfidentialTransferAccount {
+            pending_balance_credit_counter: 2.into(),
+            ..account
+        };
+        assert_eq!(counter_exceeded.credits_allowed(), Ok(()));
+        assert_eq!(
+            counter_exceeded.valid_as_destination(),
+            Err(TokenError::MaximumPendingBalanceCreditCounterExceeded.into())
+        );
+
+        let pending_balance_overflow = ConfidentialTransferAccount {
+            pending_balance_credit_counter: MAXIMUM_SAFE_PENDING_BALANCE_CREDIT_COUNTER.into(),
+            maximum_pending_balance_credit_counter: u64::MAX.into(),
+            ..account
+        };
+        assert_eq!(
+            pending_balance_overflow.valid_as_destination(),
+            Err(TokenError::ConfidentialTransferPendingBalanceOverflow.into())
+        );
+    }
+
+    #[test]
+    fn credits_until_slot() {
+        let unlimited = ConfidentialTransferCreditsUntilSlot::default();
+        assert_eq!(unlimited.credits_allowed(u64::MAX), Ok(()));
+
+        let until_slot = ConfidentialTransferCreditsUntilSlot { slot: 10.into() };
+        assert_eq!(until_slot.credits_allowed(9), Ok(()));
+        assert_eq!(
+            until_slot.credits_allowed(10),
+            Err(TokenError::ConfidentialTransferCreditsDisabledAsOfSlot.into())
+        );
+    }
+
+    #[test]
+    fn ciphertext_bytes_eq() {
+        let ciphertext = ElGamalCiphertext([7; 64]);
+        assert!(ciphertext.ct_eq_bytes(&ElGamalCiphertext([7; 64])));
+
+        let mut other_bytes = [7; 64];
+        other_bytes[63] = 8;
+        assert!(!ciphertext.ct_eq_bytes(&ElGamalCiphertext(other_bytes)));
+        assert!(!ciphertext.ct_eq_bytes(&ElGamalCiphertext::zeroed()));
+    }
+
+    #[test]
+    fn deposit_event_from_log_data() {
+        let event = ConfidentialDepositEvent {
+            token_account: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            amount: 42.into(),
+            applied: false.into(),
+        };
+        let data = bytemuck::bytes_of(&event);
+
+        assert_eq!(
+            ConfidentialDepositEvent::from_log_data(&[DEPOSIT_EVENT_PREFIX, data]),
+            Some(event)
+        );
+        assert_eq!(
+            ConfidentialDepositEvent::from_log_data(&[&b"other-event"[..], data]),
+            None
+        );
+        assert_eq!(
+            ConfidentialDepositEvent::from_log_data(&[DEPOSIT_EVENT_PREFIX, &data[1..]]),
+            None
+        );
+        assert_eq!(ConfidentialDepositEvent::from_log_data(&[data]), None);
+    }
+
+    #[test]
+    fn transfer_reference_round_trip() {
+        let aes_key = AeKey::new_rand();
+        let reference = *b"invoice-00000042";
+        let encrypted_reference = EncryptedTransferReference::encrypt(&aes_key, &reference);
+
+        assert_eq!(encrypted_reference.decrypt(&aes_key), Some(reference));
+        assert_eq!(encrypted_reference.decrypt(&AeKey::new_rand()), None);
+
+        let event = ConfidentialTransferReferenceEvent {
+            source: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            reference: encrypted_reference,
+        };
+        let data = bytemuck::bytes_of(&event);
+
+        let decoded_event = ConfidentialTransferReferenceEvent::from_log_data(&[
+            TRANSFER_REFERENCE_EVENT_PREFIX,
+            data,
+        ])
+        .unwrap();
+        assert_eq!(decoded_event, event);
+        assert_eq!(decoded_event.reference.decrypt(&aes_key), Some(reference));
+        assert_eq!(
+            ConfidentialTransferReferenceEvent::from_log_data(&[DEPOSIT_EVENT_PREFIX, data]),
+            None
+        );
+    }
 }
@@ -10,29 +10,40 @@ use {
             },
             memo_transfer::{check_previous_sibling_instruction_is_memo, memo_required},
             transfer_fee::TransferFeeConfig,
-            BaseStateWithExtensions, StateWithExtensions, StateWithExtensionsMut,
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions, StateWithExtensionsMut,
+        },
+        instruction::{
+            decode_instruction_data, decode_instruction_type, decode_versioned_instruction_data,
         },
-        instruction::{decode_instruction_data, decode_instruction_type},
         processor::Processor,
-        proof::decode_proof_instruction_context,
-        solana_zk_token_sdk::zk_token_elgamal::pod::TransferAmountCiphertext,
+        proof::{decode_proof_instruction_context, get_proof_instruction_relative},
+        solana_zk_token_sdk::{
+            instruction::transfer::TransferPubkeys,
+            zk_token_elgamal::pod::{
+                GroupedElGamalCiphertext2Handles, PedersenCommitment, TransferAmountCiphertext,
+            },
+        },
         state::{Account, Mint},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         clock::Clock,
         entrypoint::ProgramResult,
+        hash::hash,
         msg,
         program_error::ProgramError,
         pubkey::Pubkey,
-        sysvar::instructions::get_instruction_relative,
         sysvar::Sysvar,
     },
+    std::mem::size_of,
 };
 // Remove feature once zk ops syscalls are enabled on all networks
 #[cfg(feature = "zk-ops")]
 use {
-    crate::extension::non_transferable::NonTransferable,
+    crate::extension::{non_transferable::NonTransferable, transfer_fee::MAX_FEE_BASIS_POINTS},
+    solana_program::{
+        account_info::next_account_infos, log::sol_log_data, program_option::COption,
+    },
     solana_zk_token_sdk::zk_token_elgamal::ops as syscall,
 };
 
@@ -41,7 +52,7 @@ fn process_initialize_mint(
     accounts: &[AccountInfo],
     authority: &OptionalNonZeroPubkey,
     auto_approve_new_account: PodBool,
-    auditor_encryption_pubkey: &OptionalNonZeroElGamalPubkey,
+    auditor_encryption_pubkey: Option<&OptionalNonZeroElGamalPubkey>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let mint_info = next_account_info(account_info_iter)?;
@@ -58,11 +69,11 @@ fn process_initialize_mint(
     Ok(())
 }
 
-/// Processes an [UpdateMint] instruction.
+/// Processes an [UpdateMint] or [UpdateMintPartial] instruction.
 fn process_update_mint(
     accounts: &[AccountInfo],
     auto_approve_new_account: PodBool,
-    auditor_encryption_pubkey: &OptionalNonZeroElGamalPubkey,
+    auditor_encryption_pubkey: Option<&OptionalNonZeroElGamalPubkey>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let mint_info = next_account_info(account_info_iter)?;
@@ -86,7 +97,113 @@ fn process_update_mint(
     }
 
     confidential_transfer_mint.auto_approve_new_accounts = auto_approve_new_account;
-    confidential_transfer_mint.auditor_elgamal_pubkey = *auditor_encryption_pubkey;
+    if let Some(auditor_encryption_pubkey) = auditor_encryption_pubkey {
+        confidential_transfer_mint.auditor_elgamal_pubkey = *auditor_encryption_pubkey;
+    }
+    Ok(())
+}
+
+/// Processes an [InitializeAuditors] instruction.
+fn process_initialize_auditors(
+    accounts: &[AccountInfo],
+    data: &AuditorsInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(mint_data)?;
+    let confidential_transfer_auditors =
+        mint.init_extension::<ConfidentialTransferAuditors>(true)?;
+
+    confidential_transfer_auditors.auditor_elgamal_pubkeys = data.auditor_elgamal_pubkeys;
+
+    Ok(())
+}
+
+/// Processes an [UpdateAuditors] instruction.
+fn process_update_auditors(
+    accounts: &[AccountInfo],
+    data: &AuditorsInstructionData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+    let maybe_confidential_transfer_mint_authority: Option<Pubkey> = mint
+        .get_extension::<ConfidentialTransferMint>()?
+        .authority
+        .into();
+    let confidential_transfer_mint_authority =
+        maybe_confidential_transfer_mint_authority.ok_or(TokenError::NoAuthorityExists)?;
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if confidential_transfer_mint_authority != *authority_info.key {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+
+    let confidential_transfer_auditors =
+        mint.get_extension_mut::<ConfidentialTransferAuditors>()?;
+    confidential_transfer_auditors.auditor_elgamal_pubkeys = data.auditor_elgamal_pubkeys;
+    Ok(())
+}
+
+/// Processes an [InitializeMaximumTransferAmount] instruction.
+fn process_initialize_maximum_transfer_amount(
+    accounts: &[AccountInfo],
+    maximum_transfer_amount: PodU64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(mint_data)?;
+    let confidential_transfer_maximum_transfer_amount =
+        mint.init_extension::<ConfidentialTransferMaximumTransferAmount>(true)?;
+
+    confidential_transfer_maximum_transfer_amount.maximum_transfer_amount = maximum_transfer_amount;
+
+    Ok(())
+}
+
+/// Processes an [UpdateMaximumTransferAmount] instruction.
+fn process_update_maximum_transfer_amount(
+    accounts: &[AccountInfo],
+    maximum_transfer_amount: PodU64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+    let maybe_confidential_transfer_mint_authority: Option<Pubkey> = mint
+        .get_extension::<ConfidentialTransferMint>()?
+        .authority
+        .into();
+    let confidential_transfer_mint_authority =
+        maybe_confidential_transfer_mint_authority.ok_or(TokenError::NoAuthorityExists)?;
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if confidential_transfer_mint_authority != *authority_info.key {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+
+    let confidential_transfer_maximum_transfer_amount =
+        mint.get_extension_mut::<ConfidentialTransferMaximumTransferAmount>()?;
+    confidential_transfer_maximum_transfer_amount.maximum_transfer_amount = maximum_transfer_amount;
     Ok(())
 }
 
@@ -112,6 +229,7 @@ fn process_configure_account(
     let authority_info_data_len = authority_info.data_len();
 
     check_program_account(token_account_info.owner)?;
+    let token_account_data_len = token_account_info.data_len();
     let token_account_data = &mut token_account_info.data.borrow_mut();
     let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
 
@@ -130,10 +248,49 @@ fn process_configure_account(
     check_program_account(mint_info.owner)?;
     let mint_data = &mut mint_info.data.borrow();
     let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
-    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    let confidential_transfer_mint = match mint.get_extension::<ConfidentialTransferMint>() {
+        Ok(confidential_transfer_mint) => confidential_transfer_mint,
+        Err(e) => {
+            msg!("Mint is not extended for confidential transfers");
+            return Err(e);
+        }
+    };
+    let mint_has_transfer_fee = mint.get_extension::<TransferFeeConfig>().is_ok();
+
+    // The withheld fees of a confidential transfer are encrypted under the withdraw withheld
+    // authority ElGamal public key of the `ConfidentialTransferFeeConfig` extension, so the
+    // `ConfidentialTransferFeeAmount` extension can only be maintained if the mint has both
+    let mint_has_confidential_transfer_fee = mint
+        .get_extension::<ConfidentialTransferFeeConfig>()
+        .is_ok();
+    if mint_has_transfer_fee != mint_has_confidential_transfer_fee {
+        msg!("Mint must have both or neither of the transfer fee and confidential transfer fee extensions");
+        return Err(TokenError::InvalidExtensionCombination.into());
+    }
 
     // Note: The caller is expected to use the `Reallocate` instruction to ensure there is
-    // sufficient room in their token account for the new `ConfidentialTransferAccount` extension
+    // sufficient room in their token account for the new `ConfidentialTransferAccount` extension,
+    // and the `ConfidentialTransferFeeAmount` extension if the mint is extended for fees
+    let mut required_extensions = token_account.get_extension_types()?;
+    required_extensions.push(ExtensionType::ConfidentialTransferAccount);
+    if mint_has_transfer_fee {
+        required_extensions.push(ExtensionType::ConfidentialTransferFeeAmount);
+    }
+    if ExtensionType::try_calculate_account_len::<Account>(&required_extensions)?
+        > token_account_data_len
+    {
+        let extension = if mint_has_transfer_fee {
+            "confidential transfer fee"
+        } else {
+            "confidential transfer"
+        };
+        msg!(
+            "Insufficient account space for the {} extension; reallocate first",
+            extension
+        );
+        return Err(TokenError::InsufficientConfidentialTransferAccountSpace.into());
+    }
+
     let mut confidential_transfer_account =
         token_account.init_extension::<ConfidentialTransferAccount>(false)?;
     confidential_transfer_account.approved = confidential_transfer_mint.auto_approve_new_accounts;
@@ -146,6 +303,8 @@ fn process_configure_account(
     confidential_transfer_account.pending_balance_hi = EncryptedBalance::zeroed();
     confidential_transfer_account.available_balance = EncryptedBalance::zeroed();
 
+    // Note: The decryptable balance is not certified by the zero-knowledge proof, so the caller is
+    // responsible for supplying an encryption of zero
     confidential_transfer_account.decryptable_available_balance = *decryptable_zero_balance;
     confidential_transfer_account.allow_confidential_credits = true.into();
     confidential_transfer_account.pending_balance_credit_counter = 0.into();
@@ -154,7 +313,7 @@ fn process_configure_account(
     confidential_transfer_account.allow_non_confidential_credits = true.into();
 
     // if the mint is extended for fees, then initialize account for confidential transfer fees
-    if mint.get_extension::<TransferFeeConfig>().is_ok() {
+    if mint_has_transfer_fee {
         let mut confidential_transfer_fee_amount =
             token_account.init_extension::<ConfidentialTransferFeeAmount>(false)?;
         confidential_transfer_fee_amount.withheld_amount = EncryptedWithheldAmount::zeroed();
@@ -173,18 +332,16 @@ fn verify_configure_account_proof(
         // interpret `account_info` as a context state account
         check_zk_token_proof_program_account(account_info.owner)?;
         let context_state_account_data = account_info.data.borrow();
+        check_context_state_proof_type(&context_state_account_data, ProofType::PubkeyValidity)?;
         let context_state = pod_from_bytes::<ProofContextState<PubkeyValidityProofContext>>(
             &context_state_account_data,
         )?;
 
-        if context_state.proof_type != ProofType::PubkeyValidity.into() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-
         Ok(context_state.proof_context)
     } else {
         // interpret `account_info` as a sysvar
-        let zkp_instruction = get_instruction_relative(proof_instruction_offset, account_info)?;
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
         Ok(*decode_proof_instruction_context::<
             PubkeyValidityData,
             PubkeyValidityProofContext,
@@ -275,6 +432,69 @@ fn process_empty_account(
     Ok(())
 }
 
+/// Processes an [EmptyAccountFull] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_empty_account_full(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proof certifies that the sum of the available and pending balance
+    // ciphertexts holds the balance of 0.
+    let proof_context = verify_empty_account_proof(
+        next_account_info(account_info_iter)?,
+        proof_instruction_offset,
+    )?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+
+    if confidential_transfer_account.elgamal_pubkey != proof_context.pubkey {
+        msg!("Encryption public-key mismatch");
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    // Combine the pending balance with the available balance as in `ApplyPendingBalance`
+    let total_balance = syscall::add_with_lo_hi(
+        &confidential_transfer_account.available_balance,
+        &confidential_transfer_account.pending_balance_lo,
+        &confidential_transfer_account.pending_balance_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+    if total_balance != proof_context.ciphertext {
+        msg!("Total balance mismatch");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
+    confidential_transfer_account.available_balance = EncryptedBalance::zeroed();
+    confidential_transfer_account.pending_balance_lo = EncryptedBalance::zeroed();
+    confidential_transfer_account.pending_balance_hi = EncryptedBalance::zeroed();
+    confidential_transfer_account.pending_balance_credit_counter = 0.into();
+
+    // check that all balances are all-zero ciphertexts
+    confidential_transfer_account.closable()?;
+
+    Ok(())
+}
+
 /// Verify zero-knowledge proof needed for a [EmptyAccount] instruction and return the
 /// corresponding proof context.
 fn verify_empty_account_proof(
@@ -285,18 +505,16 @@ fn verify_empty_account_proof(
         // interpret `account_info` as a context state account
         check_zk_token_proof_program_account(account_info.owner)?;
         let context_state_account_data = account_info.data.borrow();
+        check_context_state_proof_type(&context_state_account_data, ProofType::ZeroBalance)?;
         let context_state = pod_from_bytes::<ProofContextState<ZeroBalanceProofContext>>(
             &context_state_account_data,
         )?;
 
-        if context_state.proof_type != ProofType::ZeroBalance.into() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-
         Ok(context_state.proof_context)
     } else {
         // interpret `account_info` as a sysvar
-        let zkp_instruction = get_instruction_relative(proof_instruction_offset, account_info)?;
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
         Ok(*decode_proof_instruction_context::<
             ZeroBalanceProofData,
             ZeroBalanceProofContext,
@@ -306,13 +524,14 @@ fn verify_empty_account_proof(
     }
 }
 
-/// Processes a [Deposit] instruction.
+/// Processes a [Deposit] or [DepositAndApply] instruction.
 #[cfg(feature = "zk-ops")]
 fn process_deposit(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     amount: u64,
     expected_decimals: u8,
+    new_decryptable_available_balance: Option<DecryptableBalance>,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let token_account_info = next_account_info(account_info_iter)?;
@@ -329,9 +548,14 @@ fn process_deposit(
     }
 
     if mint.get_extension::<NonTransferable>().is_ok() {
+        msg!("Confidential deposits are not available for non-transferable mints");
         return Err(TokenError::NonTransferable.into());
     }
 
+    if mint.get_extension::<ConfidentialMintSupply>().is_ok() {
+        return Err(TokenError::ConfidentialSupplyConversionNotSupported.into());
+    }
+
     check_program_account(token_account_info.owner)?;
     let token_account_data = &mut token_account_info.data.borrow_mut();
     let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
@@ -353,7 +577,9 @@ fn process_deposit(
     }
 
     // Wrapped SOL deposits are not supported because lamports cannot be vanished.
-    assert!(!token_account.base.is_native());
+    if token_account.base.is_native() {
+        return Err(TokenError::NativeNotSupported.into());
+    }
 
     token_account.base.amount = token_account
         .base
@@ -362,11 +588,41 @@ fn process_deposit(
         .ok_or(TokenError::Overflow)?;
     token_account.pack_base();
 
+    check_credits_until_slot(&token_account)?;
     let mut confidential_transfer_account =
         token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-    confidential_transfer_account.valid_as_destination()?;
 
-    // A deposit amount must be a 48-bit number
+    // The deposit amount is public, so it can be added directly to the available balance along
+    // with the new decryptable available balance provided by the owner
+    if let Some(new_decryptable_available_balance) = new_decryptable_available_balance {
+        confidential_transfer_account.credits_allowed()?;
+
+        // A deposit amount must not exceed `MAXIMUM_DEPOSIT_AMOUNT`
+        verify_and_split_deposit_amount(amount)?;
+
+        if amount > 0 {
+            confidential_transfer_account.available_balance =
+                syscall::add_to(&confidential_transfer_account.available_balance, amount)
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+        }
+        confidential_transfer_account.decryptable_available_balance =
+            new_decryptable_available_balance;
+        log_deposit(token_account_info.key, mint_info.key, amount, true);
+
+        return Ok(());
+    }
+
+    if let Err(e) = confidential_transfer_account.valid_as_destination() {
+        if e == ProgramError::from(TokenError::MaximumPendingBalanceCreditCounterExceeded) {
+            msg!(
+                "Pending balance credit counter of the deposit account {} is at its maximum",
+                token_account_info.key
+            );
+        }
+        return Err(e);
+    }
+
+    // A deposit amount must not exceed `MAXIMUM_DEPOSIT_AMOUNT`
     let (amount_lo, amount_hi) = verify_and_split_deposit_amount(amount)?;
 
     // Prevent unnecessary ciphertext arithmetic syscalls if `amount_lo` or `amount_hi` is zero
@@ -382,19 +638,75 @@ fn process_deposit(
     }
 
     confidential_transfer_account.increment_pending_balance_credit_counter()?;
+    log_deposit(token_account_info.key, mint_info.key, amount, false);
+    log_pending_balance_credit(token_account_info.key, confidential_transfer_account);
+
+    Ok(())
+}
+
+/// Log a deposit to the confidential balance of an account as a [ConfidentialDepositEvent].
+///
+/// See [ConfidentialDepositEvent] for the format of the log.
+#[cfg(feature = "zk-ops")]
+fn log_deposit(token_account: &Pubkey, mint: &Pubkey, amount: u64, applied: bool) {
+    msg!(
+        "Deposited {} to confidential balance of {}",
+        amount,
+        token_account
+    );
+    let event = ConfidentialDepositEvent {
+        token_account: *token_account,
+        mint: *mint,
+        amount: amount.into(),
+        applied: applied.into(),
+    };
+    sol_log_data(&[DEPOSIT_EVENT_PREFIX, bytemuck::bytes_of(&event)]);
+}
+
+/// Checks that the token account accepts confidential credits at the current slot, if the owner
+/// scheduled a slot from which they are disabled.
+#[cfg(feature = "zk-ops")]
+fn check_credits_until_slot(token_account: &StateWithExtensionsMut<Account>) -> ProgramResult {
+    if let Ok(credits_until_slot) =
+        token_account.get_extension::<ConfidentialTransferCreditsUntilSlot>()
+    {
+        credits_until_slot.credits_allowed(Clock::get()?.slot)?;
+    }
+    Ok(())
+}
 
+/// Checks that the decryptable available balance of the token account is not stale after a
+/// [PullTransfer], so that a new decryptable available balance can be computed from it.
+#[cfg(feature = "zk-ops")]
+fn check_decryptable_available_balance_current(
+    token_account: &StateWithExtensionsMut<Account>,
+) -> ProgramResult {
+    if let Ok(pull_authorization) =
+        token_account.get_extension::<ConfidentialTransferPullAuthorization>()
+    {
+        if bool::from(&pull_authorization.decryptable_available_balance_stale) {
+            msg!("The decryptable available balance must be updated with UpdateDecryptableBalance");
+            return Err(TokenError::ConfidentialTransferDecryptableBalanceStale.into());
+        }
+    }
     Ok(())
 }
 
-/// Verifies that a deposit amount is a 48-bit number and returns the least significant 16 bits and
-/// most significant 32 bits of the amount.
+/// Verifies that a deposit amount does not exceed `MAXIMUM_DEPOSIT_AMOUNT` and returns the least
+/// significant `DEPOSIT_AMOUNT_LO_BIT_LENGTH` bits and the remaining most significant
+/// `DEPOSIT_AMOUNT_HI_BIT_LENGTH` bits of the amount.
 #[cfg(feature = "zk-ops")]
 fn verify_and_split_deposit_amount(amount: u64) -> Result<(u64, u64), TokenError> {
-    if amount > MAXIMUM_DEPOSIT_TRANSFER_AMOUNT {
+    if amount > MAXIMUM_DEPOSIT_AMOUNT {
+        msg!(
+            "Deposit amount {} exceeds the maximum deposit amount {}",
+            amount,
+            MAXIMUM_DEPOSIT_AMOUNT
+        );
         return Err(TokenError::MaximumDepositAmountExceeded);
     }
-    let deposit_amount_lo = amount & (u16::MAX as u64);
-    let deposit_amount_hi = amount.checked_shr(u16::BITS).unwrap();
+    let deposit_amount_lo = amount & ((1 << DEPOSIT_AMOUNT_LO_BIT_LENGTH) - 1);
+    let deposit_amount_hi = amount >> DEPOSIT_AMOUNT_LO_BIT_LENGTH;
     Ok((deposit_amount_lo, deposit_amount_hi))
 }
 
@@ -420,7 +732,78 @@ fn process_withdraw(
     )?;
 
     let authority_info = next_account_info(account_info_iter)?;
+
+    withdraw_available_balance(
+        program_id,
+        token_account_info,
+        token_account_info,
+        mint_info,
+        authority_info,
+        account_info_iter.as_slice(),
+        &proof_context,
+        amount,
+        expected_decimals,
+        new_decryptable_available_balance,
+    )
+}
+
+/// Processes a [WithdrawTo] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_withdraw_to(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    expected_decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+    proof_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let source_account_info = next_account_info(account_info_iter)?;
+    let destination_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proof certifies that the source account has enough available balance to
+    // withdraw the amount.
+    let proof_context = verify_withdraw_proof(
+        next_account_info(account_info_iter)?,
+        proof_instruction_offset,
+    )?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+
+    withdraw_available_balance(
+        program_id,
+        source_account_info,
+        destination_account_info,
+        mint_info,
+        authority_info,
+        account_info_iter.as_slice(),
+        &proof_context,
+        amount,
+        expected_decimals,
+        new_decryptable_available_balance,
+    )
+}
+
+/// Subtracts a withdraw amount from the available balance of the source account and credits it to
+/// the non-confidential balance of the destination account, which may be the source account
+/// itself.
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "zk-ops")]
+fn withdraw_available_balance(
+    program_id: &Pubkey,
+    source_account_info: &AccountInfo,
+    destination_account_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    authority_info: &AccountInfo,
+    signers: &[AccountInfo],
+    proof_context: &WithdrawProofContext,
+    amount: u64,
+    expected_decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
     let authority_info_data_len = authority_info.data_len();
+    let is_self_withdraw = source_account_info.key == destination_account_info.key;
 
     check_program_account(mint_info.owner)?;
     let mint_data = &mint_info.data.borrow_mut();
@@ -431,61 +814,103 @@ fn process_withdraw(
     }
 
     if mint.get_extension::<NonTransferable>().is_ok() {
+        msg!("Confidential withdrawals are not available for non-transferable mints");
         return Err(TokenError::NonTransferable.into());
     }
 
-    check_program_account(token_account_info.owner)?;
-    let token_account_data = &mut token_account_info.data.borrow_mut();
-    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+    if mint.get_extension::<ConfidentialMintSupply>().is_ok() {
+        return Err(TokenError::ConfidentialSupplyConversionNotSupported.into());
+    }
 
-    Processor::validate_owner(
-        program_id,
-        &token_account.base.owner,
-        authority_info,
-        authority_info_data_len,
-        account_info_iter.as_slice(),
-    )?;
+    {
+        check_program_account(source_account_info.owner)?;
+        let token_account_data = &mut source_account_info.data.borrow_mut();
+        let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
 
-    if token_account.base.is_frozen() {
-        return Err(TokenError::AccountFrozen.into());
-    }
+        Processor::validate_owner(
+            program_id,
+            &token_account.base.owner,
+            authority_info,
+            authority_info_data_len,
+            signers,
+        )?;
 
-    if token_account.base.mint != *mint_info.key {
-        return Err(TokenError::MintMismatch.into());
-    }
+        if token_account.base.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
 
-    // Wrapped SOL withdrawals are not supported because lamports cannot be apparated.
-    assert!(!token_account.base.is_native());
+        if token_account.base.mint != *mint_info.key {
+            return Err(TokenError::MintMismatch.into());
+        }
 
-    let mut confidential_transfer_account =
-        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-    confidential_transfer_account.valid_as_source()?;
+        // Wrapped SOL withdrawals are not supported because lamports cannot be apparated.
+        if token_account.base.is_native() {
+            return Err(TokenError::NativeNotSupported.into());
+        }
 
-    // Check that the encryption public key associated with the confidential extension is
-    // consistent with the public key that was actually used to generate the zkp.
-    if confidential_transfer_account.elgamal_pubkey != proof_context.pubkey {
-        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
-    }
+        check_decryptable_available_balance_current(&token_account)?;
+        let mut confidential_transfer_account =
+            token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+        confidential_transfer_account.valid_as_source()?;
 
-    // Prevent unnecessary ciphertext arithmetic syscalls if the withdraw amount is zero
-    if amount > 0 {
-        confidential_transfer_account.available_balance =
-            syscall::subtract_from(&confidential_transfer_account.available_balance, amount)
-                .ok_or(ProgramError::InvalidInstructionData)?;
+        // Check that the encryption public key associated with the confidential extension is
+        // consistent with the public key that was actually used to generate the zkp.
+        if confidential_transfer_account.elgamal_pubkey != proof_context.pubkey {
+            return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+        }
+
+        // Prevent unnecessary ciphertext arithmetic syscalls if the withdraw amount is zero
+        if amount > 0 {
+            confidential_transfer_account.available_balance =
+                syscall::subtract_from(&confidential_transfer_account.available_balance, amount)
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+        }
+        // Check that the final available balance ciphertext is consistent with the actual
+        // ciphertext for which the zero-knowledge proof was generated for.
+        if confidential_transfer_account.available_balance != proof_context.final_ciphertext {
+            return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+        }
+
+        confidential_transfer_account.decryptable_available_balance =
+            new_decryptable_available_balance;
+        if is_self_withdraw {
+            token_account.base.amount = token_account
+                .base
+                .amount
+                .checked_add(amount)
+                .ok_or(TokenError::Overflow)?;
+            token_account.pack_base();
+            return Ok(());
+        }
     }
-    // Check that the final available balance ciphertext is consistent with the actual ciphertext
-    // for which the zero-knowledge proof was generated for.
-    if confidential_transfer_account.available_balance != proof_context.final_ciphertext {
-        return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+
+    check_program_account(destination_account_info.owner)?;
+    let destination_account_data = &mut destination_account_info.data.borrow_mut();
+    let mut destination_account =
+        StateWithExtensionsMut::<Account>::unpack(destination_account_data)?;
+
+    if destination_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
     }
 
-    confidential_transfer_account.decryptable_available_balance = new_decryptable_available_balance;
-    token_account.base.amount = token_account
+    if destination_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    if destination_account.base.is_native() {
+        return Err(TokenError::NativeNotSupported.into());
+    }
+
+    if memo_required(&destination_account) {
+        check_previous_sibling_instruction_is_memo()?;
+    }
+
+    destination_account.base.amount = destination_account
         .base
         .amount
         .checked_add(amount)
         .ok_or(TokenError::Overflow)?;
-    token_account.pack_base();
+    destination_account.pack_base();
 
     Ok(())
 }
@@ -500,17 +925,15 @@ fn verify_withdraw_proof(
         // interpret `account_info` as a context state account
         check_zk_token_proof_program_account(account_info.owner)?;
         let context_state_account_data = account_info.data.borrow();
+        check_context_state_proof_type(&context_state_account_data, ProofType::Withdraw)?;
         let context_state =
             pod_from_bytes::<ProofContextState<WithdrawProofContext>>(&context_state_account_data)?;
 
-        if context_state.proof_type != ProofType::Withdraw.into() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-
         Ok(context_state.proof_context)
     } else {
         // interpret `account_info` as a sysvar
-        let zkp_instruction = get_instruction_relative(proof_instruction_offset, account_info)?;
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
         Ok(*decode_proof_instruction_context::<
             WithdrawData,
             WithdrawProofContext,
@@ -520,13 +943,17 @@ fn verify_withdraw_proof(
     }
 }
 
-/// Processes an [Transfer] instruction.
+/// Processes a [Transfer], [TransferChecked], [TransferWithReference], or
+/// [TransferWithAdditionalProofs] instruction.
 #[cfg(feature = "zk-ops")]
 fn process_transfer(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
     new_source_decryptable_available_balance: DecryptableBalance,
     proof_instruction_offset: i64,
+    expected_decimals: Option<u8>,
+    reference: Option<&EncryptedTransferReference>,
+    with_additional_proofs: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let source_account_info = next_account_info(account_info_iter)?;
@@ -536,13 +963,29 @@ fn process_transfer(
     // either sysvar or context state account depending on `proof_instruction_offset`
     let proof_account_info = next_account_info(account_info_iter)?;
 
-    let authority_info = next_account_info(account_info_iter)?;
-
     check_program_account(mint_info.owner)?;
     let mint_data = &mint_info.data.borrow_mut();
     let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
 
+    let additional_proof_account_infos = if with_additional_proofs {
+        Some(next_account_infos(
+            account_info_iter,
+            additional_transfer_proofs_len(&mint),
+        )?)
+    } else {
+        None
+    };
+
+    let authority_info = next_account_info(account_info_iter)?;
+
+    if let Some(expected_decimals) = expected_decimals {
+        if expected_decimals != mint.base.decimals {
+            return Err(TokenError::MintDecimalsMismatch.into());
+        }
+    }
+
     if mint.get_extension::<NonTransferable>().is_ok() {
+        msg!("Tokens of non-transferable mints cannot be transferred");
         return Err(TokenError::NonTransferable.into());
     }
     let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
@@ -554,9 +997,11 @@ fn process_transfer(
     //   transfer fee is not required.
     //   - If the mint is extended for fees and the instruction is not a self-transfer, then
     //   transfer fee is required.
-    if mint.get_extension::<TransferFeeConfig>().is_err()
-        || source_account_info.key == destination_token_account_info.key
-    {
+    if !transfer_incurs_fee(
+        &mint,
+        source_account_info.key,
+        destination_token_account_info.key,
+    ) {
         // Transfer fee is not required. Decode the zero-knowledge proof as `TransferData`.
         //
         // The zero-knowledge proof certifies that:
@@ -564,46 +1009,25 @@ fn process_transfer(
         //   2. the source account has enough balance to send the transfer amount
         let proof_context = verify_transfer_proof(proof_account_info, proof_instruction_offset)?;
 
-        // Check that the auditor encryption public key associated wth the confidential mint is
-        // consistent with what was actually used to generate the zkp.
-        if !confidential_transfer_mint
-            .auditor_elgamal_pubkey
-            .equals(&proof_context.transfer_pubkeys.auditor)
-        {
-            return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
-        }
-
-        let source_transfer_amount_lo =
-            transfer_amount_source_ciphertext(&proof_context.ciphertext_lo);
-        let source_transfer_amount_hi =
-            transfer_amount_source_ciphertext(&proof_context.ciphertext_hi);
+        check_additional_transfer_proofs(
+            &mint,
+            &proof_context.transfer_pubkeys.source,
+            &transfer_amount_source_ciphertext(&proof_context.ciphertext_lo),
+            &transfer_amount_source_ciphertext(&proof_context.ciphertext_hi),
+            additional_proof_account_infos,
+        )?;
 
-        process_source_for_transfer(
+        process_transfer_without_fee(
             program_id,
             source_account_info,
+            destination_token_account_info,
             mint_info,
+            confidential_transfer_mint,
             authority_info,
             account_info_iter.as_slice(),
-            &proof_context.transfer_pubkeys.source,
-            &source_transfer_amount_lo,
-            &source_transfer_amount_hi,
-            &proof_context.new_source_ciphertext,
+            &proof_context,
             new_source_decryptable_available_balance,
         )?;
-
-        let destination_ciphertext_lo =
-            transfer_amount_destination_ciphertext(&proof_context.ciphertext_lo);
-        let destination_ciphertext_hi =
-            transfer_amount_destination_ciphertext(&proof_context.ciphertext_hi);
-
-        process_destination_for_transfer(
-            destination_token_account_info,
-            mint_info,
-            &proof_context.transfer_pubkeys.destination,
-            &destination_ciphertext_lo,
-            &destination_ciphertext_hi,
-            None,
-        )?;
     } else {
         // Transfer fee is required. Decode the zero-knowledge proof as `TransferWithFeeData`.
         //
@@ -617,12 +1041,10 @@ fn process_transfer(
         // Check that the encryption public keys associated with the mint confidential transfer and
         // confidential transfer fee extensions are consistent with the keys that were used to
         // generate the zkp.
-        if !confidential_transfer_mint
-            .auditor_elgamal_pubkey
-            .equals(&proof_context.transfer_with_fee_pubkeys.auditor)
-        {
-            return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
-        }
+        check_auditor_elgamal_pubkey(
+            confidential_transfer_mint,
+            &proof_context.transfer_with_fee_pubkeys.auditor,
+        )?;
         let confidential_transfer_fee_config =
             mint.get_extension::<ConfidentialTransferFeeConfig>()?;
         if proof_context
@@ -636,14 +1058,22 @@ fn process_transfer(
         // Check that the fee parameters in the mint are consistent with what were used to generate
         // the zkp.
         let transfer_fee_config = mint.get_extension::<TransferFeeConfig>()?;
-        let fee_parameters = transfer_fee_config.get_epoch_fee(Clock::get()?.epoch);
+        let epoch = Clock::get()?.epoch;
+        let fee_parameters = transfer_fee_config.get_epoch_fee(epoch);
         if u64::from(fee_parameters.maximum_fee)
             != u64::from(proof_context.fee_parameters.maximum_fee)
             || u16::from(fee_parameters.transfer_fee_basis_points)
                 != u16::from(proof_context.fee_parameters.fee_rate_basis_points)
         {
+            msg!(
+                "Fee parameters mismatch: epoch {} has maximum fee {} and fee rate {} basis points",
+                epoch,
+                u64::from(fee_parameters.maximum_fee),
+                u16::from(fee_parameters.transfer_fee_basis_points)
+            );
             return Err(TokenError::FeeParametersMismatch.into());
         }
+        check_transfer_fee_bounds(u16::from(fee_parameters.transfer_fee_basis_points))?;
 
         // From the proof data, decode lo and hi transfer amounts encrypted under the source
         // encryption public key
@@ -652,6 +1082,14 @@ fn process_transfer(
         let source_transfer_amount_hi =
             transfer_amount_source_ciphertext(&proof_context.ciphertext_hi);
 
+        check_additional_transfer_proofs(
+            &mint,
+            &proof_context.transfer_with_fee_pubkeys.source,
+            &source_transfer_amount_lo,
+            &source_transfer_amount_hi,
+            additional_proof_account_infos,
+        )?;
+
         process_source_for_transfer(
             program_id,
             source_account_info,
@@ -685,242 +1123,1788 @@ fn process_transfer(
         )?;
     }
 
-    Ok(())
-}
-
-/// Verify zero-knowledge proof needed for a [Transfer] instruction without fee and return the
-/// corresponding proof context.
-fn verify_transfer_proof(
-    account_info: &AccountInfo<'_>,
-    proof_instruction_offset: i64,
-) -> Result<TransferProofContext, ProgramError> {
-    if proof_instruction_offset == 0 {
-        // interpret `account_info` as a context state account
-        check_zk_token_proof_program_account(account_info.owner)?;
-        let context_state_account_data = account_info.data.borrow();
-        let context_state =
-            pod_from_bytes::<ProofContextState<TransferProofContext>>(&context_state_account_data)?;
-
-        if context_state.proof_type != ProofType::Transfer.into() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-
-        Ok(context_state.proof_context)
-    } else {
-        // interpret `account_info` as a sysvar
-        let zkp_instruction = get_instruction_relative(proof_instruction_offset, account_info)?;
-        Ok(*decode_proof_instruction_context::<
-            TransferData,
-            TransferProofContext,
-        >(
-            ProofInstruction::VerifyTransfer, &zkp_instruction
-        )?)
-    }
-}
-
-/// Verify zero-knowledge proof needed for a [Transfer] instruction with fee and return the
-/// corresponding proof context.
-fn verify_transfer_with_fee_proof(
-    account_info: &AccountInfo<'_>,
-    proof_instruction_offset: i64,
-) -> Result<TransferWithFeeProofContext, ProgramError> {
-    if proof_instruction_offset == 0 {
-        // interpret `account_info` as a context state account
-        check_zk_token_proof_program_account(account_info.owner)?;
-        let context_state_account_data = account_info.data.borrow();
-        let context_state = pod_from_bytes::<ProofContextState<TransferWithFeeProofContext>>(
-            &context_state_account_data,
-        )?;
-
-        if context_state.proof_type != ProofType::TransferWithFee.into() {
-            return Err(ProgramError::InvalidInstructionData);
-        }
-
-        Ok(context_state.proof_context)
-    } else {
-        // interpret `account_info` as a sysvar
-        let zkp_instruction = get_instruction_relative(proof_instruction_offset, account_info)?;
-        Ok(*decode_proof_instruction_context::<
-            TransferWithFeeData,
-            TransferWithFeeProofContext,
-        >(
-            ProofInstruction::VerifyTransferWithFee,
-            &zkp_instruction,
-        )?)
+    if let Some(reference) = reference {
+        log_transfer_reference(
+            source_account_info.key,
+            destination_token_account_info.key,
+            mint_info.key,
+            reference,
+        );
     }
-}
-
-/// Extract the transfer amount ciphertext encrypted under the source ElGamal public key.
-///
-/// A transfer amount ciphertext consists of the following 32-byte components that are serialized
-/// in order:
-///   1. The `commitment` component that encodes the transfer amount.
-///   2. The `decryption handle` component with respect to the source public key.
-///   3. The `decryption handle` component with respect to the destination public key.
-///   4. The `decryption handle` component with respect to the auditor public key.
-///
-/// An ElGamal ciphertext for the source consists of the `commitment` component and the `decryption
-/// handle` component with respect to the source.
-#[cfg(feature = "zk-ops")]
-fn transfer_amount_source_ciphertext(
-    transfer_amount_ciphertext: &TransferAmountCiphertext,
-) -> ElGamalCiphertext {
-    let transfer_amount_ciphertext_bytes = bytemuck::bytes_of(transfer_amount_ciphertext);
-
-    let mut source_ciphertext_bytes = [0u8; 64];
-    source_ciphertext_bytes[..32].copy_from_slice(&transfer_amount_ciphertext_bytes[..32]);
-    source_ciphertext_bytes[32..].copy_from_slice(&transfer_amount_ciphertext_bytes[32..64]);
 
-    ElGamalCiphertext(source_ciphertext_bytes)
+    Ok(())
 }
 
-/// Extract the transfer amount ciphertext encrypted under the destination ElGamal public key.
-///
-/// A transfer amount ciphertext consists of the following 32-byte components that are serialized
-/// in order:
-///   1. The `commitment` component that encodes the transfer amount.
-///   2. The `decryption handle` component with respect to the source public key.
-///   3. The `decryption handle` component with respect to the destination public key.
-///   4. The `decryption handle` component with respect to the auditor public key.
+/// Log the encrypted reference of a transfer as a [ConfidentialTransferReferenceEvent].
 ///
-/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
-/// `decryption handle` component with respect to the destination public key.
+/// The reference is not verified, since it is not part of the zero-knowledge proof.
 #[cfg(feature = "zk-ops")]
-fn transfer_amount_destination_ciphertext(
-    transfer_amount_ciphertext: &TransferAmountCiphertext,
-) -> ElGamalCiphertext {
-    let transfer_amount_ciphertext_bytes = bytemuck::bytes_of(transfer_amount_ciphertext);
-
-    let mut destination_ciphertext_bytes = [0u8; 64];
-    destination_ciphertext_bytes[..32].copy_from_slice(&transfer_amount_ciphertext_bytes[..32]);
-    destination_ciphertext_bytes[32..].copy_from_slice(&transfer_amount_ciphertext_bytes[64..96]);
-
-    ElGamalCiphertext(destination_ciphertext_bytes)
+fn log_transfer_reference(
+    source: &Pubkey,
+    destination: &Pubkey,
+    mint: &Pubkey,
+    reference: &EncryptedTransferReference,
+) {
+    let event = ConfidentialTransferReferenceEvent {
+        source: *source,
+        destination: *destination,
+        mint: *mint,
+        reference: *reference,
+    };
+    sol_log_data(&[TRANSFER_REFERENCE_EVENT_PREFIX, bytemuck::bytes_of(&event)]);
 }
 
-/// Extract the fee amount ciphertext encrypted under the destination ElGamal public key.
-///
-/// A fee encryption amount consists of the following 32-byte components that are serialized in
-/// order:
-///   1. The `commitment` component that encodes the fee amount.
-///   2. The `decryption handle` component with respect to the destination public key.
-///   3. The `decryption handle` component with respect to the withdraw withheld authority public
-///      key.
+/// Check that the fee parameters of a transfer bound the fee that is credited to the withheld
+/// amount of the destination account.
 ///
-/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
-/// `decryption handle` component with respect to the destination public key.
+/// The zero-knowledge proof for a transfer with fee certifies that the fee is either
+/// `maximum_fee` or the transfer amount scaled by the fee rate, and that the fee ciphertexts encode
+/// a value in the range of the low and high bits of a transfer amount. As long as the fee rate does
+/// not exceed `MAX_FEE_BASIS_POINTS`, the fee is never larger than the transfer amount, so a single
+/// transfer credits at most the smaller of `maximum_fee` and `MAXIMUM_DEPOSIT_TRANSFER_AMOUNT` to
+/// the withheld amount. In particular, a `maximum_fee` above `MAXIMUM_DEPOSIT_TRANSFER_AMOUNT` is
+/// never reached by a confidential transfer.
 #[cfg(feature = "zk-ops")]
-fn fee_amount_destination_ciphertext(
-    transfer_amount_ciphertext: &EncryptedFee,
-) -> ElGamalCiphertext {
-    let transfer_amount_ciphertext_bytes = bytemuck::bytes_of(transfer_amount_ciphertext);
-
-    let mut source_ciphertext_bytes = [0u8; 64];
-    source_ciphertext_bytes[..32].copy_from_slice(&transfer_amount_ciphertext_bytes[..32]);
-    source_ciphertext_bytes[32..].copy_from_slice(&transfer_amount_ciphertext_bytes[32..64]);
-
-    ElGamalCiphertext(source_ciphertext_bytes)
+fn check_transfer_fee_bounds(fee_rate_basis_points: u16) -> ProgramResult {
+    if fee_rate_basis_points > MAX_FEE_BASIS_POINTS {
+        return Err(TokenError::TransferFeeExceedsMaximum.into());
+    }
+    Ok(())
 }
 
-/// Extract the transfer amount ciphertext encrypted under the withdraw withheld authority ElGamal
-/// public key.
+/// Check that the auditor ElGamal public key that was used to generate a zero-knowledge proof is
+/// the current auditor ElGamal public key of the confidential mint.
 ///
-/// A fee encryption amount consists of the following 32-byte components that are serialized in
-/// order:
-///   1. The `commitment` component that encodes the fee amount.
-///   2. The `decryption handle` component with respect to the destination public key.
-///   3. The `decryption handle` component with respect to the withdraw withheld authority public
-///      key.
-///
-/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
-/// `decryption handle` component with respect to the withdraw withheld authority public key.
+/// On a mismatch, the hashes of the expected and supplied keys are logged so that clients can
+/// detect that they used a stale auditor key.
 #[cfg(feature = "zk-ops")]
-fn fee_amount_withdraw_withheld_authority_ciphertext(
-    transfer_amount_ciphertext: &EncryptedFee,
-) -> ElGamalCiphertext {
-    let transfer_amount_ciphertext_bytes = bytemuck::bytes_of(transfer_amount_ciphertext);
-
-    let mut destination_ciphertext_bytes = [0u8; 64];
-    destination_ciphertext_bytes[..32].copy_from_slice(&transfer_amount_ciphertext_bytes[..32]);
-    destination_ciphertext_bytes[32..].copy_from_slice(&transfer_amount_ciphertext_bytes[64..96]);
-
-    ElGamalCiphertext(destination_ciphertext_bytes)
+fn check_auditor_elgamal_pubkey(
+    confidential_transfer_mint: &ConfidentialTransferMint,
+    supplied_auditor_elgamal_pubkey: &ElGamalPubkey,
+) -> ProgramResult {
+    if !confidential_transfer_mint
+        .auditor_elgamal_pubkey
+        .equals(supplied_auditor_elgamal_pubkey)
+    {
+        msg!(
+            "Auditor ElGamal pubkey mismatch: expected key hash {}, supplied key hash {}",
+            hash(bytemuck::bytes_of(
+                &confidential_transfer_mint.auditor_elgamal_pubkey
+            )),
+            hash(bytemuck::bytes_of(supplied_auditor_elgamal_pubkey)),
+        );
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+    Ok(())
 }
 
+/// Processes the source and destination accounts of a [Transfer] or [TransferWithSplitProofs]
+/// instruction that does not incur a transfer fee.
 #[allow(clippy::too_many_arguments)]
 #[cfg(feature = "zk-ops")]
-fn process_source_for_transfer(
+fn process_transfer_without_fee(
     program_id: &Pubkey,
     source_account_info: &AccountInfo,
+    destination_token_account_info: &AccountInfo,
     mint_info: &AccountInfo,
+    confidential_transfer_mint: &ConfidentialTransferMint,
     authority_info: &AccountInfo,
     signers: &[AccountInfo],
-    source_encryption_pubkey: &ElGamalPubkey,
-    source_transfer_amount_lo: &EncryptedBalance,
-    source_transfer_amount_hi: &EncryptedBalance,
-    expected_new_source_available_balance: &EncryptedBalance,
+    proof_context: &TransferProofContext,
     new_source_decryptable_available_balance: DecryptableBalance,
 ) -> ProgramResult {
-    check_program_account(source_account_info.owner)?;
-    let authority_info_data_len = authority_info.data_len();
-    let token_account_data = &mut source_account_info.data.borrow_mut();
-    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+    // Check that the auditor encryption public key associated wth the confidential mint is
+    // consistent with what was actually used to generate the zkp.
+    check_auditor_elgamal_pubkey(
+        confidential_transfer_mint,
+        &proof_context.transfer_pubkeys.auditor,
+    )?;
 
-    Processor::validate_owner(
+    let source_transfer_amount_lo = transfer_amount_source_ciphertext(&proof_context.ciphertext_lo);
+    let source_transfer_amount_hi = transfer_amount_source_ciphertext(&proof_context.ciphertext_hi);
+
+    process_source_for_transfer(
         program_id,
-        &token_account.base.owner,
+        source_account_info,
+        mint_info,
         authority_info,
-        authority_info_data_len,
         signers,
+        &proof_context.transfer_pubkeys.source,
+        &source_transfer_amount_lo,
+        &source_transfer_amount_hi,
+        &proof_context.new_source_ciphertext,
+        new_source_decryptable_available_balance,
     )?;
 
-    if token_account.base.is_frozen() {
-        return Err(TokenError::AccountFrozen.into());
-    }
-
-    if token_account.base.mint != *mint_info.key {
-        return Err(TokenError::MintMismatch.into());
-    }
-
-    let mut confidential_transfer_account =
-        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-    confidential_transfer_account.valid_as_source()?;
+    let destination_ciphertext_lo =
+        transfer_amount_destination_ciphertext(&proof_context.ciphertext_lo);
+    let destination_ciphertext_hi =
+        transfer_amount_destination_ciphertext(&proof_context.ciphertext_hi);
 
-    // Check that the source encryption public key is consistent with what was actually used to
-    // generate the zkp.
-    if *source_encryption_pubkey != confidential_transfer_account.elgamal_pubkey {
-        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    if source_account_info.key == destination_token_account_info.key {
+        return process_destination_for_self_transfer(
+            destination_token_account_info,
+            &proof_context.transfer_pubkeys.destination,
+            &destination_ciphertext_lo,
+            &destination_ciphertext_hi,
+        );
     }
 
-    let new_source_available_balance = syscall::subtract_with_lo_hi(
-        &confidential_transfer_account.available_balance,
-        source_transfer_amount_lo,
-        source_transfer_amount_hi,
+    process_destination_for_transfer(
+        destination_token_account_info,
+        mint_info,
+        &proof_context.transfer_pubkeys.destination,
+        &destination_ciphertext_lo,
+        &destination_ciphertext_hi,
+        None,
     )
-    .ok_or(ProgramError::InvalidInstructionData)?;
+}
 
-    // Check that the computed available balance is consistent with what was actually used to
-    // generate the zkp on the client side.
-    if new_source_available_balance != *expected_new_source_available_balance {
-        return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
-    }
+/// Processes a [TransferWithSplitProofs] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_transfer_with_split_proofs(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_source_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let source_account_info = next_account_info(account_info_iter)?;
+    let destination_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let equality_proof_account_info = next_account_info(account_info_iter)?;
+    let ciphertext_validity_proof_account_info = next_account_info(account_info_iter)?;
+    let range_proof_account_info = next_account_info(account_info_iter)?;
+    let source_amount_lo_proof_account_info = next_account_info(account_info_iter)?;
+    let source_amount_hi_proof_account_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
 
-    confidential_transfer_account.available_balance = new_source_available_balance;
-    confidential_transfer_account.decryptable_available_balance =
-        new_source_decryptable_available_balance;
+    let proof_context = verify_transfer_split_proofs(
+        equality_proof_account_info,
+        ciphertext_validity_proof_account_info,
+        range_proof_account_info,
+        source_amount_lo_proof_account_info,
+        source_amount_hi_proof_account_info,
+    )?;
 
-    Ok(())
+    process_split_proof_transfer(
+        program_id,
+        source_account_info,
+        destination_token_account_info,
+        mint_info,
+        authority_info,
+        account_info_iter.as_slice(),
+        &proof_context,
+        new_source_decryptable_available_balance,
+    )
 }
 
+/// Processes the accounts of a [TransferWithSplitProofs] or [TransferWithSplitValidityProofs]
+/// instruction after the split proofs are combined into a single transfer proof context.
+///
+/// The split proofs do not certify a transfer fee. As for a [Transfer] instruction, a transfer
+/// does not incur a fee if the mint is not extended for fees or if it is a self-transfer, so the
+/// split proofs are only rejected for transfers that incur a fee.
+#[allow(clippy::too_many_arguments)]
 #[cfg(feature = "zk-ops")]
-fn process_destination_for_transfer(
+fn process_split_proof_transfer(
+    program_id: &Pubkey,
+    source_account_info: &AccountInfo,
     destination_token_account_info: &AccountInfo,
     mint_info: &AccountInfo,
-    destination_encryption_pubkey: &ElGamalPubkey,
-    destination_transfer_amount_lo: &EncryptedBalance,
-    destination_transfer_amount_hi: &EncryptedBalance,
-    encrypted_fee: Option<(&EncryptedFee, &EncryptedFee)>,
+    authority_info: &AccountInfo,
+    signers: &[AccountInfo],
+    proof_context: &TransferProofContext,
+    new_source_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mint_info.data.borrow_mut();
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+
+    if mint.get_extension::<NonTransferable>().is_ok() {
+        msg!("Tokens of non-transferable mints cannot be transferred");
+        return Err(TokenError::NonTransferable.into());
+    }
+
+    if transfer_incurs_fee(
+        &mint,
+        source_account_info.key,
+        destination_token_account_info.key,
+    ) {
+        msg!("Transfers that incur a fee cannot be processed with split proofs");
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+
+    check_additional_transfer_proofs(
+        &mint,
+        &proof_context.transfer_pubkeys.source,
+        &transfer_amount_source_ciphertext(&proof_context.ciphertext_lo),
+        &transfer_amount_source_ciphertext(&proof_context.ciphertext_hi),
+        None,
+    )?;
+
+    process_transfer_without_fee(
+        program_id,
+        source_account_info,
+        destination_token_account_info,
+        mint_info,
+        confidential_transfer_mint,
+        authority_info,
+        signers,
+        proof_context,
+        new_source_decryptable_available_balance,
+    )
+}
+
+/// Check whether a transfer between two accounts incurs a transfer fee.
+///
+/// A transfer incurs a fee if the mint is extended for fees and the transfer is not a
+/// self-transfer.
+#[cfg(feature = "zk-ops")]
+fn transfer_incurs_fee(
+    mint: &StateWithExtensions<Mint>,
+    source: &Pubkey,
+    destination: &Pubkey,
+) -> bool {
+    mint.get_extension::<TransferFeeConfig>().is_ok() && source != destination
+}
+
+/// Verify the split zero-knowledge proofs needed for a [TransferWithSplitProofs] instruction,
+/// check that they are consistent with each other, and combine them into a single transfer proof
+/// context.
+///
+/// The proofs certify that:
+///   1. the equality proof: the new source available balance ciphertext and the new source
+///      commitment encode the same value
+///   2. the ciphertext validity proof: the transfer amount ciphertexts are valid under the
+///      destination and auditor public keys
+///   3. the range proof: the new source available balance and the low and high bits of the
+///      transfer amount are in range
+///   4. the source transfer amount proofs: the low and high transfer amount ciphertexts under the
+///      source public key encrypt the amounts of the ciphertexts that are credited to the
+///      destination
+fn verify_transfer_split_proofs(
+    equality_proof_account_info: &AccountInfo<'_>,
+    ciphertext_validity_proof_account_info: &AccountInfo<'_>,
+    range_proof_account_info: &AccountInfo<'_>,
+    source_amount_lo_proof_account_info: &AccountInfo<'_>,
+    source_amount_hi_proof_account_info: &AccountInfo<'_>,
+) -> Result<TransferProofContext, ProgramError> {
+    let equality_proof_context =
+        verify_context_state_proof::<CiphertextCommitmentEqualityProofContext>(
+            equality_proof_account_info,
+            ProofType::CiphertextCommitmentEquality,
+        )?;
+    let ciphertext_validity_proof_context =
+        verify_context_state_proof::<BatchedGroupedCiphertext2HandlesValidityProofContext>(
+            ciphertext_validity_proof_account_info,
+            ProofType::BatchedGroupedCiphertext2HandlesValidity,
+        )?;
+    let range_proof_context = verify_context_state_proof::<BatchedRangeProofContext>(
+        range_proof_account_info,
+        ProofType::BatchedRangeProofU128,
+    )?;
+
+    let source_ciphertext_lo = verify_source_transfer_amount_proof(
+        source_amount_lo_proof_account_info,
+        &equality_proof_context.pubkey,
+        &ciphertext_validity_proof_context.grouped_ciphertext_lo,
+    )?;
+    let source_ciphertext_hi = verify_source_transfer_amount_proof(
+        source_amount_hi_proof_account_info,
+        &equality_proof_context.pubkey,
+        &ciphertext_validity_proof_context.grouped_ciphertext_hi,
+    )?;
+
+    combine_transfer_split_proofs(
+        &equality_proof_context,
+        &ciphertext_validity_proof_context.grouped_ciphertext_lo,
+        &ciphertext_validity_proof_context.grouped_ciphertext_hi,
+        TransferPubkeys {
+            source: equality_proof_context.pubkey,
+            destination: ciphertext_validity_proof_context.destination_pubkey,
+            auditor: ciphertext_validity_proof_context.auditor_pubkey,
+        },
+        &range_proof_context,
+        &source_ciphertext_lo,
+        &source_ciphertext_hi,
+    )
+}
+
+/// Verify the zero-knowledge proof for a transfer amount ciphertext under the source ElGamal public
+/// key of a split proof transfer and return the ciphertext.
+///
+/// The ciphertext validity proof of a split proof transfer only certifies the decryption handles
+/// with respect to the destination and auditor public keys. The source ciphertext is certified by
+/// a `VerifyCiphertextCommitmentEquality` proof for the commitment of the grouped ciphertext: the
+/// source ciphertext must share this commitment, and the proof certifies that it decrypts to the
+/// committed amount. This guarantees that the source is debited exactly the amount that the
+/// destination is credited.
+fn verify_source_transfer_amount_proof(
+    account_info: &AccountInfo<'_>,
+    source_pubkey: &ElGamalPubkey,
+    grouped_ciphertext: &GroupedElGamalCiphertext2Handles,
+) -> Result<ElGamalCiphertext, ProgramError> {
+    let proof_context = verify_context_state_proof::<CiphertextCommitmentEqualityProofContext>(
+        account_info,
+        ProofType::CiphertextCommitmentEquality,
+    )?;
+
+    let commitment = grouped_ciphertext_commitment(grouped_ciphertext);
+    if proof_context.pubkey != *source_pubkey
+        || proof_context.commitment != commitment
+        || proof_context.ciphertext.0[..32] != commitment.0
+    {
+        return Err(TokenError::ConfidentialTransferSplitProofMismatch.into());
+    }
+
+    Ok(proof_context.ciphertext)
+}
+
+/// Check that the range proof of a split proof transfer was generated for the new source available
+/// balance commitment and the commitments of the transfer amount ciphertexts, and combine the
+/// split proof contexts into a single transfer proof context.
+fn combine_transfer_split_proofs(
+    equality_proof_context: &CiphertextCommitmentEqualityProofContext,
+    grouped_ciphertext_lo: &GroupedElGamalCiphertext2Handles,
+    grouped_ciphertext_hi: &GroupedElGamalCiphertext2Handles,
+    transfer_pubkeys: TransferPubkeys,
+    range_proof_context: &BatchedRangeProofContext,
+    source_ciphertext_lo: &ElGamalCiphertext,
+    source_ciphertext_hi: &ElGamalCiphertext,
+) -> Result<TransferProofContext, ProgramError> {
+    const REMAINING_BALANCE_BIT_LENGTH: u8 = 64;
+    const TRANSFER_AMOUNT_LO_BIT_LENGTH: u8 = 16;
+    const TRANSFER_AMOUNT_HI_BIT_LENGTH: u8 = 32;
+    const PADDING_BIT_LENGTH: u8 = 16;
+
+    // The fourth commitment of the range proof is padding.
+    let expected_commitments = [
+        equality_proof_context.commitment,
+        grouped_ciphertext_commitment(grouped_ciphertext_lo),
+        grouped_ciphertext_commitment(grouped_ciphertext_hi),
+    ];
+    if range_proof_context.commitments[..expected_commitments.len()] != expected_commitments {
+        return Err(TokenError::ConfidentialTransferSplitProofMismatch.into());
+    }
+
+    let expected_bit_lengths = [
+        REMAINING_BALANCE_BIT_LENGTH,
+        TRANSFER_AMOUNT_LO_BIT_LENGTH,
+        TRANSFER_AMOUNT_HI_BIT_LENGTH,
+        PADDING_BIT_LENGTH,
+    ];
+    if range_proof_context.bit_lengths[..expected_bit_lengths.len()] != expected_bit_lengths {
+        return Err(TokenError::ConfidentialTransferSplitProofMismatch.into());
+    }
+
+    Ok(TransferProofContext {
+        ciphertext_lo: transfer_amount_ciphertext(grouped_ciphertext_lo, source_ciphertext_lo),
+        ciphertext_hi: transfer_amount_ciphertext(grouped_ciphertext_hi, source_ciphertext_hi),
+        transfer_pubkeys,
+        new_source_ciphertext: equality_proof_context.ciphertext,
+    })
+}
+
+/// Processes a [TransferWithSplitValidityProofs] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_transfer_with_split_validity_proofs(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_source_decryptable_available_balance: DecryptableBalance,
+    ciphertext_validity_proof_lo_instruction_offset: i64,
+    ciphertext_validity_proof_hi_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let source_account_info = next_account_info(account_info_iter)?;
+    let destination_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let equality_proof_account_info = next_account_info(account_info_iter)?;
+
+    // either sysvar or context state account depending on the corresponding instruction offset
+    let ciphertext_validity_proof_lo_account_info = next_account_info(account_info_iter)?;
+    let ciphertext_validity_proof_hi_account_info = next_account_info(account_info_iter)?;
+
+    let range_proof_account_info = next_account_info(account_info_iter)?;
+    let source_amount_lo_proof_account_info = next_account_info(account_info_iter)?;
+    let source_amount_hi_proof_account_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    let proof_context = verify_transfer_split_validity_proofs(
+        equality_proof_account_info,
+        ciphertext_validity_proof_lo_account_info,
+        ciphertext_validity_proof_lo_instruction_offset,
+        ciphertext_validity_proof_hi_account_info,
+        ciphertext_validity_proof_hi_instruction_offset,
+        range_proof_account_info,
+        source_amount_lo_proof_account_info,
+        source_amount_hi_proof_account_info,
+    )?;
+
+    process_split_proof_transfer(
+        program_id,
+        source_account_info,
+        destination_token_account_info,
+        mint_info,
+        authority_info,
+        account_info_iter.as_slice(),
+        &proof_context,
+        new_source_decryptable_available_balance,
+    )
+}
+
+/// Verify the split zero-knowledge proofs needed for a [TransferWithSplitValidityProofs]
+/// instruction, check that they are consistent with each other, and combine them into a single
+/// transfer proof context.
+///
+/// The proofs are the same as for a [TransferWithSplitProofs] instruction, except that the
+/// validity of the low and high transfer amount ciphertexts is certified by two separate proofs,
+/// which must be generated for the same destination and auditor public keys.
+#[allow(clippy::too_many_arguments)]
+fn verify_transfer_split_validity_proofs(
+    equality_proof_account_info: &AccountInfo<'_>,
+    ciphertext_validity_proof_lo_account_info: &AccountInfo<'_>,
+    ciphertext_validity_proof_lo_instruction_offset: i64,
+    ciphertext_validity_proof_hi_account_info: &AccountInfo<'_>,
+    ciphertext_validity_proof_hi_instruction_offset: i64,
+    range_proof_account_info: &AccountInfo<'_>,
+    source_amount_lo_proof_account_info: &AccountInfo<'_>,
+    source_amount_hi_proof_account_info: &AccountInfo<'_>,
+) -> Result<TransferProofContext, ProgramError> {
+    let equality_proof_context =
+        verify_context_state_proof::<CiphertextCommitmentEqualityProofContext>(
+            equality_proof_account_info,
+            ProofType::CiphertextCommitmentEquality,
+        )?;
+    let ciphertext_validity_proof_lo_context = verify_ciphertext_validity_proof(
+        ciphertext_validity_proof_lo_account_info,
+        ciphertext_validity_proof_lo_instruction_offset,
+    )?;
+    let ciphertext_validity_proof_hi_context = verify_ciphertext_validity_proof(
+        ciphertext_validity_proof_hi_account_info,
+        ciphertext_validity_proof_hi_instruction_offset,
+    )?;
+    let range_proof_context = verify_context_state_proof::<BatchedRangeProofContext>(
+        range_proof_account_info,
+        ProofType::BatchedRangeProofU128,
+    )?;
+
+    if ciphertext_validity_proof_lo_context.destination_pubkey
+        != ciphertext_validity_proof_hi_context.destination_pubkey
+        || ciphertext_validity_proof_lo_context.auditor_pubkey
+            != ciphertext_validity_proof_hi_context.auditor_pubkey
+    {
+        return Err(TokenError::ConfidentialTransferSplitProofMismatch.into());
+    }
+
+    let source_ciphertext_lo = verify_source_transfer_amount_proof(
+        source_amount_lo_proof_account_info,
+        &equality_proof_context.pubkey,
+        &ciphertext_validity_proof_lo_context.grouped_ciphertext,
+    )?;
+    let source_ciphertext_hi = verify_source_transfer_amount_proof(
+        source_amount_hi_proof_account_info,
+        &equality_proof_context.pubkey,
+        &ciphertext_validity_proof_hi_context.grouped_ciphertext,
+    )?;
+
+    combine_transfer_split_proofs(
+        &equality_proof_context,
+        &ciphertext_validity_proof_lo_context.grouped_ciphertext,
+        &ciphertext_validity_proof_hi_context.grouped_ciphertext,
+        TransferPubkeys {
+            source: equality_proof_context.pubkey,
+            destination: ciphertext_validity_proof_lo_context.destination_pubkey,
+            auditor: ciphertext_validity_proof_lo_context.auditor_pubkey,
+        },
+        &range_proof_context,
+        &source_ciphertext_lo,
+        &source_ciphertext_hi,
+    )
+}
+
+/// Verify a zero-knowledge proof that a grouped ciphertext with two handles is valid and return
+/// the corresponding proof context.
+fn verify_ciphertext_validity_proof(
+    account_info: &AccountInfo<'_>,
+    proof_instruction_offset: i64,
+) -> Result<GroupedCiphertext2HandlesValidityProofContext, ProgramError> {
+    if proof_instruction_offset == 0 {
+        // interpret `account_info` as a context state account
+        verify_context_state_proof::<GroupedCiphertext2HandlesValidityProofContext>(
+            account_info,
+            ProofType::GroupedCiphertext2HandlesValidity,
+        )
+    } else {
+        // interpret `account_info` as a sysvar
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
+        Ok(*decode_proof_instruction_context::<
+            GroupedCiphertext2HandlesValidityProofData,
+            GroupedCiphertext2HandlesValidityProofContext,
+        >(
+            ProofInstruction::VerifyGroupedCiphertext2HandlesValidity,
+            &zkp_instruction,
+        )?)
+    }
+}
+
+/// Verify a zero-knowledge proof that was pre-verified into a context state account and return the
+/// corresponding proof context.
+fn verify_context_state_proof<T: Pod>(
+    account_info: &AccountInfo<'_>,
+    expected_proof_type: ProofType,
+) -> Result<T, ProgramError> {
+    check_zk_token_proof_program_account(account_info.owner)?;
+    let context_state_account_data = account_info.data.borrow();
+    check_context_state_proof_type(&context_state_account_data, expected_proof_type)?;
+    let context_state = pod_from_bytes::<ProofContextState<T>>(&context_state_account_data)?;
+
+    Ok(context_state.proof_context)
+}
+
+/// Check that a context state account holds a proof of the expected type.
+///
+/// Context states for different proof types differ in length, so the proof type is read from the
+/// account header, which consists of the 32-byte context state authority followed by the one-byte
+/// proof type, before the proof context itself is interpreted.
+fn check_context_state_proof_type(
+    context_state_account_data: &[u8],
+    expected_proof_type: ProofType,
+) -> ProgramResult {
+    let proof_type = context_state_account_data
+        .get(size_of::<Pubkey>())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if *proof_type != expected_proof_type as u8 {
+        return Err(TokenError::ProofTypeMismatch.into());
+    }
+    Ok(())
+}
+
+/// Combine a grouped ciphertext with two handles and the ciphertext under the source ElGamal public
+/// key for the same commitment into a transfer amount ciphertext.
+///
+/// A grouped ciphertext with two handles consists of the `commitment` component followed by the
+/// `decryption handle` components with respect to the destination and auditor public keys. The
+/// `decryption handle` component of the source ciphertext is inserted right after the `commitment`
+/// component to match the layout of a transfer amount ciphertext.
+fn transfer_amount_ciphertext(
+    grouped_ciphertext: &GroupedElGamalCiphertext2Handles,
+    source_ciphertext: &ElGamalCiphertext,
+) -> TransferAmountCiphertext {
+    let grouped_ciphertext_bytes = bytemuck::bytes_of(grouped_ciphertext);
+
+    let mut transfer_amount_ciphertext_bytes = [0u8; 128];
+    transfer_amount_ciphertext_bytes[..32].copy_from_slice(&grouped_ciphertext_bytes[..32]);
+    transfer_amount_ciphertext_bytes[32..64].copy_from_slice(&source_ciphertext.0[32..]);
+    transfer_amount_ciphertext_bytes[64..].copy_from_slice(&grouped_ciphertext_bytes[32..]);
+
+    bytemuck::cast(transfer_amount_ciphertext_bytes)
+}
+
+/// Verify zero-knowledge proof needed for a [Transfer] instruction without fee and return the
+/// corresponding proof context.
+fn verify_transfer_proof(
+    account_info: &AccountInfo<'_>,
+    proof_instruction_offset: i64,
+) -> Result<TransferProofContext, ProgramError> {
+    if proof_instruction_offset == 0 {
+        // interpret `account_info` as a context state account
+        check_zk_token_proof_program_account(account_info.owner)?;
+        let context_state_account_data = account_info.data.borrow();
+        check_context_state_proof_type(&context_state_account_data, ProofType::Transfer)?;
+        let context_state =
+            pod_from_bytes::<ProofContextState<TransferProofContext>>(&context_state_account_data)?;
+
+        Ok(context_state.proof_context)
+    } else {
+        // interpret `account_info` as a sysvar
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
+        Ok(*decode_proof_instruction_context::<
+            TransferData,
+            TransferProofContext,
+        >(
+            ProofInstruction::VerifyTransfer, &zkp_instruction
+        )?)
+    }
+}
+
+/// Verify zero-knowledge proof needed for a [Transfer] instruction with fee and return the
+/// corresponding proof context.
+fn verify_transfer_with_fee_proof(
+    account_info: &AccountInfo<'_>,
+    proof_instruction_offset: i64,
+) -> Result<TransferWithFeeProofContext, ProgramError> {
+    if proof_instruction_offset == 0 {
+        // interpret `account_info` as a context state account
+        check_zk_token_proof_program_account(account_info.owner)?;
+        let context_state_account_data = account_info.data.borrow();
+        check_context_state_proof_type(&context_state_account_data, ProofType::TransferWithFee)?;
+        let context_state = pod_from_bytes::<ProofContextState<TransferWithFeeProofContext>>(
+            &context_state_account_data,
+        )?;
+
+        Ok(context_state.proof_context)
+    } else {
+        // interpret `account_info` as a sysvar
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
+        Ok(*decode_proof_instruction_context::<
+            TransferWithFeeData,
+            TransferWithFeeProofContext,
+        >(
+            ProofInstruction::VerifyTransferWithFee,
+            &zkp_instruction,
+        )?)
+    }
+}
+
+/// Extract the fee amount ciphertext encrypted under the destination ElGamal public key.
+///
+/// A fee encryption amount consists of the following 32-byte components that are serialized in
+/// order:
+///   1. The `commitment` component that encodes the fee amount.
+///   2. The `decryption handle` component with respect to the destination public key.
+///   3. The `decryption handle` component with respect to the withdraw withheld authority public
+///      key.
+///
+/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
+/// `decryption handle` component with respect to the destination public key.
+#[cfg(feature = "zk-ops")]
+fn fee_amount_destination_ciphertext(
+    transfer_amount_ciphertext: &EncryptedFee,
+) -> ElGamalCiphertext {
+    ElGamalCiphertext(ciphertext_extraction::fee_amount_destination_ciphertext(
+        bytemuck::cast_ref(transfer_amount_ciphertext),
+    ))
+}
+
+/// Extract the transfer amount ciphertext encrypted under the withdraw withheld authority ElGamal
+/// public key.
+///
+/// A fee encryption amount consists of the following 32-byte components that are serialized in
+/// order:
+///   1. The `commitment` component that encodes the fee amount.
+///   2. The `decryption handle` component with respect to the destination public key.
+///   3. The `decryption handle` component with respect to the withdraw withheld authority public
+///      key.
+///
+/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
+/// `decryption handle` component with respect to the withdraw withheld authority public key.
+#[cfg(feature = "zk-ops")]
+fn fee_amount_withdraw_withheld_authority_ciphertext(
+    transfer_amount_ciphertext: &EncryptedFee,
+) -> ElGamalCiphertext {
+    ElGamalCiphertext(
+        ciphertext_extraction::fee_amount_withdraw_withheld_authority_ciphertext(
+            bytemuck::cast_ref(transfer_amount_ciphertext),
+        ),
+    )
+}
+
+/// The number of additional proof accounts of a [TransferWithAdditionalProofs] instruction for
+/// the mint: a low and a high bits proof for each additional auditor, and an equality and a range
+/// proof for the maximum transfer amount.
+#[cfg(feature = "zk-ops")]
+fn additional_transfer_proofs_len(mint: &StateWithExtensions<Mint>) -> usize {
+    let auditor_proofs_len = mint
+        .get_extension::<ConfidentialTransferAuditors>()
+        .map(|auditors| auditors.auditor_elgamal_pubkeys().count() * 2)
+        .unwrap_or(0);
+    let maximum_transfer_amount_proofs_len = mint
+        .get_extension::<ConfidentialTransferMaximumTransferAmount>()
+        .map(|_| 2)
+        .unwrap_or(0);
+    auditor_proofs_len + maximum_transfer_amount_proofs_len
+}
+
+/// Check the additional zero-knowledge proofs that the mint requires for a confidential transfer.
+///
+/// A transfer that does not come with additional proofs fails if the mint has additional
+/// auditors or a maximum transfer amount. Otherwise, for each additional auditor, the proofs must
+/// re-encrypt the low and high bits of the transfer amount under the source ElGamal public key to
+/// the auditor ElGamal public key, and the proofs for the maximum transfer amount must certify
+/// that the transfer amount does not exceed it.
+#[cfg(feature = "zk-ops")]
+fn check_additional_transfer_proofs(
+    mint: &StateWithExtensions<Mint>,
+    source_elgamal_pubkey: &ElGamalPubkey,
+    source_transfer_amount_lo: &EncryptedBalance,
+    source_transfer_amount_hi: &EncryptedBalance,
+    additional_proof_account_infos: Option<&[AccountInfo]>,
+) -> ProgramResult {
+    let additional_proof_account_infos = match additional_proof_account_infos {
+        Some(additional_proof_account_infos) => additional_proof_account_infos,
+        None if additional_transfer_proofs_len(mint) == 0 => return Ok(()),
+        None => {
+            msg!("Transfers of this mint must be submitted with TransferWithAdditionalProofs");
+            return Err(TokenError::ConfidentialTransferAdditionalProofsRequired.into());
+        }
+    };
+
+    let auditor_elgamal_pubkeys = mint
+        .get_extension::<ConfidentialTransferAuditors>()
+        .map(|auditors| auditors.auditor_elgamal_pubkeys().collect::<Vec<_>>())
+        .unwrap_or_default();
+    for (auditor_elgamal_pubkey, proof_account_infos) in auditor_elgamal_pubkeys
+        .iter()
+        .zip(additional_proof_account_infos.chunks(2))
+    {
+        for (proof_account_info, source_transfer_amount) in proof_account_infos
+            .iter()
+            .zip([source_transfer_amount_lo, source_transfer_amount_hi])
+        {
+            check_zk_token_proof_program_account(proof_account_info.owner)?;
+            let context_state_account_data = proof_account_info.data.borrow();
+            let context_state = pod_from_bytes::<
+                ProofContextState<CiphertextCiphertextEqualityProofContext>,
+            >(&context_state_account_data)?;
+
+            if context_state.proof_type != ProofType::CiphertextCiphertextEquality.into() {
+                return Err(ProgramError::InvalidInstructionData);
+            }
+
+            let proof_context = &context_state.proof_context;
+            if proof_context.source_pubkey != *source_elgamal_pubkey
+                || proof_context.source_ciphertext != *source_transfer_amount
+            {
+                return Err(TokenError::ConfidentialTransferAdditionalProofMismatch.into());
+            }
+            if proof_context.destination_pubkey != *auditor_elgamal_pubkey {
+                return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+            }
+        }
+    }
+
+    if let Ok(confidential_transfer_maximum_transfer_amount) =
+        mint.get_extension::<ConfidentialTransferMaximumTransferAmount>()
+    {
+        let proof_account_infos = additional_proof_account_infos
+            .get(auditor_elgamal_pubkeys.len() * 2..)
+            .ok_or(ProgramError::NotEnoughAccountKeys)?;
+        check_maximum_transfer_amount_proofs(
+            u64::from(confidential_transfer_maximum_transfer_amount.maximum_transfer_amount),
+            source_elgamal_pubkey,
+            source_transfer_amount_lo,
+            source_transfer_amount_hi,
+            proof_account_infos,
+        )?;
+    }
+    Ok(())
+}
+
+/// Check that the equality and range proofs for the maximum transfer amount certify that the
+/// maximum transfer amount minus the transfer amount is a 64-bit amount.
+///
+/// The maximum transfer amount minus the transfer amount is computed homomorphically from the
+/// transfer amount ciphertexts under the source ElGamal public key. The equality proof certifies
+/// that this ciphertext encrypts the same amount as a commitment, and the range proof certifies
+/// the bit length of the committed amount.
+#[cfg(feature = "zk-ops")]
+fn check_maximum_transfer_amount_proofs(
+    maximum_transfer_amount: u64,
+    source_elgamal_pubkey: &ElGamalPubkey,
+    source_transfer_amount_lo: &EncryptedBalance,
+    source_transfer_amount_hi: &EncryptedBalance,
+    proof_account_infos: &[AccountInfo],
+) -> ProgramResult {
+    let (equality_proof_account_info, range_proof_account_info) = match proof_account_infos {
+        [equality_proof_account_info, range_proof_account_info] => {
+            (equality_proof_account_info, range_proof_account_info)
+        }
+        _ => return Err(ProgramError::NotEnoughAccountKeys),
+    };
+
+    let equality_proof_context =
+        verify_context_state_proof::<CiphertextCommitmentEqualityProofContext>(
+            equality_proof_account_info,
+            ProofType::CiphertextCommitmentEquality,
+        )?;
+    let range_proof_context = verify_context_state_proof::<BatchedRangeProofContext>(
+        range_proof_account_info,
+        ProofType::BatchedRangeProofU64,
+    )?;
+
+    let maximum_transfer_amount_ciphertext =
+        syscall::add_to(&EncryptedBalance::zeroed(), maximum_transfer_amount)
+            .ok_or(ProgramError::InvalidInstructionData)?;
+    let remaining_amount_ciphertext = syscall::subtract_with_lo_hi(
+        &maximum_transfer_amount_ciphertext,
+        source_transfer_amount_lo,
+        source_transfer_amount_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    if equality_proof_context.pubkey != *source_elgamal_pubkey
+        || equality_proof_context.ciphertext != remaining_amount_ciphertext
+        || range_proof_context.commitments[0] != equality_proof_context.commitment
+        || range_proof_context.bit_lengths[0] != MAXIMUM_TRANSFER_AMOUNT_RANGE_PROOF_BIT_LENGTH
+    {
+        return Err(TokenError::ConfidentialTransferAdditionalProofMismatch.into());
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+#[cfg(feature = "zk-ops")]
+fn process_source_for_transfer(
+    program_id: &Pubkey,
+    source_account_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    authority_info: &AccountInfo,
+    signers: &[AccountInfo],
+    source_encryption_pubkey: &ElGamalPubkey,
+    source_transfer_amount_lo: &EncryptedBalance,
+    source_transfer_amount_hi: &EncryptedBalance,
+    expected_new_source_available_balance: &EncryptedBalance,
+    new_source_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
+    check_program_account(source_account_info.owner)?;
+    let authority_info_data_len = authority_info.data_len();
+    let token_account_data = &mut source_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        signers,
+    )?;
+
+    check_decryptable_available_balance_current(&token_account)?;
+    debit_source_for_transfer(
+        &mut token_account,
+        mint_info,
+        source_encryption_pubkey,
+        source_transfer_amount_lo,
+        source_transfer_amount_hi,
+        expected_new_source_available_balance,
+        new_source_decryptable_available_balance,
+    )
+}
+
+/// Subtracts the transfer amount from the available balance of a source account whose transfer
+/// authority has already been validated.
+#[cfg(feature = "zk-ops")]
+fn debit_source_for_transfer(
+    token_account: &mut StateWithExtensionsMut<Account>,
+    mint_info: &AccountInfo,
+    source_encryption_pubkey: &ElGamalPubkey,
+    source_transfer_amount_lo: &EncryptedBalance,
+    source_transfer_amount_hi: &EncryptedBalance,
+    expected_new_source_available_balance: &EncryptedBalance,
+    new_source_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    if token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.valid_as_source()?;
+
+    // Check that the source encryption public key is consistent with what was actually used to
+    // generate the zkp.
+    if *source_encryption_pubkey != confidential_transfer_account.elgamal_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    let new_source_available_balance = syscall::subtract_with_lo_hi(
+        &confidential_transfer_account.available_balance,
+        source_transfer_amount_lo,
+        source_transfer_amount_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    // Check that the computed available balance is consistent with what was actually used to
+    // generate the zkp on the client side.
+    if new_source_available_balance != *expected_new_source_available_balance {
+        return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+    }
+
+    confidential_transfer_account.available_balance = new_source_available_balance;
+    confidential_transfer_account.decryptable_available_balance =
+        new_source_decryptable_available_balance;
+
+    Ok(())
+}
+
+/// Processes the destination of a self-transfer.
+///
+/// The source and destination of a self-transfer are the same account, so the transfer amount that
+/// was just subtracted from the available balance is added directly back to it. The pending
+/// balance exists to protect the proofs of outgoing transfers from concurrent incoming transfers,
+/// which cannot happen within a single instruction, so a self-transfer does not touch the pending
+/// balance and does not consume a pending balance credit. Since the transfer amount is encrypted,
+/// the program cannot tell zero-amount self-transfers apart from other self-transfers, and all of
+/// them leave the balances of the account unchanged.
+#[cfg(feature = "zk-ops")]
+fn process_destination_for_self_transfer(
+    token_account_info: &AccountInfo,
+    destination_encryption_pubkey: &ElGamalPubkey,
+    transfer_amount_lo: &EncryptedBalance,
+    transfer_amount_hi: &EncryptedBalance,
+) -> ProgramResult {
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    if memo_required(&token_account) {
+        check_previous_sibling_instruction_is_memo()?;
+    }
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+
+    if *destination_encryption_pubkey != confidential_transfer_account.elgamal_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    confidential_transfer_account.available_balance = syscall::add_with_lo_hi(
+        &confidential_transfer_account.available_balance,
+        transfer_amount_lo,
+        transfer_amount_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok(())
+}
+
+#[cfg(feature = "zk-ops")]
+fn process_destination_for_transfer(
+    destination_token_account_info: &AccountInfo,
+    mint_info: &AccountInfo,
+    destination_encryption_pubkey: &ElGamalPubkey,
+    destination_transfer_amount_lo: &EncryptedBalance,
+    destination_transfer_amount_hi: &EncryptedBalance,
+    encrypted_fee: Option<(&EncryptedFee, &EncryptedFee)>,
+) -> ProgramResult {
+    check_program_account(destination_token_account_info.owner)?;
+    let destination_token_account_data = &mut destination_token_account_info.data.borrow_mut();
+    let mut destination_token_account =
+        StateWithExtensionsMut::<Account>::unpack(destination_token_account_data)?;
+
+    if destination_token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    if destination_token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    if memo_required(&destination_token_account) {
+        check_previous_sibling_instruction_is_memo()?;
+    }
+
+    check_credits_until_slot(&destination_token_account)?;
+    let mut destination_confidential_transfer_account =
+        destination_token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    // The transfer is rejected because of the state of the destination account rather than the
+    // source account, so a full pending balance is reported with a separate error that senders
+    // can tell apart from their own
+    if let Err(e) = destination_confidential_transfer_account.valid_as_destination() {
+        if e == ProgramError::from(TokenError::MaximumPendingBalanceCreditCounterExceeded) {
+            msg!(
+                "Pending balance credit counter of the destination account {} is at its maximum",
+                destination_token_account_info.key
+            );
+            return Err(TokenError::DestinationPendingBalanceCreditCounterExceeded.into());
+        }
+        return Err(e);
+    }
+
+    if *destination_encryption_pubkey != destination_confidential_transfer_account.elgamal_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    destination_confidential_transfer_account.pending_balance_lo = syscall::add(
+        &destination_confidential_transfer_account.pending_balance_lo,
+        destination_transfer_amount_lo,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    destination_confidential_transfer_account.pending_balance_hi = syscall::add(
+        &destination_confidential_transfer_account.pending_balance_hi,
+        destination_transfer_amount_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    destination_confidential_transfer_account.increment_pending_balance_credit_counter()?;
+    log_pending_balance_credit(
+        destination_token_account_info.key,
+        destination_confidential_transfer_account,
+    );
+
+    // Process transfer fee
+    if let Some((ciphertext_fee_lo, ciphertext_fee_hi)) = encrypted_fee {
+        // Decode lo and hi fee amounts encrypted under the destination encryption public key
+        let destination_fee_lo = fee_amount_destination_ciphertext(ciphertext_fee_lo);
+        let destination_fee_hi = fee_amount_destination_ciphertext(ciphertext_fee_hi);
+
+        // Subtract the fee amount from the destination pending balance
+        destination_confidential_transfer_account.pending_balance_lo = syscall::subtract(
+            &destination_confidential_transfer_account.pending_balance_lo,
+            &destination_fee_lo,
+        )
+        .ok_or(ProgramError::InvalidInstructionData)?;
+        destination_confidential_transfer_account.pending_balance_hi = syscall::subtract(
+            &destination_confidential_transfer_account.pending_balance_hi,
+            &destination_fee_hi,
+        )
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+        // Decode lo and hi fee amounts encrypted under the withdraw authority encryption public
+        // key
+        let withdraw_withheld_authority_fee_lo =
+            fee_amount_withdraw_withheld_authority_ciphertext(ciphertext_fee_lo);
+        let withdraw_withheld_authority_fee_hi =
+            fee_amount_withdraw_withheld_authority_ciphertext(ciphertext_fee_hi);
+
+        let mut destination_confidential_transfer_fee_amount =
+            destination_token_account.get_extension_mut::<ConfidentialTransferFeeAmount>()?;
+
+        // Add the fee amount to the destination withheld fee
+        destination_confidential_transfer_fee_amount.withheld_amount = syscall::add_with_lo_hi(
+            &destination_confidential_transfer_fee_amount.withheld_amount,
+            &withdraw_withheld_authority_fee_lo,
+            &withdraw_withheld_authority_fee_hi,
+        )
+        .ok_or(ProgramError::InvalidInstructionData)?;
+
+        // Indexers can detect fee accrual from the hash without the withdraw withheld authority
+        // ElGamal secret key
+        msg!(
+            "Withheld amount of {} updated, ciphertext hash {}",
+            destination_token_account_info.key,
+            hash(bytemuck::bytes_of(
+                &destination_confidential_transfer_fee_amount.withheld_amount
+            )),
+        );
+    }
+
+    Ok(())
+}
+
+/// Processes an [ApplyPendingBalance] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_apply_pending_balance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    ApplyPendingBalanceData {
+        expected_pending_balance_credit_counter,
+        new_decryptable_available_balance,
+    }: &ApplyPendingBalanceData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+
+    // The client can only have observed credits that were already made, so an expected counter
+    // beyond the current one could never be reconciled with the actual counter
+    if u64::from(*expected_pending_balance_credit_counter)
+        > u64::from(confidential_transfer_account.pending_balance_credit_counter)
+    {
+        return Err(TokenError::InvalidPendingBalanceCreditCounter.into());
+    }
+
+    confidential_transfer_account.available_balance = syscall::add_with_lo_hi(
+        &confidential_transfer_account.available_balance,
+        &confidential_transfer_account.pending_balance_lo,
+        &confidential_transfer_account.pending_balance_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    confidential_transfer_account.actual_pending_balance_credit_counter =
+        confidential_transfer_account.pending_balance_credit_counter;
+    confidential_transfer_account.expected_pending_balance_credit_counter =
+        *expected_pending_balance_credit_counter;
+    confidential_transfer_account.decryptable_available_balance =
+        *new_decryptable_available_balance;
+    confidential_transfer_account.pending_balance_credit_counter = 0.into();
+    confidential_transfer_account.pending_balance_lo = EncryptedBalance::zeroed();
+    confidential_transfer_account.pending_balance_hi = EncryptedBalance::zeroed();
+
+    msg!(
+        "Applied pending balance of {}: expected_pending_balance_credit_counter={}, actual_pending_balance_credit_counter={}",
+        token_account_info.key,
+        u64::from(confidential_transfer_account.expected_pending_balance_credit_counter),
+        u64::from(confidential_transfer_account.actual_pending_balance_credit_counter),
+    );
+
+    Ok(())
+}
+
+/// Log the pending balance credit counter of an account that was credited to its pending balance.
+///
+/// The ciphertexts of an account are opaque, but the credit counter is public, so off-chain
+/// observers can use this log to detect that an account has a pending balance to be applied.
+#[cfg(feature = "zk-ops")]
+fn log_pending_balance_credit(
+    token_account: &Pubkey,
+    confidential_transfer_account: &ConfidentialTransferAccount,
+) {
+    msg!(
+        "Credited pending balance of {}: pending_balance_credit_counter={}",
+        token_account,
+        u64::from(confidential_transfer_account.pending_balance_credit_counter),
+    );
+    if confidential_transfer_account.remaining_pending_balance_credits() == 0 {
+        msg!(
+            "Warning: pending balance of {} must be applied before it can be credited again",
+            token_account,
+        );
+    }
+}
+
+/// Processes a [MintConfidential] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_mint_confidential(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    expected_decimals: u8,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proof certifies that the mint amount ciphertexts are validly encrypted under
+    // the destination and supply ElGamal public keys
+    let validity_proof_context =
+        verify_context_state_proof::<BatchedGroupedCiphertext2HandlesValidityProofContext>(
+            next_account_info(account_info_iter)?,
+            ProofType::BatchedGroupedCiphertext2HandlesValidity,
+        )?;
+
+    // zero-knowledge proof certifies that the lo and hi bits of the mint amount are in range
+    let range_proof_context = verify_context_state_proof::<BatchedRangeProofContext>(
+        next_account_info(account_info_iter)?,
+        ProofType::BatchedRangeProofU64,
+    )?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+
+    if expected_decimals != mint.base.decimals {
+        return Err(TokenError::MintDecimalsMismatch.into());
+    }
+
+    let mint_authority = mint.base.mint_authority.ok_or(TokenError::FixedSupply)?;
+    Processor::validate_owner(
+        program_id,
+        &mint_authority,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    if mint.get_extension::<NonTransferable>().is_ok() {
+        return Err(TokenError::NonTransferable.into());
+    }
+
+    // Check that the auditor encryption public key associated wth the confidential mint is
+    // consistent with what was actually used to generate the zkp. A mint without an auditor
+    // accepts any supply ElGamal public key.
+    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    if Option::<ElGamalPubkey>::from(confidential_transfer_mint.auditor_elgamal_pubkey).is_some() {
+        check_auditor_elgamal_pubkey(
+            confidential_transfer_mint,
+            &validity_proof_context.auditor_pubkey,
+        )?;
+    }
+
+    // Check that the commitments of the range proof are the commitments of the mint amount
+    // ciphertexts, so that the lo and hi bits of the mint amount cannot wrap around the group
+    // order
+    if range_proof_context.commitments[0]
+        != grouped_ciphertext_commitment(&validity_proof_context.grouped_ciphertext_lo)
+        || range_proof_context.bit_lengths[0] != MINT_AMOUNT_LO_BIT_LENGTH
+        || range_proof_context.commitments[1]
+            != grouped_ciphertext_commitment(&validity_proof_context.grouped_ciphertext_hi)
+        || range_proof_context.bit_lengths[1] != MINT_AMOUNT_HI_BIT_LENGTH
+    {
+        return Err(TokenError::ConfidentialMintRangeProofMismatch.into());
+    }
+
+    let confidential_mint_supply = mint.get_extension_mut::<ConfidentialMintSupply>()?;
+    if confidential_mint_supply.supply_elgamal_pubkey != validity_proof_context.auditor_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    if token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    check_credits_until_slot(&token_account)?;
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.valid_as_destination()?;
+
+    // Check that the destination encryption public key is consistent with what was actually used
+    // to generate the zkp.
+    if validity_proof_context.destination_pubkey != confidential_transfer_account.elgamal_pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    confidential_transfer_account.pending_balance_lo = syscall::add(
+        &confidential_transfer_account.pending_balance_lo,
+        &grouped_ciphertext_destination_ciphertext(&validity_proof_context.grouped_ciphertext_lo),
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+    confidential_transfer_account.pending_balance_hi = syscall::add(
+        &confidential_transfer_account.pending_balance_hi,
+        &grouped_ciphertext_destination_ciphertext(&validity_proof_context.grouped_ciphertext_hi),
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    confidential_transfer_account.increment_pending_balance_credit_counter()?;
+    log_pending_balance_credit(token_account_info.key, confidential_transfer_account);
+
+    confidential_mint_supply.confidential_supply = syscall::add_with_lo_hi(
+        &confidential_mint_supply.confidential_supply,
+        &grouped_ciphertext_auditor_ciphertext(&validity_proof_context.grouped_ciphertext_lo),
+        &grouped_ciphertext_auditor_ciphertext(&validity_proof_context.grouped_ciphertext_hi),
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    Ok(())
+}
+
+/// Extract the Pedersen commitment from a grouped ciphertext with two handles.
+///
+/// The `commitment` component is the first 32-byte component of a grouped ciphertext.
+fn grouped_ciphertext_commitment(
+    grouped_ciphertext: &GroupedElGamalCiphertext2Handles,
+) -> PedersenCommitment {
+    let grouped_ciphertext_bytes = bytemuck::bytes_of(grouped_ciphertext);
+
+    let mut commitment_bytes = [0u8; 32];
+    commitment_bytes.copy_from_slice(&grouped_ciphertext_bytes[..32]);
+
+    PedersenCommitment(commitment_bytes)
+}
+
+/// Extract the ciphertext encrypted under the destination ElGamal public key from a grouped
+/// ciphertext with two handles.
+///
+/// A grouped ciphertext with two handles consists of the following 32-byte components that are
+/// serialized in order:
+///   1. The `commitment` component that encodes the amount.
+///   2. The `decryption handle` component with respect to the destination public key.
+///   3. The `decryption handle` component with respect to the auditor public key.
+///
+/// An ElGamal ciphertext for the destination consists of the `commitment` component and the
+/// `decryption handle` component with respect to the destination public key.
+#[cfg(feature = "zk-ops")]
+fn grouped_ciphertext_destination_ciphertext(
+    grouped_ciphertext: &GroupedElGamalCiphertext2Handles,
+) -> ElGamalCiphertext {
+    let grouped_ciphertext_bytes = bytemuck::bytes_of(grouped_ciphertext);
+
+    let mut destination_ciphertext_bytes = [0u8; 64];
+    destination_ciphertext_bytes.copy_from_slice(&grouped_ciphertext_bytes[..64]);
+
+    ElGamalCiphertext(destination_ciphertext_bytes)
+}
+
+/// Extract the ciphertext encrypted under the auditor ElGamal public key from a grouped ciphertext
+/// with two handles.
+///
+/// An ElGamal ciphertext for the auditor consists of the `commitment` component and the
+/// `decryption handle` component with respect to the auditor public key.
+#[cfg(feature = "zk-ops")]
+fn grouped_ciphertext_auditor_ciphertext(
+    grouped_ciphertext: &GroupedElGamalCiphertext2Handles,
+) -> ElGamalCiphertext {
+    let grouped_ciphertext_bytes = bytemuck::bytes_of(grouped_ciphertext);
+
+    let mut auditor_ciphertext_bytes = [0u8; 64];
+    auditor_ciphertext_bytes[..32].copy_from_slice(&grouped_ciphertext_bytes[..32]);
+    auditor_ciphertext_bytes[32..].copy_from_slice(&grouped_ciphertext_bytes[64..96]);
+
+    ElGamalCiphertext(auditor_ciphertext_bytes)
+}
+
+/// Processes an [InitializeConfidentialMintSupply] instruction.
+fn process_initialize_confidential_mint_supply(
+    accounts: &[AccountInfo],
+    supply_elgamal_pubkey: &ElGamalPubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_info = next_account_info(account_info_iter)?;
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(mint_data)?;
+    let confidential_mint_supply = mint.init_extension::<ConfidentialMintSupply>(true)?;
+
+    confidential_mint_supply.supply_elgamal_pubkey = *supply_elgamal_pubkey;
+
+    Ok(())
+}
+
+/// Processes a [BurnConfidential] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_burn_confidential(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+    expected_decimals: u8,
+    new_decryptable_available_balance: DecryptableBalance,
+    proof_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proof certifies that the account has enough available balance to burn the
+    // amount.
+    let proof_context = verify_withdraw_proof(
+        next_account_info(account_info_iter)?,
+        proof_instruction_offset,
+    )?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mut mint_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(mint_data)?;
+
+    if expected_decimals != mint.base.decimals {
+        return Err(TokenError::MintDecimalsMismatch.into());
+    }
+
+    if mint.get_extension::<NonTransferable>().is_ok() {
+        return Err(TokenError::NonTransferable.into());
+    }
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    if token_account.base.mint != *mint_info.key {
+        return Err(TokenError::MintMismatch.into());
+    }
+
+    check_decryptable_available_balance_current(&token_account)?;
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.valid_as_source()?;
+
+    // Check that the encryption public key associated with the confidential extension is
+    // consistent with the public key that was actually used to generate the zkp.
+    if confidential_transfer_account.elgamal_pubkey != proof_context.pubkey {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    // Prevent unnecessary ciphertext arithmetic syscalls if the burn amount is zero
+    if amount > 0 {
+        confidential_transfer_account.available_balance =
+            syscall::subtract_from(&confidential_transfer_account.available_balance, amount)
+                .ok_or(ProgramError::InvalidInstructionData)?;
+    }
+    // Check that the final available balance ciphertext is consistent with the actual ciphertext
+    // for which the zero-knowledge proof was generated for.
+    if confidential_transfer_account.available_balance != proof_context.final_ciphertext {
+        return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+    }
+
+    confidential_transfer_account.decryptable_available_balance = new_decryptable_available_balance;
+
+    // Tokens of a mint with a confidential supply can only be minted with `MintConfidential`,
+    // which credits the confidential supply. Otherwise, confidential balances are only credited
+    // by `Deposit`, so the supply accounts for the tokens in confidential balances.
+    if mint.get_extension::<ConfidentialMintSupply>().is_ok() {
+        let confidential_mint_supply = mint.get_extension_mut::<ConfidentialMintSupply>()?;
+        // Prevent unnecessary ciphertext arithmetic syscalls if the burn amount is zero
+        if amount > 0 {
+            confidential_mint_supply.confidential_supply =
+                syscall::subtract_from(&confidential_mint_supply.confidential_supply, amount)
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+        }
+    } else {
+        mint.base.supply = mint
+            .base
+            .supply
+            .checked_sub(amount)
+            .ok_or(TokenError::Overflow)?;
+        mint.pack_base();
+    }
+
+    Ok(())
+}
+
+/// Processes a [RotateElGamalKey] instruction.
+fn process_rotate_elgamal_key(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_elgamal_pubkey: &ElGamalPubkey,
+    new_decryptable_available_balance: &DecryptableBalance,
+    proof_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+
+    // zero-knowledge proof certifies that the ciphertext under the new ElGamal public key
+    // encrypts the same amount as the current available balance ciphertext
+    let proof_context = verify_ciphertext_ciphertext_equality_proof(
+        next_account_info(account_info_iter)?,
+        proof_instruction_offset,
+    )?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+
+    // Check that the current and the new encryption public keys are consistent with the public
+    // keys that were actually used to generate the zkp.
+    if confidential_transfer_account.elgamal_pubkey != proof_context.source_pubkey
+        || *new_elgamal_pubkey != proof_context.destination_pubkey
+    {
+        return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+    }
+
+    // The proof only covers the available balance, so the pending balance must be empty.
+    if confidential_transfer_account.pending_balance_lo != EncryptedBalance::zeroed()
+        || confidential_transfer_account.pending_balance_hi != EncryptedBalance::zeroed()
+    {
+        return Err(TokenError::ConfidentialTransferPendingBalanceNotEmpty.into());
+    }
+
+    // Check that the current available balance ciphertext is consistent with the ciphertext for
+    // which the zero-knowledge proof was generated for.
+    if confidential_transfer_account.available_balance != proof_context.source_ciphertext {
+        return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+    }
+
+    confidential_transfer_account.elgamal_pubkey = *new_elgamal_pubkey;
+    confidential_transfer_account.available_balance = proof_context.destination_ciphertext;
+    confidential_transfer_account.decryptable_available_balance =
+        *new_decryptable_available_balance;
+
+    Ok(())
+}
+
+/// Verify a zero-knowledge proof that two ciphertexts encrypt the same amount, as needed for a
+/// [RotateElGamalKey] or [ForwardPendingBalance] instruction, and return the corresponding proof
+/// context.
+fn verify_ciphertext_ciphertext_equality_proof(
+    account_info: &AccountInfo<'_>,
+    proof_instruction_offset: i64,
+) -> Result<CiphertextCiphertextEqualityProofContext, ProgramError> {
+    if proof_instruction_offset == 0 {
+        // interpret `account_info` as a context state account
+        check_zk_token_proof_program_account(account_info.owner)?;
+        let context_state_account_data = account_info.data.borrow();
+        check_context_state_proof_type(
+            &context_state_account_data,
+            ProofType::CiphertextCiphertextEquality,
+        )?;
+        let context_state = pod_from_bytes::<
+            ProofContextState<CiphertextCiphertextEqualityProofContext>,
+        >(&context_state_account_data)?;
+
+        Ok(context_state.proof_context)
+    } else {
+        // interpret `account_info` as a sysvar
+        let zkp_instruction =
+            get_proof_instruction_relative(proof_instruction_offset, account_info)?;
+        Ok(*decode_proof_instruction_context::<
+            CiphertextCiphertextEqualityProofData,
+            CiphertextCiphertextEqualityProofContext,
+        >(
+            ProofInstruction::VerifyCiphertextCiphertextEquality,
+            &zkp_instruction,
+        )?)
+    }
+}
+
+/// Processes a [DisableConfidentialCredits], [EnableConfidentialCredits], or
+/// [EnableConfidentialCreditsUntilSlot] instruction.
+fn process_allow_confidential_credits(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    allow_confidential_credits: bool,
+    allow_confidential_credits_until_slot: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.allow_confidential_credits = allow_confidential_credits.into();
+
+    // The scheduled slot is kept in a separate extension, so that accounts that never schedule one
+    // do not need to be reallocated
+    if let Ok(credits_until_slot) =
+        token_account.get_extension_mut::<ConfidentialTransferCreditsUntilSlot>()
+    {
+        credits_until_slot.slot = allow_confidential_credits_until_slot.into();
+    } else if allow_confidential_credits_until_slot != 0 {
+        let credits_until_slot =
+            token_account.init_extension::<ConfidentialTransferCreditsUntilSlot>(true)?;
+        credits_until_slot.slot = allow_confidential_credits_until_slot.into();
+    }
+
+    Ok(())
+}
+
+/// Processes an [UpdateMaxPendingBalanceCreditCounter] instruction.
+fn process_update_max_pending_balance_credit_counter(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    maximum_pending_balance_credit_counter: &PodU64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.maximum_pending_balance_credit_counter =
+        *maximum_pending_balance_credit_counter;
+
+    Ok(())
+}
+
+/// Processes an [UpdateDecryptableBalance] instruction.
+fn process_update_decryptable_balance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let token_account_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = &mut token_account_info.data.borrow_mut();
+    let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+    Processor::validate_owner(
+        program_id,
+        &token_account.base.owner,
+        authority_info,
+        authority_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let mut confidential_transfer_account =
+        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+    confidential_transfer_account.decryptable_available_balance = new_decryptable_available_balance;
+
+    if let Ok(pull_authorization) =
+        token_account.get_extension_mut::<ConfidentialTransferPullAuthorization>()
+    {
+        pull_authorization.decryptable_available_balance_stale = false.into();
+    }
+
+    Ok(())
+}
+
+/// Processes a [ForwardPendingBalance] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_forward_pending_balance(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    equality_proof_lo_instruction_offset: i64,
+    equality_proof_hi_instruction_offset: i64,
 ) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let source_account_info = next_account_info(account_info_iter)?;
+    let destination_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // either sysvar or context state account depending on the corresponding instruction offset
+    let equality_proof_lo_account_info = next_account_info(account_info_iter)?;
+    let equality_proof_hi_account_info = next_account_info(account_info_iter)?;
+
+    let authority_info = next_account_info(account_info_iter)?;
+    let authority_info_data_len = authority_info.data_len();
+
+    if source_account_info.key == destination_token_account_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mint_info.data.borrow_mut();
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+
+    if mint.get_extension::<NonTransferable>().is_ok() {
+        return Err(TokenError::NonTransferable.into());
+    }
+    // The forwarded amounts are certified by equality proofs, which do not account for transfer
+    // fees
+    if mint.get_extension::<TransferFeeConfig>().is_ok() {
+        return Err(TokenError::InvalidExtensionCombination.into());
+    }
+    // The forwarded amounts are neither encrypted under the auditor ElGamal public keys nor
+    // certified to be within the maximum transfer amount of the mint
+    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+    if Option::<ElGamalPubkey>::from(confidential_transfer_mint.auditor_elgamal_pubkey).is_some()
+        || additional_transfer_proofs_len(&mint) > 0
+    {
+        return Err(TokenError::ConfidentialTransferForwardNotSupported.into());
+    }
+
+    // zero-knowledge proofs certify that the ciphertexts under the destination ElGamal public key
+    // encrypt the same amounts as the low and high bits of the source pending balance
+    let proof_context_lo = verify_ciphertext_ciphertext_equality_proof(
+        equality_proof_lo_account_info,
+        equality_proof_lo_instruction_offset,
+    )?;
+    let proof_context_hi = verify_ciphertext_ciphertext_equality_proof(
+        equality_proof_hi_account_info,
+        equality_proof_hi_instruction_offset,
+    )?;
+    if proof_context_lo.source_pubkey != proof_context_hi.source_pubkey
+        || proof_context_lo.destination_pubkey != proof_context_hi.destination_pubkey
+    {
+        return Err(TokenError::ConfidentialTransferSplitProofMismatch.into());
+    }
+
+    let forwarded_credits = {
+        check_program_account(source_account_info.owner)?;
+        let token_account_data = &mut source_account_info.data.borrow_mut();
+        let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+        Processor::validate_owner(
+            program_id,
+            &token_account.base.owner,
+            authority_info,
+            authority_info_data_len,
+            account_info_iter.as_slice(),
+        )?;
+
+        if token_account.base.is_frozen() {
+            return Err(TokenError::AccountFrozen.into());
+        }
+
+        if token_account.base.mint != *mint_info.key {
+            return Err(TokenError::MintMismatch.into());
+        }
+
+        let mut confidential_transfer_account =
+            token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
+        confidential_transfer_account.valid_as_source()?;
+
+        if confidential_transfer_account.elgamal_pubkey != proof_context_lo.source_pubkey {
+            return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
+        }
+
+        // The proofs must cover the entire pending balance, which cannot be forwarded in part
+        if confidential_transfer_account.pending_balance_lo != proof_context_lo.source_ciphertext
+            || confidential_transfer_account.pending_balance_hi
+                != proof_context_hi.source_ciphertext
+        {
+            return Err(TokenError::ConfidentialTransferBalanceMismatch.into());
+        }
+
+        confidential_transfer_account.pending_balance_lo = EncryptedBalance::zeroed();
+        confidential_transfer_account.pending_balance_hi = EncryptedBalance::zeroed();
+
+        let forwarded_credits =
+            u64::from(confidential_transfer_account.pending_balance_credit_counter);
+        confidential_transfer_account.pending_balance_credit_counter = 0.into();
+        forwarded_credits
+    };
+
     check_program_account(destination_token_account_info.owner)?;
     let destination_token_account_data = &mut destination_token_account_info.data.borrow_mut();
     let mut destination_token_account =
@@ -938,77 +2922,46 @@ fn process_destination_for_transfer(
         check_previous_sibling_instruction_is_memo()?;
     }
 
+    check_credits_until_slot(&destination_token_account)?;
     let mut destination_confidential_transfer_account =
         destination_token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-    destination_confidential_transfer_account.valid_as_destination()?;
 
-    if *destination_encryption_pubkey != destination_confidential_transfer_account.elgamal_pubkey {
+    // The forwarded low bits may hold as many credits as the source pending balance received, so
+    // they count against the destination credit counter as such
+    destination_confidential_transfer_account.valid_as_destination_of_credits(forwarded_credits)?;
+
+    if proof_context_lo.destination_pubkey
+        != destination_confidential_transfer_account.elgamal_pubkey
+    {
         return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch.into());
     }
 
     destination_confidential_transfer_account.pending_balance_lo = syscall::add(
         &destination_confidential_transfer_account.pending_balance_lo,
-        destination_transfer_amount_lo,
+        &proof_context_lo.destination_ciphertext,
     )
     .ok_or(ProgramError::InvalidInstructionData)?;
 
     destination_confidential_transfer_account.pending_balance_hi = syscall::add(
         &destination_confidential_transfer_account.pending_balance_hi,
-        destination_transfer_amount_hi,
+        &proof_context_hi.destination_ciphertext,
     )
     .ok_or(ProgramError::InvalidInstructionData)?;
 
-    destination_confidential_transfer_account.increment_pending_balance_credit_counter()?;
-
-    // Process transfer fee
-    if let Some((ciphertext_fee_lo, ciphertext_fee_hi)) = encrypted_fee {
-        // Decode lo and hi fee amounts encrypted under the destination encryption public key
-        let destination_fee_lo = fee_amount_destination_ciphertext(ciphertext_fee_lo);
-        let destination_fee_hi = fee_amount_destination_ciphertext(ciphertext_fee_hi);
-
-        // Subtract the fee amount from the destination pending balance
-        destination_confidential_transfer_account.pending_balance_lo = syscall::subtract(
-            &destination_confidential_transfer_account.pending_balance_lo,
-            &destination_fee_lo,
-        )
-        .ok_or(ProgramError::InvalidInstructionData)?;
-        destination_confidential_transfer_account.pending_balance_hi = syscall::subtract(
-            &destination_confidential_transfer_account.pending_balance_hi,
-            &destination_fee_hi,
-        )
-        .ok_or(ProgramError::InvalidInstructionData)?;
-
-        // Decode lo and hi fee amounts encrypted under the withdraw authority encryption public
-        // key
-        let withdraw_withheld_authority_fee_lo =
-            fee_amount_withdraw_withheld_authority_ciphertext(ciphertext_fee_lo);
-        let withdraw_withheld_authority_fee_hi =
-            fee_amount_withdraw_withheld_authority_ciphertext(ciphertext_fee_hi);
-
-        let mut destination_confidential_transfer_fee_amount =
-            destination_token_account.get_extension_mut::<ConfidentialTransferFeeAmount>()?;
-
-        // Add the fee amount to the destination withheld fee
-        destination_confidential_transfer_fee_amount.withheld_amount = syscall::add_with_lo_hi(
-            &destination_confidential_transfer_fee_amount.withheld_amount,
-            &withdraw_withheld_authority_fee_lo,
-            &withdraw_withheld_authority_fee_hi,
-        )
-        .ok_or(ProgramError::InvalidInstructionData)?;
-    }
+    destination_confidential_transfer_account.add_pending_balance_credits(forwarded_credits)?;
+    log_pending_balance_credit(
+        destination_token_account_info.key,
+        destination_confidential_transfer_account,
+    );
 
     Ok(())
 }
 
-/// Processes an [ApplyPendingBalance] instruction.
-#[cfg(feature = "zk-ops")]
-fn process_apply_pending_balance(
+/// Processes an [DisableNonConfidentialCredits] or [EnableNonConfidentialCredits] instruction.
+fn process_allow_non_confidential_credits(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    ApplyPendingBalanceData {
-        expected_pending_balance_credit_counter,
-        new_decryptable_available_balance,
-    }: &ApplyPendingBalanceData,
+    allow_non_confidential_credits: bool,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let token_account_info = next_account_info(account_info_iter)?;
@@ -1029,37 +2982,24 @@ fn process_apply_pending_balance(
 
     let mut confidential_transfer_account =
         token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-
-    confidential_transfer_account.available_balance = syscall::add_with_lo_hi(
-        &confidential_transfer_account.available_balance,
-        &confidential_transfer_account.pending_balance_lo,
-        &confidential_transfer_account.pending_balance_hi,
-    )
-    .ok_or(ProgramError::InvalidInstructionData)?;
-
-    confidential_transfer_account.actual_pending_balance_credit_counter =
-        confidential_transfer_account.pending_balance_credit_counter;
-    confidential_transfer_account.expected_pending_balance_credit_counter =
-        *expected_pending_balance_credit_counter;
-    confidential_transfer_account.decryptable_available_balance =
-        *new_decryptable_available_balance;
-    confidential_transfer_account.pending_balance_credit_counter = 0.into();
-    confidential_transfer_account.pending_balance_lo = EncryptedBalance::zeroed();
-    confidential_transfer_account.pending_balance_hi = EncryptedBalance::zeroed();
+    confidential_transfer_account.allow_non_confidential_credits =
+        allow_non_confidential_credits.into();
 
     Ok(())
 }
 
-/// Processes a [DisableConfidentialCredits] or [EnableConfidentialCredits] instruction.
-fn process_allow_confidential_credits(
+/// Processes an [ApproveConfidentialDelegate] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_approve_confidential_delegate(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    allow_confidential_credits: bool,
+    data: &ApproveConfidentialDelegateInstructionData,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let token_account_info = next_account_info(account_info_iter)?;
-    let authority_info = next_account_info(account_info_iter)?;
-    let authority_info_data_len = authority_info.data_len();
+    let delegate_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let owner_info_data_len = owner_info.data_len();
 
     check_program_account(token_account_info.owner)?;
     let token_account_data = &mut token_account_info.data.borrow_mut();
@@ -1068,28 +3008,219 @@ fn process_allow_confidential_credits(
     Processor::validate_owner(
         program_id,
         &token_account.base.owner,
-        authority_info,
-        authority_info_data_len,
+        owner_info,
+        owner_info_data_len,
         account_info_iter.as_slice(),
     )?;
 
-    let mut confidential_transfer_account =
-        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-    confidential_transfer_account.allow_confidential_credits = allow_confidential_credits.into();
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    // The allowance can only be spent from the confidential balance of the account
+    token_account.get_extension::<ConfidentialTransferAccount>()?;
+
+    // The delegate is approved as with a regular `Approve`, but the public delegated amount is
+    // cleared so that the delegate cannot also move the non-confidential balance
+    token_account.base.delegate = COption::Some(*delegate_info.key);
+    token_account.base.delegated_amount = 0;
+    token_account.pack_base();
+
+    let delegate_allowance = if let Ok(delegate_allowance) =
+        token_account.get_extension_mut::<ConfidentialTransferDelegateAllowance>()
+    {
+        delegate_allowance
+    } else {
+        token_account.init_extension::<ConfidentialTransferDelegateAllowance>(true)?
+    };
+    delegate_allowance.delegate = Some(*delegate_info.key).try_into()?;
+    delegate_allowance.allowance =
+        syscall::add_to(&EncryptedBalance::zeroed(), u64::from(data.amount))
+            .ok_or(ProgramError::InvalidInstructionData)?;
+    delegate_allowance.decryptable_allowance = data.decryptable_allowance;
 
     Ok(())
 }
 
-/// Processes an [DisableNonConfidentialCredits] or [EnableNonConfidentialCredits] instruction.
-fn process_allow_non_confidential_credits(
+/// Processes a [DelegatedTransfer] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_delegated_transfer(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    allow_non_confidential_credits: bool,
+    new_source_decryptable_available_balance: DecryptableBalance,
+    new_decryptable_allowance: DecryptableBalance,
+    proof_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let source_account_info = next_account_info(account_info_iter)?;
+    let destination_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // either sysvar or context state account depending on `proof_instruction_offset`
+    let proof_account_info = next_account_info(account_info_iter)?;
+
+    let equality_proof_account_info = next_account_info(account_info_iter)?;
+    let range_proof_account_info = next_account_info(account_info_iter)?;
+
+    let delegate_info = next_account_info(account_info_iter)?;
+    let delegate_info_data_len = delegate_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mint_info.data.borrow_mut();
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+
+    if mint.get_extension::<NonTransferable>().is_ok() {
+        return Err(TokenError::NonTransferable.into());
+    }
+    // The allowance is charged with the amount certified by a `VerifyTransfer` proof, which does
+    // not account for transfer fees
+    if mint.get_extension::<TransferFeeConfig>().is_ok() {
+        return Err(TokenError::InvalidExtensionCombination.into());
+    }
+    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+
+    let proof_context = verify_transfer_proof(proof_account_info, proof_instruction_offset)?;
+
+    check_auditor_elgamal_pubkey(
+        confidential_transfer_mint,
+        &proof_context.transfer_pubkeys.auditor,
+    )?;
+
+    let source_transfer_amount_lo = transfer_amount_source_ciphertext(&proof_context.ciphertext_lo);
+    let source_transfer_amount_hi = transfer_amount_source_ciphertext(&proof_context.ciphertext_hi);
+
+    check_additional_transfer_proofs(
+        &mint,
+        &proof_context.transfer_pubkeys.source,
+        &source_transfer_amount_lo,
+        &source_transfer_amount_hi,
+        None,
+    )?;
+
+    {
+        check_program_account(source_account_info.owner)?;
+        let token_account_data = &mut source_account_info.data.borrow_mut();
+        let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+        // The signer must be the account delegate, so that `Revoke` or the approval of another
+        // delegate also revokes the allowance
+        if token_account.base.delegate != COption::Some(*delegate_info.key) {
+            return Err(TokenError::ConfidentialTransferDelegateNotApproved.into());
+        }
+
+        let delegate_allowance =
+            token_account.get_extension_mut::<ConfidentialTransferDelegateAllowance>()?;
+        if Option::<Pubkey>::from(delegate_allowance.delegate) != Some(*delegate_info.key) {
+            return Err(TokenError::ConfidentialTransferDelegateNotApproved.into());
+        }
+
+        Processor::validate_owner(
+            program_id,
+            delegate_info.key,
+            delegate_info,
+            delegate_info_data_len,
+            account_info_iter.as_slice(),
+        )?;
+
+        delegate_allowance.allowance = check_delegate_allowance_proofs(
+            &delegate_allowance.allowance,
+            &proof_context.transfer_pubkeys.source,
+            &source_transfer_amount_lo,
+            &source_transfer_amount_hi,
+            equality_proof_account_info,
+            range_proof_account_info,
+        )?;
+        delegate_allowance.decryptable_allowance = new_decryptable_allowance;
+
+        check_decryptable_available_balance_current(&token_account)?;
+        debit_source_for_transfer(
+            &mut token_account,
+            mint_info,
+            &proof_context.transfer_pubkeys.source,
+            &source_transfer_amount_lo,
+            &source_transfer_amount_hi,
+            &proof_context.new_source_ciphertext,
+            new_source_decryptable_available_balance,
+        )?;
+    }
+
+    let destination_ciphertext_lo =
+        transfer_amount_destination_ciphertext(&proof_context.ciphertext_lo);
+    let destination_ciphertext_hi =
+        transfer_amount_destination_ciphertext(&proof_context.ciphertext_hi);
+
+    if source_account_info.key == destination_token_account_info.key {
+        return process_destination_for_self_transfer(
+            destination_token_account_info,
+            &proof_context.transfer_pubkeys.destination,
+            &destination_ciphertext_lo,
+            &destination_ciphertext_hi,
+        );
+    }
+
+    process_destination_for_transfer(
+        destination_token_account_info,
+        mint_info,
+        &proof_context.transfer_pubkeys.destination,
+        &destination_ciphertext_lo,
+        &destination_ciphertext_hi,
+        None,
+    )
+}
+
+/// Check that the equality and range proofs of a delegated transfer certify that the remaining
+/// allowance minus the transfer amount is a 64-bit amount, and return the new allowance.
+///
+/// The new allowance is computed homomorphically from the transfer amount ciphertexts under the
+/// source ElGamal public key. A transfer amount that exceeds the remaining allowance would wrap
+/// the new allowance around the group order, which the range proof rules out.
+#[cfg(feature = "zk-ops")]
+fn check_delegate_allowance_proofs(
+    allowance: &EncryptedBalance,
+    source_elgamal_pubkey: &ElGamalPubkey,
+    source_transfer_amount_lo: &EncryptedBalance,
+    source_transfer_amount_hi: &EncryptedBalance,
+    equality_proof_account_info: &AccountInfo,
+    range_proof_account_info: &AccountInfo,
+) -> Result<EncryptedBalance, ProgramError> {
+    let equality_proof_context =
+        verify_context_state_proof::<CiphertextCommitmentEqualityProofContext>(
+            equality_proof_account_info,
+            ProofType::CiphertextCommitmentEquality,
+        )?;
+    let range_proof_context = verify_context_state_proof::<BatchedRangeProofContext>(
+        range_proof_account_info,
+        ProofType::BatchedRangeProofU64,
+    )?;
+
+    let new_allowance = syscall::subtract_with_lo_hi(
+        allowance,
+        source_transfer_amount_lo,
+        source_transfer_amount_hi,
+    )
+    .ok_or(ProgramError::InvalidInstructionData)?;
+
+    if equality_proof_context.pubkey != *source_elgamal_pubkey
+        || equality_proof_context.ciphertext != new_allowance
+        || range_proof_context.commitments[0] != equality_proof_context.commitment
+        || range_proof_context.bit_lengths[0] != DELEGATE_ALLOWANCE_RANGE_PROOF_BIT_LENGTH
+    {
+        return Err(TokenError::ConfidentialTransferDelegateAllowanceMismatch.into());
+    }
+    Ok(new_allowance)
+}
+
+/// Processes an [ApprovePullTransfer] instruction.
+fn process_approve_pull_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &ApprovePullTransferInstructionData,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
     let token_account_info = next_account_info(account_info_iter)?;
-    let authority_info = next_account_info(account_info_iter)?;
-    let authority_info_data_len = authority_info.data_len();
+    let puller_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let owner_info_data_len = owner_info.data_len();
 
     check_program_account(token_account_info.owner)?;
     let token_account_data = &mut token_account_info.data.borrow_mut();
@@ -1098,17 +3229,202 @@ fn process_allow_non_confidential_credits(
     Processor::validate_owner(
         program_id,
         &token_account.base.owner,
-        authority_info,
-        authority_info_data_len,
+        owner_info,
+        owner_info_data_len,
         account_info_iter.as_slice(),
     )?;
 
-    let mut confidential_transfer_account =
-        token_account.get_extension_mut::<ConfidentialTransferAccount>()?;
-    confidential_transfer_account.allow_non_confidential_credits =
-        allow_non_confidential_credits.into();
-
-    Ok(())
+    if token_account.base.is_frozen() {
+        return Err(TokenError::AccountFrozen.into());
+    }
+
+    // The authorized amount can only be pulled from the confidential balance of the account
+    token_account.get_extension::<ConfidentialTransferAccount>()?;
+
+    let pull_authorization = if let Ok(pull_authorization) =
+        token_account.get_extension_mut::<ConfidentialTransferPullAuthorization>()
+    {
+        pull_authorization
+    } else {
+        token_account.init_extension::<ConfidentialTransferPullAuthorization>(true)?
+    };
+    pull_authorization.puller = Some(*puller_info.key).try_into()?;
+    pull_authorization.amount_lo = data.amount_lo;
+    pull_authorization.amount_hi = data.amount_hi;
+
+    Ok(())
+}
+
+/// Processes a [PullTransfer] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_pull_transfer(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    proof_instruction_offset: i64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let source_account_info = next_account_info(account_info_iter)?;
+    let destination_token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+
+    // either sysvar or context state account depending on `proof_instruction_offset`
+    let proof_account_info = next_account_info(account_info_iter)?;
+
+    let puller_info = next_account_info(account_info_iter)?;
+    let puller_info_data_len = puller_info.data_len();
+
+    check_program_account(mint_info.owner)?;
+    let mint_data = &mint_info.data.borrow_mut();
+    let mint = StateWithExtensions::<Mint>::unpack(mint_data)?;
+
+    if mint.get_extension::<NonTransferable>().is_ok() {
+        return Err(TokenError::NonTransferable.into());
+    }
+    // The authorized transfer amount is certified by a `VerifyTransfer` proof, which does not
+    // account for transfer fees
+    if mint.get_extension::<TransferFeeConfig>().is_ok() {
+        return Err(TokenError::InvalidExtensionCombination.into());
+    }
+    let confidential_transfer_mint = mint.get_extension::<ConfidentialTransferMint>()?;
+
+    let proof_context = verify_transfer_proof(proof_account_info, proof_instruction_offset)?;
+
+    check_auditor_elgamal_pubkey(
+        confidential_transfer_mint,
+        &proof_context.transfer_pubkeys.auditor,
+    )?;
+
+    let source_transfer_amount_lo = transfer_amount_source_ciphertext(&proof_context.ciphertext_lo);
+    let source_transfer_amount_hi = transfer_amount_source_ciphertext(&proof_context.ciphertext_hi);
+
+    {
+        check_program_account(source_account_info.owner)?;
+        let token_account_data = &mut source_account_info.data.borrow_mut();
+        let mut token_account = StateWithExtensionsMut::<Account>::unpack(token_account_data)?;
+
+        let pull_authorization =
+            token_account.get_extension_mut::<ConfidentialTransferPullAuthorization>()?;
+        let puller = Option::<Pubkey>::from(pull_authorization.puller)
+            .ok_or(TokenError::ConfidentialTransferPullNotAuthorized)?;
+
+        // Check that the puller and the transfer amount are the ones that were authorized by the
+        // source account owner
+        if puller != *puller_info.key
+            || pull_authorization.amount_lo != source_transfer_amount_lo
+            || pull_authorization.amount_hi != source_transfer_amount_hi
+        {
+            return Err(TokenError::ConfidentialTransferPullNotAuthorized.into());
+        }
+
+        // The authorization can only be used once, and the decryptable available balance is stale
+        // until the owner updates it
+        *pull_authorization = ConfidentialTransferPullAuthorization {
+            decryptable_available_balance_stale: true.into(),
+            ..ConfidentialTransferPullAuthorization::default()
+        };
+
+        Processor::validate_owner(
+            program_id,
+            &puller,
+            puller_info,
+            puller_info_data_len,
+            account_info_iter.as_slice(),
+        )?;
+
+        // Only the owner can encrypt the new decryptable available balance, so it is left
+        // unchanged until the owner updates it
+        let decryptable_available_balance = token_account
+            .get_extension::<ConfidentialTransferAccount>()?
+            .decryptable_available_balance;
+        debit_source_for_transfer(
+            &mut token_account,
+            mint_info,
+            &proof_context.transfer_pubkeys.source,
+            &source_transfer_amount_lo,
+            &source_transfer_amount_hi,
+            &proof_context.new_source_ciphertext,
+            decryptable_available_balance,
+        )?;
+    }
+
+    let destination_ciphertext_lo =
+        transfer_amount_destination_ciphertext(&proof_context.ciphertext_lo);
+    let destination_ciphertext_hi =
+        transfer_amount_destination_ciphertext(&proof_context.ciphertext_hi);
+
+    if source_account_info.key == destination_token_account_info.key {
+        return process_destination_for_self_transfer(
+            destination_token_account_info,
+            &proof_context.transfer_pubkeys.destination,
+            &destination_ciphertext_lo,
+            &destination_ciphertext_hi,
+        );
+    }
+
+    process_destination_for_transfer(
+        destination_token_account_info,
+        mint_info,
+        &proof_context.transfer_pubkeys.destination,
+        &destination_ciphertext_lo,
+        &destination_ciphertext_hi,
+        None,
+    )
+}
+
+/// Processes an [AtomicSwap] instruction.
+#[cfg(feature = "zk-ops")]
+fn process_atomic_swap(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    first_new_source_decryptable_available_balance: DecryptableBalance,
+    second_new_source_decryptable_available_balance: DecryptableBalance,
+) -> ProgramResult {
+    const LEG_ACCOUNTS_LEN: usize = 5;
+    if accounts.len() < 2 * LEG_ACCOUNTS_LEN {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    }
+    let first_leg_accounts = &accounts[..LEG_ACCOUNTS_LEN];
+    let second_leg_accounts = &accounts[LEG_ACCOUNTS_LEN..2 * LEG_ACCOUNTS_LEN];
+
+    // Each leg must credit the owner of the source account of the other leg so that the swap
+    // cannot be used to send both transfers to a third party
+    let first_source_owner = token_account_owner(&first_leg_accounts[0])?;
+    let second_source_owner = token_account_owner(&second_leg_accounts[0])?;
+    if token_account_owner(&first_leg_accounts[1])? != second_source_owner
+        || token_account_owner(&second_leg_accounts[1])? != first_source_owner
+    {
+        return Err(TokenError::OwnerMismatch.into());
+    }
+
+    // Both legs are processed as regular transfers with proofs in context state accounts. If the
+    // second leg fails, the whole instruction fails and the first leg is rolled back.
+    process_transfer(
+        program_id,
+        first_leg_accounts,
+        first_new_source_decryptable_available_balance,
+        0,
+        None,
+        None,
+        false,
+    )?;
+    process_transfer(
+        program_id,
+        second_leg_accounts,
+        second_new_source_decryptable_available_balance,
+        0,
+        None,
+        None,
+        false,
+    )
+}
+
+/// Returns the owner of a token account.
+#[cfg(feature = "zk-ops")]
+fn token_account_owner(token_account_info: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    check_program_account(token_account_info.owner)?;
+    let token_account_data = token_account_info.data.borrow();
+    let token_account = StateWithExtensions::<Account>::unpack(&token_account_data)?;
+    Ok(token_account.base.owner)
 }
 
 #[allow(dead_code)]
@@ -1136,7 +3452,7 @@ pub(crate) fn process_instruction(
             process_update_mint(
                 accounts,
                 data.auto_approve_new_accounts,
-                &data.auditor_elgamal_pubkey,
+                Some(&data.auditor_elgamal_pubkey),
             )
         }
         ConfidentialTransferInstruction::ConfigureAccount => {
@@ -1164,7 +3480,13 @@ pub(crate) fn process_instruction(
             #[cfg(feature = "zk-ops")]
             {
                 let data = decode_instruction_data::<DepositInstructionData>(input)?;
-                process_deposit(program_id, accounts, data.amount.into(), data.decimals)
+                process_deposit(
+                    program_id,
+                    accounts,
+                    data.amount.into(),
+                    data.decimals,
+                    None,
+                )
             }
             #[cfg(not(feature = "zk-ops"))]
             Err(ProgramError::InvalidInstructionData)
@@ -1183,17 +3505,76 @@ pub(crate) fn process_instruction(
                     data.proof_instruction_offset as i64,
                 )
             }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
         }
         ConfidentialTransferInstruction::Transfer => {
             msg!("ConfidentialTransferInstruction::Transfer");
             #[cfg(feature = "zk-ops")]
-            let data = decode_instruction_data::<TransferInstructionData>(input)?;
-            process_transfer(
-                program_id,
-                accounts,
-                data.new_source_decryptable_available_balance,
-                data.proof_instruction_offset as i64,
-            )
+            {
+                let data = decode_instruction_data::<TransferInstructionData>(input)?;
+                process_transfer(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                    data.proof_instruction_offset as i64,
+                    None,
+                    None,
+                    false,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::TransferChecked => {
+            msg!("ConfidentialTransferInstruction::TransferChecked");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<TransferCheckedInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_transfer(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                    data.proof_instruction_offset as i64,
+                    Some(data.decimals),
+                    None,
+                    false,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::TransferWithReference => {
+            msg!("ConfidentialTransferInstruction::TransferWithReference");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<TransferWithReferenceInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_transfer(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                    data.proof_instruction_offset as i64,
+                    None,
+                    Some(&data.reference),
+                    false,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
         }
         ConfidentialTransferInstruction::ApplyPendingBalance => {
             msg!("ConfidentialTransferInstruction::ApplyPendingBalance");
@@ -1212,11 +3593,11 @@ pub(crate) fn process_instruction(
         }
         ConfidentialTransferInstruction::DisableConfidentialCredits => {
             msg!("ConfidentialTransferInstruction::DisableConfidentialCredits");
-            process_allow_confidential_credits(program_id, accounts, false)
+            process_allow_confidential_credits(program_id, accounts, false, 0)
         }
         ConfidentialTransferInstruction::EnableConfidentialCredits => {
             msg!("ConfidentialTransferInstruction::EnableConfidentialCredits");
-            process_allow_confidential_credits(program_id, accounts, true)
+            process_allow_confidential_credits(program_id, accounts, true, 0)
         }
         ConfidentialTransferInstruction::DisableNonConfidentialCredits => {
             msg!("ConfidentialTransferInstruction::DisableNonConfidentialCredits");
@@ -1226,5 +3607,522 @@ pub(crate) fn process_instruction(
             msg!("ConfidentialTransferInstruction::EnableNonConfidentialCredits");
             process_allow_non_confidential_credits(program_id, accounts, true)
         }
+        ConfidentialTransferInstruction::MintConfidential => {
+            msg!("ConfidentialTransferInstruction::MintConfidential");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<MintConfidentialInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_mint_confidential(program_id, accounts, data.decimals)
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::InitializeConfidentialMintSupply => {
+            msg!("ConfidentialTransferInstruction::InitializeConfidentialMintSupply");
+            let data = decode_versioned_instruction_data::<InitializeConfidentialMintSupplyData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_initialize_confidential_mint_supply(accounts, &data.supply_elgamal_pubkey)
+        }
+        ConfidentialTransferInstruction::BurnConfidential => {
+            msg!("ConfidentialTransferInstruction::BurnConfidential");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<BurnConfidentialInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_burn_confidential(
+                    program_id,
+                    accounts,
+                    data.amount.into(),
+                    data.decimals,
+                    data.new_decryptable_available_balance,
+                    data.proof_instruction_offset as i64,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::RotateElGamalKey => {
+            msg!("ConfidentialTransferInstruction::RotateElGamalKey");
+            let data = decode_versioned_instruction_data::<RotateElGamalKeyInstructionData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_rotate_elgamal_key(
+                program_id,
+                accounts,
+                &data.new_elgamal_pubkey,
+                &data.new_decryptable_available_balance,
+                data.proof_instruction_offset as i64,
+            )
+        }
+        ConfidentialTransferInstruction::InitializeAuditors => {
+            msg!("ConfidentialTransferInstruction::InitializeAuditors");
+            process_initialize_auditors(
+                accounts,
+                decode_versioned_instruction_data::<AuditorsInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?,
+            )
+        }
+        ConfidentialTransferInstruction::UpdateAuditors => {
+            msg!("ConfidentialTransferInstruction::UpdateAuditors");
+            process_update_auditors(
+                accounts,
+                decode_versioned_instruction_data::<AuditorsInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?,
+            )
+        }
+        ConfidentialTransferInstruction::TransferWithAdditionalProofs => {
+            msg!("ConfidentialTransferInstruction::TransferWithAdditionalProofs");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<
+                    TransferWithAdditionalProofsInstructionData,
+                >(input, CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION)?;
+                process_transfer(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                    data.proof_instruction_offset as i64,
+                    None,
+                    None,
+                    true,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::TransferWithSplitProofs => {
+            msg!("ConfidentialTransferInstruction::TransferWithSplitProofs");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<
+                    TransferWithSplitProofsInstructionData,
+                >(input, CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION)?;
+                process_transfer_with_split_proofs(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::WithdrawTo => {
+            msg!("ConfidentialTransferInstruction::WithdrawTo");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<WithdrawInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_withdraw_to(
+                    program_id,
+                    accounts,
+                    data.amount.into(),
+                    data.decimals,
+                    data.new_decryptable_available_balance,
+                    data.proof_instruction_offset as i64,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::EnableConfidentialCreditsUntilSlot => {
+            msg!("ConfidentialTransferInstruction::EnableConfidentialCreditsUntilSlot");
+            let data = decode_versioned_instruction_data::<EnableConfidentialCreditsUntilSlotData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_allow_confidential_credits(program_id, accounts, true, data.slot.into())
+        }
+        ConfidentialTransferInstruction::UpdateMaxPendingBalanceCreditCounter => {
+            msg!("ConfidentialTransferInstruction::UpdateMaxPendingBalanceCreditCounter");
+            let data = decode_versioned_instruction_data::<UpdateMaxPendingBalanceCreditCounterData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_update_max_pending_balance_credit_counter(
+                program_id,
+                accounts,
+                &data.maximum_pending_balance_credit_counter,
+            )
+        }
+        ConfidentialTransferInstruction::TransferWithSplitValidityProofs => {
+            msg!("ConfidentialTransferInstruction::TransferWithSplitValidityProofs");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<
+                    TransferWithSplitValidityProofsInstructionData,
+                >(input, CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION)?;
+                process_transfer_with_split_validity_proofs(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                    data.ciphertext_validity_proof_lo_instruction_offset as i64,
+                    data.ciphertext_validity_proof_hi_instruction_offset as i64,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::ApproveConfidentialDelegate => {
+            msg!("ConfidentialTransferInstruction::ApproveConfidentialDelegate");
+            #[cfg(feature = "zk-ops")]
+            {
+                process_approve_confidential_delegate(
+                    program_id,
+                    accounts,
+                    decode_versioned_instruction_data::<ApproveConfidentialDelegateInstructionData>(
+                        input,
+                        CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                    )?,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::DelegatedTransfer => {
+            msg!("ConfidentialTransferInstruction::DelegatedTransfer");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<DelegatedTransferInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_delegated_transfer(
+                    program_id,
+                    accounts,
+                    data.new_source_decryptable_available_balance,
+                    data.new_decryptable_allowance,
+                    data.proof_instruction_offset as i64,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::UpdateMintPartial => {
+            msg!("ConfidentialTransferInstruction::UpdateMintPartial");
+            let data = decode_versioned_instruction_data::<UpdateMintPartialData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_update_mint(
+                accounts,
+                data.auto_approve_new_accounts,
+                bool::from(&data.update_auditor_elgamal_pubkey)
+                    .then_some(&data.auditor_elgamal_pubkey),
+            )
+        }
+        ConfidentialTransferInstruction::ApprovePullTransfer => {
+            msg!("ConfidentialTransferInstruction::ApprovePullTransfer");
+            process_approve_pull_transfer(
+                program_id,
+                accounts,
+                decode_versioned_instruction_data::<ApprovePullTransferInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?,
+            )
+        }
+        ConfidentialTransferInstruction::PullTransfer => {
+            msg!("ConfidentialTransferInstruction::PullTransfer");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<PullTransferInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_pull_transfer(program_id, accounts, data.proof_instruction_offset as i64)
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::InitializeMaximumTransferAmount => {
+            msg!("ConfidentialTransferInstruction::InitializeMaximumTransferAmount");
+            let data = decode_versioned_instruction_data::<MaximumTransferAmountInstructionData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_initialize_maximum_transfer_amount(accounts, data.maximum_transfer_amount)
+        }
+        ConfidentialTransferInstruction::UpdateMaximumTransferAmount => {
+            msg!("ConfidentialTransferInstruction::UpdateMaximumTransferAmount");
+            let data = decode_versioned_instruction_data::<MaximumTransferAmountInstructionData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_update_maximum_transfer_amount(accounts, data.maximum_transfer_amount)
+        }
+        ConfidentialTransferInstruction::DepositAndApply => {
+            msg!("ConfidentialTransferInstruction::DepositAndApply");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<DepositAndApplyInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_deposit(
+                    program_id,
+                    accounts,
+                    data.amount.into(),
+                    data.decimals,
+                    Some(data.new_decryptable_available_balance),
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::AtomicSwap => {
+            msg!("ConfidentialTransferInstruction::AtomicSwap");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<AtomicSwapInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_atomic_swap(
+                    program_id,
+                    accounts,
+                    data.first_new_source_decryptable_available_balance,
+                    data.second_new_source_decryptable_available_balance,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::EmptyAccountFull => {
+            msg!("ConfidentialTransferInstruction::EmptyAccountFull");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<EmptyAccountInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_empty_account_full(
+                    program_id,
+                    accounts,
+                    data.proof_instruction_offset as i64,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+        ConfidentialTransferInstruction::UpdateDecryptableBalance => {
+            msg!("ConfidentialTransferInstruction::UpdateDecryptableBalance");
+            let data = decode_versioned_instruction_data::<UpdateDecryptableBalanceInstructionData>(
+                input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            )?;
+            process_update_decryptable_balance(
+                program_id,
+                accounts,
+                data.new_decryptable_available_balance,
+            )
+        }
+        ConfidentialTransferInstruction::ForwardPendingBalance => {
+            msg!("ConfidentialTransferInstruction::ForwardPendingBalance");
+            #[cfg(feature = "zk-ops")]
+            {
+                let data = decode_versioned_instruction_data::<ForwardPendingBalanceInstructionData>(
+                    input,
+                    CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+                )?;
+                process_forward_pending_balance(
+                    program_id,
+                    accounts,
+                    data.equality_proof_lo_instruction_offset as i64,
+                    data.equality_proof_hi_instruction_offset as i64,
+                )
+            }
+            #[cfg(not(feature = "zk-ops"))]
+            {
+                Err(ProgramError::InvalidInstructionData)
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "zk-ops"))]
+mod tests {
+    use {
+        super::*,
+        solana_zk_token_sdk::{
+            encryption::elgamal::{self, ElGamalKeypair},
+            instruction::{
+                transfer::{FeeParameters, TransferWithFeeData},
+                ZkProofData,
+            },
+        },
+    };
+
+    #[test]
+    fn verify_and_split_deposit_amount_boundaries() {
+        assert_eq!(verify_and_split_deposit_amount(0), Ok((0, 0)));
+        assert_eq!(
+            verify_and_split_deposit_amount(u16::MAX as u64),
+            Ok((u16::MAX as u64, 0))
+        );
+        assert_eq!(verify_and_split_deposit_amount(1 << 16), Ok((0, 1)));
+
+        // the largest deposit amount fills both the low and high bits
+        assert_eq!(MAXIMUM_DEPOSIT_AMOUNT, MAXIMUM_DEPOSIT_TRANSFER_AMOUNT);
+        assert_eq!(
+            verify_and_split_deposit_amount(MAXIMUM_DEPOSIT_AMOUNT),
+            Ok((u16::MAX as u64, u32::MAX as u64))
+        );
+
+        assert_eq!(
+            verify_and_split_deposit_amount(MAXIMUM_DEPOSIT_AMOUNT + 1),
+            Err(TokenError::MaximumDepositAmountExceeded)
+        );
+        assert_eq!(
+            verify_and_split_deposit_amount(u64::MAX),
+            Err(TokenError::MaximumDepositAmountExceeded)
+        );
+    }
+
+    #[test]
+    fn maximum_fee_transfer_is_bounded() {
+        const TRANSFER_AMOUNT: u64 = 10_000;
+        const FEE_RATE_BASIS_POINTS: u16 = 250;
+        const MAXIMUM_FEE: u64 = 100;
+
+        let source_keypair = ElGamalKeypair::new_rand();
+        let destination_keypair = ElGamalKeypair::new_rand();
+        let auditor_keypair = ElGamalKeypair::new_rand();
+        let withdraw_withheld_authority_keypair = ElGamalKeypair::new_rand();
+
+        // the fee of 250 computed from the fee rate is capped at the maximum fee
+        let source_ciphertext = source_keypair.pubkey().encrypt(TRANSFER_AMOUNT);
+        let proof_data = TransferWithFeeData::new(
+            TRANSFER_AMOUNT,
+            (TRANSFER_AMOUNT, &source_ciphertext),
+            &source_keypair,
+            (destination_keypair.pubkey(), auditor_keypair.pubkey()),
+            FeeParameters {
+                fee_rate_basis_points: FEE_RATE_BASIS_POINTS,
+                maximum_fee: MAXIMUM_FEE,
+            },
+            withdraw_withheld_authority_keypair.pubkey(),
+        )
+        .unwrap();
+        proof_data.verify_proof().unwrap();
+        let proof_context = proof_data.context_data();
+
+        assert_eq!(
+            check_transfer_fee_bounds(u16::from(
+                proof_context.fee_parameters.fee_rate_basis_points
+            )),
+            Ok(())
+        );
+
+        let decrypt_fee = |fee_ciphertext: &EncryptedFee| {
+            let destination_fee: elgamal::ElGamalCiphertext =
+                fee_amount_destination_ciphertext(fee_ciphertext)
+                    .try_into()
+                    .unwrap();
+            destination_keypair.secret().decrypt_u32(&destination_fee)
+        };
+        assert_eq!(
+            decrypt_fee(&proof_context.fee_ciphertext_lo),
+            Some(MAXIMUM_FEE)
+        );
+        assert_eq!(decrypt_fee(&proof_context.fee_ciphertext_hi), Some(0));
+
+        assert_eq!(check_transfer_fee_bounds(MAX_FEE_BASIS_POINTS), Ok(()));
+        assert_eq!(
+            check_transfer_fee_bounds(MAX_FEE_BASIS_POINTS + 1),
+            Err(TokenError::TransferFeeExceedsMaximum.into())
+        );
+    }
+
+    #[cfg(feature = "test-mock-proofs")]
+    #[test]
+    fn mock_proof_context_state_is_not_verified() {
+        // a pre-baked context that no zero-balance proof could certify, since the ciphertext
+        // encrypts a nonzero amount
+        let elgamal_keypair = ElGamalKeypair::new_rand();
+        let proof_context = ZeroBalanceProofContext {
+            pubkey: (*elgamal_keypair.pubkey()).into(),
+            ciphertext: elgamal_keypair.pubkey().encrypt(42_u64).into(),
+        };
+        let data = ProofContextState::encode(
+            &Pubkey::new_unique(),
+            ProofType::ZeroBalance,
+            &proof_context,
+        );
+        let key = Pubkey::new_unique();
+
+        let verify = |owner: &Pubkey, expected_proof_type: ProofType| {
+            let mut lamports = 0;
+            let mut data = data.clone();
+            let account_info = AccountInfo::new(
+                &key,
+                false,
+                false,
+                &mut lamports,
+                &mut data,
+                owner,
+                false,
+                0,
+            );
+            verify_context_state_proof::<ZeroBalanceProofContext>(
+                &account_info,
+                expected_proof_type,
+            )
+            .map(|context| context.ciphertext)
+        };
+
+        assert_eq!(
+            verify(&crate::mock_proof_program::id(), ProofType::ZeroBalance),
+            Ok(proof_context.ciphertext)
+        );
+
+        // the proof type is still checked
+        assert_eq!(
+            verify(&crate::mock_proof_program::id(), ProofType::PubkeyValidity),
+            Err(TokenError::ProofTypeMismatch.into())
+        );
+
+        // the same context in an account of any other owner is rejected
+        assert_eq!(
+            verify(&Pubkey::new_unique(), ProofType::ZeroBalance),
+            Err(ProgramError::IncorrectProgramId)
+        );
     }
 }
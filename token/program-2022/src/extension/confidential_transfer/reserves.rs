@@ -0,0 +1,136 @@
+use {
+    crate::{error::TokenError, extension::confidential_transfer::EncryptedBalance},
+    solana_zk_token_sdk::{
+        encryption::{
+            elgamal::{ElGamalCiphertext, ElGamalKeypair},
+            pedersen::{Pedersen, PedersenCommitment, PedersenOpening},
+        },
+        instruction::{
+            batched_range_proof::batched_range_proof_u64::BatchedRangeProofU64Data,
+            ciphertext_commitment_equality::CiphertextCommitmentEqualityProofData, ZkProofData,
+        },
+        zk_token_elgamal::pod::ElGamalPubkey as PodElGamalPubkey,
+    },
+};
+
+/// Bit length of the amount by which the total balance exceeds the threshold
+const SURPLUS_BIT_LENGTH: u8 = 64;
+
+/// Attestation that the total available balance of a set of confidential token accounts is at
+/// least a public threshold, without revealing the balances or their total
+///
+/// For each account, an equality proof certifies that a Pedersen commitment holds the same value
+/// as the account's available balance ciphertext. The commitments are summed, the threshold is
+/// subtracted, and a range proof certifies that the result is a 64-bit value, which implies that
+/// the total is not below the threshold.
+pub struct ReservesAttestation {
+    /// The public threshold that the total balance is attested against
+    pub threshold: u64,
+    /// Proofs that the commitments are equal to the available balances, in account order
+    pub equality_proofs: Vec<CiphertextCommitmentEqualityProofData>,
+    /// Proof that the sum of the commitments minus the threshold is in range
+    pub range_proof: BatchedRangeProofU64Data,
+}
+
+impl ReservesAttestation {
+    /// Create an attestation from the ElGamal keypair, available balance ciphertext, and decrypted
+    /// available balance of each account
+    pub fn new(
+        accounts: &[(&ElGamalKeypair, &EncryptedBalance, u64)],
+        threshold: u64,
+    ) -> Result<Self, TokenError> {
+        let mut total_balance: u64 = 0;
+        let mut total_commitment = Pedersen::encode(0_u64);
+        let mut total_opening = PedersenOpening::default();
+        let mut equality_proofs = Vec::with_capacity(accounts.len());
+
+        for (elgamal_keypair, available_balance, decrypted_available_balance) in accounts {
+            let available_balance: ElGamalCiphertext = (**available_balance)
+                .try_into()
+                .map_err(|_| TokenError::AccountDecryption)?;
+
+            let opening = PedersenOpening::new_rand();
+            let commitment = Pedersen::with(*decrypted_available_balance, &opening);
+
+            equality_proofs.push(
+                CiphertextCommitmentEqualityProofData::new(
+                    elgamal_keypair,
+                    &available_balance,
+                    &commitment,
+                    &opening,
+                    *decrypted_available_balance,
+                )
+                .map_err(|_| TokenError::ProofGeneration)?,
+            );
+
+            total_balance = total_balance
+                .checked_add(*decrypted_available_balance)
+                .ok_or(TokenError::Overflow)?;
+            total_commitment = total_commitment + commitment;
+            total_opening = total_opening + opening;
+        }
+
+        let surplus = total_balance
+            .checked_sub(threshold)
+            .ok_or(TokenError::InsufficientFunds)?;
+        let surplus_commitment = total_commitment - Pedersen::encode(threshold);
+
+        let range_proof = BatchedRangeProofU64Data::new(
+            vec![&surplus_commitment],
+            vec![surplus],
+            vec![SURPLUS_BIT_LENGTH as usize],
+            vec![&total_opening],
+        )
+        .map_err(|_| TokenError::ProofGeneration)?;
+
+        Ok(Self {
+            threshold,
+            equality_proofs,
+            range_proof,
+        })
+    }
+
+    /// Verify the attestation against the ElGamal public key and available balance ciphertext of
+    /// each account, in the order that the attestation was created with
+    pub fn verify(
+        &self,
+        accounts: &[(&PodElGamalPubkey, &EncryptedBalance)],
+    ) -> Result<(), TokenError> {
+        if accounts.len() != self.equality_proofs.len() {
+            return Err(TokenError::ConfidentialTransferBalanceMismatch);
+        }
+
+        let mut total_commitment = Pedersen::encode(0_u64);
+        for ((elgamal_pubkey, available_balance), equality_proof) in
+            accounts.iter().zip(self.equality_proofs.iter())
+        {
+            let context = equality_proof.context_data();
+            if context.pubkey != **elgamal_pubkey {
+                return Err(TokenError::ConfidentialTransferElGamalPubkeyMismatch);
+            }
+            if context.ciphertext != **available_balance {
+                return Err(TokenError::ConfidentialTransferBalanceMismatch);
+            }
+            equality_proof
+                .verify_proof()
+                .map_err(|_| TokenError::ConfidentialTransferBalanceMismatch)?;
+
+            let commitment: PedersenCommitment = context
+                .commitment
+                .try_into()
+                .map_err(|_| TokenError::ConfidentialTransferBalanceMismatch)?;
+            total_commitment = total_commitment + commitment;
+        }
+
+        let surplus_commitment = total_commitment - Pedersen::encode(self.threshold);
+        let range_context = self.range_proof.context_data();
+        if range_context.commitments[0] != surplus_commitment.into()
+            || range_context.bit_lengths[0] != SURPLUS_BIT_LENGTH
+        {
+            return Err(TokenError::ConfidentialTransferBalanceMismatch);
+        }
+        self.range_proof
+            .verify_proof()
+            .map_err(|_| TokenError::ConfidentialTransferBalanceMismatch)
+    }
+}
@@ -30,24 +30,22 @@ use {
 
 // Remove feature once zk ops syscalls are enabled on all networks
 #[cfg(feature = "zk-ops")]
-use solana_zk_token_sdk::zk_token_elgamal::ops as syscall;
+use {solana_program::hash::hash, solana_zk_token_sdk::zk_token_elgamal::ops as syscall};
 
 #[cfg(feature = "proof-program")]
-use {
-    crate::{
-        extension::{
-            confidential_transfer::{
-                instruction::{ProofInstruction, WithdrawWithheldTokensData},
-                processor::decode_proof_instruction,
-                ConfidentialTransferAccount, ConfidentialTransferMint,
-            },
-            confidential_transfer_fee::instruction::{
-                WithdrawWithheldTokensFromAccountsData, WithdrawWithheldTokensFromMintData,
-            },
+use crate::{
+    extension::{
+        confidential_transfer::{
+            instruction::{ProofInstruction, WithdrawWithheldTokensData},
+            processor::decode_proof_instruction,
+            ConfidentialTransferAccount, ConfidentialTransferMint,
+        },
+        confidential_transfer_fee::instruction::{
+            WithdrawWithheldTokensFromAccountsData, WithdrawWithheldTokensFromMintData,
         },
-        processor::Processor,
     },
-    solana_program::sysvar::instructions::get_instruction_relative,
+    processor::Processor,
+    proof::get_proof_instruction_relative,
 };
 
 /// Processes an [InitializeConfidentialTransferFeeConfig] instruction.
@@ -128,7 +126,7 @@ fn process_withdraw_withheld_tokens_from_mint(
     // Zero-knowledge proof certifies that the exact withheld amount is credited to the destination
     // account.
     let zkp_instruction =
-        get_instruction_relative(proof_instruction_offset, instructions_sysvar_info)?;
+        get_proof_instruction_relative(proof_instruction_offset, instructions_sysvar_info)?;
     let proof_data = decode_proof_instruction::<WithdrawWithheldTokensData>(
         ProofInstruction::VerifyWithdrawWithheldTokens,
         &zkp_instruction,
@@ -254,7 +252,7 @@ fn process_withdraw_withheld_tokens_from_accounts(
     // Zero-knowledge proof certifies that the exact aggregate withheld amount is credited to the
     // source account.
     let zkp_instruction =
-        get_instruction_relative(proof_instruction_offset, instructions_sysvar_info)?;
+        get_proof_instruction_relative(proof_instruction_offset, instructions_sysvar_info)?;
     let proof_data = decode_proof_instruction::<WithdrawWithheldTokensData>(
         ProofInstruction::VerifyWithdrawWithheldTokens,
         &zkp_instruction,
@@ -328,6 +326,10 @@ fn process_harvest_withheld_tokens_to_mint(accounts: &[AccountInfo]) -> ProgramR
     let confidential_transfer_fee_mint =
         mint.get_extension_mut::<ConfidentialTransferFeeConfig>()?;
 
+    // Accounts that cannot be harvested, e.g. closed accounts, accounts of a different mint, or
+    // accounts without the `ConfidentialTransferFeeAmount` extension, are skipped so that a single
+    // bad account does not fail the whole batch.
+    let mut harvested_account_count: u64 = 0;
     for token_account_info in token_account_infos {
         match harvest_from_account(mint_account_info.key, token_account_info) {
             Ok(withheld_amount) => {
@@ -338,12 +340,25 @@ fn process_harvest_withheld_tokens_to_mint(accounts: &[AccountInfo]) -> ProgramR
                 .ok_or(ProgramError::InvalidInstructionData)?;
 
                 confidential_transfer_fee_mint.withheld_amount = new_mint_withheld_amount;
+                harvested_account_count = harvested_account_count.saturating_add(1);
             }
             Err(e) => {
-                msg!("Error harvesting from {}: {}", token_account_info.key, e);
+                msg!("Skipping harvest from {}: {}", token_account_info.key, e);
             }
         }
     }
+    msg!(
+        "Harvested withheld tokens from {} of {} accounts",
+        harvested_account_count,
+        token_account_infos.len()
+    );
+    msg!(
+        "Withheld amount of {} updated, ciphertext hash {}",
+        mint_account_info.key,
+        hash(bytemuck::bytes_of(
+            &confidential_transfer_fee_mint.withheld_amount
+        )),
+    );
     Ok(())
 }
 
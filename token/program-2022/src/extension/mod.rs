@@ -4,7 +4,12 @@ use {
     crate::{
         error::TokenError,
         extension::{
-            confidential_transfer::{ConfidentialTransferAccount, ConfidentialTransferMint},
+            confidential_transfer::{
+                ConfidentialMintSupply, ConfidentialTransferAccount, ConfidentialTransferAuditors,
+                ConfidentialTransferCreditsUntilSlot, ConfidentialTransferDelegateAllowance,
+                ConfidentialTransferMaximumTransferAmount, ConfidentialTransferMint,
+                ConfidentialTransferPullAuthorization,
+            },
             confidential_transfer_fee::{
                 ConfidentialTransferFeeAmount, ConfidentialTransferFeeConfig,
             },
@@ -900,6 +905,18 @@ pub enum ExtensionType {
     MetadataPointer,
     /// Mint contains token-metadata
     TokenMetadata,
+    /// Includes the confidential supply of tokens minted with confidential mint amounts
+    ConfidentialMintSupply,
+    /// Includes the ElGamal public keys of additional confidential transfer auditors
+    ConfidentialTransferAuditors,
+    /// Includes the slot from which incoming confidential transfers are rejected
+    ConfidentialTransferCreditsUntilSlot,
+    /// Includes the encrypted allowance of the delegate of a confidential balance
+    ConfidentialTransferDelegateAllowance,
+    /// Includes a single-use authorization for another account to pull a confidential transfer
+    ConfidentialTransferPullAuthorization,
+    /// Includes the maximum amount of a single confidential transfer
+    ConfidentialTransferMaximumTransferAmount,
     /// Test variable-length mint extension
     #[cfg(test)]
     VariableLenMintTest = u16::MAX - 2,
@@ -974,6 +991,22 @@ impl ExtensionType {
             }
             ExtensionType::MetadataPointer => pod_get_packed_len::<MetadataPointer>(),
             ExtensionType::TokenMetadata => unreachable!(),
+            ExtensionType::ConfidentialMintSupply => pod_get_packed_len::<ConfidentialMintSupply>(),
+            ExtensionType::ConfidentialTransferAuditors => {
+                pod_get_packed_len::<ConfidentialTransferAuditors>()
+            }
+            ExtensionType::ConfidentialTransferCreditsUntilSlot => {
+                pod_get_packed_len::<ConfidentialTransferCreditsUntilSlot>()
+            }
+            ExtensionType::ConfidentialTransferDelegateAllowance => {
+                pod_get_packed_len::<ConfidentialTransferDelegateAllowance>()
+            }
+            ExtensionType::ConfidentialTransferPullAuthorization => {
+                pod_get_packed_len::<ConfidentialTransferPullAuthorization>()
+            }
+            ExtensionType::ConfidentialTransferMaximumTransferAmount => {
+                pod_get_packed_len::<ConfidentialTransferMaximumTransferAmount>()
+            }
             #[cfg(test)]
             ExtensionType::AccountPaddingTest => pod_get_packed_len::<AccountPaddingTest>(),
             #[cfg(test)]
@@ -1033,7 +1066,10 @@ impl ExtensionType {
             | ExtensionType::TransferHook
             | ExtensionType::ConfidentialTransferFeeConfig
             | ExtensionType::MetadataPointer
-            | ExtensionType::TokenMetadata => AccountType::Mint,
+            | ExtensionType::TokenMetadata
+            | ExtensionType::ConfidentialMintSupply
+            | ExtensionType::ConfidentialTransferAuditors
+            | ExtensionType::ConfidentialTransferMaximumTransferAmount => AccountType::Mint,
             ExtensionType::ImmutableOwner
             | ExtensionType::TransferFeeAmount
             | ExtensionType::ConfidentialTransferAccount
@@ -1041,7 +1077,10 @@ impl ExtensionType {
             | ExtensionType::NonTransferableAccount
             | ExtensionType::TransferHookAccount
             | ExtensionType::CpiGuard
-            | ExtensionType::ConfidentialTransferFeeAmount => AccountType::Account,
+            | ExtensionType::ConfidentialTransferFeeAmount
+            | ExtensionType::ConfidentialTransferCreditsUntilSlot
+            | ExtensionType::ConfidentialTransferDelegateAllowance
+            | ExtensionType::ConfidentialTransferPullAuthorization => AccountType::Account,
             #[cfg(test)]
             ExtensionType::VariableLenMintTest => AccountType::Mint,
             #[cfg(test)]
@@ -1895,6 +1895,24 @@ pub fn decode_instruction_data<T: Pod>(input_with_type: &[u8]) -> Result<&T, Pro
     }
 }
 
+/// Utility function for decoding instruction data that is prefixed with a layout version
+///
+/// The version byte directly follows the instruction type, so that the layout of the data can
+/// change without breaking instructions encoded by older clients. Fails with
+/// `TokenError::UnsupportedInstructionDataVersion` if the data was not encoded with `version`.
+pub fn decode_versioned_instruction_data<T: Pod>(
+    input_with_type: &[u8],
+    version: u8,
+) -> Result<&T, ProgramError> {
+    match input_with_type.get(1) {
+        None => Err(ProgramError::InvalidInstructionData),
+        Some(&data_version) if data_version != version => {
+            Err(TokenError::UnsupportedInstructionDataVersion.into())
+        }
+        Some(_) => decode_instruction_data(&input_with_type[1..]),
+    }
+}
+
 /// Utility function for encoding instruction data
 pub(crate) fn encode_instruction<T: Into<u8>, D: Pod>(
     token_program_id: &Pubkey,
@@ -1913,6 +1931,26 @@ pub(crate) fn encode_instruction<T: Into<u8>, D: Pod>(
     }
 }
 
+/// Utility function for encoding instruction data prefixed with a layout version
+pub(crate) fn encode_versioned_instruction<T: Into<u8>, D: Pod>(
+    token_program_id: &Pubkey,
+    accounts: Vec<AccountMeta>,
+    token_instruction_type: TokenInstruction,
+    instruction_type: T,
+    version: u8,
+    instruction_data: &D,
+) -> Instruction {
+    let mut data = token_instruction_type.pack();
+    data.push(T::into(instruction_type));
+    data.push(version);
+    data.extend_from_slice(bytemuck::bytes_of(instruction_data));
+    Instruction {
+        program_id: *token_program_id,
+        accounts,
+        data,
+    }
+}
+
 /// Creates a `WithdrawExcessLamports` Instruction
 pub fn withdraw_excess_lamports(
     token_program_id: &Pubkey,
@@ -2450,6 +2488,108 @@ mod test {
         ));
     }
 
+    #[test]
+    fn test_versioned_instruction_data() {
+        use crate::extension::confidential_transfer::{
+            instruction::{
+                deposit, update_decryptable_balance, update_max_pending_balance_credit_counter,
+                ConfidentialTransferInstruction, DepositInstructionData,
+                UpdateDecryptableBalanceInstructionData, UpdateMaxPendingBalanceCreditCounterData,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            },
+            DecryptableBalance,
+        };
+        use bytemuck::Zeroable;
+
+        let token_program_id = crate::id();
+        let pubkey = Pubkey::new_unique();
+
+        // unversioned instruction data keeps the layout that older clients encode
+        let mut older_format = TokenInstruction::ConfidentialTransferExtension.pack();
+        older_format.push(ConfidentialTransferInstruction::Deposit.into());
+        older_format.extend_from_slice(&42u64.to_le_bytes());
+        older_format.push(6);
+        let instruction =
+            deposit(&token_program_id, &pubkey, &pubkey, 42, 6, &pubkey, &[]).unwrap();
+        assert_eq!(instruction.data, older_format);
+
+        let input = &older_format[1..];
+        assert!(matches!(
+            decode_instruction_type(input).unwrap(),
+            ConfidentialTransferInstruction::Deposit
+        ));
+        let data = decode_instruction_data::<DepositInstructionData>(input).unwrap();
+        assert_eq!(u64::from(data.amount), 42);
+        assert_eq!(data.decimals, 6);
+
+        // versioned instruction data is prefixed with the version
+        let new_decryptable_available_balance = DecryptableBalance::zeroed();
+        let instruction = update_decryptable_balance(
+            &token_program_id,
+            &pubkey,
+            &pubkey,
+            &[],
+            new_decryptable_available_balance,
+        )
+        .unwrap();
+        let mut input = instruction.data[1..].to_vec();
+        assert_eq!(input[1], CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION);
+        let data = decode_versioned_instruction_data::<UpdateDecryptableBalanceInstructionData>(
+            &input,
+            CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        )
+        .unwrap();
+        assert_eq!(
+            data.new_decryptable_available_balance,
+            new_decryptable_available_balance
+        );
+
+        // unknown versions are rejected rather than misparsed
+        input[1] = CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION + 1;
+        assert_eq!(
+            decode_versioned_instruction_data::<UpdateDecryptableBalanceInstructionData>(
+                &input,
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            ),
+            Err(TokenError::UnsupportedInstructionDataVersion.into())
+        );
+
+        // data without a version is rejected
+        assert_eq!(
+            decode_versioned_instruction_data::<UpdateDecryptableBalanceInstructionData>(
+                &input[..1],
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            ),
+            Err(ProgramError::InvalidInstructionData)
+        );
+
+        // instructions added on top of the original extension are versioned as well, so their
+        // unversioned layout is rejected
+        let instruction =
+            update_max_pending_balance_credit_counter(&token_program_id, &pubkey, &pubkey, &[], 42)
+                .unwrap();
+        let input = &instruction.data[1..];
+        assert_eq!(input[1], CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION);
+        let data = decode_versioned_instruction_data::<UpdateMaxPendingBalanceCreditCounterData>(
+            input,
+            CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+        )
+        .unwrap();
+        assert_eq!(u64::from(data.maximum_pending_balance_credit_counter), 42);
+
+        let mut older_format = TokenInstruction::ConfidentialTransferExtension.pack();
+        older_format
+            .push(ConfidentialTransferInstruction::UpdateMaxPendingBalanceCreditCounter.into());
+        older_format.extend_from_slice(&42u64.to_le_bytes());
+        assert_eq!(
+            decode_versioned_instruction_data::<UpdateMaxPendingBalanceCreditCounterData>(
+                &older_format[1..],
+                CONFIDENTIAL_TRANSFER_INSTRUCTION_DATA_VERSION,
+            ),
+            Err(TokenError::UnsupportedInstructionDataVersion.into())
+        );
+    }
+
     proptest! {
         #![proptest_config(ProptestConfig::with_cases(1024))]
         #[test]
@@ -110,13 +110,35 @@ pub fn check_spl_token_program_account(spl_token_program_id: &Pubkey) -> Program
 }
 
 /// Checks that the supplied program ID is correct for the ZK Token proof program
+///
+/// With the `test-mock-proofs` feature, accounts owned by
+/// `mock_proof_program::id()` are also accepted.
 pub fn check_zk_token_proof_program_account(zk_token_proof_program_id: &Pubkey) -> ProgramResult {
+    #[cfg(feature = "test-mock-proofs")]
+    if zk_token_proof_program_id == &mock_proof_program::id() {
+        return Ok(());
+    }
     if zk_token_proof_program_id != &solana_zk_token_sdk::zk_token_proof_program::id() {
         return Err(ProgramError::IncorrectProgramId);
     }
     Ok(())
 }
 
+#[cfg(all(feature = "test-mock-proofs", not(debug_assertions)))]
+compile_error!("the `test-mock-proofs` feature must not be enabled in release builds");
+
+/// Sentinel owner for mock proof context state accounts, for testing only
+///
+/// Context state accounts owned by this id are read with the usual
+/// `ProofContextState` layout, but their contexts are never cryptographically
+/// verified. Tests can pre-bake the expected context with
+/// `ProofContextState::encode` and load the account directly into the test
+/// environment, skipping proof generation and verification entirely.
+#[cfg(feature = "test-mock-proofs")]
+pub mod mock_proof_program {
+    solana_program::declare_id!("MockZkTokenProof111111111111111111111111111");
+}
+
 /// Checks two pubkeys for equality in a computationally cheap way using
 /// `sol_memcmp`
 pub fn cmp_pubkeys(a: &Pubkey, b: &Pubkey) -> bool {
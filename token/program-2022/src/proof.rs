@@ -1,8 +1,12 @@
 //! Helper for processing instruction data from ZK Token proof program
 
 use {
+    crate::error::TokenError,
     bytemuck::Pod,
-    solana_program::{instruction::Instruction, msg, program_error::ProgramError, pubkey::Pubkey},
+    solana_program::{
+        account_info::AccountInfo, instruction::Instruction, msg, program_error::ProgramError,
+        pubkey::Pubkey, sysvar::instructions::get_instruction_relative,
+    },
     solana_zk_token_sdk::{
         instruction::ZkProofData, zk_token_proof_instruction::ProofInstruction,
         zk_token_proof_program,
@@ -27,6 +31,25 @@ pub fn decode_proof_instruction_context<T: Pod + ZkProofData<U>, U: Pod>(
         .ok_or(ProgramError::InvalidInstructionData)
 }
 
+/// Loads the proof instruction located at `proof_instruction_offset` relative to the current
+/// instruction from the instructions sysvar.
+pub fn get_proof_instruction_relative(
+    proof_instruction_offset: i64,
+    instructions_sysvar_info: &AccountInfo,
+) -> Result<Instruction, ProgramError> {
+    match get_instruction_relative(proof_instruction_offset, instructions_sysvar_info) {
+        Ok(instruction) => Ok(instruction),
+        Err(ProgramError::InvalidArgument) => {
+            msg!(
+                "Proof instruction offset {} is out of range of the transaction",
+                proof_instruction_offset
+            );
+            Err(TokenError::InvalidProofInstructionOffset.into())
+        }
+        Err(e) => Err(e),
+    }
+}
+
 /// A proof location type meant to be used for arguments to instruction constructors.
 #[derive(Clone, Copy)]
 pub enum ProofLocation<'a, T> {
@@ -1,8 +1,16 @@
 #![cfg(feature = "serde-traits")]
 
 use {
-    solana_program::program_option::COption, solana_sdk::pubkey::Pubkey,
-    spl_token_2022::instruction, std::str::FromStr,
+    solana_program::program_option::COption,
+    solana_sdk::pubkey::Pubkey,
+    spl_token_2022::{
+        extension::confidential_transfer::{
+            ConfidentialTransferAccount, ConfidentialTransferAccountView,
+        },
+        instruction,
+        solana_zk_token_sdk::zk_token_elgamal::pod::{ElGamalCiphertext, ElGamalPubkey},
+    },
+    std::str::FromStr,
 };
 
 #[test]
@@ -35,3 +43,31 @@ fn token_program_serde_with_none() {
 
     serde_json::from_str::<instruction::TokenInstruction>(&serialized).unwrap();
 }
+
+#[test]
+fn confidential_transfer_account_view_serde() {
+    let account = ConfidentialTransferAccount {
+        approved: true.into(),
+        elgamal_pubkey: ElGamalPubkey([1; 32]),
+        available_balance: ElGamalCiphertext([2; 64]),
+        allow_confidential_credits: true.into(),
+        pending_balance_credit_counter: 3.into(),
+        maximum_pending_balance_credit_counter: 65536.into(),
+        ..ConfidentialTransferAccount::default()
+    };
+    let view = ConfidentialTransferAccountView::from(&account);
+
+    let serialized = serde_json::to_string(&view).unwrap();
+    assert_eq!(
+        serialized,
+        format!(
+            "{{\"approved\":true,\"elgamalPubkey\":\"{}\",\"allowConfidentialCredits\":true,\"allowNonConfidentialCredits\":false,\"pendingBalanceCreditCounter\":3,\"maximumPendingBalanceCreditCounter\":65536,\"expectedPendingBalanceCreditCounter\":0,\"actualPendingBalanceCreditCounter\":0}}",
+            Pubkey::new_from_array([1; 32]),
+        )
+    );
+
+    assert_eq!(
+        serde_json::from_str::<ConfidentialTransferAccountView>(&serialized).unwrap(),
+        view
+    );
+}